@@ -2,6 +2,7 @@
 use crate::actor::Topic;
 use crate::agent::base::AgentType;
 use crate::agent::error::{AgentBuildError, RunnableAgentError};
+use crate::agent::executor::memory_helper::MemoryHelper;
 use crate::agent::hooks::AgentHooks;
 use crate::agent::state::AgentState;
 use crate::agent::task::Task;
@@ -88,7 +89,12 @@ where
         let tx = runtime.tx();
 
         let agent: Arc<BaseAgent<T, ActorAgent>> = Arc::new(
-            BaseAgent::<T, ActorAgent>::new(self.inner, llm, self.memory, tx, self.stream).await?,
+            BaseAgent::<T, ActorAgent>::new(self.inner, llm, self.memory, tx, self.stream)
+                .await?
+                .with_vars(self.vars)
+                .with_speech_to_text(self.speech_to_text)
+                .with_moderation(self.moderation)
+                .with_memory_factory(self.memory_factory),
         );
 
         // Create agent actor
@@ -120,7 +126,7 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, ActorAgent> {
 
     pub async fn run(
         self: Arc<Self>,
-        task: Task,
+        mut task: Task,
     ) -> Result<<T as AgentDeriveT>::Output, RunnableAgentError>
     where
         Value: From<<T as AgentExecutor>::Output>,
@@ -129,9 +135,12 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, ActorAgent> {
         let submission_id = task.submission_id;
         let tx = self.tx().map_err(|_| RunnableAgentError::EmptyTx)?;
 
-        let context = self.create_context();
+        let context = self.create_context()?;
 
-        //Run Hook
+        //Run Hooks
+        self.transcribe_audio(&mut task).await?;
+        self.inner.on_prepare_task(&mut task, &context).await;
+        self.moderate_input(&mut task).await?;
         let hook_outcome = self.inner.on_run_start(&task, &context).await;
         match hook_outcome {
             HookOutcome::Abort => return Err(RunnableAgentError::Abort),
@@ -141,7 +150,16 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, ActorAgent> {
         // Execute the agent's logic using the executor
         match self.inner().execute(&task, context.clone()).await {
             Ok(output) => {
-                let value: Value = output.clone().into();
+                let mut value: Value = output.clone().into();
+
+                //Extract Agent output into the desired type
+                let mut agent_out: <T as AgentDeriveT>::Output = output.into();
+                if let Some(redacted) = self.moderate_output(&task, &agent_out, &context).await? {
+                    value = serde_json::to_value(&redacted)
+                        .map_err(|e| RunnableAgentError::SerializationError(e.to_string()))?;
+                    agent_out = redacted;
+                }
+                MemoryHelper::run_pending_update(&context.memory()).await;
 
                 #[cfg(not(target_arch = "wasm32"))]
                 tx.send(Event::TaskComplete {
@@ -154,9 +172,6 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, ActorAgent> {
                 .await
                 .map_err(|e| RunnableAgentError::ExecutorError(e.to_string()))?;
 
-                //Extract Agent output into the desired type
-                let agent_out: <T as AgentDeriveT>::Output = output.into();
-
                 //Run On complete Hook
                 self.inner
                     .on_run_complete(&task, &agent_out, &context)
@@ -180,7 +195,7 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, ActorAgent> {
 
     pub async fn run_stream(
         self: Arc<Self>,
-        task: Task,
+        mut task: Task,
     ) -> Result<
         std::pin::Pin<
             Box<dyn Stream<Item = Result<<T as AgentDeriveT>::Output, RunnableAgentError>> + Send>,
@@ -191,10 +206,13 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, ActorAgent> {
         <T as AgentDeriveT>::Output: From<<T as AgentExecutor>::Output>,
     {
         // let submission_id = task.submission_id;
-        let context = self.create_context();
+        let context = self.create_context()?;
+        self.transcribe_audio(&mut task).await?;
+        self.inner.on_prepare_task(&mut task, &context).await;
+        self.moderate_input(&mut task).await?;
 
         // Execute the agent's streaming logic using the executor
-        match self.inner().execute_stream(&task, context).await {
+        match self.inner().execute_stream(&task, context.clone()).await {
             Ok(stream) => {
                 use futures::StreamExt;
                 // Transform the stream to convert agent output to TaskResult
@@ -209,7 +227,15 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, ActorAgent> {
                     }
                 });
 
-                Ok(Box::pin(transformed_stream))
+                // Run the memory's pending update once the stream is drained
+                let memory = context.memory();
+                let pending_update = futures::stream::once(async move {
+                    MemoryHelper::run_pending_update(&memory).await;
+                    None
+                })
+                .filter_map(futures::future::ready);
+
+                Ok(Box::pin(transformed_stream.chain(pending_update)))
             }
             Err(e) => {
                 // Send error event for stream creation failure
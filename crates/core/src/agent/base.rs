@@ -1,12 +1,17 @@
 use crate::agent::config::AgentConfig;
 use crate::agent::memory::MemoryProvider;
+use crate::agent::moderation::{output_text, ModerationGuard};
+use crate::agent::session::{MemoryFactory, SessionStore};
+use crate::agent::task::Task;
 use crate::agent::{output::AgentOutputT, AgentExecutor, Context};
 use crate::protocol::Event;
 use crate::{protocol::ActorID, tool::ToolT};
 use async_trait::async_trait;
+use autoagents_llm::audio::{SpeechToTextProvider, TranscriptionRequest};
 use autoagents_llm::LLMProvider;
 
 use serde_json::Value;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::{fmt::Debug, sync::Arc};
 
@@ -35,6 +40,12 @@ pub trait AgentDeriveT: Send + Sync + 'static + Debug {
     /// Get the agent's description
     fn description(&self) -> &'static str;
 
+    /// Get the agent's instructions template used as the system prompt.
+    /// Empty by default, in which case the description is sent verbatim.
+    fn instructions(&self) -> &'static str {
+        ""
+    }
+
     // If you provide None then its taken as String output
     fn output_schema(&self) -> Option<Value>;
 
@@ -50,7 +61,6 @@ pub trait AgentType: 'static + Send + Sync {
 }
 
 /// Base agent type that wraps an AgentDeriveT implementation with additional runtime components
-#[derive(Clone)]
 pub struct BaseAgent<T: AgentDeriveT + AgentExecutor + AgentHooks, A: AgentType> {
     /// The inner agent implementation (from macro)
     pub(crate) inner: Arc<T>,
@@ -64,9 +74,38 @@ pub struct BaseAgent<T: AgentDeriveT + AgentExecutor + AgentHooks, A: AgentType>
     pub(crate) tx: Option<Sender<Event>>,
     //Stream
     pub(crate) stream: bool,
+    /// Variables available to the instruction template
+    pub(crate) vars: HashMap<String, Value>,
+    /// Transcribes the audio input of tasks
+    pub(crate) speech_to_text: Option<Arc<dyn SpeechToTextProvider>>,
+    /// Moderates the prompt and output of each run
+    pub(crate) moderation: Option<ModerationGuard>,
+    /// Creates the memory of each new session
+    pub(crate) memory_factory: Option<MemoryFactory>,
+    /// Sessions of this agent, shared between clones
+    pub(crate) sessions: Arc<SessionStore>,
     pub(crate) marker: PhantomData<A>,
 }
 
+impl<T: AgentDeriveT + AgentExecutor + AgentHooks, A: AgentType> Clone for BaseAgent<T, A> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            llm: self.llm.clone(),
+            id: self.id,
+            memory: self.memory.clone(),
+            tx: self.tx.clone(),
+            stream: self.stream,
+            vars: self.vars.clone(),
+            speech_to_text: self.speech_to_text.clone(),
+            moderation: self.moderation.clone(),
+            memory_factory: self.memory_factory.clone(),
+            sessions: self.sessions.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
 impl<T: AgentDeriveT + AgentExecutor + AgentHooks, A: AgentType> Debug for BaseAgent<T, A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(format!("A: {} - T: {}", self.inner().name(), A::type_name()).as_str())
@@ -89,6 +128,11 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks, A: AgentType> BaseAgent<T, A>
             tx: Some(tx),
             memory: memory.map(|m| Arc::new(Mutex::new(m))),
             stream,
+            vars: HashMap::new(),
+            speech_to_text: None,
+            moderation: None,
+            memory_factory: None,
+            sessions: Arc::new(SessionStore::default()),
             marker: PhantomData,
         };
 
@@ -98,6 +142,97 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks, A: AgentType> BaseAgent<T, A>
         Ok(agent)
     }
 
+    /// Set the variables available to the instruction template
+    pub fn with_vars(mut self, vars: HashMap<String, Value>) -> Self {
+        self.vars = vars;
+        self
+    }
+
+    /// Set the provider that transcribes the audio input of tasks
+    pub fn with_speech_to_text(
+        mut self,
+        speech_to_text: Option<Arc<dyn SpeechToTextProvider>>,
+    ) -> Self {
+        self.speech_to_text = speech_to_text;
+        self
+    }
+
+    /// Replace the task's audio input with its transcript, appended to the prompt
+    pub(crate) async fn transcribe_audio(&self, task: &mut Task) -> Result<(), RunnableAgentError> {
+        let Some(audio) = task.audio.take() else {
+            return Ok(());
+        };
+        let speech_to_text = self.speech_to_text.as_ref().ok_or_else(|| {
+            RunnableAgentError::task_error("Task has audio input but no speech-to-text provider")
+        })?;
+        let mut request = TranscriptionRequest::new(audio.data, audio.name);
+        request.language = audio.language;
+        let transcription = speech_to_text
+            .transcribe(&request)
+            .await
+            .map_err(|e| RunnableAgentError::task_error(format!("Transcription failed: {e}")))?;
+        if task.prompt.trim().is_empty() {
+            task.prompt = transcription.text;
+        } else {
+            task.prompt = format!("{}\n\n{}", task.prompt, transcription.text);
+        }
+        Ok(())
+    }
+
+    /// Set the guard that moderates the prompt and output of each run
+    pub fn with_moderation(mut self, moderation: Option<ModerationGuard>) -> Self {
+        self.moderation = moderation;
+        self
+    }
+
+    /// Run the task prompt through the moderation guard, if any
+    pub(crate) async fn moderate_input(&self, task: &mut Task) -> Result<(), RunnableAgentError> {
+        match &self.moderation {
+            Some(guard) => guard.check_input(task, self.id, &self.tx).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Run the final output through the moderation guard, returning a redacted
+    /// replacement if the policy calls for one.
+    ///
+    /// The executor has already stored the raw response in memory, so a blocked
+    /// response is removed from it and a redacted one is replaced. Only the message the
+    /// executor recorded with [`Context::set_stored_response`] is touched.
+    pub(crate) async fn moderate_output(
+        &self,
+        task: &Task,
+        output: &<T as AgentDeriveT>::Output,
+        context: &Context,
+    ) -> Result<Option<<T as AgentDeriveT>::Output>, RunnableAgentError> {
+        let Some(guard) = &self.moderation else {
+            return Ok(None);
+        };
+        let verdict = guard.check_output(task, output, self.id, &self.tx).await;
+        let replacement = match &verdict {
+            Ok(None) => return verdict,
+            Ok(Some(redacted)) => Some(output_text(redacted)?),
+            Err(_) => None,
+        };
+        if let (Some(memory), Some(handle)) = (context.memory(), context.stored_response()) {
+            if let Err(e) = memory
+                .lock()
+                .await
+                .amend(handle, replacement.as_deref())
+                .await
+            {
+                log::warn!("Failed to apply the moderation verdict to memory: {e}");
+            }
+        }
+        verdict
+    }
+
+    /// Set the factory used to create the memory of each session
+    pub fn with_memory_factory(mut self, memory_factory: Option<MemoryFactory>) -> Self {
+        self.memory_factory = memory_factory;
+        self
+    }
+
     pub fn inner(&self) -> Arc<T> {
         self.inner.clone()
     }
@@ -121,25 +256,42 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks, A: AgentType> BaseAgent<T, A>
         self.stream
     }
 
-    pub(crate) fn create_context(&self) -> Arc<Context> {
-        Arc::new(
+    pub(crate) fn create_context(&self) -> Result<Arc<Context>, RunnableAgentError> {
+        self.create_context_with_memory(self.memory())
+    }
+
+    /// Create a run context backed by the given memory instead of the agent's own
+    pub(crate) fn create_context_with_memory(
+        &self,
+        memory: Option<Arc<Mutex<Box<dyn MemoryProvider>>>>,
+    ) -> Result<Arc<Context>, RunnableAgentError> {
+        Ok(Arc::new(
             Context::new(self.llm(), self.tx.clone())
-                .with_memory(self.memory())
+                .with_memory(memory)
                 .with_tools(self.tools())
-                .with_config(self.agent_config())
-                .with_stream(self.stream()),
-        )
+                .with_config(self.agent_config()?)
+                .with_stream(self.stream())
+                .with_vars(self.vars.clone()),
+        ))
     }
 
-    pub fn agent_config(&self) -> AgentConfig {
-        let output_schema = self.inner().output_schema();
-        let structured_schema = output_schema.map(|schema| serde_json::from_value(schema).unwrap());
-        AgentConfig {
+    /// Build the agent configuration, parsing the derived output schema if present
+    pub fn agent_config(&self) -> Result<AgentConfig, RunnableAgentError> {
+        let structured_schema = self
+            .inner()
+            .output_schema()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| {
+                RunnableAgentError::SerializationError(format!("Invalid output schema: {e}"))
+            })?;
+        Ok(AgentConfig {
             name: self.name().into(),
             description: self.description().into(),
+            instructions: self.inner().instructions().into(),
             id: self.id,
             output_schema: structured_schema,
-        }
+        })
     }
 
     /// Get the LLM provider
@@ -170,6 +322,7 @@ mod tests {
             name: "test_agent".to_string(),
             id: Uuid::new_v4(),
             description: "A test agent".to_string(),
+            instructions: String::new(),
             output_schema: None,
         };
 
@@ -191,6 +344,7 @@ mod tests {
             name: "test_agent".to_string(),
             id: Uuid::new_v4(),
             description: "A test agent".to_string(),
+            instructions: String::new(),
             output_schema: Some(schema.clone()),
         };
 
@@ -284,4 +438,76 @@ mod tests {
         assert!(base_agent.memory().is_none());
         assert!(base_agent.stream);
     }
+
+    struct FixedTranscriber;
+
+    #[async_trait]
+    impl SpeechToTextProvider for FixedTranscriber {
+        async fn transcribe(
+            &self,
+            request: &TranscriptionRequest,
+        ) -> Result<autoagents_llm::audio::Transcription, autoagents_llm::error::LLMError> {
+            assert_eq!(request.filename, "clip.wav");
+            assert_eq!(request.language.as_deref(), Some("en"));
+            Ok(autoagents_llm::audio::Transcription {
+                text: "Book a table for two".to_string(),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_transcribes_audio_input() {
+        use crate::agent::task::AudioInput;
+
+        let mock_agent = MockAgentImpl::new("voice", "voice assistant");
+        let (tx, _rx): (Sender<Event>, Receiver<Event>) = channel(32);
+        let base_agent = BaseAgent::<_, DirectAgent>::new(
+            mock_agent,
+            Arc::new(MockLLMProvider),
+            None,
+            tx,
+            false,
+        )
+        .await
+        .unwrap()
+        .with_speech_to_text(Some(Arc::new(FixedTranscriber)));
+
+        let audio = AudioInput::new("clip.wav", vec![1, 2, 3]).language("en");
+        let output = base_agent
+            .run(Task::new("").with_audio(audio.clone()))
+            .await
+            .unwrap();
+        assert_eq!(output.result, "Processed: Book a table for two");
+
+        let output = base_agent
+            .run(Task::new("Reply in French.").with_audio(audio))
+            .await
+            .unwrap();
+        assert_eq!(
+            output.result,
+            "Processed: Reply in French.\n\nBook a table for two"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_audio_input_requires_speech_to_text() {
+        use crate::agent::task::AudioInput;
+
+        let mock_agent = MockAgentImpl::new("voice", "voice assistant");
+        let (tx, _rx): (Sender<Event>, Receiver<Event>) = channel(32);
+        let base_agent = BaseAgent::<_, DirectAgent>::new(
+            mock_agent,
+            Arc::new(MockLLMProvider),
+            None,
+            tx,
+            false,
+        )
+        .await
+        .unwrap();
+
+        let task = Task::new("").with_audio(AudioInput::new("clip.wav", vec![1]));
+        let error = base_agent.run(task).await.unwrap_err();
+        assert!(matches!(error, RunnableAgentError::TaskError(_)));
+    }
 }
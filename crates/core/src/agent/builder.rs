@@ -3,11 +3,16 @@ use crate::actor::Topic;
 use crate::agent::base::AgentType;
 use crate::agent::hooks::AgentHooks;
 use crate::agent::memory::MemoryProvider;
+use crate::agent::moderation::ModerationGuard;
+use crate::agent::session::MemoryFactory;
 use crate::agent::task::Task;
 use crate::agent::{AgentDeriveT, AgentExecutor};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::runtime::Runtime;
+use autoagents_llm::audio::SpeechToTextProvider;
 use autoagents_llm::LLMProvider;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::sync::Arc;
 
@@ -17,6 +22,10 @@ pub struct AgentBuilder<T: AgentDeriveT + AgentExecutor + AgentHooks, A: AgentTy
     pub(crate) stream: bool,
     pub(crate) llm: Option<Arc<dyn LLMProvider>>,
     pub(crate) memory: Option<Box<dyn MemoryProvider>>,
+    pub(crate) memory_factory: Option<MemoryFactory>,
+    pub(crate) vars: HashMap<String, Value>,
+    pub(crate) speech_to_text: Option<Arc<dyn SpeechToTextProvider>>,
+    pub(crate) moderation: Option<ModerationGuard>,
     #[cfg(not(target_arch = "wasm32"))]
     pub(crate) runtime: Option<Arc<dyn Runtime>>,
     #[cfg(not(target_arch = "wasm32"))]
@@ -31,6 +40,10 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks, A: AgentType> AgentBuilder<T,
             inner,
             llm: None,
             memory: None,
+            memory_factory: None,
+            vars: HashMap::new(),
+            speech_to_text: None,
+            moderation: None,
             #[cfg(not(target_arch = "wasm32"))]
             runtime: None,
             stream: false,
@@ -57,6 +70,33 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks, A: AgentType> AgentBuilder<T,
         self
     }
 
+    /// Set the factory used to create the memory of each session
+    pub fn memory_factory<F>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> Box<dyn MemoryProvider> + Send + Sync + 'static,
+    {
+        self.memory_factory = Some(Arc::new(factory));
+        self
+    }
+
+    /// Set a variable available to the agent's instruction template
+    pub fn var(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.vars.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set the provider that transcribes the audio input of tasks
+    pub fn speech_to_text(mut self, speech_to_text: Arc<dyn SpeechToTextProvider>) -> Self {
+        self.speech_to_text = Some(speech_to_text);
+        self
+    }
+
+    /// Moderate the task prompt and the final output of every run
+    pub fn moderation(mut self, guard: ModerationGuard) -> Self {
+        self.moderation = Some(guard);
+        self
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn runtime(mut self, runtime: Arc<dyn Runtime>) -> Self {
         self.runtime = Some(runtime);
@@ -7,6 +7,9 @@ pub struct AgentConfig {
     pub name: String,
     /// The agent's description
     pub description: String,
+    /// The agent's instructions, a minijinja template used as the system prompt.
+    /// When empty, `description` is used verbatim instead.
+    pub instructions: String,
     /// The Agent ID
     pub id: ActorID,
     /// The output schema for the agent
@@ -18,6 +21,7 @@ impl AgentConfig {
         Self {
             name,
             description,
+            instructions: String::new(),
             id: ActorID::new_v4(),
             output_schema: None,
         }
@@ -1 +1,3 @@
 pub const DEFAULT_CHANNEL_BUFFER: usize = 1000;
+/// Window size of the default session memory when no memory factory is set
+pub const DEFAULT_SESSION_WINDOW_SIZE: usize = 100;
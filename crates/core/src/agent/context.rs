@@ -1,13 +1,15 @@
 #[cfg(not(target_arch = "wasm32"))]
 use crate::actor::{ActorMessage, Topic};
-use crate::agent::memory::MemoryProvider;
+use crate::agent::memory::{MemoryProvider, MessageHandle};
 use crate::agent::state::AgentState;
 use crate::agent::AgentConfig;
 use crate::protocol::Event;
 use crate::tool::ToolT;
 use autoagents_llm::chat::ChatMessage;
 use autoagents_llm::LLMProvider;
+use serde_json::Value;
 use std::any::Any;
+use std::collections::HashMap;
 use std::sync::Arc;
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::sync::{mpsc, Mutex};
@@ -26,6 +28,9 @@ pub struct Context {
     state: Arc<Mutex<AgentState>>,
     tx: Option<mpsc::Sender<Event>>,
     stream: bool,
+    vars: HashMap<String, Value>,
+    /// Final response stored in memory during the run
+    stored_response: std::sync::Mutex<Option<MessageHandle>>,
 }
 
 #[derive(Clone, Debug, thiserror::Error)]
@@ -48,6 +53,8 @@ impl Context {
             state: Arc::new(Mutex::new(AgentState::new())),
             stream: false,
             tx,
+            vars: HashMap::new(),
+            stored_response: std::sync::Mutex::new(None),
         }
     }
 
@@ -94,6 +101,18 @@ impl Context {
         self
     }
 
+    /// Set the variables available to instruction templates
+    pub fn with_vars(mut self, vars: HashMap<String, Value>) -> Self {
+        self.vars = vars;
+        self
+    }
+
+    /// Set a single variable available to instruction templates
+    pub fn with_var(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.vars.insert(key.into(), value.into());
+        self
+    }
+
     // Getters
     pub fn llm(&self) -> &Arc<dyn LLMProvider> {
         &self.llm
@@ -126,6 +145,21 @@ impl Context {
     pub fn stream(&self) -> bool {
         self.stream
     }
+
+    pub fn vars(&self) -> &HashMap<String, Value> {
+        &self.vars
+    }
+
+    /// Record where the run's final response was stored in memory, so output moderation
+    /// can amend exactly that message
+    pub fn set_stored_response(&self, handle: Option<MessageHandle>) {
+        *self.stored_response.lock().unwrap() = handle;
+    }
+
+    /// Where the run's final response was stored in memory, if it was
+    pub fn stored_response(&self) -> Option<MessageHandle> {
+        *self.stored_response.lock().unwrap()
+    }
 }
 
 #[cfg(test)]
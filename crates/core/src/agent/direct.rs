@@ -1,10 +1,14 @@
 use crate::agent::base::AgentType;
 use crate::agent::error::{AgentBuildError, RunnableAgentError};
+use crate::agent::executor::memory_helper::MemoryHelper;
 use crate::agent::task::Task;
-use crate::agent::{AgentBuilder, AgentDeriveT, AgentExecutor, AgentHooks, BaseAgent, HookOutcome};
+use crate::agent::{
+    AgentBuilder, AgentDeriveT, AgentExecutor, AgentHooks, BaseAgent, Context, HookOutcome,
+};
 use crate::error::Error;
 use crate::protocol::Event;
 use futures::Stream;
+use std::sync::Arc;
 
 use crate::agent::constants::DEFAULT_CHANNEL_BUFFER;
 
@@ -41,7 +45,12 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> AgentBuilder<T, DirectAgent>
         ))?;
         let (tx, rx): (Sender<Event>, Receiver<Event>) = channel(DEFAULT_CHANNEL_BUFFER);
         let agent: BaseAgent<T, DirectAgent> =
-            BaseAgent::<T, DirectAgent>::new(self.inner, llm, self.memory, tx, self.stream).await?;
+            BaseAgent::<T, DirectAgent>::new(self.inner, llm, self.memory, tx, self.stream)
+                .await?
+                .with_vars(self.vars)
+                .with_speech_to_text(self.speech_to_text)
+                .with_moderation(self.moderation)
+                .with_memory_factory(self.memory_factory);
         let stream = receiver_into_stream(rx);
         Ok(DirectAgentHandle::new(agent, stream))
     }
@@ -52,9 +61,23 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, DirectAgent> {
     where
         <T as AgentDeriveT>::Output: From<<T as AgentExecutor>::Output>,
     {
-        let context = self.create_context();
+        let context = self.create_context()?;
+        self.run_with_context(task, context).await
+    }
 
-        //Run Hook
+    /// Run a task with a prepared context
+    pub(crate) async fn run_with_context(
+        &self,
+        mut task: Task,
+        context: Arc<Context>,
+    ) -> Result<<T as AgentDeriveT>::Output, RunnableAgentError>
+    where
+        <T as AgentDeriveT>::Output: From<<T as AgentExecutor>::Output>,
+    {
+        //Run Hooks
+        self.transcribe_audio(&mut task).await?;
+        self.inner.on_prepare_task(&mut task, &context).await;
+        self.moderate_input(&mut task).await?;
         let hook_outcome = self.inner.on_run_start(&task, &context).await;
         match hook_outcome {
             HookOutcome::Abort => return Err(RunnableAgentError::Abort),
@@ -67,7 +90,11 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, DirectAgent> {
                 let output: <T as AgentExecutor>::Output = output;
 
                 //Extract Agent output into the desired type
-                let agent_out: <T as AgentDeriveT>::Output = output.into();
+                let mut agent_out: <T as AgentDeriveT>::Output = output.into();
+                if let Some(redacted) = self.moderate_output(&task, &agent_out, &context).await? {
+                    agent_out = redacted;
+                }
+                MemoryHelper::run_pending_update(&context.memory()).await;
 
                 //Run On complete Hook
                 self.inner
@@ -84,7 +111,7 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, DirectAgent> {
 
     pub async fn run_stream(
         &self,
-        task: Task,
+        mut task: Task,
     ) -> Result<
         std::pin::Pin<Box<dyn Stream<Item = Result<<T as AgentDeriveT>::Output, Error>> + Send>>,
         RunnableAgentError,
@@ -92,9 +119,12 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, DirectAgent> {
     where
         <T as AgentDeriveT>::Output: From<<T as AgentExecutor>::Output>,
     {
-        let context = self.create_context();
+        let context = self.create_context()?;
 
-        //Run Hook
+        //Run Hooks
+        self.transcribe_audio(&mut task).await?;
+        self.inner.on_prepare_task(&mut task, &context).await;
+        self.moderate_input(&mut task).await?;
         let hook_outcome = self.inner.on_run_start(&task, &context).await;
         match hook_outcome {
             HookOutcome::Abort => return Err(RunnableAgentError::Abort),
@@ -114,7 +144,15 @@ impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, DirectAgent> {
                     }
                 });
 
-                Ok(Box::pin(transformed_stream))
+                // Run the memory's pending update once the stream is drained
+                let memory = context.memory();
+                let pending_update = futures::stream::once(async move {
+                    MemoryHelper::run_pending_update(&memory).await;
+                    None
+                })
+                .filter_map(futures::future::ready);
+
+                Ok(Box::pin(transformed_stream.chain(pending_update)))
             }
             Err(e) => {
                 // Send error event for stream creation failure
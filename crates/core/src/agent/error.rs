@@ -46,6 +46,10 @@ pub enum RunnableAgentError {
     #[error("Abort the execution")]
     Abort,
 
+    /// Content was rejected by the agent's moderation guard
+    #[error("Blocked by moderation: {0}")]
+    ModerationBlocked(String),
+
     /// Generic error wrapper for any std::error::Error
     #[error(transparent)]
     Other(#[from] Box<dyn std::error::Error + Send + Sync>),
@@ -0,0 +1,582 @@
+use autoagents_llm::chat::{ChatMessage, ChatRole, MessageType};
+use autoagents_llm::LLMProvider;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+/// Context window used when the model's capabilities don't report one
+pub const DEFAULT_CONTEXT_WINDOW: usize = 8_192;
+
+/// Tokens added per message for role and formatting overhead
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Estimates the number of tokens in a text
+pub trait TokenCounter: Send + Sync {
+    fn count(&self, text: &str) -> usize;
+}
+
+/// Estimates about four characters per token, which is close enough for English text
+/// with most BPE tokenizers
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicTokenCounter;
+
+impl TokenCounter for HeuristicTokenCounter {
+    fn count(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(4)
+    }
+}
+
+/// Counts tokens exactly with a Hugging Face tokenizer
+#[cfg(feature = "tokenizers")]
+pub struct HuggingFaceTokenCounter {
+    tokenizer: tokenizers::Tokenizer,
+}
+
+#[cfg(feature = "tokenizers")]
+impl HuggingFaceTokenCounter {
+    pub fn new(tokenizer: tokenizers::Tokenizer) -> Self {
+        Self { tokenizer }
+    }
+
+    /// Load a `tokenizer.json` file
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, String> {
+        tokenizers::Tokenizer::from_file(path)
+            .map(Self::new)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "tokenizers")]
+impl TokenCounter for HuggingFaceTokenCounter {
+    fn count(&self, text: &str) -> usize {
+        match self.tokenizer.encode(text, false) {
+            Ok(encoding) => encoding.len(),
+            Err(_) => HeuristicTokenCounter.count(text),
+        }
+    }
+}
+
+/// What to do with the oldest turns when the conversation exceeds the budget
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BudgetStrategy {
+    /// Drop the oldest turns
+    #[default]
+    Trim,
+    /// Replace the oldest turns with an LLM-written summary, falling back to trimming
+    /// if summarization fails
+    Summarize,
+}
+
+/// Fits the messages sent to the LLM into the model's context window.
+///
+/// The leading system prompt and the latest user message (with everything after it) are
+/// always kept. Oversized tool results are truncated first, then the oldest turns are
+/// trimmed or summarized. Tool calls and their results are removed together, so the
+/// provider never receives a tool result without the call that produced it.
+///
+/// Unless a window is set with [`ContextBudget::new`], the window comes from the model's
+/// [`capabilities`](LLMProvider::capabilities), or [`DEFAULT_CONTEXT_WINDOW`] if unknown.
+#[derive(Clone)]
+pub struct ContextBudget {
+    context_window: Option<usize>,
+    reserved_output_tokens: Option<usize>,
+    max_tool_result_tokens: Option<usize>,
+    strategy: BudgetStrategy,
+    counter: Arc<dyn TokenCounter>,
+    /// Summary of the turns dropped last time, extended as more turns are dropped
+    summary: Arc<Mutex<Option<CachedSummary>>>,
+}
+
+/// Summary of the first `len` messages of the conversation history
+#[derive(Debug, Clone)]
+struct CachedSummary {
+    len: usize,
+    fingerprint: u64,
+    text: String,
+}
+
+impl Default for ContextBudget {
+    fn default() -> Self {
+        Self {
+            context_window: None,
+            reserved_output_tokens: None,
+            max_tool_result_tokens: None,
+            strategy: BudgetStrategy::default(),
+            counter: Arc::new(HeuristicTokenCounter),
+            summary: Arc::default(),
+        }
+    }
+}
+
+impl Debug for ContextBudget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextBudget")
+            .field("context_window", &self.context_window)
+            .field("reserved_output_tokens", &self.reserved_output_tokens)
+            .field("max_tool_result_tokens", &self.max_tool_result_tokens)
+            .field("strategy", &self.strategy)
+            .finish()
+    }
+}
+
+impl ContextBudget {
+    /// Create a budget for a context window of `context_window` tokens
+    pub fn new(context_window: usize) -> Self {
+        Self {
+            context_window: Some(context_window),
+            ..Self::default()
+        }
+    }
+
+    /// Tokens kept free for the model's response, by default an eighth of the window up
+    /// to 4096
+    pub fn with_reserved_output_tokens(mut self, tokens: usize) -> Self {
+        self.reserved_output_tokens = Some(tokens);
+        self
+    }
+
+    /// Maximum size of a single tool result before it is truncated, by default a
+    /// quarter of the window
+    pub fn with_max_tool_result_tokens(mut self, tokens: usize) -> Self {
+        self.max_tool_result_tokens = Some(tokens);
+        self
+    }
+
+    pub fn with_strategy(mut self, strategy: BudgetStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn with_token_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.counter = counter;
+        self
+    }
+
+    /// Context window used for `llm`
+    pub fn context_window(&self, llm: &dyn LLMProvider) -> usize {
+        self.context_window.unwrap_or_else(|| {
+            llm.capabilities()
+                .and_then(|c| c.context_window)
+                .map_or(DEFAULT_CONTEXT_WINDOW, |window| window as usize)
+        })
+    }
+
+    /// Tokens available for the prompt sent to `llm`
+    pub fn input_budget(&self, llm: &dyn LLMProvider) -> usize {
+        let window = self.context_window(llm);
+        window.saturating_sub(self.reserved_output_tokens(window))
+    }
+
+    fn reserved_output_tokens(&self, window: usize) -> usize {
+        self.reserved_output_tokens
+            .unwrap_or((window / 8).min(4_096))
+    }
+
+    fn max_tool_result_tokens(&self, window: usize) -> usize {
+        self.max_tool_result_tokens.unwrap_or((window / 4).max(256))
+    }
+
+    /// Estimated token count of a conversation
+    pub fn count_messages(&self, messages: &[ChatMessage]) -> usize {
+        messages.iter().map(|m| self.count_message(m)).sum()
+    }
+
+    fn count_message(&self, message: &ChatMessage) -> usize {
+        let calls = match &message.message_type {
+            MessageType::ToolUse(calls) | MessageType::ToolResult(calls) => calls
+                .iter()
+                .map(|call| {
+                    self.counter.count(&call.function.name)
+                        + self.counter.count(&call.function.arguments)
+                })
+                .sum(),
+            _ => 0,
+        };
+        MESSAGE_OVERHEAD_TOKENS + self.counter.count(&message.content) + calls
+    }
+
+    /// Fit the messages into the budget
+    pub async fn fit(
+        &self,
+        messages: Vec<ChatMessage>,
+        llm: &Arc<dyn LLMProvider>,
+    ) -> Vec<ChatMessage> {
+        let window = self.context_window(llm.as_ref());
+        let max_tool_result_tokens = self.max_tool_result_tokens(window);
+        let mut messages: Vec<ChatMessage> = messages
+            .into_iter()
+            .map(|m| self.truncate_tool_result(m, max_tool_result_tokens))
+            .collect();
+
+        let budget = self.input_budget(llm.as_ref());
+        if self.count_messages(&messages) <= budget {
+            return messages;
+        }
+
+        // Split into the pinned system prompt, droppable turns and the pinned current turn
+        let system_len = messages
+            .iter()
+            .take_while(|m| m.role == ChatRole::System)
+            .count();
+        let current_start = current_turn_start(&messages).max(system_len);
+        let current = messages.split_off(current_start);
+        let history = messages.split_off(system_len);
+        let mut system = messages;
+
+        let mut turns = group_turns(history);
+        let fixed = self.count_messages(&system) + self.count_messages(&current);
+        let mut dropped = Vec::new();
+        while !turns.is_empty()
+            && fixed + turns.iter().map(|t| self.count_messages(t)).sum::<usize>() > budget
+        {
+            dropped.extend(turns.remove(0));
+        }
+
+        if self.strategy == BudgetStrategy::Summarize && !dropped.is_empty() {
+            match self.summarize(&dropped, llm, max_tool_result_tokens).await {
+                Some(summary) => attach_summary(&mut system, summary),
+                None => log::warn!("Context summarization failed, dropping oldest turns"),
+            }
+        }
+        if !dropped.is_empty() {
+            log::debug!(
+                "Context budget of {budget} tokens exceeded, removed {} message(s)",
+                dropped.len()
+            );
+        }
+
+        system.extend(turns.into_iter().flatten());
+        system.extend(current);
+        if self.count_messages(&system) > budget {
+            log::warn!(
+                "Messages still exceed the context budget of {budget} tokens after trimming"
+            );
+        }
+        system
+    }
+
+    fn truncate_tool_result(&self, mut message: ChatMessage, max_tokens: usize) -> ChatMessage {
+        if let MessageType::ToolResult(calls) = &mut message.message_type {
+            for call in calls {
+                call.function.arguments = self.truncate(&call.function.arguments, max_tokens);
+            }
+        }
+        if message.role == ChatRole::Tool {
+            message.content = self.truncate(&message.content, max_tokens);
+        }
+        message
+    }
+
+    fn truncate(&self, text: &str, max_tokens: usize) -> String {
+        let tokens = self.counter.count(text);
+        if tokens <= max_tokens {
+            return text.to_string();
+        }
+        let chars = text.chars().count();
+        let keep = chars * max_tokens / tokens;
+        let mut truncated: String = text.chars().take(keep).collect();
+        truncated.push_str(&format!(
+            "\n...[truncated, about {} tokens omitted]",
+            tokens - max_tokens
+        ));
+        truncated
+    }
+
+    /// Summarize the dropped turns. The summary of the turns dropped on an earlier call is
+    /// reused, so only turns dropped since then are sent to the LLM.
+    async fn summarize(
+        &self,
+        dropped: &[ChatMessage],
+        llm: &Arc<dyn LLMProvider>,
+        max_tokens: usize,
+    ) -> Option<String> {
+        let cached = self.summary.lock().unwrap().clone();
+        let (previous, new) = match cached {
+            Some(cached)
+                if cached.len <= dropped.len()
+                    && cached.fingerprint == fingerprint(&dropped[..cached.len]) =>
+            {
+                (Some(cached.text), &dropped[cached.len..])
+            }
+            _ => (None, dropped),
+        };
+        if new.is_empty() {
+            return previous;
+        }
+
+        let transcript = new
+            .iter()
+            .map(|m| format!("{:?}: {}", m.role, message_text(m)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let transcript = self.truncate(&transcript, max_tokens);
+        let prompt = match &previous {
+            Some(previous) => format!(
+                "Update this summary of a conversation with the messages that follow it, in a \
+                 few sentences. Keep facts, decisions and tool results that are needed to \
+                 continue it.\n\nSummary:\n{previous}\n\nMessages:\n{transcript}"
+            ),
+            None => format!(
+                "Summarize the following conversation in a few sentences. Keep facts, decisions \
+                 and tool results that are needed to continue it.\n\n{transcript}"
+            ),
+        };
+        let request = [ChatMessage::user().content(prompt).build()];
+        let response = llm.chat(&request, None, None).await.ok()?;
+        let summary = response.text().filter(|s| !s.trim().is_empty())?;
+
+        *self.summary.lock().unwrap() = Some(CachedSummary {
+            len: dropped.len(),
+            fingerprint: fingerprint(dropped),
+            text: summary.clone(),
+        });
+        Some(summary)
+    }
+}
+
+/// Identifies a run of messages, to tell whether a cached summary still covers them
+fn fingerprint(messages: &[ChatMessage]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for message in messages {
+        format!("{:?}", message.role).hash(&mut hasher);
+        message_text(message).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Index of the latest block of consecutive user messages: the current task prompt with its
+/// attachments, or the latest observation
+fn current_turn_start(messages: &[ChatMessage]) -> usize {
+    let Some(last_user) = messages.iter().rposition(|m| m.role == ChatRole::User) else {
+        return messages.len();
+    };
+    messages[..last_user]
+        .iter()
+        .rposition(|m| m.role != ChatRole::User)
+        .map_or(0, |i| i + 1)
+}
+
+fn message_text(message: &ChatMessage) -> String {
+    match &message.message_type {
+        MessageType::ToolUse(calls) | MessageType::ToolResult(calls) => calls
+            .iter()
+            .map(|c| format!("{}({})", c.function.name, c.function.arguments))
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => message.content.clone(),
+    }
+}
+
+fn attach_summary(system: &mut Vec<ChatMessage>, summary: String) {
+    let summary = format!("Summary of the earlier conversation:\n{summary}");
+    match system.last_mut() {
+        Some(message) => {
+            message.content.push_str("\n\n");
+            message.content.push_str(&summary);
+        }
+        None => system.push(ChatMessage::user().content(summary).build()),
+    }
+}
+
+/// Group messages so that tool calls stay together with their results. Tool results
+/// without a preceding call are dropped, since providers reject them.
+fn group_turns(messages: Vec<ChatMessage>) -> Vec<Vec<ChatMessage>> {
+    let mut turns: Vec<Vec<ChatMessage>> = Vec::new();
+    for message in messages {
+        let is_result = matches!(message.message_type, MessageType::ToolResult(_));
+        if is_result {
+            match turns.last_mut() {
+                Some(turn)
+                    if turn.iter().any(|m| {
+                        matches!(
+                            m.message_type,
+                            MessageType::ToolUse(_) | MessageType::ToolResult(_)
+                        )
+                    }) =>
+                {
+                    turn.push(message)
+                }
+                _ => {}
+            }
+        } else {
+            turns.push(vec![message]);
+        }
+    }
+    turns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use autoagents_llm::models::ModelCapabilities;
+    use autoagents_llm::{FunctionCall, ToolCall};
+    use autoagents_test_utils::llm::ScriptedLLMProvider;
+
+    fn tool_call(name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: format!("call_{name}"),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: name.to_string(),
+                arguments: arguments.to_string(),
+            },
+        }
+    }
+
+    fn text(role: ChatRole, content: &str) -> ChatMessage {
+        ChatMessage {
+            role,
+            message_type: MessageType::Text,
+            content: content.to_string(),
+        }
+    }
+
+    fn llm(responses: &[&str]) -> Arc<dyn LLMProvider> {
+        Arc::new(ScriptedLLMProvider::new(responses.to_vec()))
+    }
+
+    #[test]
+    fn test_context_window_from_capabilities() {
+        let llm =
+            ScriptedLLMProvider::new(Vec::<String>::new()).with_capabilities(ModelCapabilities {
+                context_window: Some(32_000),
+                ..ModelCapabilities::default()
+            });
+        let budget = ContextBudget::default();
+        assert_eq!(budget.context_window(&llm), 32_000);
+        assert_eq!(budget.input_budget(&llm), 32_000 - 4_000);
+        assert_eq!(ContextBudget::new(1_000).context_window(&llm), 1_000);
+
+        let unknown = ScriptedLLMProvider::new(Vec::<String>::new());
+        assert_eq!(budget.context_window(&unknown), DEFAULT_CONTEXT_WINDOW);
+    }
+
+    #[tokio::test]
+    async fn test_messages_within_budget_are_unchanged() {
+        let budget = ContextBudget::new(1_000);
+        let messages = vec![
+            text(ChatRole::System, "system"),
+            text(ChatRole::User, "hello"),
+        ];
+        let fitted = budget.fit(messages.clone(), &llm(&[])).await;
+        assert_eq!(fitted.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_trims_oldest_turns_and_keeps_tool_pairs() {
+        let budget = ContextBudget::new(100).with_reserved_output_tokens(0);
+        let filler = "x".repeat(300);
+        let messages = vec![
+            text(ChatRole::System, "system"),
+            text(ChatRole::User, &filler),
+            ChatMessage {
+                role: ChatRole::Assistant,
+                message_type: MessageType::ToolUse(vec![tool_call("search", "{}")]),
+                content: String::new(),
+            },
+            ChatMessage {
+                role: ChatRole::Tool,
+                message_type: MessageType::ToolResult(vec![tool_call("search", &filler)]),
+                content: String::new(),
+            },
+            text(ChatRole::Assistant, "found it"),
+            text(ChatRole::User, "what now?"),
+        ];
+
+        let fitted = budget.fit(messages, &llm(&[])).await;
+        let contents: Vec<&str> = fitted.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["system", "found it", "what now?"]);
+        assert!(budget.count_messages(&fitted) <= 100);
+    }
+
+    #[tokio::test]
+    async fn test_orphan_tool_results_are_dropped() {
+        let budget = ContextBudget::new(60).with_reserved_output_tokens(0);
+        let messages = vec![
+            ChatMessage {
+                role: ChatRole::Tool,
+                message_type: MessageType::ToolResult(vec![tool_call("search", "old")]),
+                content: String::new(),
+            },
+            text(ChatRole::Assistant, &"y".repeat(400)),
+            text(ChatRole::User, "next"),
+        ];
+        let fitted = budget.fit(messages, &llm(&[])).await;
+        assert_eq!(fitted.len(), 1);
+        assert_eq!(fitted[0].content, "next");
+    }
+
+    #[tokio::test]
+    async fn test_truncates_large_tool_results() {
+        let budget = ContextBudget::new(10_000).with_max_tool_result_tokens(10);
+        let messages = vec![ChatMessage {
+            role: ChatRole::Tool,
+            message_type: MessageType::ToolResult(vec![tool_call("dump", &"z".repeat(4_000))]),
+            content: String::new(),
+        }];
+        let fitted = budget.fit(messages, &llm(&[])).await;
+        let MessageType::ToolResult(calls) = &fitted[0].message_type else {
+            panic!("expected tool result");
+        };
+        assert!(calls[0].function.arguments.starts_with(&"z".repeat(40)));
+        assert!(calls[0].function.arguments.contains("[truncated"));
+        assert!(calls[0].function.arguments.len() < 200);
+    }
+
+    #[tokio::test]
+    async fn test_summarizes_dropped_turns() {
+        let budget = ContextBudget::new(100)
+            .with_reserved_output_tokens(0)
+            .with_strategy(BudgetStrategy::Summarize);
+        let messages = vec![
+            text(ChatRole::System, "system"),
+            text(ChatRole::User, &"my name is Ada ".repeat(20)),
+            text(ChatRole::Assistant, "nice to meet you"),
+            text(ChatRole::User, "what is my name?"),
+        ];
+        let fitted = budget.fit(messages, &llm(&["The user is Ada."])).await;
+        assert_eq!(fitted.len(), 3);
+        assert!(fitted[0].content.ends_with("The user is Ada."));
+        assert_eq!(fitted[2].content, "what is my name?");
+    }
+
+    #[tokio::test]
+    async fn test_reuses_summary_of_already_dropped_turns() {
+        let budget = ContextBudget::new(100)
+            .with_reserved_output_tokens(0)
+            .with_strategy(BudgetStrategy::Summarize);
+        let scripted = Arc::new(ScriptedLLMProvider::new([
+            "The user is Ada.",
+            "The user is Ada, a mathematician.",
+        ]));
+        let llm: Arc<dyn LLMProvider> = scripted.clone();
+        let mut messages = vec![
+            text(ChatRole::System, "system"),
+            text(ChatRole::User, &"my name is Ada ".repeat(20)),
+            text(ChatRole::Assistant, "nice to meet you"),
+            text(ChatRole::User, "what is my name?"),
+        ];
+
+        budget.fit(messages.clone(), &llm).await;
+        let fitted = budget.fit(messages.clone(), &llm).await;
+        assert_eq!(scripted.requests().len(), 1);
+        assert!(fitted[0].content.ends_with("The user is Ada."));
+
+        messages.push(text(
+            ChatRole::Assistant,
+            &"I am a mathematician ".repeat(20),
+        ));
+        messages.push(text(ChatRole::User, "what do I do?"));
+        let fitted = budget.fit(messages, &llm).await;
+        let requests = scripted.requests();
+        assert_eq!(requests.len(), 2);
+        let prompt = &requests[1][0].content;
+        assert!(prompt.contains("The user is Ada."));
+        assert!(prompt.contains("mathematician"));
+        assert!(!prompt.contains("my name is Ada"));
+        assert!(fitted[0]
+            .content
+            .ends_with("The user is Ada, a mathematician."));
+    }
+}
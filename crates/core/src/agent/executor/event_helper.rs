@@ -104,6 +104,15 @@ impl EventHelper {
         Self::send(tx, Event::StreamChunk { sub_id, chunk }).await;
     }
 
+    /// Send stream reasoning event
+    pub async fn send_stream_reasoning(
+        tx: &Option<mpsc::Sender<Event>>,
+        sub_id: SubmissionId,
+        reasoning: String,
+    ) {
+        Self::send(tx, Event::StreamReasoning { sub_id, reasoning }).await;
+    }
+
     /// Send stream tool call event
     pub async fn send_stream_tool_call(
         tx: &Option<mpsc::Sender<Event>>,
@@ -0,0 +1,232 @@
+use crate::agent::context::Context;
+use crate::agent::task::Task;
+use autoagents_llm::chat::{ChatMessage, ChatRole};
+use minijinja::{Environment, UndefinedBehavior};
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+/// Number of recent messages included in `memory_summary`
+const MEMORY_SUMMARY_MESSAGES: usize = 10;
+/// Maximum characters kept per message in `memory_summary`
+const MEMORY_SUMMARY_MESSAGE_CHARS: usize = 200;
+
+/// Error returned when the agent instructions cannot be rendered
+#[derive(Debug, Error)]
+pub enum InstructionError {
+    #[error("Failed to render instruction template: {0}")]
+    Render(String),
+}
+
+/// Helper for rendering agent instructions as minijinja templates.
+///
+/// Only the instructions are rendered; an agent without instructions uses its
+/// description verbatim.
+///
+/// The following variables are available in templates:
+///
+/// * `name`, `description` - the agent metadata
+/// * `task` - the current task (`task.prompt`, `task.submission_id`, `task.vars`, `task.metadata`)
+/// * `date` (`YYYY-MM-DD`) and `datetime` (RFC 3339) - the current UTC time
+/// * `tools` - list of `{name, description, schema}`
+/// * `memory` - list of `{role, content}` recalled from memory, and `memory_summary`,
+///   a short text summary of the most recent messages
+/// * `vars` - user variables from `Context` and `Task`, where task variables take precedence.
+///   User variables are also available at the top level if they don't shadow a built-in.
+pub struct InstructionRenderer;
+
+impl InstructionRenderer {
+    /// Render the system instructions for the given task
+    pub fn render(
+        context: &Context,
+        task: &Task,
+        memory: &[ChatMessage],
+    ) -> Result<String, InstructionError> {
+        let config = context.config();
+        let template = &config.instructions;
+
+        // Descriptions and plain instructions are returned verbatim
+        if template.is_empty() {
+            return Ok(config.description.clone());
+        }
+        if !Self::is_template(template) {
+            return Ok(template.clone());
+        }
+
+        let mut vars = context.vars().clone();
+        vars.extend(task.vars.clone());
+
+        let tools: Vec<Value> = context
+            .tools()
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.name(),
+                    "description": tool.description(),
+                    "schema": tool.args_schema(),
+                })
+            })
+            .collect();
+        let memory_values: Vec<Value> = memory
+            .iter()
+            .map(|message| json!({"role": Self::role_name(&message.role), "content": message.content}))
+            .collect();
+        let now = chrono::Utc::now();
+
+        let mut template_context: Map<String, Value> =
+            vars.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        template_context.extend([
+            ("name".to_string(), json!(config.name)),
+            ("description".to_string(), json!(config.description)),
+            (
+                "task".to_string(),
+                json!({
+                    "prompt": task.prompt,
+                    "submission_id": task.submission_id.to_string(),
+                    "vars": task.vars,
+                    "metadata": task.metadata,
+                }),
+            ),
+            (
+                "date".to_string(),
+                json!(now.format("%Y-%m-%d").to_string()),
+            ),
+            ("datetime".to_string(), json!(now.to_rfc3339())),
+            ("tools".to_string(), Value::Array(tools)),
+            ("memory".to_string(), Value::Array(memory_values)),
+            (
+                "memory_summary".to_string(),
+                json!(Self::memory_summary(memory)),
+            ),
+            ("vars".to_string(), json!(vars)),
+        ]);
+
+        Self::render_template(template, &Value::Object(template_context))
+    }
+
+    /// Render a template string with the given variables
+    pub fn render_template(template: &str, vars: &Value) -> Result<String, InstructionError> {
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        // Printing a missing variable is an error, but `{% if var %}` checks still work
+        env.set_undefined_behavior(UndefinedBehavior::SemiStrict);
+        env.render_str(template, vars)
+            .map_err(|e| InstructionError::Render(e.to_string()))
+    }
+
+    fn is_template(text: &str) -> bool {
+        text.contains("{{") || text.contains("{%") || text.contains("{#")
+    }
+
+    fn role_name(role: &ChatRole) -> &'static str {
+        match role {
+            ChatRole::System => "system",
+            ChatRole::User => "user",
+            ChatRole::Assistant => "assistant",
+            ChatRole::Tool => "tool",
+        }
+    }
+
+    fn memory_summary(memory: &[ChatMessage]) -> String {
+        let start = memory.len().saturating_sub(MEMORY_SUMMARY_MESSAGES);
+        memory[start..]
+            .iter()
+            .filter(|message| !message.content.is_empty())
+            .map(|message| {
+                let mut content: String = message
+                    .content
+                    .chars()
+                    .take(MEMORY_SUMMARY_MESSAGE_CHARS)
+                    .collect();
+                if message.content.chars().count() > MEMORY_SUMMARY_MESSAGE_CHARS {
+                    content.push_str("...");
+                }
+                format!("{}: {}", Self::role_name(&message.role), content)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentConfig;
+    use autoagents_test_utils::llm::MockLLMProvider;
+    use std::sync::Arc;
+
+    fn context(instructions: &str) -> Context {
+        let mut config = AgentConfig::new("helper".to_string(), "A helper".to_string());
+        config.instructions = instructions.to_string();
+        Context::new(Arc::new(MockLLMProvider), None)
+            .with_config(config)
+            .with_var("company", "Acme")
+    }
+
+    #[test]
+    fn test_plain_instructions_are_unchanged() {
+        let rendered = InstructionRenderer::render(
+            &context("Be nice. {not a template}"),
+            &Task::new("hi"),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(rendered, "Be nice. {not a template}");
+    }
+
+    #[test]
+    fn test_falls_back_to_description() {
+        let rendered = InstructionRenderer::render(&context(""), &Task::new("hi"), &[]).unwrap();
+        assert_eq!(rendered, "A helper");
+    }
+
+    #[test]
+    fn test_description_is_never_rendered() {
+        let config = AgentConfig::new(
+            "helper".to_string(),
+            "Replies with {{ json }} like {% raw %}".to_string(),
+        );
+        let context = Context::new(Arc::new(MockLLMProvider), None).with_config(config);
+        let rendered = InstructionRenderer::render(&context, &Task::new("hi"), &[]).unwrap();
+        assert_eq!(rendered, "Replies with {{ json }} like {% raw %}");
+    }
+
+    #[test]
+    fn test_renders_task_vars_and_builtins() {
+        let template = "{{ name }} for {{ company }} helping {{ vars.user }} with: {{ task.prompt }} on {{ date }}";
+        let task = Task::new("write a poem").with_var("user", "Ada");
+        let rendered = InstructionRenderer::render(&context(template), &task, &[]).unwrap();
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        assert_eq!(
+            rendered,
+            format!("helper for Acme helping Ada with: write a poem on {today}")
+        );
+    }
+
+    #[test]
+    fn test_task_vars_override_context_vars() {
+        let task = Task::new("hi").with_var("company", "Globex");
+        let rendered = InstructionRenderer::render(&context("{{ company }}"), &task, &[]).unwrap();
+        assert_eq!(rendered, "Globex");
+    }
+
+    #[test]
+    fn test_memory_summary_and_conditionals() {
+        let memory = vec![
+            ChatMessage::user().content("My name is Ada").build(),
+            ChatMessage::assistant().content("Hello Ada").build(),
+        ];
+        let template = "{% if missing %}never{% endif %}{{ memory_summary }}|{{ memory | length }}";
+        let rendered =
+            InstructionRenderer::render(&context(template), &Task::new("hi"), &memory).unwrap();
+        assert_eq!(rendered, "user: My name is Ada\nassistant: Hello Ada|2");
+    }
+
+    #[test]
+    fn test_undefined_variable_is_an_error() {
+        let err = InstructionRenderer::render(&context("Hi {{ nobody }}"), &Task::new("hi"), &[])
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Failed to render instruction template"));
+    }
+}
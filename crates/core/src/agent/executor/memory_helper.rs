@@ -1,9 +1,12 @@
-use crate::agent::memory::MemoryProvider;
+use crate::agent::memory::{MemoryProvider, MessageHandle};
+use crate::agent::task::Task;
+use crate::agent::Context;
 use crate::tool::ToolCallResult;
 use autoagents_llm::chat::{ChatMessage, ChatRole, ImageMime, MessageType};
 use autoagents_llm::ToolCall;
 use std::sync::Arc;
 
+use super::text_tool_protocol::TextToolProtocol;
 use super::tool_processor::ToolProcessor;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -36,6 +39,7 @@ impl MemoryHelper {
     ) {
         if let Some(mem) = memory {
             let mut mem = mem.lock().await;
+            let (tool_results, images) = Self::split_tool_images(tool_results);
 
             // Record assistant calling tools
             let _ = mem
@@ -48,7 +52,7 @@ impl MemoryHelper {
 
             // Create and store tool results
             let result_tool_calls =
-                ToolProcessor::create_result_tool_calls(tool_calls, tool_results);
+                ToolProcessor::create_result_tool_calls(tool_calls, &tool_results);
 
             let _ = mem
                 .remember(&ChatMessage {
@@ -57,9 +61,78 @@ impl MemoryHelper {
                     content: String::new(),
                 })
                 .await;
+
+            for message in images {
+                let _ = mem.remember(&message).await;
+            }
+        }
+    }
+
+    /// Store a text-protocol tool interaction, formatting the observation from the results
+    pub async fn store_text_tool_results(
+        memory: &Option<Arc<Mutex<Box<dyn MemoryProvider>>>>,
+        response_text: &str,
+        tool_results: &[ToolCallResult],
+    ) {
+        let (tool_results, images) = Self::split_tool_images(tool_results);
+        Self::store_text_tool_interaction(
+            memory,
+            response_text,
+            TextToolProtocol::format_observation(&tool_results),
+        )
+        .await;
+        for message in images {
+            Self::store_message(memory, message).await;
+        }
+    }
+
+    /// Store a text-protocol tool interaction: the model's action and the observation
+    pub async fn store_text_tool_interaction(
+        memory: &Option<Arc<Mutex<Box<dyn MemoryProvider>>>>,
+        response_text: &str,
+        observation: String,
+    ) {
+        if let Some(mem) = memory {
+            let mut mem = mem.lock().await;
+            let _ = mem
+                .remember(&ChatMessage {
+                    role: ChatRole::Assistant,
+                    message_type: MessageType::Text,
+                    content: response_text.to_string(),
+                })
+                .await;
+            let _ = mem
+                .remember(&ChatMessage {
+                    role: ChatRole::User,
+                    message_type: MessageType::Text,
+                    content: observation,
+                })
+                .await;
         }
     }
 
+    /// Move the images attached to tool results into user image messages
+    fn split_tool_images(
+        tool_results: &[ToolCallResult],
+    ) -> (Vec<ToolCallResult>, Vec<ChatMessage>) {
+        let mut images = Vec::new();
+        let tool_results = tool_results
+            .iter()
+            .cloned()
+            .map(|mut result| {
+                for (mime, data) in result.take_images() {
+                    images.push(ChatMessage {
+                        role: ChatRole::User,
+                        message_type: MessageType::Image((mime, data)),
+                        content: format!("Image returned by the {} tool", result.tool_name),
+                    });
+                }
+                result
+            })
+            .collect();
+        (tool_results, images)
+    }
+
     /// Store user message in memory
     pub async fn store_user_message(
         memory: &Option<Arc<Mutex<Box<dyn MemoryProvider>>>>,
@@ -85,20 +158,35 @@ impl MemoryHelper {
         }
     }
 
-    /// Store assistant response in memory
+    /// Store the task prompt and its attachments in memory
+    pub async fn store_task(memory: &Option<Arc<Mutex<Box<dyn MemoryProvider>>>>, task: &Task) {
+        if let Some(mem) = memory {
+            let mut mem = mem.lock().await;
+            for message in task.to_chat_messages() {
+                let _ = mem.remember(&message).await;
+            }
+        }
+    }
+
+    /// Store assistant response in memory, returning its handle if the memory tracks
+    /// stored messages
     pub async fn store_assistant_response(
         memory: &Option<Arc<Mutex<Box<dyn MemoryProvider>>>>,
         response: String,
-    ) {
-        Self::store_message(
-            memory,
-            ChatMessage {
-                role: ChatRole::Assistant,
-                message_type: MessageType::Text,
-                content: response,
-            },
-        )
-        .await;
+    ) -> Option<MessageHandle> {
+        let mem = memory.as_ref()?;
+        let message = ChatMessage {
+            role: ChatRole::Assistant,
+            message_type: MessageType::Text,
+            content: response,
+        };
+        mem.lock().await.remember_tracked(&message).await.ok()?
+    }
+
+    /// Store the run's final response in memory and record it on the context
+    pub async fn store_final_response(context: &Context, response: String) {
+        let handle = Self::store_assistant_response(&context.memory(), response).await;
+        context.set_stored_response(handle);
     }
 
     /// Recall messages from memory
@@ -112,4 +200,24 @@ impl MemoryHelper {
         }
         Vec::new()
     }
+
+    /// Context the memory adds to the system prompt, if any
+    pub async fn system_context(
+        memory: &Option<Arc<Mutex<Box<dyn MemoryProvider>>>>,
+    ) -> Option<String> {
+        match memory {
+            Some(mem) => mem.lock().await.system_context(),
+            None => None,
+        }
+    }
+
+    /// Run the memory's pending update without holding the memory lock
+    pub async fn run_pending_update(memory: &Option<Arc<Mutex<Box<dyn MemoryProvider>>>>) {
+        if let Some(mem) = memory {
+            let update = mem.lock().await.take_pending_update();
+            if let Some(update) = update {
+                update.await;
+            }
+        }
+    }
 }
@@ -1,10 +1,17 @@
+pub mod context_budget;
 pub mod event_helper;
+pub mod instructions;
 pub mod memory_helper;
+pub mod output_validator;
+pub mod text_tool_protocol;
 pub mod tool_processor;
 
 use crate::agent::context::Context;
+use crate::agent::executor::context_budget::ContextBudget;
 use crate::agent::task::Task;
 use async_trait::async_trait;
+use autoagents_llm::chat::{ChatMessage, MessageType};
+use autoagents_llm::LLMProvider;
 use futures::Stream;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
@@ -21,18 +28,62 @@ pub enum TurnResult<T> {
     Complete(T),
 }
 
+/// How an executor asks the LLM to call tools
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ToolCallingMode {
+    /// Use native function calling, switching to the text protocol if the model's
+    /// capabilities lack tool calling or the provider reports `LLMError::NoToolSupport`
+    #[default]
+    Auto,
+    /// Always use the provider's native function calling
+    Native,
+    /// Always describe tools in the prompt and parse calls from the response text
+    Text,
+}
+
 /// Configuration for executors
 #[derive(Debug, Clone)]
 pub struct ExecutorConfig {
     pub max_turns: usize,
+    /// How many times the LLM is re-prompted when the final output does not match the
+    /// agent's output schema
+    pub structured_output_retries: usize,
+    /// How tools are offered to the LLM
+    pub tool_calling_mode: ToolCallingMode,
+    /// Keeps the messages sent to the LLM within the model's context window
+    pub context_budget: Option<ContextBudget>,
 }
 
 impl Default for ExecutorConfig {
     fn default() -> Self {
-        Self { max_turns: 10 }
+        Self {
+            max_turns: 10,
+            structured_output_retries: 2,
+            tool_calling_mode: ToolCallingMode::default(),
+            context_budget: None,
+        }
     }
 }
 
+/// Replace images and PDFs the model cannot read with their text, dropping messages left
+/// empty. Messages are kept as they are when the model's capabilities are unknown.
+pub(crate) fn strip_unsupported_media(messages: &mut Vec<ChatMessage>, llm: &dyn LLMProvider) {
+    let Some(capabilities) = llm.capabilities() else {
+        return;
+    };
+    messages.retain_mut(|message| {
+        if capabilities.supports_message(&message.message_type) {
+            return true;
+        }
+        log::debug!(
+            "Dropping media the model does not accept from a {:?} message",
+            message.role
+        );
+        message.message_type = MessageType::Text;
+        !message.content.is_empty()
+    });
+}
+
 /// Base trait for agent execution strategies
 ///
 /// Executors are responsible for implementing the specific execution logic
@@ -132,6 +183,7 @@ mod tests {
         fn config(&self) -> ExecutorConfig {
             ExecutorConfig {
                 max_turns: self.max_turns,
+                ..Default::default()
             }
         }
 
@@ -232,28 +284,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_strip_unsupported_media() {
+        use autoagents_llm::chat::ImageMime;
+        use autoagents_llm::models::ModelCapabilities;
+        use autoagents_test_utils::llm::ScriptedLLMProvider;
+
+        let messages = vec![
+            ChatMessage::user()
+                .image(ImageMime::PNG, vec![1, 2, 3])
+                .build(),
+            ChatMessage::user()
+                .image_url("https://example.com/cat.png")
+                .content("What is this?")
+                .build(),
+            ChatMessage::user().content("Hello").build(),
+        ];
+
+        // Unknown capabilities leave the messages untouched
+        let mut unchanged = messages.clone();
+        strip_unsupported_media(&mut unchanged, &ScriptedLLMProvider::new(["ok"]));
+        assert_eq!(unchanged.len(), 3);
+
+        let llm = ScriptedLLMProvider::new(["ok"]).with_capabilities(ModelCapabilities::default());
+        let mut stripped = messages.clone();
+        strip_unsupported_media(&mut stripped, &llm);
+        assert_eq!(stripped.len(), 2);
+        assert!(stripped
+            .iter()
+            .all(|m| matches!(m.message_type, MessageType::Text)));
+        assert_eq!(stripped[0].content, "What is this?");
+
+        let llm = ScriptedLLMProvider::new(["ok"]).with_capabilities(ModelCapabilities {
+            vision: true,
+            ..Default::default()
+        });
+        let mut kept = messages;
+        strip_unsupported_media(&mut kept, &llm);
+        assert_eq!(kept.len(), 3);
+    }
+
     #[test]
     fn test_executor_config_default() {
         let config = ExecutorConfig::default();
         assert_eq!(config.max_turns, 10);
+        assert_eq!(config.structured_output_retries, 2);
+        assert_eq!(config.tool_calling_mode, ToolCallingMode::Auto);
     }
 
     #[test]
     fn test_executor_config_custom() {
-        let config = ExecutorConfig { max_turns: 5 };
+        let config = ExecutorConfig {
+            max_turns: 5,
+            ..Default::default()
+        };
         assert_eq!(config.max_turns, 5);
     }
 
     #[test]
     fn test_executor_config_clone() {
-        let config = ExecutorConfig { max_turns: 15 };
+        let config = ExecutorConfig {
+            max_turns: 15,
+            ..Default::default()
+        };
         let cloned = config.clone();
         assert_eq!(config.max_turns, cloned.max_turns);
     }
 
     #[test]
     fn test_executor_config_debug() {
-        let config = ExecutorConfig { max_turns: 20 };
+        let config = ExecutorConfig {
+            max_turns: 20,
+            ..Default::default()
+        };
         let debug_str = format!("{config:?}");
         assert!(debug_str.contains("ExecutorConfig"));
         assert!(debug_str.contains("20"));
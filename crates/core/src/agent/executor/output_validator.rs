@@ -0,0 +1,151 @@
+use crate::agent::context::Context;
+use autoagents_llm::chat::ChatMessage;
+use autoagents_llm::validated_llm::{repair_until_valid, RepairError};
+use thiserror::Error;
+
+/// Error returned when the final agent response cannot be made to match the output schema
+#[derive(Debug, Error)]
+pub enum OutputValidationError {
+    #[error("LLM error while repairing output: {0}")]
+    LLMError(String),
+
+    #[error("Output did not match schema after {attempts} attempt(s): {}", errors.join("; "))]
+    ValidationFailed {
+        attempts: usize,
+        errors: Vec<String>,
+    },
+}
+
+/// Helper for enforcing the agent's structured output schema
+pub struct OutputValidator;
+
+impl OutputValidator {
+    /// Validate a final response against the agent's output schema, re-prompting the LLM
+    /// with the validation errors up to `retries` times.
+    ///
+    /// Returns the response unchanged when the agent has no output schema, otherwise the
+    /// validated JSON document serialized without surrounding prose or code fences.
+    pub async fn ensure_valid(
+        context: &Context,
+        messages: &[ChatMessage],
+        response_text: String,
+        retries: usize,
+    ) -> Result<String, OutputValidationError> {
+        let Some(schema) = context.config().output_schema.clone() else {
+            return Ok(response_text);
+        };
+
+        let repaired = repair_until_valid(
+            context.llm().as_ref(),
+            messages,
+            response_text.clone(),
+            None,
+            Some(&schema),
+            retries,
+            |text| schema.parse_response(text).err().unwrap_or_default(),
+        )
+        .await;
+        let text = match repaired {
+            Ok(None) => response_text,
+            Ok(Some(response)) => response.text().unwrap_or_default(),
+            Err(RepairError::LLM(e)) => return Err(OutputValidationError::LLMError(e.to_string())),
+            Err(RepairError::Invalid {
+                attempts, errors, ..
+            }) => return Err(OutputValidationError::ValidationFailed { attempts, errors }),
+        };
+        // Normalize to the bare JSON document, without fences or prose
+        schema
+            .parse_response(&text)
+            .map(|value| value.to_string())
+            .map_err(|errors| OutputValidationError::ValidationFailed {
+                attempts: retries + 1,
+                errors,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentConfig;
+    use autoagents_llm::chat::StructuredOutputFormat;
+    use autoagents_test_utils::llm::ScriptedLLMProvider;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn context_with_schema(llm: Arc<ScriptedLLMProvider>) -> Context {
+        Context::new(llm, None).with_config(
+            AgentConfig::new("test".to_string(), "test".to_string()).with_output_schema(
+                StructuredOutputFormat {
+                    name: "MathOut".to_string(),
+                    description: None,
+                    schema: Some(json!({
+                        "type": "object",
+                        "properties": {"value": {"type": "number"}},
+                        "required": ["value"]
+                    })),
+                    strict: Some(true),
+                },
+            ),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_valid_response_is_normalized() {
+        let llm = Arc::new(ScriptedLLMProvider::new(Vec::<String>::new()));
+        let context = context_with_schema(llm.clone());
+        let output = OutputValidator::ensure_valid(
+            &context,
+            &[],
+            "```json\n{\"value\": 4}\n```".to_string(),
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, r#"{"value":4}"#);
+        assert!(llm.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_response_is_repaired() {
+        let llm = Arc::new(ScriptedLLMProvider::new([r#"{"value": 4}"#]));
+        let context = context_with_schema(llm.clone());
+        let messages = vec![ChatMessage::user().content("2 + 2").build()];
+        let output = OutputValidator::ensure_valid(&context, &messages, "four".to_string(), 2)
+            .await
+            .unwrap();
+        assert_eq!(output, r#"{"value":4}"#);
+
+        let calls = llm.requests();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].len(), 3);
+        assert_eq!(calls[0][1].content, "four");
+        assert!(calls[0][2].content.contains("not valid JSON"));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_retries() {
+        let llm = Arc::new(ScriptedLLMProvider::new([r#"{"value": "x"}"#]));
+        let context = context_with_schema(llm.clone());
+        let err = OutputValidator::ensure_valid(&context, &[], "four".to_string(), 1)
+            .await
+            .unwrap_err();
+        match err {
+            OutputValidationError::ValidationFailed { attempts, errors } => {
+                assert_eq!(attempts, 2);
+                assert!(errors[0].contains("$.value: expected number"));
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_no_schema_passes_through() {
+        let llm = Arc::new(ScriptedLLMProvider::new(Vec::<String>::new()));
+        let context = Context::new(llm, None);
+        let output = OutputValidator::ensure_valid(&context, &[], "plain".to_string(), 2)
+            .await
+            .unwrap();
+        assert_eq!(output, "plain");
+    }
+}
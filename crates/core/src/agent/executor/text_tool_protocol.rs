@@ -0,0 +1,348 @@
+use crate::tool::{ToolCallResult, ToolT};
+use autoagents_llm::{FunctionCall, ToolCall};
+use serde_json::Value;
+
+/// Prefixes that mark the end of a model turn in the text protocol. Anything after an
+/// `Observation:` line was hallucinated by the model and is discarded.
+const OBSERVATION_PREFIX: &str = "observation:";
+const ACTION_PREFIX: &str = "action:";
+const ACTION_INPUT_PREFIX: &str = "action input:";
+const FINAL_ANSWER_PREFIX: &str = "final answer:";
+
+/// Keys accepted for the tool name and arguments in JSON-block tool calls
+const NAME_KEYS: [&str; 3] = ["tool", "name", "action"];
+const ARGUMENT_KEYS: [&str; 4] = ["arguments", "args", "action_input", "parameters"];
+
+/// Helper for prompt-based tool calling with providers that lack native function calling.
+///
+/// Tools are described in the system prompt, and the model answers either with
+/// `Action:` / `Action Input:` lines or with a JSON block such as
+/// `{"tool": "name", "arguments": {...}}`. Tool results are fed back as `Observation:` messages.
+pub struct TextToolProtocol;
+
+impl TextToolProtocol {
+    /// Render the tool descriptions and calling convention for the system prompt
+    pub fn render_instructions(tools: &[Box<dyn ToolT>]) -> String {
+        let mut prompt = String::from("You have access to the following tools:\n");
+        for tool in tools {
+            prompt.push_str(&format!(
+                "\n- {}: {}\n  Arguments (JSON schema): {}\n",
+                tool.name(),
+                tool.description(),
+                tool.args_schema()
+            ));
+        }
+        prompt.push_str(
+            "\nTo use a tool, respond with exactly the following format and then stop:\n\
+             Thought: <your reasoning>\n\
+             Action: <tool name>\n\
+             Action Input: <JSON object with the tool arguments>\n\
+             \n\
+             You may instead respond with a JSON code block: {\"tool\": \"<tool name>\", \"arguments\": {...}}\n\
+             The tool result will be sent back to you as \"Observation: <result>\". Never write the Observation yourself.\n\
+             When you have enough information to answer, respond with:\n\
+             Final Answer: <your answer>",
+        );
+        prompt
+    }
+
+    /// Parse the tool calls requested in a model response.
+    ///
+    /// Returns an empty list when the response is a final answer. JSON blocks are only
+    /// treated as tool calls when they name one of the available tools, so structured
+    /// final answers are never mistaken for actions.
+    pub fn parse_tool_calls(text: &str, tools: &[Box<dyn ToolT>]) -> Vec<ToolCall> {
+        let text = Self::strip_observations(text);
+
+        let calls = Self::parse_action_lines(text);
+        if !calls.is_empty() {
+            return calls;
+        }
+
+        if Self::final_answer_start(text).is_some() {
+            return Vec::new();
+        }
+        Self::parse_json_blocks(text, tools)
+    }
+
+    /// Extract the final answer from a response without tool calls
+    pub fn final_answer(text: &str) -> String {
+        match Self::final_answer_start(text) {
+            Some(start) => text[start..].trim().to_string(),
+            None => text.trim().to_string(),
+        }
+    }
+
+    /// The part of the response that belongs to the model, without hallucinated observations
+    pub fn strip_observations(text: &str) -> &str {
+        let mut offset = 0;
+        for line in text.split_inclusive('\n') {
+            if line
+                .trim_start()
+                .to_ascii_lowercase()
+                .starts_with(OBSERVATION_PREFIX)
+            {
+                return text[..offset].trim_end();
+            }
+            offset += line.len();
+        }
+        text.trim_end()
+    }
+
+    /// Format tool results as the observation message sent back to the model
+    pub fn format_observation(results: &[ToolCallResult]) -> String {
+        match results {
+            [result] => format!("Observation: {}", Self::result_text(result)),
+            _ => results
+                .iter()
+                .map(|result| {
+                    format!(
+                        "Observation ({}): {}",
+                        result.tool_name,
+                        Self::result_text(result)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    fn result_text(result: &ToolCallResult) -> String {
+        match &result.result {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    fn final_answer_start(text: &str) -> Option<usize> {
+        text.to_ascii_lowercase()
+            .rfind(FINAL_ANSWER_PREFIX)
+            .map(|i| i + FINAL_ANSWER_PREFIX.len())
+    }
+
+    /// Parse `Action:` / `Action Input:` pairs
+    fn parse_action_lines(text: &str) -> Vec<ToolCall> {
+        let mut calls = Vec::new();
+        let mut lines = text.lines().peekable();
+
+        while let Some(line) = lines.next() {
+            let trimmed = line.trim();
+            let lower = trimmed.to_ascii_lowercase();
+            if !lower.starts_with(ACTION_PREFIX) {
+                continue;
+            }
+            let name = trimmed[ACTION_PREFIX.len()..]
+                .trim()
+                .trim_matches(|c| c == '`' || c == '"' || c == '[' || c == ']')
+                .to_string();
+            if name.is_empty() {
+                continue;
+            }
+
+            let mut input = String::new();
+            while let Some(next) = lines.peek() {
+                let next_lower = next.trim().to_ascii_lowercase();
+                if next_lower.starts_with(ACTION_INPUT_PREFIX) {
+                    input.push_str(&next.trim()[ACTION_INPUT_PREFIX.len()..]);
+                    input.push('\n');
+                } else if next_lower.starts_with(ACTION_PREFIX)
+                    || next_lower.starts_with("thought:")
+                    || next_lower.starts_with(FINAL_ANSWER_PREFIX)
+                {
+                    break;
+                } else if !input.is_empty() {
+                    input.push_str(next);
+                    input.push('\n');
+                }
+                lines.next();
+            }
+
+            calls.push(Self::tool_call(name, Self::normalize_arguments(&input)));
+        }
+        calls
+    }
+
+    /// Parse JSON objects (optionally in code fences) that name an available tool
+    fn parse_json_blocks(text: &str, tools: &[Box<dyn ToolT>]) -> Vec<ToolCall> {
+        let mut candidates: Vec<&str> = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("```") {
+            let after = &rest[start + 3..];
+            let body_start = after.find('\n').map(|i| i + 1).unwrap_or(0);
+            let Some(end) = after[body_start..].find("```") else {
+                break;
+            };
+            candidates.push(after[body_start..body_start + end].trim());
+            rest = &after[body_start + end + 3..];
+        }
+        if candidates.is_empty() {
+            candidates.push(text.trim());
+        }
+
+        let mut calls = Vec::new();
+        for candidate in candidates {
+            let Ok(value) = serde_json::from_str::<Value>(candidate) else {
+                continue;
+            };
+            let objects = match value {
+                Value::Array(items) => items,
+                other => vec![other],
+            };
+            for object in objects {
+                if let Some(call) = Self::json_tool_call(&object, tools) {
+                    calls.push(call);
+                }
+            }
+        }
+        calls
+    }
+
+    fn json_tool_call(object: &Value, tools: &[Box<dyn ToolT>]) -> Option<ToolCall> {
+        let object = object.as_object()?;
+        let name = NAME_KEYS
+            .iter()
+            .find_map(|key| object.get(*key).and_then(Value::as_str))?;
+        if !tools.iter().any(|tool| tool.name() == name) {
+            return None;
+        }
+        let arguments = ARGUMENT_KEYS
+            .iter()
+            .find_map(|key| object.get(*key))
+            .map(|args| match args {
+                Value::String(s) => Self::normalize_arguments(s),
+                other => other.to_string(),
+            })
+            .unwrap_or_else(|| "{}".to_string());
+        Some(Self::tool_call(name.to_string(), arguments))
+    }
+
+    /// Strip code fences from the raw input. Invalid JSON is passed through unchanged so
+    /// the tool error is reported back to the model as an observation.
+    fn normalize_arguments(input: &str) -> String {
+        let mut trimmed = input.trim();
+        if let Some(stripped) = trimmed.strip_prefix("```") {
+            let body = stripped.trim_start_matches("json");
+            trimmed = body.trim_end().trim_end_matches("```").trim();
+        }
+        if trimmed.is_empty() {
+            return "{}".to_string();
+        }
+        match serde_json::from_str::<Value>(trimmed) {
+            Ok(value) => value.to_string(),
+            Err(_) => trimmed.to_string(),
+        }
+    }
+
+    fn tool_call(name: String, arguments: String) -> ToolCall {
+        ToolCall {
+            id: format!("call_{}", uuid::Uuid::new_v4().simple()),
+            call_type: "function".to_string(),
+            function: FunctionCall { name, arguments },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::{ToolCallError, ToolRuntime};
+    use serde_json::json;
+
+    #[derive(Debug)]
+    struct AddTool;
+
+    impl ToolRuntime for AddTool {
+        fn execute(&self, args: Value) -> Result<Value, ToolCallError> {
+            Ok(json!(
+                args["a"].as_i64().unwrap_or(0) + args["b"].as_i64().unwrap_or(0)
+            ))
+        }
+    }
+
+    impl ToolT for AddTool {
+        fn name(&self) -> &'static str {
+            "add"
+        }
+
+        fn description(&self) -> &'static str {
+            "Add two numbers"
+        }
+
+        fn args_schema(&self) -> Value {
+            json!({"type": "object", "properties": {"a": {"type": "integer"}, "b": {"type": "integer"}}})
+        }
+    }
+
+    fn tools() -> Vec<Box<dyn ToolT>> {
+        vec![Box::new(AddTool)]
+    }
+
+    #[test]
+    fn test_render_instructions_lists_tools() {
+        let prompt = TextToolProtocol::render_instructions(&tools());
+        assert!(prompt.contains("- add: Add two numbers"));
+        assert!(prompt.contains("Action Input:"));
+        assert!(prompt.contains("Final Answer:"));
+    }
+
+    #[test]
+    fn test_parse_action_lines() {
+        let text = "Thought: I need to add\nAction: add\nAction Input: {\"a\": 1, \"b\": 2}\nObservation: 3\nFinal Answer: 3";
+        let calls = TextToolProtocol::parse_tool_calls(text, &tools());
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function.name, "add");
+        assert_eq!(
+            serde_json::from_str::<Value>(&calls[0].function.arguments).unwrap(),
+            json!({"a": 1, "b": 2})
+        );
+    }
+
+    #[test]
+    fn test_parse_multiline_fenced_action_input() {
+        let text = "Action: `add`\nAction Input:\n```json\n{\"a\": 1,\n \"b\": 2}\n```";
+        let calls = TextToolProtocol::parse_tool_calls(text, &tools());
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function.name, "add");
+        assert_eq!(calls[0].function.arguments, r#"{"a":1,"b":2}"#);
+    }
+
+    #[test]
+    fn test_parse_json_block() {
+        let text = "Let me call it.\n```json\n{\"tool\": \"add\", \"arguments\": {\"a\": 2, \"b\": 3}}\n```";
+        let calls = TextToolProtocol::parse_tool_calls(text, &tools());
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function.arguments, r#"{"a":2,"b":3}"#);
+    }
+
+    #[test]
+    fn test_structured_final_answer_is_not_a_tool_call() {
+        let text = r#"{"name": "Ada", "value": 5}"#;
+        assert!(TextToolProtocol::parse_tool_calls(text, &tools()).is_empty());
+        assert!(TextToolProtocol::parse_tool_calls("Final Answer: 5", &tools()).is_empty());
+    }
+
+    #[test]
+    fn test_final_answer_extraction() {
+        assert_eq!(
+            TextToolProtocol::final_answer("Thought: done\nFinal Answer: 42"),
+            "42"
+        );
+        assert_eq!(TextToolProtocol::final_answer("  plain  "), "plain");
+    }
+
+    #[test]
+    fn test_format_observation() {
+        let result = ToolCallResult {
+            tool_name: "add".to_string(),
+            success: true,
+            arguments: json!({}),
+            result: json!(3),
+        };
+        assert_eq!(
+            TextToolProtocol::format_observation(std::slice::from_ref(&result)),
+            "Observation: 3"
+        );
+        let both = TextToolProtocol::format_observation(&[result.clone(), result]);
+        assert_eq!(both, "Observation (add): 3\nObservation (add): 3");
+    }
+}
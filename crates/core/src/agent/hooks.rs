@@ -15,6 +15,9 @@ pub enum HookOutcome {
 pub trait AgentHooks: AgentDeriveT + Send + Sync {
     /// Hook called when builder creates a new instance of BaseAgent
     async fn on_agent_create(&self) {}
+    /// Called before the run starts, with the ability to modify the task, e.g. to attach
+    /// retrieved context
+    async fn on_prepare_task(&self, _task: &mut Task, _ctx: &Context) {}
     /// Called when the Agent Execution is Triggered, Ability to Abort is Given for users
     async fn on_run_start(&self, _task: &Task, _ctx: &Context) -> HookOutcome {
         HookOutcome::Continue
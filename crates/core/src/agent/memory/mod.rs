@@ -1,13 +1,22 @@
 use async_trait::async_trait;
 use autoagents_llm::{chat::ChatMessage, error::LLMError};
+use futures::future::BoxFuture;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::sync::broadcast;
 
+mod profile;
+#[cfg(not(target_arch = "wasm32"))]
+mod shared;
 mod sliding_window;
+mod transcript;
+pub use profile::{ProfileFact, ProfileMemory};
+#[cfg(not(target_arch = "wasm32"))]
+pub use shared::SharedMemory;
 pub use sliding_window::SlidingWindowMemory;
+pub use transcript::{Transcript, TranscriptError, TranscriptFormat};
 
 #[cfg(test)]
 mod tests {
@@ -664,6 +673,7 @@ mod tests {
         let provider = MockMemoryProvider::new();
         assert!(!provider.needs_summary());
         assert!(provider.get_event_receiver().is_none());
+        assert!(provider.system_context().is_none());
     }
 
     #[tokio::test]
@@ -695,6 +705,61 @@ mod tests {
         provider.replace_with_summary("Summary text".to_string()); // Should not panic
         assert_eq!(provider.size(), 0); // Should not change size in default implementation
     }
+
+    #[tokio::test]
+    async fn test_memory_provider_export_import() {
+        let source = MockMemoryProvider::with_messages(vec![
+            ChatMessage::user().content("hello").build(),
+            ChatMessage::assistant().content("hi there").build(),
+        ]);
+        let transcript = source.export(TranscriptFormat::Markdown).await.unwrap();
+        assert!(transcript.contains("### Assistant\n\nhi there"));
+
+        let mut target = MockMemoryProvider::new();
+        let imported = target
+            .import(&transcript, TranscriptFormat::Markdown)
+            .await
+            .unwrap();
+        assert_eq!(imported, 2);
+        assert_eq!(target.messages[1].content, "hi there");
+
+        let failing = MockMemoryProvider::with_failure();
+        assert!(matches!(
+            failing.export(TranscriptFormat::OpenAIJsonl).await,
+            Err(TranscriptError::Memory(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_memory_provider_cannot_amend_by_default() {
+        let mut provider = MockMemoryProvider::new();
+        let handle = provider
+            .remember_tracked(&ChatMessage::assistant().content("hi there").build())
+            .await
+            .unwrap();
+        assert!(handle.is_none());
+        assert_eq!(provider.size(), 1);
+        assert!(provider
+            .amend(MessageHandle::new(0), Some("[redacted]"))
+            .await
+            .is_err());
+        assert_eq!(provider.messages[0].content, "hi there");
+    }
+}
+
+/// Identifies a message stored with [`MemoryProvider::remember_tracked`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MessageHandle(u64);
+
+impl MessageHandle {
+    /// A handle for the `sequence`-th message stored by a memory
+    pub fn new(sequence: u64) -> Self {
+        Self(sequence)
+    }
+
+    pub fn sequence(self) -> u64 {
+        self.0
+    }
 }
 
 /// Event emitted when a message is added to reactive memory
@@ -762,6 +827,10 @@ impl MessageCondition {
 pub enum MemoryType {
     /// Simple sliding window that keeps the N most recent messages
     SlidingWindow,
+    /// Blackboard shared between agents that broadcasts every write
+    Shared,
+    /// Sliding window that extracts a long-term profile of the user
+    Profile,
 }
 
 /// Trait for memory providers that can store and retrieve conversation history.
@@ -840,6 +909,19 @@ pub trait MemoryProvider: Send + Sync {
     /// Replace all messages with a summary
     fn replace_with_summary(&mut self, _summary: String) {}
 
+    /// Text appended to the agent's system prompt, such as facts remembered about the user
+    fn system_context(&self) -> Option<String> {
+        None
+    }
+
+    /// Work left over from storing messages, such as extracting facts with an LLM.
+    ///
+    /// Agents take it after each run and await it once the memory lock is released, so slow
+    /// work doesn't hold up other users of the memory.
+    fn take_pending_update(&mut self) -> Option<BoxFuture<'static, ()>> {
+        None
+    }
+
     /// Get a receiver for reactive events if this memory supports them
     #[cfg(not(target_arch = "wasm32"))]
     fn get_event_receiver(&self) -> Option<broadcast::Receiver<MessageEvent>> {
@@ -859,4 +941,55 @@ pub trait MemoryProvider: Send + Sync {
     ) -> Result<(), LLMError> {
         self.remember(message).await
     }
+
+    /// Export all stored messages as a transcript in the given format
+    async fn export(&self, format: TranscriptFormat) -> Result<String, TranscriptError> {
+        let messages = self
+            .recall("", None)
+            .await
+            .map_err(|e| TranscriptError::Memory(e.to_string()))?;
+        Ok(Transcript::encode(&messages, format))
+    }
+
+    /// Store a message and return a handle that [`amend`](Self::amend) accepts, or `None`
+    /// if this memory can't address the messages it stores
+    async fn remember_tracked(
+        &mut self,
+        message: &ChatMessage,
+    ) -> Result<Option<MessageHandle>, LLMError> {
+        self.remember(message).await?;
+        Ok(None)
+    }
+
+    /// Replace the content of a message stored with
+    /// [`remember_tracked`](Self::remember_tracked), or remove it when `replacement` is
+    /// `None`. A message that has already left the memory is ignored.
+    ///
+    /// Keeps memory in line with a moderated output. Fails for memories that can't amend
+    /// their messages.
+    async fn amend(
+        &mut self,
+        _handle: MessageHandle,
+        _replacement: Option<&str>,
+    ) -> Result<(), LLMError> {
+        Err(LLMError::ProviderError(format!(
+            "{:?} memory can't amend stored messages",
+            self.memory_type()
+        )))
+    }
+
+    /// Load the messages of a transcript into memory, returning how many were stored
+    async fn import(
+        &mut self,
+        data: &str,
+        format: TranscriptFormat,
+    ) -> Result<usize, TranscriptError> {
+        let messages = Transcript::decode(data, format)?;
+        for message in &messages {
+            self.remember(message)
+                .await
+                .map_err(|e| TranscriptError::Memory(e.to_string()))?;
+        }
+        Ok(messages.len())
+    }
 }
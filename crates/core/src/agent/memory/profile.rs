@@ -0,0 +1,498 @@
+//! Long-term user profile memory.
+//!
+//! Wraps a sliding window of the conversation and periodically asks the LLM to extract
+//! durable facts about the user, which are kept across sessions and injected into the
+//! system prompt.
+
+use super::{MemoryProvider, MemoryType, MessageHandle, SlidingWindowMemory};
+use crate::utils::strip_code_fence;
+use async_trait::async_trait;
+use autoagents_llm::chat::{ChatMessage, ChatRole, MessageType, StructuredOutputFormat};
+use autoagents_llm::error::LLMError;
+use autoagents_llm::LLMProvider;
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::{Arc, RwLock};
+
+/// Number of new messages after which facts are extracted
+const DEFAULT_EXTRACT_EVERY: usize = 6;
+/// Facts extracted with a lower confidence are ignored
+const DEFAULT_MIN_CONFIDENCE: f32 = 0.5;
+
+const EXTRACTION_PROMPT: &str = "You maintain a long-term profile of the user. From the \
+conversation below, extract durable facts about the user such as their name, preferences, \
+and ongoing projects. Ignore small talk and anything that only matters for the current \
+request. Use short snake_case keys and reuse the key of a known fact when updating it. To \
+retract a known fact that is no longer true, return it with an empty value. Give each fact \
+a confidence between 0 and 1. Respond with JSON: {\"facts\": [{\"key\": ..., \"value\": ..., \
+\"confidence\": ...}]}. Return an empty list when there is nothing new.";
+
+/// A durable fact about the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileFact {
+    pub key: String,
+    pub value: String,
+    pub confidence: f32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExtractedFacts {
+    #[serde(default)]
+    facts: Vec<ExtractedFact>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExtractedFact {
+    key: String,
+    #[serde(default)]
+    value: String,
+    #[serde(default = "default_confidence")]
+    confidence: f32,
+}
+
+fn default_confidence() -> f32 {
+    1.0
+}
+
+/// Memory that builds a long-term profile of the user.
+///
+/// Messages are kept in a sliding window like [`SlidingWindowMemory`]. Once
+/// `extract_every` messages are stored, the agent sends the recent turns to the LLM after
+/// the run (see [`MemoryProvider::take_pending_update`]), which returns facts that are
+/// merged into the profile: a fact with a known key replaces the old value, and an empty
+/// value removes it. The profile is rendered into the system prompt through
+/// [`MemoryProvider::system_context`].
+///
+/// The profile outlives the conversation: [`ProfileMemory::clear`] only clears the
+/// window, and [`ProfileMemory::for_session`] creates a memory for a new session that
+/// shares the same profile, e.g. from an agent's memory factory.
+pub struct ProfileMemory {
+    llm: Arc<dyn LLMProvider>,
+    window: SlidingWindowMemory,
+    window_size: usize,
+    facts: Arc<RwLock<Vec<ProfileFact>>>,
+    extract_every: usize,
+    min_confidence: f32,
+    pending: usize,
+}
+
+impl ProfileMemory {
+    /// Create a profile memory keeping `window_size` recent messages
+    pub fn new(llm: Arc<dyn LLMProvider>, window_size: usize) -> Self {
+        Self {
+            llm,
+            window: SlidingWindowMemory::new(window_size),
+            window_size,
+            facts: Arc::new(RwLock::new(Vec::new())),
+            extract_every: DEFAULT_EXTRACT_EVERY,
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+            pending: 0,
+        }
+    }
+
+    /// Extract facts every `messages` new messages
+    pub fn with_extract_every(mut self, messages: usize) -> Self {
+        self.extract_every = messages.max(1);
+        self
+    }
+
+    /// Ignore extracted facts with a confidence below `confidence`
+    pub fn with_min_confidence(mut self, confidence: f32) -> Self {
+        self.min_confidence = confidence;
+        self
+    }
+
+    /// Start from previously saved facts
+    pub fn with_facts(self, facts: Vec<ProfileFact>) -> Self {
+        *self.facts.write().unwrap() = facts;
+        self
+    }
+
+    /// A memory with an empty window sharing this memory's profile
+    pub fn for_session(&self) -> Self {
+        Self {
+            llm: self.llm.clone(),
+            window: SlidingWindowMemory::new(self.window_size),
+            window_size: self.window_size,
+            facts: self.facts.clone(),
+            extract_every: self.extract_every,
+            min_confidence: self.min_confidence,
+            pending: 0,
+        }
+    }
+
+    /// Current facts, sorted by key
+    pub fn facts(&self) -> Vec<ProfileFact> {
+        let mut facts = self.facts.read().unwrap().clone();
+        facts.sort_by(|a, b| a.key.cmp(&b.key));
+        facts
+    }
+
+    /// Remove all facts
+    pub fn clear_profile(&self) {
+        self.facts.write().unwrap().clear();
+    }
+
+    /// Extract facts from the messages stored since the last extraction.
+    ///
+    /// Returns the number of facts added, updated or removed.
+    pub async fn extract(&mut self) -> Result<usize, LLMError> {
+        self.extraction().run().await
+    }
+
+    /// Take the messages stored since the last extraction
+    fn extraction(&mut self) -> Extraction {
+        // Messages that already left the window can't be extracted any more
+        let recent = self
+            .window
+            .recent_messages(self.pending.min(self.window_size));
+        self.pending = 0;
+        Extraction {
+            llm: self.llm.clone(),
+            facts: self.facts.clone(),
+            min_confidence: self.min_confidence,
+            recent,
+        }
+    }
+}
+
+/// A fact extraction over some recent messages, which runs without borrowing the memory
+struct Extraction {
+    llm: Arc<dyn LLMProvider>,
+    facts: Arc<RwLock<Vec<ProfileFact>>>,
+    min_confidence: f32,
+    recent: Vec<ChatMessage>,
+}
+
+impl Extraction {
+    async fn run(self) -> Result<usize, LLMError> {
+        let transcript: Vec<String> = self
+            .recent
+            .iter()
+            .filter(|m| m.message_type == MessageType::Text && !m.content.is_empty())
+            .filter(|m| matches!(m.role, ChatRole::User | ChatRole::Assistant))
+            .map(|m| format!("{:?}: {}", m.role, m.content))
+            .collect();
+        if transcript.is_empty() {
+            return Ok(0);
+        }
+
+        let known = serde_json::to_string(
+            &self
+                .facts
+                .read()
+                .unwrap()
+                .iter()
+                .map(|f| json!({"key": f.key, "value": f.value}))
+                .collect::<Vec<_>>(),
+        )
+        .unwrap_or_default();
+        let messages = vec![
+            ChatMessage {
+                role: ChatRole::System,
+                message_type: MessageType::Text,
+                content: format!("{EXTRACTION_PROMPT}\n\nKnown facts: {known}"),
+            },
+            ChatMessage::user().content(transcript.join("\n")).build(),
+        ];
+        let response = self
+            .llm
+            .chat(&messages, None, Some(Self::output_format()))
+            .await?;
+        let text = response.text().unwrap_or_default();
+        let extracted: ExtractedFacts =
+            serde_json::from_str(strip_code_fence(&text)).map_err(|e| {
+                LLMError::ResponseFormatError {
+                    message: format!("Invalid profile extraction response: {e}"),
+                    raw_response: text.clone(),
+                }
+            })?;
+        Ok(self.merge(extracted.facts))
+    }
+
+    fn merge(&self, extracted: Vec<ExtractedFact>) -> usize {
+        let now = Utc::now();
+        let mut facts = self.facts.write().unwrap();
+        let mut changed = 0;
+        for fact in extracted {
+            let key = fact.key.trim().to_lowercase();
+            let value = fact.value.trim().to_string();
+            if key.is_empty() || fact.confidence < self.min_confidence {
+                continue;
+            }
+            let existing = facts.iter().position(|f| f.key == key);
+            match (existing, value.is_empty()) {
+                (Some(index), true) => {
+                    facts.remove(index);
+                }
+                (None, true) => continue,
+                (Some(index), false) => {
+                    let current = &mut facts[index];
+                    if current.value == value {
+                        current.confidence = current.confidence.max(fact.confidence);
+                    } else {
+                        current.value = value;
+                        current.confidence = fact.confidence;
+                    }
+                    current.updated_at = now;
+                }
+                (None, false) => facts.push(ProfileFact {
+                    key,
+                    value,
+                    confidence: fact.confidence,
+                    created_at: now,
+                    updated_at: now,
+                }),
+            }
+            changed += 1;
+        }
+        changed
+    }
+
+    fn output_format() -> StructuredOutputFormat {
+        StructuredOutputFormat {
+            name: "ProfileFacts".to_string(),
+            description: Some("Durable facts about the user".to_string()),
+            schema: Some(json!({
+                "type": "object",
+                "properties": {
+                    "facts": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "key": {"type": "string"},
+                                "value": {"type": "string"},
+                                "confidence": {"type": "number"}
+                            },
+                            "required": ["key", "value", "confidence"],
+                            "additionalProperties": false
+                        }
+                    }
+                },
+                "required": ["facts"],
+                "additionalProperties": false
+            })),
+            strict: Some(true),
+        }
+    }
+}
+
+#[async_trait]
+impl MemoryProvider for ProfileMemory {
+    async fn remember(&mut self, message: &ChatMessage) -> Result<(), LLMError> {
+        self.remember_tracked(message).await.map(|_| ())
+    }
+
+    /// Extracts facts once `extract_every` messages are stored. A failed extraction is
+    /// logged and its turns are not retried, so a failing LLM isn't called on every turn.
+    fn take_pending_update(&mut self) -> Option<BoxFuture<'static, ()>> {
+        if self.pending < self.extract_every {
+            return None;
+        }
+        let extraction = self.extraction();
+        Some(Box::pin(async move {
+            if let Err(e) = extraction.run().await {
+                log::warn!("Profile extraction failed: {e}");
+            }
+        }))
+    }
+
+    async fn recall(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<ChatMessage>, LLMError> {
+        self.window.recall(query, limit).await
+    }
+
+    async fn clear(&mut self) -> Result<(), LLMError> {
+        self.pending = 0;
+        self.window.clear().await
+    }
+
+    async fn remember_tracked(
+        &mut self,
+        message: &ChatMessage,
+    ) -> Result<Option<MessageHandle>, LLMError> {
+        let handle = self.window.remember_tracked(message).await?;
+        self.pending = self.pending.saturating_add(1);
+        Ok(handle)
+    }
+
+    async fn amend(
+        &mut self,
+        handle: MessageHandle,
+        replacement: Option<&str>,
+    ) -> Result<(), LLMError> {
+        self.window.amend(handle, replacement).await
+    }
+
+    fn memory_type(&self) -> MemoryType {
+        MemoryType::Profile
+    }
+
+    fn size(&self) -> usize {
+        self.window.size()
+    }
+
+    fn system_context(&self) -> Option<String> {
+        let facts = self.facts();
+        if facts.is_empty() {
+            return None;
+        }
+        let lines: Vec<String> = facts
+            .iter()
+            .map(|f| format!("- {}: {}", f.key, f.value))
+            .collect();
+        Some(format!("Known facts about the user:\n{}", lines.join("\n")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use autoagents_test_utils::llm::ScriptedLLMProvider;
+
+    async fn exchange(memory: &mut ProfileMemory, user: &str, assistant: &str) {
+        memory
+            .remember(&ChatMessage::user().content(user).build())
+            .await
+            .unwrap();
+        memory
+            .remember(&ChatMessage::assistant().content(assistant).build())
+            .await
+            .unwrap();
+        if let Some(update) = memory.take_pending_update() {
+            update.await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extracts_and_updates_facts() {
+        let llm = Arc::new(ScriptedLLMProvider::new([
+            r#"{"facts": [{"key": "Name", "value": "Ada", "confidence": 0.9},
+                          {"key": "editor", "value": "vim", "confidence": 0.3}]}"#,
+            r#"```json
+{"facts": [{"key": "name", "value": "Ada Lovelace", "confidence": 0.95},
+           {"key": "project", "value": "analytical engine", "confidence": 0.8}]}
+```"#,
+        ]));
+        let mut memory = ProfileMemory::new(llm.clone(), 10).with_extract_every(2);
+
+        exchange(&mut memory, "Hi, I'm Ada", "Hello Ada!").await;
+        let facts = memory.facts();
+        assert_eq!(facts.len(), 1);
+        assert_eq!(facts[0].key, "name");
+        assert_eq!(facts[0].value, "Ada");
+
+        exchange(&mut memory, "I'm working on the engine", "Sounds fun").await;
+        let facts = memory.facts();
+        assert_eq!(facts.len(), 2);
+        assert_eq!(facts[0].value, "Ada Lovelace");
+        assert!(facts[0].updated_at >= facts[0].created_at);
+
+        // Only the turns since the previous extraction are sent
+        let requests = llm.requests();
+        assert!(!requests[1][1].content.contains("Hi, I'm Ada"));
+        assert!(requests[1][0].content.contains("Ada"));
+
+        assert_eq!(
+            memory.system_context().unwrap(),
+            "Known facts about the user:\n- name: Ada Lovelace\n- project: analytical engine"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_profile_is_shared_across_sessions() {
+        let llm = Arc::new(ScriptedLLMProvider::new([
+            r#"{"facts": [{"key": "language", "value": "Rust", "confidence": 1.0}]}"#,
+            r#"{"facts": [{"key": "language", "value": "", "confidence": 1.0}]}"#,
+        ]));
+        let mut first = ProfileMemory::new(llm, 10).with_extract_every(2);
+        exchange(&mut first, "I write Rust", "Nice").await;
+        first.clear().await.unwrap();
+        assert!(first.is_empty());
+
+        let mut second = first.for_session();
+        assert!(second.is_empty());
+        assert_eq!(second.facts()[0].value, "Rust");
+
+        exchange(&mut second, "I stopped writing Rust", "Ok").await;
+        assert!(first.facts().is_empty());
+        assert!(first.system_context().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failed_extraction_is_not_retried_every_turn() {
+        let llm = Arc::new(ScriptedLLMProvider::new([
+            "not json",
+            r#"{"facts": [{"key": "name", "value": "Ada", "confidence": 1.0}]}"#,
+        ]));
+        let mut memory = ProfileMemory::new(llm.clone(), 10).with_extract_every(2);
+        exchange(&mut memory, "hello", "hi").await;
+        assert_eq!(memory.size(), 2);
+        assert!(memory.facts().is_empty());
+
+        memory
+            .remember(&ChatMessage::user().content("I'm Ada").build())
+            .await
+            .unwrap();
+        assert!(memory.take_pending_update().is_none());
+        assert_eq!(llm.requests().len(), 1);
+
+        memory
+            .remember(&ChatMessage::assistant().content("Hi Ada").build())
+            .await
+            .unwrap();
+        memory.take_pending_update().unwrap().await;
+        assert_eq!(memory.facts()[0].value, "Ada");
+    }
+
+    #[tokio::test]
+    async fn test_extracts_when_window_is_smaller_than_extract_every() {
+        let llm = Arc::new(ScriptedLLMProvider::new([
+            r#"{"facts": [{"key": "name", "value": "Ada", "confidence": 1.0}]}"#,
+        ]));
+        let mut memory = ProfileMemory::new(llm.clone(), 2).with_extract_every(4);
+        exchange(&mut memory, "hello", "hi").await;
+        assert!(llm.requests().is_empty());
+
+        exchange(&mut memory, "I'm Ada", "Hi Ada").await;
+        assert_eq!(memory.facts()[0].value, "Ada");
+        let transcript = &llm.requests()[0][1].content;
+        assert!(transcript.contains("I'm Ada"));
+        assert!(!transcript.contains("hello"));
+    }
+
+    #[tokio::test]
+    async fn test_agent_extracts_facts_after_the_run() {
+        use crate::agent::prebuilt::executor::ReActAgent;
+        use crate::agent::task::Task;
+        use crate::agent::{AgentBuilder, DirectAgent};
+        use crate::tests::agent::MockAgentImpl;
+
+        let llm = Arc::new(ScriptedLLMProvider::new([
+            r#"{"result": "Hello Ada"}"#,
+            r#"{"facts": [{"key": "name", "value": "Ada", "confidence": 1.0}]}"#,
+        ]));
+        let profile = ProfileMemory::new(llm.clone(), 10).with_extract_every(2);
+        let handle = AgentBuilder::<_, DirectAgent>::new(ReActAgent::new(MockAgentImpl::new(
+            "assistant",
+            "Assistant",
+        )))
+        .llm(llm.clone())
+        .memory(Box::new(profile.for_session()))
+        .build()
+        .await
+        .unwrap();
+
+        handle.agent.run(Task::new("I'm Ada")).await.unwrap();
+        assert_eq!(llm.requests().len(), 2);
+        assert_eq!(profile.facts()[0].value, "Ada");
+    }
+}
@@ -0,0 +1,348 @@
+//! Shared blackboard memory with reactive triggers.
+
+use super::{MemoryProvider, MemoryType, MessageCondition, MessageEvent, MessageHandle};
+use crate::agent::task::Task;
+use async_trait::async_trait;
+use autoagents_llm::chat::{ChatMessage, ChatRole};
+use autoagents_llm::error::LLMError;
+use ractor::ActorRef;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Number of events buffered for slow subscribers before they start missing events
+const EVENT_BUFFER: usize = 256;
+
+struct SharedState {
+    messages: Vec<ChatMessage>,
+    /// Sequence number of each message, see [`MessageHandle`]
+    sequences: Vec<u64>,
+    next_sequence: u64,
+    capacity: Option<usize>,
+}
+
+/// Memory shared by several agents, acting as a blackboard.
+///
+/// Every write is broadcast as a [`MessageEvent`], and triggers registered with
+/// [`SharedMemory::on_message`] or [`SharedMemory::trigger_actor`] run when a message
+/// matching their [`MessageCondition`] is written. Clones share the same messages and
+/// subscribers; use [`SharedMemory::for_agent`] to give each agent a handle that tags its
+/// writes with the agent name.
+///
+/// Triggered agents usually write their answer back to the blackboard, so conditions
+/// should exclude the agent's own messages (e.g. with `MessageCondition::RoleNot`) to
+/// avoid triggering it again.
+#[derive(Clone)]
+pub struct SharedMemory {
+    state: Arc<RwLock<SharedState>>,
+    tx: broadcast::Sender<MessageEvent>,
+    agent: Option<String>,
+}
+
+impl Default for SharedMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SharedMemory {
+    /// Create an unbounded shared memory
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(EVENT_BUFFER);
+        Self {
+            state: Arc::new(RwLock::new(SharedState {
+                messages: Vec::new(),
+                sequences: Vec::new(),
+                next_sequence: 0,
+                capacity: None,
+            })),
+            tx,
+            agent: None,
+        }
+    }
+
+    /// Create a shared memory that keeps at most `capacity` messages, dropping the oldest
+    pub fn with_capacity(capacity: usize) -> Self {
+        let memory = Self::new();
+        memory.state.write().unwrap().capacity = Some(capacity);
+        memory
+    }
+
+    /// A handle to the same memory whose writes are attributed to `agent`
+    pub fn for_agent(&self, agent: impl Into<String>) -> Self {
+        Self {
+            state: self.state.clone(),
+            tx: self.tx.clone(),
+            agent: Some(agent.into()),
+        }
+    }
+
+    /// All messages currently on the blackboard, oldest first
+    pub fn messages(&self) -> Vec<ChatMessage> {
+        self.state.read().unwrap().messages.clone()
+    }
+
+    /// Receive every message written from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<MessageEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Run `handler` for every written message matching `condition`.
+    ///
+    /// Handlers run one at a time in write order. Abort the returned handle to remove
+    /// the trigger.
+    pub fn on_message<F, Fut>(&self, condition: MessageCondition, handler: F) -> JoinHandle<()>
+    where
+        F: Fn(MessageEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut rx = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) if condition.matches(&event) => handler(event).await,
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("Shared memory trigger skipped {skipped} message(s)");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Send a new task to an actor agent for every written message matching `condition`
+    pub fn trigger_actor<F>(
+        &self,
+        condition: MessageCondition,
+        actor: ActorRef<Task>,
+        make_task: F,
+    ) -> JoinHandle<()>
+    where
+        F: Fn(&MessageEvent) -> Task + Send + Sync + 'static,
+    {
+        self.on_message(condition, move |event| {
+            let task = make_task(&event);
+            let actor = actor.clone();
+            async move {
+                if let Err(e) = actor.cast(task) {
+                    log::error!("Failed to send triggered task to actor: {e}");
+                }
+            }
+        })
+    }
+
+    fn role_of(&self, message: &ChatMessage) -> String {
+        match &self.agent {
+            Some(agent) => agent.clone(),
+            None => match message.role {
+                ChatRole::System => "system",
+                ChatRole::User => "user",
+                ChatRole::Assistant => "assistant",
+                ChatRole::Tool => "tool",
+            }
+            .to_string(),
+        }
+    }
+
+    fn write(&self, message: &ChatMessage, role: String) -> MessageHandle {
+        let sequence = {
+            let mut state = self.state.write().unwrap();
+            let sequence = state.next_sequence;
+            state.next_sequence += 1;
+            state.messages.push(message.clone());
+            state.sequences.push(sequence);
+            if let Some(capacity) = state.capacity {
+                let excess = state.messages.len().saturating_sub(capacity);
+                state.messages.drain(..excess);
+                state.sequences.drain(..excess);
+            }
+            sequence
+        };
+        // Sending only fails when nobody is subscribed
+        let _ = self.tx.send(MessageEvent {
+            role,
+            msg: message.clone(),
+        });
+        MessageHandle::new(sequence)
+    }
+}
+
+#[async_trait]
+impl MemoryProvider for SharedMemory {
+    async fn remember(&mut self, message: &ChatMessage) -> Result<(), LLMError> {
+        self.write(message, self.role_of(message));
+        Ok(())
+    }
+
+    async fn recall(
+        &self,
+        _query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<ChatMessage>, LLMError> {
+        let state = self.state.read().unwrap();
+        let limit = limit.unwrap_or(state.messages.len());
+        let start = state.messages.len().saturating_sub(limit);
+        Ok(state.messages[start..].to_vec())
+    }
+
+    async fn clear(&mut self) -> Result<(), LLMError> {
+        let mut state = self.state.write().unwrap();
+        state.messages.clear();
+        state.sequences.clear();
+        Ok(())
+    }
+
+    fn memory_type(&self) -> MemoryType {
+        MemoryType::Shared
+    }
+
+    fn size(&self) -> usize {
+        self.state.read().unwrap().messages.len()
+    }
+
+    fn get_event_receiver(&self) -> Option<broadcast::Receiver<MessageEvent>> {
+        Some(self.subscribe())
+    }
+
+    async fn remember_with_role(
+        &mut self,
+        message: &ChatMessage,
+        role: String,
+    ) -> Result<(), LLMError> {
+        self.write(message, role);
+        Ok(())
+    }
+
+    async fn remember_tracked(
+        &mut self,
+        message: &ChatMessage,
+    ) -> Result<Option<MessageHandle>, LLMError> {
+        Ok(Some(self.write(message, self.role_of(message))))
+    }
+
+    /// Edits the board in place; subscribers have already received the original message
+    async fn amend(
+        &mut self,
+        handle: MessageHandle,
+        replacement: Option<&str>,
+    ) -> Result<(), LLMError> {
+        let mut state = self.state.write().unwrap();
+        let Some(index) = state.sequences.iter().position(|s| *s == handle.sequence()) else {
+            return Ok(());
+        };
+        match replacement {
+            Some(text) => state.messages[index].content = text.to_string(),
+            None => {
+                state.messages.remove(index);
+                state.sequences.remove(index);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_clones_share_messages_and_events() {
+        let memory = SharedMemory::with_capacity(2);
+        let mut writer = memory.for_agent("researcher");
+        let mut rx = memory.subscribe();
+
+        writer
+            .remember(&ChatMessage::user().content("one").build())
+            .await
+            .unwrap();
+        writer
+            .remember(&ChatMessage::user().content("two").build())
+            .await
+            .unwrap();
+        writer
+            .remember(&ChatMessage::user().content("three").build())
+            .await
+            .unwrap();
+
+        let contents: Vec<String> = memory.messages().into_iter().map(|m| m.content).collect();
+        assert_eq!(contents, vec!["two", "three"]);
+        assert_eq!(
+            memory.recall("", Some(1)).await.unwrap()[0].content,
+            "three"
+        );
+
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.role, "researcher");
+        assert_eq!(event.msg.content, "one");
+    }
+
+    #[tokio::test]
+    async fn test_triggers_only_fire_on_matching_messages() {
+        let memory = SharedMemory::new();
+        let (tx, mut rx) = mpsc::channel(10);
+        let trigger = memory.on_message(
+            MessageCondition::All(vec![
+                MessageCondition::RoleNot("reviewer".to_string()),
+                MessageCondition::Contains("draft".to_string()),
+            ]),
+            move |event| {
+                let tx = tx.clone();
+                async move {
+                    let task = Task::new(format!("Review: {}", event.msg.content));
+                    tx.send(task).await.unwrap();
+                }
+            },
+        );
+
+        let mut writer = memory.for_agent("writer");
+        let mut reviewer = memory.for_agent("reviewer");
+        writer
+            .remember(&ChatMessage::assistant().content("hello").build())
+            .await
+            .unwrap();
+        reviewer
+            .remember(&ChatMessage::assistant().content("draft looks good").build())
+            .await
+            .unwrap();
+        writer
+            .remember(&ChatMessage::assistant().content("draft v1").build())
+            .await
+            .unwrap();
+
+        let task = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.prompt, "Review: draft v1");
+        assert!(rx.try_recv().is_err());
+        trigger.abort();
+    }
+
+    #[tokio::test]
+    async fn test_amend_leaves_other_agents_messages() {
+        let memory = SharedMemory::new();
+        let mut writer = memory.for_agent("writer");
+        let mut reviewer = memory.for_agent("reviewer");
+
+        let handle = writer
+            .remember_tracked(&ChatMessage::assistant().content("draft").build())
+            .await
+            .unwrap()
+            .unwrap();
+        reviewer
+            .remember(&ChatMessage::assistant().content("looks good").build())
+            .await
+            .unwrap();
+
+        writer.amend(handle, Some("[redacted]")).await.unwrap();
+        let contents: Vec<String> = memory.messages().into_iter().map(|m| m.content).collect();
+        assert_eq!(contents, vec!["[redacted]", "looks good"]);
+
+        writer.amend(handle, None).await.unwrap();
+        assert_eq!(memory.messages()[0].content, "looks good");
+    }
+}
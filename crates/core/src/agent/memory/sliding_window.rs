@@ -6,7 +6,7 @@ use async_trait::async_trait;
 use autoagents_llm::{chat::ChatMessage, error::LLMError};
 use std::collections::VecDeque;
 
-use super::{MemoryProvider, MemoryType};
+use super::{MemoryProvider, MemoryType, MessageHandle};
 
 /// Strategy for handling memory when window size limit is reached
 #[derive(Debug, Clone)]
@@ -28,6 +28,9 @@ pub enum TrimStrategy {
 #[derive(Debug, Clone)]
 pub struct SlidingWindowMemory {
     messages: VecDeque<ChatMessage>,
+    /// Sequence number of each message, see [`MessageHandle`]
+    sequences: VecDeque<u64>,
+    next_sequence: u64,
     window_size: usize,
     trim_strategy: TrimStrategy,
     needs_summary: bool,
@@ -61,6 +64,8 @@ impl SlidingWindowMemory {
 
         Self {
             messages: VecDeque::with_capacity(window_size),
+            sequences: VecDeque::with_capacity(window_size),
+            next_sequence: 0,
             window_size,
             trim_strategy: strategy,
             needs_summary: false,
@@ -100,6 +105,14 @@ impl SlidingWindowMemory {
         self.messages.range(start..).cloned().collect()
     }
 
+    fn push(&mut self, message: ChatMessage) -> MessageHandle {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.messages.push_back(message);
+        self.sequences.push_back(sequence);
+        MessageHandle::new(sequence)
+    }
+
     /// Check if memory needs summarization
     pub fn needs_summary(&self) -> bool {
         self.needs_summary
@@ -117,8 +130,8 @@ impl SlidingWindowMemory {
     /// * `summary` - The summary text to replace all messages with
     pub fn replace_with_summary(&mut self, summary: String) {
         self.messages.clear();
-        self.messages
-            .push_back(ChatMessage::assistant().content(summary).build());
+        self.sequences.clear();
+        self.push(ChatMessage::assistant().content(summary).build());
         self.needs_summary = false;
     }
 }
@@ -126,18 +139,25 @@ impl SlidingWindowMemory {
 #[async_trait]
 impl MemoryProvider for SlidingWindowMemory {
     async fn remember(&mut self, message: &ChatMessage) -> Result<(), LLMError> {
+        self.remember_tracked(message).await.map(|_| ())
+    }
+
+    async fn remember_tracked(
+        &mut self,
+        message: &ChatMessage,
+    ) -> Result<Option<MessageHandle>, LLMError> {
         if self.messages.len() >= self.window_size {
             match self.trim_strategy {
                 TrimStrategy::Drop => {
                     self.messages.pop_front();
+                    self.sequences.pop_front();
                 }
                 TrimStrategy::Summarize => {
                     self.mark_for_summary();
                 }
             }
         }
-        self.messages.push_back(message.clone());
-        Ok(())
+        Ok(Some(self.push(message.clone())))
     }
 
     async fn recall(
@@ -151,6 +171,7 @@ impl MemoryProvider for SlidingWindowMemory {
 
     async fn clear(&mut self) -> Result<(), LLMError> {
         self.messages.clear();
+        self.sequences.clear();
         Ok(())
     }
 
@@ -172,10 +193,28 @@ impl MemoryProvider for SlidingWindowMemory {
 
     fn replace_with_summary(&mut self, summary: String) {
         self.messages.clear();
-        self.messages
-            .push_back(ChatMessage::assistant().content(summary).build());
+        self.sequences.clear();
+        self.push(ChatMessage::assistant().content(summary).build());
         self.needs_summary = false;
     }
+
+    async fn amend(
+        &mut self,
+        handle: MessageHandle,
+        replacement: Option<&str>,
+    ) -> Result<(), LLMError> {
+        let Some(index) = self.sequences.iter().position(|s| *s == handle.sequence()) else {
+            return Ok(());
+        };
+        match replacement {
+            Some(text) => self.messages[index].content = text.to_string(),
+            None => {
+                self.messages.remove(index);
+                self.sequences.remove(index);
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -439,4 +478,38 @@ mod tests {
         assert!(!memory.needs_summary());
         assert!(memory.get_event_receiver().is_none());
     }
+
+    #[tokio::test]
+    async fn test_amend_targets_the_tracked_message() {
+        let mut memory = SlidingWindowMemory::new(3);
+        let first = memory
+            .remember_tracked(&ChatMessage::assistant().content("one").build())
+            .await
+            .unwrap()
+            .unwrap();
+        let second = memory
+            .remember_tracked(&ChatMessage::assistant().content("two").build())
+            .await
+            .unwrap()
+            .unwrap();
+        memory
+            .remember(&ChatMessage::assistant().content("three").build())
+            .await
+            .unwrap();
+
+        memory.amend(first, Some("[redacted]")).await.unwrap();
+        memory.amend(second, None).await.unwrap();
+        let contents: Vec<String> = memory.messages().into_iter().map(|m| m.content).collect();
+        assert_eq!(contents, vec!["[redacted]", "three"]);
+
+        // Messages that left the window are ignored
+        for content in ["four", "five", "six"] {
+            memory
+                .remember(&ChatMessage::user().content(content).build())
+                .await
+                .unwrap();
+        }
+        memory.amend(first, None).await.unwrap();
+        assert_eq!(memory.size(), 3);
+    }
 }
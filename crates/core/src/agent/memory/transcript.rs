@@ -0,0 +1,822 @@
+//! Conversion between memory contents and standard chat transcript formats.
+
+use autoagents_llm::chat::{ChatMessage, ChatRole, ImageMime, MessageType, DEFAULT_PDF_NAME};
+use autoagents_llm::{FunctionCall, ToolCall};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use thiserror::Error;
+
+const PDF_MIME: &str = "application/pdf";
+const TOOL_CALL_PREFIX: &str = "**Tool call** ";
+const TOOL_RESULT_PREFIX: &str = "**Tool result** ";
+
+/// Transcript formats supported by [`Transcript`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    /// OpenAI chat fine-tuning format: one `{"messages": [...]}` object per line
+    OpenAIJsonl,
+    /// Anthropic Messages API request body: `{"system": ..., "messages": [...]}`
+    Anthropic,
+    /// Human readable Markdown with one `### Role` section per message
+    Markdown,
+}
+
+#[derive(Debug, Error)]
+pub enum TranscriptError {
+    #[error("Invalid transcript: {0}")]
+    Parse(String),
+
+    #[error("Memory error: {0}")]
+    Memory(String),
+}
+
+/// Encodes and decodes chat messages as transcripts.
+///
+/// Tool calls, tool results, images and PDFs are preserved in every format. Tool result
+/// names are restored from the matching tool call when a format doesn't store them.
+pub struct Transcript;
+
+impl Transcript {
+    pub fn encode(messages: &[ChatMessage], format: TranscriptFormat) -> String {
+        // Cache breakpoints only shape requests, they aren't part of the conversation
+        let messages: Vec<ChatMessage> = messages
+            .iter()
+            .filter(|m| !m.is_cache_breakpoint())
+            .cloned()
+            .collect();
+        let messages = messages.as_slice();
+        match format {
+            TranscriptFormat::OpenAIJsonl => {
+                let messages: Vec<Value> = messages.iter().flat_map(openai::encode).collect();
+                format!("{}\n", json!({ "messages": messages }))
+            }
+            TranscriptFormat::Anthropic => anthropic::encode(messages),
+            TranscriptFormat::Markdown => markdown::encode(messages),
+        }
+    }
+
+    pub fn decode(
+        data: &str,
+        format: TranscriptFormat,
+    ) -> Result<Vec<ChatMessage>, TranscriptError> {
+        let mut messages = match format {
+            TranscriptFormat::OpenAIJsonl => openai::decode(data)?,
+            TranscriptFormat::Anthropic => anthropic::decode(data)?,
+            TranscriptFormat::Markdown => markdown::decode(data)?,
+        };
+        restore_tool_result_names(&mut messages);
+        Ok(messages)
+    }
+}
+
+fn role_name(role: &ChatRole) -> &'static str {
+    match role {
+        ChatRole::System => "system",
+        ChatRole::User => "user",
+        ChatRole::Assistant => "assistant",
+        ChatRole::Tool => "tool",
+    }
+}
+
+fn parse_role(role: &str) -> Result<ChatRole, TranscriptError> {
+    match role.to_ascii_lowercase().as_str() {
+        "system" | "developer" => Ok(ChatRole::System),
+        "user" => Ok(ChatRole::User),
+        "assistant" => Ok(ChatRole::Assistant),
+        "tool" => Ok(ChatRole::Tool),
+        other => Err(TranscriptError::Parse(format!("unknown role '{other}'"))),
+    }
+}
+
+fn data_url(mime: &str, data: &[u8]) -> String {
+    format!("data:{mime};base64,{}", BASE64.encode(data))
+}
+
+fn parse_data_url(url: &str) -> Option<(String, Vec<u8>)> {
+    let (mime, data) = url.strip_prefix("data:")?.split_once(";base64,")?;
+    Some((mime.to_string(), BASE64.decode(data).ok()?))
+}
+
+/// An image or PDF of a transcript message
+struct Media {
+    /// URL or data URL of the file
+    url: String,
+    /// File name, for formats that store one
+    name: Option<String>,
+}
+
+impl Media {
+    fn new(url: impl Into<String>, name: Option<&str>) -> Self {
+        Self {
+            url: url.into(),
+            name: name.filter(|n| !n.is_empty()).map(str::to_string),
+        }
+    }
+}
+
+/// Build a message for an image or PDF referenced by a URL or data URL
+fn media_message(role: ChatRole, content: String, media: Media) -> ChatMessage {
+    let message_type = match parse_data_url(&media.url) {
+        Some((mime, data)) if mime == PDF_MIME => MessageType::Pdf {
+            name: media.name.unwrap_or_else(|| DEFAULT_PDF_NAME.to_string()),
+            data,
+        },
+        Some((mime, data)) => match ImageMime::from_mime_type(&mime) {
+            Some(mime) => MessageType::Image((mime, data)),
+            None => MessageType::ImageURL(media.url),
+        },
+        None => MessageType::ImageURL(media.url),
+    };
+    ChatMessage {
+        role,
+        message_type,
+        content,
+    }
+}
+
+/// URL or data URL of the media in a message
+fn media_url(message: &ChatMessage) -> Option<String> {
+    match &message.message_type {
+        MessageType::Image((mime, data)) => Some(data_url(mime.mime_type(), data)),
+        MessageType::Pdf { data, .. } => Some(data_url(PDF_MIME, data)),
+        MessageType::ImageURL(url) => Some(url.clone()),
+        _ => None,
+    }
+}
+
+/// Turn the parts of one transcript message into chat messages
+fn assemble(
+    role: ChatRole,
+    text: String,
+    media: Vec<Media>,
+    tool_calls: Vec<ToolCall>,
+    tool_results: Vec<ToolCall>,
+) -> Vec<ChatMessage> {
+    let mut messages = Vec::new();
+    if !tool_results.is_empty() {
+        messages.push(ChatMessage {
+            role: ChatRole::Tool,
+            message_type: MessageType::ToolResult(tool_results),
+            content: String::new(),
+        });
+    }
+    if !tool_calls.is_empty() {
+        messages.push(ChatMessage {
+            role,
+            message_type: MessageType::ToolUse(tool_calls),
+            content: text,
+        });
+    } else if !media.is_empty() {
+        let mut text = Some(text);
+        for media in media {
+            messages.push(media_message(
+                role.clone(),
+                text.take().unwrap_or_default(),
+                media,
+            ));
+        }
+    } else if messages.is_empty() || !text.is_empty() {
+        messages.push(ChatMessage {
+            role,
+            message_type: MessageType::Text,
+            content: text,
+        });
+    }
+    messages
+}
+
+/// Fill in tool result names from the tool calls with the same id
+fn restore_tool_result_names(messages: &mut [ChatMessage]) {
+    let mut names: HashMap<String, String> = HashMap::new();
+    for message in messages {
+        match &mut message.message_type {
+            MessageType::ToolUse(calls) => {
+                for call in calls {
+                    names.insert(call.id.clone(), call.function.name.clone());
+                }
+            }
+            MessageType::ToolResult(results) => {
+                for result in results.iter_mut().filter(|r| r.function.name.is_empty()) {
+                    if let Some(name) = names.get(&result.id) {
+                        result.function.name = name.clone();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn tool_call(id: String, name: String, arguments: String) -> ToolCall {
+    ToolCall {
+        id,
+        call_type: "function".to_string(),
+        function: FunctionCall { name, arguments },
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+mod openai {
+    use super::*;
+
+    pub(super) fn encode(message: &ChatMessage) -> Vec<Value> {
+        let role = role_name(&message.role);
+        match &message.message_type {
+            MessageType::Text => vec![json!({"role": role, "content": message.content})],
+            MessageType::ToolUse(calls) => {
+                let tool_calls: Vec<Value> = calls
+                    .iter()
+                    .map(|call| {
+                        json!({
+                            "id": call.id,
+                            "type": "function",
+                            "function": {
+                                "name": call.function.name,
+                                "arguments": call.function.arguments,
+                            }
+                        })
+                    })
+                    .collect();
+                let content = if message.content.is_empty() {
+                    Value::Null
+                } else {
+                    Value::String(message.content.clone())
+                };
+                vec![json!({"role": "assistant", "content": content, "tool_calls": tool_calls})]
+            }
+            MessageType::CacheBreakpoint => Vec::new(),
+            MessageType::ToolResult(results) => results
+                .iter()
+                .map(|result| {
+                    json!({
+                        "role": "tool",
+                        "tool_call_id": result.id,
+                        "content": result.function.arguments,
+                    })
+                })
+                .collect(),
+            MessageType::Image(_) | MessageType::ImageURL(_) | MessageType::Pdf { .. } => {
+                let mut parts = Vec::new();
+                if !message.content.is_empty() {
+                    parts.push(json!({"type": "text", "text": message.content}));
+                }
+                let url = media_url(message).unwrap_or_default();
+                parts.push(match &message.message_type {
+                    MessageType::Pdf { name, .. } => json!({
+                        "type": "file",
+                        "file": {"filename": name, "file_data": url},
+                    }),
+                    _ => json!({"type": "image_url", "image_url": {"url": url}}),
+                });
+                vec![json!({"role": role, "content": parts})]
+            }
+        }
+    }
+
+    pub(super) fn decode(data: &str) -> Result<Vec<ChatMessage>, TranscriptError> {
+        let mut messages: Vec<ChatMessage> = Vec::new();
+        for line in data.lines().filter(|l| !l.trim().is_empty()) {
+            let value: Value = serde_json::from_str(line)
+                .map_err(|e| TranscriptError::Parse(format!("invalid JSON line: {e}")))?;
+            let items = match value.get("messages").and_then(Value::as_array) {
+                Some(items) => items.clone(),
+                None => vec![value],
+            };
+            for item in &items {
+                for message in decode_message(item)? {
+                    merge_tool_results(&mut messages, message);
+                }
+            }
+        }
+        Ok(messages)
+    }
+
+    fn decode_message(value: &Value) -> Result<Vec<ChatMessage>, TranscriptError> {
+        let role = parse_role(str_field(value, "role"))?;
+        if role == ChatRole::Tool {
+            let result = tool_call(
+                str_field(value, "tool_call_id").to_string(),
+                str_field(value, "name").to_string(),
+                content_text(value.get("content")),
+            );
+            return Ok(assemble(role, String::new(), vec![], vec![], vec![result]));
+        }
+
+        let tool_calls: Vec<ToolCall> = value
+            .get("tool_calls")
+            .and_then(Value::as_array)
+            .map(|calls| {
+                calls
+                    .iter()
+                    .map(|call| {
+                        let function = call.get("function").cloned().unwrap_or_default();
+                        tool_call(
+                            str_field(call, "id").to_string(),
+                            str_field(&function, "name").to_string(),
+                            str_field(&function, "arguments").to_string(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut media = Vec::new();
+        if let Some(parts) = value.get("content").and_then(Value::as_array) {
+            for part in parts {
+                match str_field(part, "type") {
+                    "image_url" => media.push(Media::new(
+                        part.pointer("/image_url/url")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default(),
+                        None,
+                    )),
+                    "file" => media.push(Media::new(
+                        part.pointer("/file/file_data")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default(),
+                        part.pointer("/file/filename").and_then(Value::as_str),
+                    )),
+                    _ => {}
+                }
+            }
+        }
+        Ok(assemble(
+            role,
+            content_text(value.get("content")),
+            media,
+            tool_calls,
+            vec![],
+        ))
+    }
+
+    /// Text of a string content or of the text parts of a content array
+    fn content_text(content: Option<&Value>) -> String {
+        match content {
+            Some(Value::String(text)) => text.clone(),
+            Some(Value::Array(parts)) => parts
+                .iter()
+                .filter(|p| str_field(p, "type") == "text")
+                .map(|p| str_field(p, "text"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => String::new(),
+        }
+    }
+
+    /// Consecutive tool messages are stored as a single `ToolResult` message
+    fn merge_tool_results(messages: &mut Vec<ChatMessage>, message: ChatMessage) {
+        if let (Some(last), MessageType::ToolResult(results)) =
+            (messages.last_mut(), &message.message_type)
+        {
+            if let MessageType::ToolResult(previous) = &mut last.message_type {
+                previous.extend(results.iter().cloned());
+                return;
+            }
+        }
+        messages.push(message);
+    }
+}
+
+mod anthropic {
+    use super::*;
+
+    pub(super) fn encode(messages: &[ChatMessage]) -> String {
+        let system: Vec<&str> = messages
+            .iter()
+            .filter(|m| m.role == ChatRole::System)
+            .map(|m| m.content.as_str())
+            .collect();
+        let encoded: Vec<Value> = messages
+            .iter()
+            .filter(|m| m.role != ChatRole::System)
+            .map(encode_message)
+            .collect();
+
+        let mut body = json!({ "messages": encoded });
+        if !system.is_empty() {
+            body["system"] = Value::String(system.join("\n\n"));
+        }
+        serde_json::to_string_pretty(&body).unwrap_or_default()
+    }
+
+    fn encode_message(message: &ChatMessage) -> Value {
+        let role = match message.role {
+            ChatRole::Assistant => "assistant",
+            _ => "user",
+        };
+        let text_block =
+            (!message.content.is_empty()).then(|| json!({"type": "text", "text": message.content}));
+        let blocks: Vec<Value> = match &message.message_type {
+            MessageType::Text => return json!({"role": role, "content": message.content}),
+            MessageType::Image((mime, data)) => vec![json!({
+                "type": "image",
+                "source": {"type": "base64", "media_type": mime.mime_type(), "data": BASE64.encode(data)},
+            })],
+            MessageType::ImageURL(url) => vec![json!({
+                "type": "image",
+                "source": {"type": "url", "url": url},
+            })],
+            MessageType::Pdf { name, data } => vec![json!({
+                "type": "document",
+                "source": {"type": "base64", "media_type": PDF_MIME, "data": BASE64.encode(data)},
+                "title": name,
+            })],
+            MessageType::ToolUse(calls) => {
+                let mut blocks: Vec<Value> = text_block.clone().into_iter().collect();
+                blocks.extend(calls.iter().map(|call| {
+                    json!({
+                        "type": "tool_use",
+                        "id": call.id,
+                        "name": call.function.name,
+                        "input": serde_json::from_str::<Value>(&call.function.arguments)
+                            .unwrap_or_else(|_| Value::String(call.function.arguments.clone())),
+                    })
+                }));
+                return json!({"role": "assistant", "content": blocks});
+            }
+            MessageType::ToolResult(results) => {
+                let blocks: Vec<Value> = results
+                    .iter()
+                    .map(|result| {
+                        json!({
+                            "type": "tool_result",
+                            "tool_use_id": result.id,
+                            "content": result.function.arguments,
+                        })
+                    })
+                    .collect();
+                return json!({"role": "user", "content": blocks});
+            }
+            MessageType::CacheBreakpoint => Vec::new(),
+        };
+        let blocks: Vec<Value> = blocks.into_iter().chain(text_block).collect();
+        json!({"role": role, "content": blocks})
+    }
+
+    pub(super) fn decode(data: &str) -> Result<Vec<ChatMessage>, TranscriptError> {
+        let body: Value = serde_json::from_str(data)
+            .map_err(|e| TranscriptError::Parse(format!("invalid JSON: {e}")))?;
+        let mut messages = Vec::new();
+
+        let system = text_of(body.get("system"));
+        if !system.is_empty() {
+            messages.push(ChatMessage {
+                role: ChatRole::System,
+                message_type: MessageType::Text,
+                content: system,
+            });
+        }
+
+        let items = body
+            .get("messages")
+            .and_then(Value::as_array)
+            .ok_or_else(|| TranscriptError::Parse("missing 'messages' array".to_string()))?;
+        for item in items {
+            messages.extend(decode_message(item)?);
+        }
+        Ok(messages)
+    }
+
+    fn decode_message(value: &Value) -> Result<Vec<ChatMessage>, TranscriptError> {
+        let role = parse_role(str_field(value, "role"))?;
+        let Some(blocks) = value.get("content").and_then(Value::as_array) else {
+            return Ok(assemble(
+                role,
+                text_of(value.get("content")),
+                vec![],
+                vec![],
+                vec![],
+            ));
+        };
+
+        let mut texts = Vec::new();
+        let mut media = Vec::new();
+        let mut tool_calls = Vec::new();
+        let mut tool_results = Vec::new();
+        for block in blocks {
+            match str_field(block, "type") {
+                "text" => texts.push(str_field(block, "text").to_string()),
+                "image" | "document" => {
+                    let source = block.get("source").cloned().unwrap_or_default();
+                    let url = match str_field(&source, "type") {
+                        "url" => str_field(&source, "url").to_string(),
+                        _ => format!(
+                            "data:{};base64,{}",
+                            str_field(&source, "media_type"),
+                            str_field(&source, "data")
+                        ),
+                    };
+                    media.push(Media::new(url, block.get("title").and_then(Value::as_str)));
+                }
+                "tool_use" => tool_calls.push(tool_call(
+                    str_field(block, "id").to_string(),
+                    str_field(block, "name").to_string(),
+                    block
+                        .get("input")
+                        .map(|input| match input {
+                            Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        })
+                        .unwrap_or_else(|| "{}".to_string()),
+                )),
+                "tool_result" => tool_results.push(tool_call(
+                    str_field(block, "tool_use_id").to_string(),
+                    String::new(),
+                    text_of(block.get("content")),
+                )),
+                _ => {}
+            }
+        }
+        Ok(assemble(
+            role,
+            texts.join("\n"),
+            media,
+            tool_calls,
+            tool_results,
+        ))
+    }
+
+    /// Text of a string or of an array of text blocks
+    fn text_of(value: Option<&Value>) -> String {
+        match value {
+            Some(Value::String(text)) => text.clone(),
+            Some(Value::Array(blocks)) => blocks
+                .iter()
+                .filter_map(|b| b.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => String::new(),
+        }
+    }
+}
+
+mod markdown {
+    use super::*;
+
+    fn heading(role: &ChatRole) -> &'static str {
+        match role {
+            ChatRole::System => "### System",
+            ChatRole::User => "### User",
+            ChatRole::Assistant => "### Assistant",
+            ChatRole::Tool => "### Tool",
+        }
+    }
+
+    pub(super) fn encode(messages: &[ChatMessage]) -> String {
+        let mut out = String::new();
+        for message in messages {
+            let mut sections = vec![heading(&message.role).to_string()];
+            if !message.content.is_empty() {
+                sections.push(message.content.clone());
+            }
+            match &message.message_type {
+                MessageType::Text | MessageType::CacheBreakpoint => {}
+                MessageType::Image(_) | MessageType::ImageURL(_) => sections.push(format!(
+                    "![image]({})",
+                    media_url(message).unwrap_or_default()
+                )),
+                MessageType::Pdf { name, .. } => sections.push(format!(
+                    "[{name}]({})",
+                    media_url(message).unwrap_or_default()
+                )),
+                MessageType::ToolUse(calls) => {
+                    for call in calls {
+                        sections.push(format!(
+                            "{TOOL_CALL_PREFIX}`{}` `{}`\n\n```json\n{}\n```",
+                            call.function.name, call.id, call.function.arguments
+                        ));
+                    }
+                }
+                MessageType::ToolResult(results) => {
+                    for result in results {
+                        sections.push(format!(
+                            "{TOOL_RESULT_PREFIX}`{}` `{}`\n\n```\n{}\n```",
+                            result.function.name, result.id, result.function.arguments
+                        ));
+                    }
+                }
+            }
+            out.push_str(&sections.join("\n\n"));
+            out.push_str("\n\n");
+        }
+        out
+    }
+
+    pub(super) fn decode(data: &str) -> Result<Vec<ChatMessage>, TranscriptError> {
+        let mut messages = Vec::new();
+        let mut current: Option<(ChatRole, Vec<&str>)> = None;
+        for line in data.lines() {
+            let role = line
+                .strip_prefix("### ")
+                .and_then(|name| parse_role(name.trim()).ok());
+            match role {
+                Some(role) => {
+                    if let Some((role, body)) = current.take() {
+                        messages.extend(decode_section(role, &body)?);
+                    }
+                    current = Some((role, Vec::new()));
+                }
+                None => match &mut current {
+                    Some((_, body)) => body.push(line),
+                    None if line.trim().is_empty() => {}
+                    None => {
+                        return Err(TranscriptError::Parse(
+                            "text before the first '### Role' heading".to_string(),
+                        ))
+                    }
+                },
+            }
+        }
+        if let Some((role, body)) = current {
+            messages.extend(decode_section(role, &body)?);
+        }
+        Ok(messages)
+    }
+
+    fn decode_section(role: ChatRole, body: &[&str]) -> Result<Vec<ChatMessage>, TranscriptError> {
+        let mut text = Vec::new();
+        let mut media = Vec::new();
+        let mut tool_calls = Vec::new();
+        let mut tool_results = Vec::new();
+
+        let mut lines = body.iter();
+        while let Some(line) = lines.next() {
+            let call_line = line
+                .strip_prefix(TOOL_CALL_PREFIX)
+                .map(|rest| (rest, true))
+                .or_else(|| {
+                    line.strip_prefix(TOOL_RESULT_PREFIX)
+                        .map(|rest| (rest, false))
+                });
+            if let Some((rest, is_call)) = call_line {
+                let ids: Vec<&str> = rest.split('`').skip(1).step_by(2).collect();
+                let [name, id] = ids[..] else {
+                    return Err(TranscriptError::Parse(format!("invalid tool line: {line}")));
+                };
+                let payload = fenced_block(&mut lines).ok_or_else(|| {
+                    TranscriptError::Parse(format!("missing code block after: {line}"))
+                })?;
+                let call = tool_call(id.to_string(), name.to_string(), payload);
+                if is_call {
+                    tool_calls.push(call);
+                } else {
+                    tool_results.push(call);
+                }
+            } else if let Some(url) = line
+                .strip_prefix("![image](")
+                .and_then(|rest| rest.strip_suffix(')'))
+            {
+                media.push(Media::new(url, None));
+            } else if let Some((name, url)) = pdf_link(line) {
+                media.push(Media::new(url, Some(name)));
+            } else {
+                text.push(*line);
+            }
+        }
+
+        let text = text.join("\n").trim_matches('\n').to_string();
+        Ok(assemble(role, text, media, tool_calls, tool_results))
+    }
+
+    /// Name and data URL of a `[name](data:application/pdf;base64,...)` line
+    fn pdf_link(line: &str) -> Option<(&str, &str)> {
+        let (name, url) = line
+            .strip_prefix('[')?
+            .strip_suffix(')')?
+            .rsplit_once("](")?;
+        url.starts_with(&format!("data:{PDF_MIME};"))
+            .then_some((name, url))
+    }
+
+    /// Read the next fenced code block, skipping blank lines before it
+    fn fenced_block<'a>(lines: &mut impl Iterator<Item = &'a &'a str>) -> Option<String> {
+        let opening = lines.find(|l| !l.trim().is_empty())?;
+        if !opening.starts_with("```") {
+            return None;
+        }
+        let mut content = Vec::new();
+        for line in lines.by_ref() {
+            if line.trim_end() == "```" {
+                return Some(content.join("\n"));
+            }
+            content.push(*line);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation() -> Vec<ChatMessage> {
+        vec![
+            ChatMessage {
+                role: ChatRole::System,
+                message_type: MessageType::Text,
+                content: "You are helpful".to_string(),
+            },
+            ChatMessage::user()
+                .content("What is in this picture?")
+                .image(ImageMime::PNG, vec![1, 2, 3])
+                .build(),
+            ChatMessage::user()
+                .image_url("https://example.com/cat.jpg")
+                .build(),
+            ChatMessage::user()
+                .pdf_file("report.pdf", vec![0x25, 0x50])
+                .build(),
+            ChatMessage {
+                role: ChatRole::Assistant,
+                message_type: MessageType::ToolUse(vec![tool_call(
+                    "call_1".to_string(),
+                    "lookup".to_string(),
+                    r#"{"query":"cat"}"#.to_string(),
+                )]),
+                content: "Let me check".to_string(),
+            },
+            ChatMessage {
+                role: ChatRole::Tool,
+                message_type: MessageType::ToolResult(vec![tool_call(
+                    "call_1".to_string(),
+                    "lookup".to_string(),
+                    "a cat".to_string(),
+                )]),
+                content: String::new(),
+            },
+            ChatMessage::assistant().content("It is a cat.").build(),
+        ]
+    }
+
+    fn assert_same(decoded: &[ChatMessage], expected: &[ChatMessage]) {
+        assert_eq!(decoded.len(), expected.len());
+        for (decoded, expected) in decoded.iter().zip(expected) {
+            assert_eq!(decoded.role, expected.role);
+            assert_eq!(decoded.message_type, expected.message_type);
+            assert_eq!(decoded.content, expected.content);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_all_formats() {
+        for format in [
+            TranscriptFormat::OpenAIJsonl,
+            TranscriptFormat::Anthropic,
+            TranscriptFormat::Markdown,
+        ] {
+            let encoded = Transcript::encode(&conversation(), format);
+            let decoded = Transcript::decode(&encoded, format).unwrap();
+            assert_same(&decoded, &conversation());
+        }
+    }
+
+    #[test]
+    fn test_openai_jsonl_shape() {
+        let encoded = Transcript::encode(&conversation(), TranscriptFormat::OpenAIJsonl);
+        assert_eq!(encoded.lines().count(), 1);
+        let value: Value = serde_json::from_str(encoded.trim()).unwrap();
+        let messages = value["messages"].as_array().unwrap();
+        assert_eq!(messages[4]["tool_calls"][0]["function"]["name"], "lookup");
+        assert_eq!(messages[5]["role"], "tool");
+        assert_eq!(messages[5]["tool_call_id"], "call_1");
+        assert!(messages[1]["content"][1]["image_url"]["url"]
+            .as_str()
+            .unwrap()
+            .starts_with("data:image/png;base64,"));
+    }
+
+    #[test]
+    fn test_anthropic_shape() {
+        let encoded = Transcript::encode(&conversation(), TranscriptFormat::Anthropic);
+        let value: Value = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(value["system"], "You are helpful");
+        let messages = value["messages"].as_array().unwrap();
+        assert_eq!(
+            messages[0]["content"][0]["source"]["media_type"],
+            "image/png"
+        );
+        assert_eq!(messages[3]["content"][1]["input"]["query"], "cat");
+        assert_eq!(messages[4]["content"][0]["type"], "tool_result");
+    }
+
+    #[test]
+    fn test_decode_multiple_jsonl_conversations() {
+        let data = r#"{"messages": [{"role": "user", "content": "hi"}, {"role": "assistant", "content": "hello"}]}
+{"messages": [{"role": "user", "content": "bye"}]}
+"#;
+        let messages = Transcript::decode(data, TranscriptFormat::OpenAIJsonl).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[2].content, "bye");
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(Transcript::decode("not json", TranscriptFormat::OpenAIJsonl).is_err());
+        assert!(Transcript::decode("{}", TranscriptFormat::Anthropic).is_err());
+        assert!(Transcript::decode("stray text", TranscriptFormat::Markdown).is_err());
+    }
+}
@@ -2,6 +2,7 @@
 mod config;
 pub mod error;
 pub mod memory;
+mod moderation;
 mod output;
 mod protocol;
 pub mod task;
@@ -22,6 +23,7 @@ mod actor;
 pub(crate) mod constants;
 mod direct;
 mod hooks;
+mod session;
 mod state;
 
 pub use actor::ActorAgent;
@@ -31,8 +33,20 @@ pub use base::{AgentDeriveT, BaseAgent};
 pub use builder::AgentBuilder;
 pub use context::{Context, ContextError};
 pub use direct::DirectAgent;
+#[cfg(feature = "tokenizers")]
+pub use executor::context_budget::HuggingFaceTokenCounter;
 pub use executor::{
-    event_helper::EventHelper, memory_helper::MemoryHelper, tool_processor::ToolProcessor,
-    AgentExecutor, ExecutorConfig, TurnResult,
+    context_budget::{BudgetStrategy, ContextBudget, HeuristicTokenCounter, TokenCounter},
+    event_helper::EventHelper,
+    instructions::{InstructionError, InstructionRenderer},
+    memory_helper::MemoryHelper,
+    output_validator::{OutputValidationError, OutputValidator},
+    text_tool_protocol::TextToolProtocol,
+    tool_processor::ToolProcessor,
+    AgentExecutor, ExecutorConfig, ToolCallingMode, TurnResult,
 };
 pub use hooks::{AgentHooks, HookOutcome};
+pub use moderation::{
+    ModerationAction, ModerationGuard, ModerationPolicy, ModerationStage, MODERATION_FLAGGED_KEY,
+};
+pub use session::{MemoryFactory, Session, SessionError, SessionTurn};
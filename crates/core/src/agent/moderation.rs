@@ -0,0 +1,531 @@
+//! Moderation of agent prompts and outputs.
+//!
+//! A [`ModerationGuard`] checks the task prompt before the run starts and the final output
+//! once the executor has returned, before `on_run_complete` and the
+//! [`Event::TaskComplete`] event. A blocked output is removed from the agent's memory and a
+//! redacted one replaces the stored response, so later turns don't recall the original.
+//!
+//! Streaming runs do not moderate the output at all, only the prompt. Output moderation
+//! also runs after the executor, so events sent while it works, such as
+//! [`Event::StreamChunk`] and tool call events, carry the unmoderated text.
+
+use crate::agent::error::RunnableAgentError;
+use crate::agent::executor::event_helper::EventHelper;
+use crate::agent::output::AgentOutputT;
+use crate::agent::task::Task;
+use crate::protocol::{ActorID, Event};
+use autoagents_llm::moderation::{ModerationProvider, ModerationResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::sync::mpsc::Sender;
+
+#[cfg(target_arch = "wasm32")]
+use futures::channel::mpsc::Sender;
+
+/// Task metadata key holding the categories of a flagged prompt
+pub const MODERATION_FLAGGED_KEY: &str = "moderation_flagged";
+
+const DEFAULT_REDACTION: &str = "[redacted]";
+
+/// The point of the run at which content was moderated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationStage {
+    /// The task prompt, checked before `on_run_start`
+    Input,
+    /// The final output, checked before `on_run_complete`
+    Output,
+}
+
+/// What the guard does with flagged content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationAction {
+    /// Fail the run with [`RunnableAgentError::ModerationBlocked`]
+    #[default]
+    Block,
+    /// Replace the flagged text with the policy's redaction text
+    Redact,
+    /// Let the content through; flagged prompts are marked in the task metadata
+    Flag,
+}
+
+/// Which stages are moderated and how violations are handled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModerationPolicy {
+    /// Action for flagged prompts, `None` to skip input moderation
+    pub input: Option<ModerationAction>,
+    /// Action for flagged outputs, `None` to skip output moderation
+    pub output: Option<ModerationAction>,
+    /// Text that replaces redacted content
+    pub redaction: String,
+}
+
+impl Default for ModerationPolicy {
+    fn default() -> Self {
+        Self {
+            input: Some(ModerationAction::Block),
+            output: Some(ModerationAction::Block),
+            redaction: DEFAULT_REDACTION.to_string(),
+        }
+    }
+}
+
+impl ModerationPolicy {
+    pub fn input(mut self, action: Option<ModerationAction>) -> Self {
+        self.input = action;
+        self
+    }
+
+    pub fn output(mut self, action: Option<ModerationAction>) -> Self {
+        self.output = action;
+        self
+    }
+
+    pub fn redaction(mut self, redaction: impl Into<String>) -> Self {
+        self.redaction = redaction.into();
+        self
+    }
+}
+
+/// Checks the task prompt and the final output of an agent against a
+/// [`ModerationProvider`].
+///
+/// Every violation emits an [`Event::ModerationViolation`], whatever the action. If the
+/// provider itself fails, the run fails too rather than letting unchecked content
+/// through. Streaming runs only moderate the prompt.
+#[derive(Clone)]
+pub struct ModerationGuard {
+    provider: Arc<dyn ModerationProvider>,
+    policy: ModerationPolicy,
+}
+
+impl std::fmt::Debug for ModerationGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModerationGuard")
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl ModerationGuard {
+    pub fn new(provider: Arc<dyn ModerationProvider>) -> Self {
+        Self {
+            provider,
+            policy: ModerationPolicy::default(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: ModerationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> &ModerationPolicy {
+        &self.policy
+    }
+
+    async fn moderate(
+        &self,
+        stage: ModerationStage,
+        text: &str,
+    ) -> Result<ModerationResult, RunnableAgentError> {
+        self.provider.moderate(text).await.map_err(|e| {
+            RunnableAgentError::ModerationBlocked(format!("{stage:?} moderation failed: {e}"))
+        })
+    }
+
+    async fn report(
+        &self,
+        tx: &Option<Sender<Event>>,
+        task: &Task,
+        actor_id: ActorID,
+        stage: ModerationStage,
+        action: ModerationAction,
+        result: &ModerationResult,
+    ) {
+        EventHelper::send(
+            tx,
+            Event::ModerationViolation {
+                sub_id: task.submission_id,
+                actor_id,
+                stage,
+                action,
+                categories: result.categories.clone(),
+            },
+        )
+        .await;
+    }
+
+    /// Moderate the task prompt, rewriting or annotating the task according to the policy
+    pub(crate) async fn check_input(
+        &self,
+        task: &mut Task,
+        actor_id: ActorID,
+        tx: &Option<Sender<Event>>,
+    ) -> Result<(), RunnableAgentError> {
+        let Some(action) = self.policy.input else {
+            return Ok(());
+        };
+        let result = self.moderate(ModerationStage::Input, &task.prompt).await?;
+        if !result.flagged {
+            return Ok(());
+        }
+        self.report(tx, task, actor_id, ModerationStage::Input, action, &result)
+            .await;
+        match action {
+            ModerationAction::Block => Err(blocked(ModerationStage::Input, &result)),
+            ModerationAction::Redact => {
+                task.prompt = self.policy.redaction.clone();
+                Ok(())
+            }
+            ModerationAction::Flag => {
+                task.metadata.insert(
+                    MODERATION_FLAGGED_KEY.to_string(),
+                    Value::from(result.categories),
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Moderate the final output, returning its redacted replacement if one is needed
+    pub(crate) async fn check_output<O: AgentOutputT>(
+        &self,
+        task: &Task,
+        output: &O,
+        actor_id: ActorID,
+        tx: &Option<Sender<Event>>,
+    ) -> Result<Option<O>, RunnableAgentError> {
+        let Some(action) = self.policy.output else {
+            return Ok(None);
+        };
+        let value = serde_json::to_value(output)
+            .map_err(|e| RunnableAgentError::SerializationError(e.to_string()))?;
+        let mut texts = Vec::new();
+        collect_strings(&value, &mut texts);
+        if texts.is_empty() {
+            return Ok(None);
+        }
+        let result = self
+            .moderate(ModerationStage::Output, &texts.join("\n"))
+            .await?;
+        if !result.flagged {
+            return Ok(None);
+        }
+        self.report(tx, task, actor_id, ModerationStage::Output, action, &result)
+            .await;
+        match action {
+            ModerationAction::Block => Err(blocked(ModerationStage::Output, &result)),
+            ModerationAction::Redact => {
+                let mut redacted = value;
+                redact_strings(&mut redacted, &self.policy.redaction);
+                // Outputs that can't hold the redaction text, such as enums, are blocked
+                serde_json::from_value(redacted)
+                    .map(Some)
+                    .map_err(|_| blocked(ModerationStage::Output, &result))
+            }
+            ModerationAction::Flag => Ok(None),
+        }
+    }
+}
+
+fn blocked(stage: ModerationStage, result: &ModerationResult) -> RunnableAgentError {
+    let stage = match stage {
+        ModerationStage::Input => "Input",
+        ModerationStage::Output => "Output",
+    };
+    if result.categories.is_empty() {
+        RunnableAgentError::ModerationBlocked(format!("{stage} was flagged"))
+    } else {
+        RunnableAgentError::ModerationBlocked(format!(
+            "{stage} was flagged for {}",
+            result.categories.join(", ")
+        ))
+    }
+}
+
+/// The text of an output as it is stored in memory: strings as they are, anything else as JSON
+pub(crate) fn output_text<O: AgentOutputT>(output: &O) -> Result<String, RunnableAgentError> {
+    match serde_json::to_value(output) {
+        Ok(Value::String(text)) => Ok(text),
+        Ok(value) => Ok(value.to_string()),
+        Err(e) => Err(RunnableAgentError::SerializationError(e.to_string())),
+    }
+}
+
+fn collect_strings<'a>(value: &'a Value, texts: &mut Vec<&'a str>) {
+    match value {
+        Value::String(text) => texts.push(text),
+        Value::Array(items) => items.iter().for_each(|item| collect_strings(item, texts)),
+        Value::Object(fields) => fields.values().for_each(|v| collect_strings(v, texts)),
+        _ => {}
+    }
+}
+
+fn redact_strings(value: &mut Value, redaction: &str) {
+    match value {
+        Value::String(text) => *text = redaction.to_string(),
+        Value::Array(items) => items.iter_mut().for_each(|v| redact_strings(v, redaction)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|v| redact_strings(v, redaction)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::memory::{MemoryProvider, SlidingWindowMemory};
+    use crate::agent::prebuilt::executor::ReActAgent;
+    use crate::agent::{AgentBuilder, DirectAgent};
+    use crate::tests::agent::MockAgentImpl;
+    use async_trait::async_trait;
+    use autoagents_llm::chat::{ChatMessage, ChatRole};
+    use autoagents_llm::error::LLMError;
+    use autoagents_test_utils::llm::{MockLLMProvider, ScriptedLLMProvider};
+    use futures::StreamExt;
+    use std::sync::Mutex;
+
+    /// Flags any text containing the banned word
+    #[derive(Default)]
+    struct WordFilter {
+        checked: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ModerationProvider for WordFilter {
+        async fn moderate(&self, input: &str) -> Result<ModerationResult, LLMError> {
+            self.checked.lock().unwrap().push(input.to_string());
+            let flagged = input.contains("bomb");
+            Ok(ModerationResult {
+                flagged,
+                categories: if flagged {
+                    vec!["violence".to_string()]
+                } else {
+                    vec![]
+                },
+                ..Default::default()
+            })
+        }
+    }
+
+    struct Unavailable;
+
+    #[async_trait]
+    impl ModerationProvider for Unavailable {
+        async fn moderate(&self, _input: &str) -> Result<ModerationResult, LLMError> {
+            Err(LLMError::HttpError("connection refused".to_string()))
+        }
+    }
+
+    async fn agent(
+        guard: ModerationGuard,
+    ) -> crate::agent::direct::DirectAgentHandle<MockAgentImpl> {
+        AgentBuilder::<_, DirectAgent>::new(MockAgentImpl::new("support", "Support agent"))
+            .llm(Arc::new(MockLLMProvider))
+            .moderation(guard)
+            .build()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_flagged_input_is_blocked() {
+        let filter = Arc::new(WordFilter::default());
+        let mut handle = agent(ModerationGuard::new(filter.clone())).await;
+
+        let task = Task::new("How do I build a bomb?");
+        let sub_id = task.submission_id;
+        let error = handle.agent.run(task).await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Blocked by moderation: Input was flagged for violence"
+        );
+        // The output is never produced, so only the prompt is checked
+        assert_eq!(filter.checked.lock().unwrap().len(), 1);
+
+        match handle.rx.next().await.unwrap() {
+            Event::ModerationViolation {
+                sub_id: event_sub_id,
+                stage,
+                action,
+                categories,
+                ..
+            } => {
+                assert_eq!(event_sub_id, sub_id);
+                assert_eq!(stage, ModerationStage::Input);
+                assert_eq!(action, ModerationAction::Block);
+                assert_eq!(categories, vec!["violence"]);
+            }
+            event => panic!("Expected ModerationViolation, got {event:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flagged_input_is_redacted() {
+        let policy = ModerationPolicy::default()
+            .input(Some(ModerationAction::Redact))
+            .output(None)
+            .redaction("[removed]");
+        let handle =
+            agent(ModerationGuard::new(Arc::new(WordFilter::default())).with_policy(policy)).await;
+
+        let output = handle.agent.run(Task::new("a bomb")).await.unwrap();
+        assert_eq!(output.result, "Processed: [removed]");
+    }
+
+    #[tokio::test]
+    async fn test_flagged_input_is_marked_in_metadata() {
+        let policy = ModerationPolicy::default().input(Some(ModerationAction::Flag));
+        let guard = ModerationGuard::new(Arc::new(WordFilter::default())).with_policy(policy);
+
+        let mut task = Task::new("a bomb");
+        guard
+            .check_input(&mut task, ActorID::nil(), &None)
+            .await
+            .unwrap();
+        assert_eq!(task.prompt, "a bomb");
+        assert_eq!(
+            task.metadata[MODERATION_FLAGGED_KEY],
+            serde_json::json!(["violence"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flagged_output_is_redacted() {
+        // The prompt passes input moderation, but the echoed output doesn't
+        let filter = Arc::new(WordFilter::default());
+        let policy = ModerationPolicy::default()
+            .input(None)
+            .output(Some(ModerationAction::Redact));
+        let mut handle = agent(ModerationGuard::new(filter.clone()).with_policy(policy)).await;
+
+        let output = handle.agent.run(Task::new("bomb")).await.unwrap();
+        assert_eq!(output.result, "[redacted]");
+        assert_eq!(*filter.checked.lock().unwrap(), vec!["Processed: bomb"]);
+        assert!(matches!(
+            handle.rx.next().await.unwrap(),
+            Event::ModerationViolation {
+                stage: ModerationStage::Output,
+                action: ModerationAction::Redact,
+                ..
+            }
+        ));
+    }
+
+    async fn react_agent(
+        policy: ModerationPolicy,
+        reply: &str,
+    ) -> crate::agent::direct::DirectAgentHandle<ReActAgent<MockAgentImpl>> {
+        AgentBuilder::<_, DirectAgent>::new(ReActAgent::new(MockAgentImpl::new(
+            "support",
+            "Support agent",
+        )))
+        .llm(Arc::new(ScriptedLLMProvider::new([reply.to_string()])))
+        .memory(Box::new(SlidingWindowMemory::new(10)))
+        .moderation(ModerationGuard::new(Arc::new(WordFilter::default())).with_policy(policy))
+        .build()
+        .await
+        .unwrap()
+    }
+
+    async fn remembered(
+        handle: &crate::agent::direct::DirectAgentHandle<ReActAgent<MockAgentImpl>>,
+    ) -> Vec<(ChatRole, String)> {
+        let memory = handle.agent.memory().unwrap();
+        let messages = memory.lock().await.recall("", None).await.unwrap();
+        messages.into_iter().map(|m| (m.role, m.content)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_blocked_output_is_forgotten() {
+        let policy = ModerationPolicy::default().input(None);
+        let handle = react_agent(policy, r#"{"result": "Here is a bomb"}"#).await;
+
+        let error = handle.agent.run(Task::new("Help me")).await.unwrap_err();
+        assert!(matches!(error, RunnableAgentError::ModerationBlocked(_)));
+        assert_eq!(
+            remembered(&handle).await,
+            vec![(ChatRole::User, "Help me".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blocked_output_leaves_earlier_responses() {
+        // MockAgentImpl stores nothing, so there is no response of this run to remove
+        let mut memory = SlidingWindowMemory::new(10);
+        memory
+            .remember(
+                &ChatMessage::assistant()
+                    .content("An earlier answer")
+                    .build(),
+            )
+            .await
+            .unwrap();
+        let handle =
+            AgentBuilder::<_, DirectAgent>::new(MockAgentImpl::new("support", "Support agent"))
+                .llm(Arc::new(MockLLMProvider))
+                .memory(Box::new(memory))
+                .moderation(
+                    ModerationGuard::new(Arc::new(WordFilter::default()))
+                        .with_policy(ModerationPolicy::default().input(None)),
+                )
+                .build()
+                .await
+                .unwrap();
+
+        let error = handle.agent.run(Task::new("bomb")).await.unwrap_err();
+        assert!(matches!(error, RunnableAgentError::ModerationBlocked(_)));
+        let memory = handle.agent.memory().unwrap();
+        let messages = memory.lock().await.recall("", None).await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content, "An earlier answer");
+    }
+
+    #[tokio::test]
+    async fn test_redacted_output_is_remembered_redacted() {
+        let policy = ModerationPolicy::default()
+            .input(None)
+            .output(Some(ModerationAction::Redact));
+        let handle = react_agent(policy, r#"{"result": "Here is a bomb"}"#).await;
+
+        let output = handle.agent.run(Task::new("Help me")).await.unwrap();
+        assert_eq!(output.result, "[redacted]");
+        assert_eq!(
+            remembered(&handle).await,
+            vec![
+                (ChatRole::User, "Help me".to_string()),
+                (
+                    ChatRole::Assistant,
+                    r#"{"result":"[redacted]"}"#.to_string()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clean_content_passes_through() {
+        let filter = Arc::new(WordFilter::default());
+        let handle = agent(ModerationGuard::new(filter.clone())).await;
+
+        let output = handle.agent.run(Task::new("Hello")).await.unwrap();
+        assert_eq!(output.result, "Processed: Hello");
+        assert_eq!(
+            *filter.checked.lock().unwrap(),
+            vec!["Hello", "Processed: Hello"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_provider_failure_fails_closed() {
+        let handle = agent(ModerationGuard::new(Arc::new(Unavailable))).await;
+
+        let error = handle.agent.run(Task::new("Hello")).await.unwrap_err();
+        assert!(matches!(error, RunnableAgentError::ModerationBlocked(_)));
+    }
+}
@@ -1,6 +1,10 @@
+use crate::agent::executor::strip_unsupported_media;
 use crate::agent::hooks::HookOutcome;
 use crate::agent::task::Task;
-use crate::agent::{AgentDeriveT, AgentExecutor, AgentHooks, Context, EventHelper, ExecutorConfig};
+use crate::agent::{
+    AgentDeriveT, AgentExecutor, AgentHooks, Context, EventHelper, ExecutorConfig,
+    InstructionRenderer, OutputValidator,
+};
 use crate::tool::{ToolCallResult, ToolT};
 use async_trait::async_trait;
 use autoagents_llm::chat::{ChatMessage, ChatRole, MessageType};
@@ -38,18 +42,26 @@ pub enum BasicExecutorError {
 
     #[error("Other error: {0}")]
     Other(String),
+
+    #[error("Extracting Agent Output Error: {0}")]
+    AgentOutputError(String),
+
+    #[error("Instruction template error: {0}")]
+    InstructionError(String),
 }
 
 /// Wrapper type for Basic executor
 #[derive(Debug)]
 pub struct BasicAgent<T: AgentDeriveT> {
     inner: Arc<T>,
+    config: ExecutorConfig,
 }
 
 impl<T: AgentDeriveT> Clone for BasicAgent<T> {
     fn clone(&self) -> Self {
         Self {
             inner: Arc::clone(&self.inner),
+            config: self.config.clone(),
         }
     }
 }
@@ -58,8 +70,18 @@ impl<T: AgentDeriveT> BasicAgent<T> {
     pub fn new(inner: T) -> Self {
         Self {
             inner: Arc::new(inner),
+            config: ExecutorConfig {
+                max_turns: 1,
+                ..Default::default()
+            },
         }
     }
+
+    /// Override the executor configuration
+    pub fn with_config(mut self, config: ExecutorConfig) -> Self {
+        self.config = config;
+        self
+    }
 }
 
 impl<T: AgentDeriveT> Deref for BasicAgent<T> {
@@ -79,6 +101,10 @@ impl<T: AgentDeriveT> AgentDeriveT for BasicAgent<T> {
         self.inner.description()
     }
 
+    fn instructions(&self) -> &'static str {
+        self.inner.instructions()
+    }
+
     fn output_schema(&self) -> Option<Value> {
         self.inner.output_schema()
     }
@@ -101,6 +127,10 @@ where
         self.inner.on_agent_create().await
     }
 
+    async fn on_prepare_task(&self, task: &mut Task, ctx: &Context) {
+        self.inner.on_prepare_task(task, ctx).await
+    }
+
     async fn on_run_start(&self, task: &Task, ctx: &Context) -> HookOutcome {
         self.inner.on_run_start(task, ctx).await
     }
@@ -144,7 +174,7 @@ impl<T: AgentDeriveT> AgentExecutor for BasicAgent<T> {
     type Error = BasicExecutorError;
 
     fn config(&self) -> ExecutorConfig {
-        ExecutorConfig { max_turns: 1 }
+        self.config.clone()
     }
 
     async fn execute(
@@ -162,24 +192,28 @@ impl<T: AgentDeriveT> AgentExecutor for BasicAgent<T> {
         )
         .await;
 
+        let instructions = InstructionRenderer::render(&context, task, &[])
+            .map_err(|e| BasicExecutorError::InstructionError(e.to_string()))?;
         let mut messages = vec![ChatMessage {
             role: ChatRole::System,
             message_type: MessageType::Text,
-            content: context.config().description.clone(),
+            content: instructions,
         }];
-
-        let chat_msg = ChatMessage {
-            role: ChatRole::User,
-            message_type: MessageType::Text,
-            content: task.prompt.clone(),
-        };
-        messages.push(chat_msg);
+        messages.extend(task.to_chat_messages());
+        strip_unsupported_media(&mut messages, context.llm().as_ref());
         let response = context
             .llm()
             .chat(&messages, None, context.config().output_schema.clone())
             .await
             .map_err(|e| BasicExecutorError::LLMError(e.to_string()))?;
-        let response_text = response.text().unwrap_or_default();
+        let response_text = OutputValidator::ensure_valid(
+            &context,
+            &messages,
+            response.text().unwrap_or_default(),
+            self.config.structured_output_retries,
+        )
+        .await
+        .map_err(|e| BasicExecutorError::AgentOutputError(e.to_string()))?;
         Ok(BasicAgentOutput {
             response: response_text,
             done: true,
@@ -204,18 +238,15 @@ impl<T: AgentDeriveT> AgentExecutor for BasicAgent<T> {
         )
         .await;
 
+        let instructions = InstructionRenderer::render(&context, task, &[])
+            .map_err(|e| BasicExecutorError::InstructionError(e.to_string()))?;
         let mut messages = vec![ChatMessage {
             role: ChatRole::System,
             message_type: MessageType::Text,
-            content: context.config().description.clone(),
+            content: instructions,
         }];
-
-        let chat_msg = ChatMessage {
-            role: ChatRole::User,
-            message_type: MessageType::Text,
-            content: task.prompt.clone(),
-        };
-        messages.push(chat_msg);
+        messages.extend(task.to_chat_messages());
+        strip_unsupported_media(&mut messages, context.llm().as_ref());
 
         let stream = context
             .llm()
@@ -301,6 +332,7 @@ mod tests {
             id: ActorID::new_v4(),
             name: "test_agent".to_string(),
             description: "Test agent description".to_string(),
+            instructions: String::new(),
             output_schema: None,
         };
 
@@ -324,4 +356,35 @@ mod tests {
         let config = basic_agent.config();
         assert_eq!(config.max_turns, 1);
     }
+
+    #[tokio::test]
+    async fn test_basic_agent_rejects_output_not_matching_schema() {
+        use crate::agent::task::Task;
+        use crate::agent::{AgentConfig, Context};
+        use autoagents_llm::chat::StructuredOutputFormat;
+
+        let mock_agent = MockAgentImpl::new("test_agent", "Test agent description");
+        let basic_agent = BasicAgent::new(mock_agent).with_config(ExecutorConfig {
+            max_turns: 1,
+            structured_output_retries: 1,
+            ..Default::default()
+        });
+
+        let config = AgentConfig::new("test_agent".to_string(), "desc".to_string())
+            .with_output_schema(StructuredOutputFormat {
+                name: "Out".to_string(),
+                description: None,
+                schema: Some(serde_json::json!({"type": "object"})),
+                strict: None,
+            });
+        let context = Context::new(Arc::new(MockLLMProvider {}), None).with_config(config);
+
+        let result = basic_agent
+            .execute(&Task::new("Test task"), Arc::new(context))
+            .await;
+        assert!(matches!(
+            result,
+            Err(BasicExecutorError::AgentOutputError(_))
+        ));
+    }
 }
@@ -1,6 +1,9 @@
-use crate::agent::executor::AgentExecutor;
+use crate::agent::executor::{strip_unsupported_media, AgentExecutor};
 use crate::agent::task::Task;
-use crate::agent::{AgentDeriveT, Context, ExecutorConfig, TurnResult};
+use crate::agent::{
+    AgentDeriveT, Context, ExecutorConfig, InstructionRenderer, OutputValidator, TextToolProtocol,
+    ToolCallingMode, TurnResult,
+};
 use crate::protocol::{Event, StreamingTurnResult, SubmissionId};
 use crate::tool::{to_llm_tool, ToolCallResult, ToolT};
 use async_trait::async_trait;
@@ -13,6 +16,7 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use thiserror::Error;
 
@@ -87,18 +91,26 @@ pub enum ReActExecutorError {
 
     #[error("Extracting Agent Output Error: {0}")]
     AgentOutputError(String),
+
+    #[error("Instruction template error: {0}")]
+    InstructionError(String),
 }
 
 /// Wrapper type for ReAct executor
 #[derive(Debug)]
 pub struct ReActAgent<T: AgentDeriveT> {
     inner: Arc<T>,
+    config: ExecutorConfig,
+    /// Set once the provider reported no native tool support in `ToolCallingMode::Auto`
+    text_tools_fallback: Arc<AtomicBool>,
 }
 
 impl<T: AgentDeriveT> Clone for ReActAgent<T> {
     fn clone(&self) -> Self {
         Self {
             inner: Arc::clone(&self.inner),
+            config: self.config.clone(),
+            text_tools_fallback: Arc::clone(&self.text_tools_fallback),
         }
     }
 }
@@ -107,8 +119,51 @@ impl<T: AgentDeriveT> ReActAgent<T> {
     pub fn new(inner: T) -> Self {
         Self {
             inner: Arc::new(inner),
+            config: ExecutorConfig::default(),
+            text_tools_fallback: Arc::new(AtomicBool::new(false)),
         }
     }
+
+    /// Override the executor configuration
+    pub fn with_config(mut self, config: ExecutorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Select how tools are offered to the LLM
+    pub fn with_tool_calling_mode(mut self, mode: ToolCallingMode) -> Self {
+        self.config.tool_calling_mode = mode;
+        self
+    }
+
+    /// Whether tools are described in the prompt instead of sent natively
+    fn use_text_tools(&self, context: &Context, tools: &[Box<dyn ToolT>]) -> bool {
+        !tools.is_empty()
+            && match self.config.tool_calling_mode {
+                ToolCallingMode::Text => true,
+                ToolCallingMode::Native => false,
+                ToolCallingMode::Auto => {
+                    self.text_tools_fallback.load(Ordering::Relaxed)
+                        || context
+                            .llm()
+                            .capabilities()
+                            .is_some_and(|capabilities| !capabilities.tool_calling)
+                }
+            }
+    }
+
+    /// Switch to the text protocol if the provider rejected native tools in auto mode
+    fn fallback_to_text_tools(&self, error: &LLMError, tools: &[Box<dyn ToolT>]) -> bool {
+        let LLMError::NoToolSupport(reason) = error else {
+            return false;
+        };
+        if tools.is_empty() || self.config.tool_calling_mode != ToolCallingMode::Auto {
+            return false;
+        }
+        log::debug!("Provider has no native tool support ({reason}), using text tool calling");
+        self.text_tools_fallback.store(true, Ordering::Relaxed);
+        true
+    }
 }
 
 impl<T: AgentDeriveT> Deref for ReActAgent<T> {
@@ -128,6 +183,10 @@ impl<T: AgentDeriveT> AgentDeriveT for ReActAgent<T> {
         self.inner.description()
     }
 
+    fn instructions(&self) -> &'static str {
+        self.inner.instructions()
+    }
+
     fn output_schema(&self) -> Option<Value> {
         self.inner.output_schema()
     }
@@ -150,6 +209,10 @@ where
         self.inner.on_agent_create().await
     }
 
+    async fn on_prepare_task(&self, task: &mut Task, ctx: &Context) {
+        self.inner.on_prepare_task(task, ctx).await
+    }
+
     async fn on_run_start(&self, task: &Task, ctx: &Context) -> HookOutcome {
         self.inner.on_run_start(task, ctx).await
     }
@@ -191,27 +254,73 @@ impl<T: AgentDeriveT + AgentHooks> ReActAgent<T> {
     async fn process_turn(
         &self,
         context: &Context,
+        task: &Task,
         tools: &[Box<dyn ToolT>],
     ) -> Result<TurnResult<ReActAgentOutput>, ReActExecutorError> {
-        let messages = self.prepare_messages(context).await;
-        let response = self.get_llm_response(context, &messages, tools).await?;
+        if !self.use_text_tools(context, tools) {
+            let messages = self.prepare_messages(context, task, tools, false).await?;
+            match self.get_llm_response(context, &messages, tools).await {
+                Ok(response) => {
+                    let response_text = response.text().unwrap_or_default();
+                    return if let Some(tool_calls) = response.tool_calls() {
+                        self.handle_tool_calls(context, tools, tool_calls.clone(), response_text)
+                            .await
+                    } else {
+                        let response_text = self
+                            .validate_output(context, &messages, response_text)
+                            .await?;
+                        self.handle_text_response(context, response_text).await
+                    };
+                }
+                Err(e) if self.fallback_to_text_tools(&e, tools) => {}
+                Err(e) => return Err(ReActExecutorError::LLMError(e.to_string())),
+            }
+        }
+
+        // Text protocol: tools are described in the prompt, so no tools or schema are sent
+        let messages = self.prepare_messages(context, task, tools, true).await?;
+        let response = context
+            .llm()
+            .chat(&messages, None, None)
+            .await
+            .map_err(|e| ReActExecutorError::LLMError(e.to_string()))?;
         let response_text = response.text().unwrap_or_default();
 
-        if let Some(tool_calls) = response.tool_calls() {
-            self.handle_tool_calls(context, tools, tool_calls.clone(), response_text)
-                .await
+        let tool_calls = TextToolProtocol::parse_tool_calls(&response_text, tools);
+        if tool_calls.is_empty() {
+            let answer = TextToolProtocol::final_answer(&response_text);
+            let answer = self.validate_output(context, &messages, answer).await?;
+            self.handle_text_response(context, answer).await
         } else {
-            self.handle_text_response(context, response_text).await
+            self.handle_text_tool_calls(context, tools, tool_calls, response_text)
+                .await
         }
     }
 
+    /// Validate the final response against the agent's output schema before it is stored
+    async fn validate_output(
+        &self,
+        context: &Context,
+        messages: &[ChatMessage],
+        response_text: String,
+    ) -> Result<String, ReActExecutorError> {
+        OutputValidator::ensure_valid(
+            context,
+            messages,
+            response_text,
+            self.config.structured_output_retries,
+        )
+        .await
+        .map_err(|e| ReActExecutorError::AgentOutputError(e.to_string()))
+    }
+
     /// Get LLM response for the given messages and tools
     async fn get_llm_response(
         &self,
         context: &Context,
         messages: &[ChatMessage],
         tools: &[Box<dyn ToolT>],
-    ) -> Result<Box<dyn autoagents_llm::chat::ChatResponse>, ReActExecutorError> {
+    ) -> Result<Box<dyn autoagents_llm::chat::ChatResponse>, LLMError> {
         let llm = context.llm();
         let agent_config = context.config();
         let tools_serialized: Vec<Tool> = tools.iter().map(to_llm_tool).collect();
@@ -226,7 +335,6 @@ impl<T: AgentDeriveT + AgentHooks> ReActAgent<T> {
             agent_config.output_schema.clone(),
         )
         .await
-        .map_err(|e| ReActExecutorError::LLMError(e.to_string()))
     }
 
     /// Handle tool calls and return the result
@@ -237,19 +345,7 @@ impl<T: AgentDeriveT + AgentHooks> ReActAgent<T> {
         tool_calls: Vec<ToolCall>,
         response_text: String,
     ) -> Result<TurnResult<ReActAgentOutput>, ReActExecutorError> {
-        let tx_event = context.tx().ok();
-
-        // Process tool calls
-        let mut tool_results = Vec::new();
-        for call in &tool_calls {
-            if let Some(result) = ToolProcessor::process_single_tool_call_with_hooks(
-                self, context, tools, call, &tx_event,
-            )
-            .await
-            {
-                tool_results.push(result);
-            }
-        }
+        let tool_results = self.execute_tool_calls(context, tools, &tool_calls).await;
 
         // Store in memory
         MemoryHelper::store_tool_interaction(
@@ -260,22 +356,31 @@ impl<T: AgentDeriveT + AgentHooks> ReActAgent<T> {
         )
         .await;
 
-        // Update state - use try_lock to avoid deadlock
-        {
-            let state = context.state();
-            #[cfg(not(target_arch = "wasm32"))]
-            if let Ok(mut guard) = state.try_lock() {
-                for result in &tool_results {
-                    guard.record_tool_call(result.clone());
-                }
-            };
-            #[cfg(target_arch = "wasm32")]
-            if let Some(mut guard) = state.try_lock() {
-                for result in &tool_results {
-                    guard.record_tool_call(result.clone());
-                }
-            };
-        }
+        self.record_tool_results(context, &tool_results);
+
+        Ok(TurnResult::Continue(Some(ReActAgentOutput {
+            response: response_text,
+            done: true,
+            tool_calls: tool_results,
+        })))
+    }
+
+    /// Handle tool calls parsed from a text-protocol response
+    async fn handle_text_tool_calls(
+        &self,
+        context: &Context,
+        tools: &[Box<dyn ToolT>],
+        tool_calls: Vec<ToolCall>,
+        response_text: String,
+    ) -> Result<TurnResult<ReActAgentOutput>, ReActExecutorError> {
+        let tool_results = self.execute_tool_calls(context, tools, &tool_calls).await;
+        let response_text = TextToolProtocol::strip_observations(&response_text).to_string();
+
+        // Tool calls and results are kept as plain text for providers without tool messages
+        MemoryHelper::store_text_tool_results(&context.memory(), &response_text, &tool_results)
+            .await;
+
+        self.record_tool_results(context, &tool_results);
 
         Ok(TurnResult::Continue(Some(ReActAgentOutput {
             response: response_text,
@@ -284,6 +389,45 @@ impl<T: AgentDeriveT + AgentHooks> ReActAgent<T> {
         })))
     }
 
+    /// Execute tool calls with hooks
+    async fn execute_tool_calls(
+        &self,
+        context: &Context,
+        tools: &[Box<dyn ToolT>],
+        tool_calls: &[ToolCall],
+    ) -> Vec<ToolCallResult> {
+        let tx_event = context.tx().ok();
+        let mut tool_results = Vec::new();
+        for call in tool_calls {
+            if let Some(result) = ToolProcessor::process_single_tool_call_with_hooks(
+                self, context, tools, call, &tx_event,
+            )
+            .await
+            {
+                tool_results.push(result);
+            }
+        }
+        tool_results
+    }
+
+    /// Record tool results in the agent state
+    fn record_tool_results(&self, context: &Context, tool_results: &[ToolCallResult]) {
+        // Use try_lock to avoid deadlock
+        let state = context.state();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(mut guard) = state.try_lock() {
+            for result in tool_results {
+                guard.record_tool_call(result.clone());
+            }
+        };
+        #[cfg(target_arch = "wasm32")]
+        if let Some(mut guard) = state.try_lock() {
+            for result in tool_results {
+                guard.record_tool_call(result.clone());
+            }
+        };
+    }
+
     /// Handle text-only response
     async fn handle_text_response(
         &self,
@@ -291,7 +435,7 @@ impl<T: AgentDeriveT + AgentHooks> ReActAgent<T> {
         response_text: String,
     ) -> Result<TurnResult<ReActAgentOutput>, ReActExecutorError> {
         if !response_text.is_empty() {
-            MemoryHelper::store_assistant_response(&context.memory(), response_text.clone()).await;
+            MemoryHelper::store_final_response(context, response_text.clone()).await;
         }
 
         Ok(TurnResult::Complete(ReActAgentOutput {
@@ -302,30 +446,74 @@ impl<T: AgentDeriveT + AgentHooks> ReActAgent<T> {
     }
 
     /// Prepare messages for the current turn
-    async fn prepare_messages(&self, context: &Context) -> Vec<ChatMessage> {
+    async fn prepare_messages(
+        &self,
+        context: &Context,
+        task: &Task,
+        tools: &[Box<dyn ToolT>],
+        text_tools: bool,
+    ) -> Result<Vec<ChatMessage>, ReActExecutorError> {
+        let recalled = MemoryHelper::recall_messages(&context.memory()).await;
+
+        let mut system = InstructionRenderer::render(context, task, &recalled)
+            .map_err(|e| ReActExecutorError::InstructionError(e.to_string()))?;
+        if let Some(memory_context) = MemoryHelper::system_context(&context.memory()).await {
+            system.push_str("\n\n");
+            system.push_str(&memory_context);
+        }
+        if text_tools {
+            system.push_str("\n\n");
+            system.push_str(&TextToolProtocol::render_instructions(tools));
+            // No schema is sent with text tools, so the model only learns it from the prompt
+            if let Some(schema) = &context.config().output_schema {
+                system.push_str("\n\nThis applies to your Final Answer: ");
+                system.push_str(&schema.prompt_instructions());
+            }
+        }
         let mut messages = vec![ChatMessage {
             role: ChatRole::System,
             message_type: MessageType::Text,
-            content: context.config().description.clone(),
+            content: system,
         }];
-
-        let recalled = MemoryHelper::recall_messages(&context.memory()).await;
         messages.extend(recalled);
+        strip_unsupported_media(&mut messages, context.llm().as_ref());
 
-        messages
+        if let Some(budget) = &self.config.context_budget {
+            messages = budget.fit(messages, context.llm()).await;
+        }
+        Ok(messages)
     }
 
     /// Process a streaming turn with tool support
     async fn process_streaming_turn(
         &self,
         context: &Context,
+        task: &Task,
         tools: &[Box<dyn ToolT>],
         tx: &mut Sender<Result<ReActAgentOutput, ReActExecutorError>>,
-        submission_id: SubmissionId,
     ) -> Result<StreamingTurnResult, ReActExecutorError> {
-        let messages = self.prepare_messages(context).await;
-        let mut stream = self.get_llm_stream(context, &messages, tools).await?;
+        let submission_id = task.submission_id;
+        let mut text_tools = self.use_text_tools(context, tools);
+        let mut messages = self
+            .prepare_messages(context, task, tools, text_tools)
+            .await?;
+        let mut stream = match self
+            .get_llm_stream(context, &messages, tools, text_tools)
+            .await
+        {
+            Ok(stream) => stream,
+            Err(e) if !text_tools && self.fallback_to_text_tools(&e, tools) => {
+                text_tools = true;
+                messages = self.prepare_messages(context, task, tools, true).await?;
+                self.get_llm_stream(context, &messages, tools, true)
+                    .await
+                    .map_err(|e| ReActExecutorError::LLMError(e.to_string()))?
+            }
+            Err(e) => return Err(ReActExecutorError::LLMError(e.to_string())),
+        };
 
+        // With an output schema the answer is held back until it validates
+        let hold_back = context.config().output_schema.is_some();
         let mut response_text = String::new();
         let mut tool_calls_map: HashMap<usize, (Option<String>, Option<String>, String)> =
             HashMap::new();
@@ -335,36 +523,81 @@ impl<T: AgentDeriveT + AgentHooks> ReActAgent<T> {
             let chunk = chunk_result.map_err(|e| ReActExecutorError::LLMError(e.to_string()))?;
 
             if let Some(choice) = chunk.choices.first() {
+                let tx_event = context.tx().ok();
+
+                // Reasoning only goes out as events, never into the response or memory
+                if let Some(reasoning) = &choice.delta.reasoning_content {
+                    EventHelper::send_stream_reasoning(&tx_event, submission_id, reasoning.clone())
+                        .await;
+                }
+
                 // Handle content
                 if let Some(content) = &choice.delta.content {
                     response_text.push_str(content);
-                    let _ = tx
-                        .send(Ok(ReActAgentOutput {
-                            response: content.to_string(),
-                            tool_calls: vec![],
-                            done: false,
-                        }))
-                        .await;
+                    if !hold_back {
+                        Self::send_stream_content(tx, content.to_string()).await;
+                    }
                 }
 
                 // Handle tool calls
                 self.process_stream_tool_calls(&mut tool_calls_map, choice);
 
                 // Send stream chunk event
-                let tx_event = context.tx().ok();
                 EventHelper::send_stream_chunk(&tx_event, submission_id, choice.clone()).await;
             }
         }
 
-        // Process collected tool calls if any
-        self.finalize_stream_tool_calls(
-            context,
-            tools,
-            tool_calls_map,
-            submission_id,
-            response_text,
-        )
-        .await
+        let result = if text_tools {
+            self.finalize_stream_text_tool_calls(
+                context,
+                tools,
+                &messages,
+                submission_id,
+                response_text.clone(),
+            )
+            .await?
+        } else {
+            if tool_calls_map.is_empty() {
+                response_text = self
+                    .validate_output(context, &messages, response_text)
+                    .await?;
+            }
+
+            // Process collected tool calls if any
+            self.finalize_stream_tool_calls(
+                context,
+                tools,
+                tool_calls_map,
+                submission_id,
+                response_text.clone(),
+            )
+            .await?
+        };
+
+        if hold_back {
+            let held = match &result {
+                StreamingTurnResult::Complete(answer) => answer.clone(),
+                StreamingTurnResult::ToolCallsProcessed(_) => response_text,
+            };
+            if !held.is_empty() {
+                Self::send_stream_content(tx, held).await;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Forward streamed response text to the caller
+    async fn send_stream_content(
+        tx: &mut Sender<Result<ReActAgentOutput, ReActExecutorError>>,
+        content: String,
+    ) {
+        let _ = tx
+            .send(Ok(ReActAgentOutput {
+                response: content,
+                tool_calls: vec![],
+                done: false,
+            }))
+            .await;
     }
 
     /// Get streaming LLM response
@@ -373,11 +606,16 @@ impl<T: AgentDeriveT + AgentHooks> ReActAgent<T> {
         context: &Context,
         messages: &[ChatMessage],
         tools: &[Box<dyn ToolT>],
+        text_tools: bool,
     ) -> Result<
         Pin<Box<dyn Stream<Item = Result<autoagents_llm::chat::StreamResponse, LLMError>> + Send>>,
-        ReActExecutorError,
+        LLMError,
     > {
         let llm = context.llm();
+        if text_tools {
+            return llm.chat_stream_struct(messages, None, None).await;
+        }
+
         let agent_config = context.config();
         let tools_serialized: Vec<Tool> = tools.iter().map(to_llm_tool).collect();
 
@@ -391,7 +629,6 @@ impl<T: AgentDeriveT + AgentHooks> ReActAgent<T> {
             agent_config.output_schema.clone(),
         )
         .await
-        .map_err(|e| ReActExecutorError::LLMError(e.to_string()))
     }
 
     /// Process tool calls from stream chunks
@@ -417,6 +654,53 @@ impl<T: AgentDeriveT + AgentHooks> ReActAgent<T> {
         }
     }
 
+    /// Parse and process text-protocol tool calls from a streamed response
+    async fn finalize_stream_text_tool_calls(
+        &self,
+        context: &Context,
+        tools: &[Box<dyn ToolT>],
+        messages: &[ChatMessage],
+        submission_id: SubmissionId,
+        response_text: String,
+    ) -> Result<StreamingTurnResult, ReActExecutorError> {
+        let tool_calls = TextToolProtocol::parse_tool_calls(&response_text, tools);
+        if tool_calls.is_empty() {
+            let answer = TextToolProtocol::final_answer(&response_text);
+            let answer = self.validate_output(context, messages, answer).await?;
+            if !answer.is_empty() {
+                MemoryHelper::store_final_response(context, answer.clone()).await;
+            }
+            return Ok(StreamingTurnResult::Complete(answer));
+        }
+
+        let tx_event = context.tx().ok();
+        for tool_call in &tool_calls {
+            EventHelper::send_stream_tool_call(
+                &tx_event,
+                submission_id,
+                serde_json::to_value(tool_call).unwrap_or(Value::Null),
+            )
+            .await;
+        }
+
+        let tool_results = ToolProcessor::process_tool_calls(tools, tool_calls, tx_event).await;
+
+        MemoryHelper::store_text_tool_results(
+            &context.memory(),
+            TextToolProtocol::strip_observations(&response_text),
+            &tool_results,
+        )
+        .await;
+
+        let state = context.state();
+        let mut guard = state.lock().await;
+        for result in &tool_results {
+            guard.record_tool_call(result.clone());
+        }
+
+        Ok(StreamingTurnResult::ToolCallsProcessed(tool_results))
+    }
+
     /// Finalize and process collected tool calls from streaming
     async fn finalize_stream_tool_calls(
         &self,
@@ -428,8 +712,7 @@ impl<T: AgentDeriveT + AgentHooks> ReActAgent<T> {
     ) -> Result<StreamingTurnResult, ReActExecutorError> {
         if tool_calls_map.is_empty() {
             if !response_text.is_empty() {
-                MemoryHelper::store_assistant_response(&context.memory(), response_text.clone())
-                    .await;
+                MemoryHelper::store_final_response(context, response_text.clone()).await;
             }
             return Ok(StreamingTurnResult::Complete(response_text));
         }
@@ -494,7 +777,7 @@ impl<T: AgentDeriveT + AgentHooks> AgentExecutor for ReActAgent<T> {
     type Error = ReActExecutorError;
 
     fn config(&self) -> ExecutorConfig {
-        ExecutorConfig { max_turns: 10 }
+        self.config.clone()
     }
 
     async fn execute(
@@ -503,12 +786,7 @@ impl<T: AgentDeriveT + AgentHooks> AgentExecutor for ReActAgent<T> {
         context: Arc<Context>,
     ) -> Result<Self::Output, Self::Error> {
         // Initialize task
-        MemoryHelper::store_user_message(
-            &context.memory(),
-            task.prompt.clone(),
-            task.image.clone(),
-        )
-        .await;
+        MemoryHelper::store_task(&context.memory(), task).await;
 
         // Record task in state - use try_lock to avoid deadlock
         {
@@ -546,7 +824,7 @@ impl<T: AgentDeriveT + AgentHooks> AgentExecutor for ReActAgent<T> {
             //Run Hook
             self.on_turn_start(turn_num, &context).await;
 
-            match self.process_turn(&context, tools).await? {
+            match self.process_turn(&context, task, tools).await? {
                 TurnResult::Complete(result) => {
                     if !accumulated_tool_calls.is_empty() {
                         return Ok(ReActAgentOutput {
@@ -598,12 +876,7 @@ impl<T: AgentDeriveT + AgentHooks> AgentExecutor for ReActAgent<T> {
         Self::Error,
     > {
         // Initialize task
-        MemoryHelper::store_user_message(
-            &context.memory(),
-            task.prompt.clone(),
-            task.image.clone(),
-        )
-        .await;
+        MemoryHelper::store_task(&context.memory(), task).await;
 
         // Record task in state - use try_lock to avoid deadlock
         {
@@ -635,6 +908,7 @@ impl<T: AgentDeriveT + AgentHooks> AgentExecutor for ReActAgent<T> {
         // Clone necessary components
         let executor = self.clone();
         let context_clone = context.clone();
+        let task = task.clone();
         let submission_id = task.submission_id;
         let max_turns = executor.config().max_turns;
 
@@ -651,7 +925,7 @@ impl<T: AgentDeriveT + AgentHooks> AgentExecutor for ReActAgent<T> {
 
                 // Process streaming turn
                 match executor
-                    .process_streaming_turn(&context_clone, tools, &mut tx, submission_id)
+                    .process_streaming_turn(&context_clone, &task, tools, &mut tx)
                     .await
                 {
                     Ok(StreamingTurnResult::Complete(response)) => {
@@ -724,4 +998,282 @@ mod tests {
             ReActAgentOutput::extract_agent_output(react_value).unwrap();
         assert_eq!(extracted, agent_output);
     }
+
+    #[tokio::test]
+    async fn test_execute_fails_when_output_never_matches_schema() {
+        use crate::agent::AgentConfig;
+        use crate::tests::agent::MockAgentImpl;
+        use autoagents_llm::chat::StructuredOutputFormat;
+        use autoagents_test_utils::llm::MockLLMProvider;
+
+        let agent =
+            ReActAgent::new(MockAgentImpl::new("test", "desc")).with_config(ExecutorConfig {
+                max_turns: 2,
+                structured_output_retries: 1,
+                ..Default::default()
+            });
+        let config = AgentConfig::new("test".to_string(), "desc".to_string()).with_output_schema(
+            StructuredOutputFormat {
+                name: "TestAgentOutput".to_string(),
+                description: None,
+                schema: Some(serde_json::json!({"type": "object"})),
+                strict: None,
+            },
+        );
+        let context = Context::new(Arc::new(MockLLMProvider), None).with_config(config);
+
+        let result = agent.execute(&Task::new("test"), Arc::new(context)).await;
+        assert!(matches!(
+            result,
+            Err(ReActExecutorError::AgentOutputError(_))
+        ));
+        assert_eq!(agent.config().max_turns, 2);
+    }
+
+    #[derive(Debug)]
+    struct AddTool;
+
+    impl crate::tool::ToolRuntime for AddTool {
+        fn execute(&self, args: Value) -> Result<Value, crate::tool::ToolCallError> {
+            Ok(serde_json::json!(
+                args["a"].as_i64().unwrap_or(0) + args["b"].as_i64().unwrap_or(0)
+            ))
+        }
+    }
+
+    impl ToolT for AddTool {
+        fn name(&self) -> &'static str {
+            "add"
+        }
+
+        fn description(&self) -> &'static str {
+            "Add two numbers"
+        }
+
+        fn args_schema(&self) -> Value {
+            serde_json::json!({"type": "object"})
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_text_tool_calling() {
+        use crate::agent::memory::{MemoryProvider, SlidingWindowMemory};
+        use crate::tests::agent::MockAgentImpl;
+        use autoagents_test_utils::llm::ScriptedLLMProvider;
+        use tokio::sync::Mutex;
+
+        let llm = Arc::new(ScriptedLLMProvider::new([
+            "Thought: I should add\nAction: add\nAction Input: {\"a\": 1, \"b\": 2}",
+            "Final Answer: 3",
+        ]));
+        let memory: Box<dyn MemoryProvider> = Box::new(SlidingWindowMemory::new(10));
+        let context = Context::new(llm.clone(), None)
+            .with_memory(Some(Arc::new(Mutex::new(memory))))
+            .with_tools(vec![Box::new(AddTool)]);
+
+        let agent = ReActAgent::new(MockAgentImpl::new("test", "desc"))
+            .with_tool_calling_mode(ToolCallingMode::Text);
+        let output = agent
+            .execute(&Task::new("What is 1 + 2?"), Arc::new(context))
+            .await
+            .unwrap();
+
+        assert_eq!(output.response, "3");
+        assert_eq!(output.tool_calls.len(), 1);
+        assert_eq!(output.tool_calls[0].result, serde_json::json!(3));
+
+        let requests = llm.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0][0].content.contains("- add: Add two numbers"));
+        assert_eq!(requests[1].last().unwrap().content, "Observation: 3");
+    }
+
+    #[tokio::test]
+    async fn test_system_prompt_renders_instruction_template() {
+        use crate::agent::AgentConfig;
+        use crate::tests::agent::MockAgentImpl;
+        use autoagents_test_utils::llm::ScriptedLLMProvider;
+
+        let llm = Arc::new(ScriptedLLMProvider::new(["done"]));
+        let mut config = AgentConfig::new("helper".to_string(), "desc".to_string());
+        config.instructions = "You help {{ user }} at {{ company }}: {{ task.prompt }}".to_string();
+        let context = Context::new(llm.clone(), None)
+            .with_config(config)
+            .with_var("company", "Acme");
+
+        let agent = ReActAgent::new(MockAgentImpl::new("helper", "desc"));
+        let task = Task::new("plan a trip").with_var("user", "Ada");
+        agent.execute(&task, Arc::new(context)).await.unwrap();
+
+        assert_eq!(
+            llm.requests()[0][0].content,
+            "You help Ada at Acme: plan a trip"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_system_prompt_includes_memory_context() {
+        use crate::agent::memory::{MemoryProvider, ProfileFact, ProfileMemory};
+        use crate::tests::agent::MockAgentImpl;
+        use autoagents_test_utils::llm::ScriptedLLMProvider;
+        use tokio::sync::Mutex;
+
+        let llm = Arc::new(ScriptedLLMProvider::new(["done"]));
+        let now = chrono::Utc::now();
+        let memory = ProfileMemory::new(llm.clone(), 10).with_facts(vec![ProfileFact {
+            key: "name".to_string(),
+            value: "Ada".to_string(),
+            confidence: 1.0,
+            created_at: now,
+            updated_at: now,
+        }]);
+        let memory: Box<dyn MemoryProvider> = Box::new(memory);
+        let context =
+            Context::new(llm.clone(), None).with_memory(Some(Arc::new(Mutex::new(memory))));
+
+        let agent = ReActAgent::new(MockAgentImpl::new("helper", "desc"));
+        agent
+            .execute(&Task::new("hello"), Arc::new(context))
+            .await
+            .unwrap();
+
+        assert!(llm.requests()[0][0]
+            .content
+            .ends_with("Known facts about the user:\n- name: Ada"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_forwards_reasoning_as_events() {
+        use crate::agent::memory::{MemoryProvider, SlidingWindowMemory};
+        use crate::tests::agent::MockAgentImpl;
+        use autoagents_test_utils::llm::ScriptedLLMProvider;
+        use tokio::sync::Mutex;
+
+        let llm = Arc::new(ScriptedLLMProvider::new(["The answer"]).with_reasoning("Thinking..."));
+        let memory: Box<dyn MemoryProvider> = Box::new(SlidingWindowMemory::new(10));
+        let memory = Arc::new(Mutex::new(memory));
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        let context = Context::new(llm, Some(tx)).with_memory(Some(memory.clone()));
+
+        let agent = ReActAgent::new(MockAgentImpl::new("test", "desc"));
+        let outputs: Vec<ReActAgentOutput> = agent
+            .execute_stream(&Task::new("question"), Arc::new(context))
+            .await
+            .unwrap()
+            .map(|output| output.unwrap())
+            .collect()
+            .await;
+        assert!(outputs.iter().all(|o| !o.response.contains("Thinking")));
+        assert_eq!(outputs.last().unwrap().response, "The answer");
+
+        let mut reasoning = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let Event::StreamReasoning { reasoning: r, .. } = event {
+                reasoning.push(r);
+            }
+        }
+        assert_eq!(reasoning, vec!["Thinking...".to_string()]);
+
+        let stored = memory.lock().await.recall("", None).await.unwrap();
+        assert_eq!(stored.last().unwrap().content, "The answer");
+        assert!(stored.iter().all(|m| !m.content.contains("Thinking")));
+    }
+
+    #[tokio::test]
+    async fn test_stream_holds_back_output_until_it_validates() {
+        use crate::agent::AgentConfig;
+        use crate::tests::agent::MockAgentImpl;
+        use autoagents_llm::chat::StructuredOutputFormat;
+        use autoagents_test_utils::llm::ScriptedLLMProvider;
+
+        let llm = Arc::new(ScriptedLLMProvider::new([
+            "not json",
+            r#"{"value": 1, "message": "ok"}"#,
+        ]));
+        let config = AgentConfig::new("test".to_string(), "desc".to_string()).with_output_schema(
+            StructuredOutputFormat {
+                name: "TestAgentOutput".to_string(),
+                description: None,
+                schema: Some(serde_json::json!({"type": "object", "required": ["value"]})),
+                strict: None,
+            },
+        );
+        let context = Context::new(llm, None).with_config(config);
+
+        let agent = ReActAgent::new(MockAgentImpl::new("test", "desc"));
+        let outputs: Vec<ReActAgentOutput> = agent
+            .execute_stream(&Task::new("question"), Arc::new(context))
+            .await
+            .unwrap()
+            .map(|output| output.unwrap())
+            .collect()
+            .await;
+        assert!(outputs.iter().all(|o| !o.response.contains("not json")));
+        let chunks: Vec<&str> = outputs
+            .iter()
+            .filter(|o| !o.done)
+            .map(|o| o.response.as_str())
+            .collect();
+        // The validated answer is normalized to compact JSON
+        assert_eq!(chunks, vec![r#"{"message":"ok","value":1}"#]);
+        assert_eq!(
+            outputs.last().unwrap().response,
+            r#"{"message":"ok","value":1}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_text_tool_prompt_includes_output_schema() {
+        use crate::agent::AgentConfig;
+        use crate::tests::agent::MockAgentImpl;
+        use autoagents_llm::chat::StructuredOutputFormat;
+        use autoagents_test_utils::llm::ScriptedLLMProvider;
+
+        let llm = Arc::new(ScriptedLLMProvider::new([r#"Final Answer: {"value": 1}"#]));
+        let config = AgentConfig::new("test".to_string(), "desc".to_string()).with_output_schema(
+            StructuredOutputFormat {
+                name: "TestAgentOutput".to_string(),
+                description: None,
+                schema: Some(serde_json::json!({"type": "object"})),
+                strict: None,
+            },
+        );
+        let context = Context::new(llm.clone(), None)
+            .with_config(config)
+            .with_tools(vec![Box::new(AddTool)]);
+
+        let agent = ReActAgent::new(MockAgentImpl::new("test", "desc"))
+            .with_tool_calling_mode(ToolCallingMode::Text);
+        let output = agent
+            .execute(&Task::new("question"), Arc::new(context))
+            .await
+            .unwrap();
+
+        assert_eq!(output.response, r#"{"value":1}"#);
+        assert!(llm.requests()[0][0].content.contains("`TestAgentOutput`"));
+    }
+
+    #[tokio::test]
+    async fn test_auto_mode_uses_text_tools_without_tool_capability() {
+        use crate::tests::agent::MockAgentImpl;
+        use autoagents_llm::models::ModelCapabilities;
+        use autoagents_test_utils::llm::ScriptedLLMProvider;
+
+        let llm = Arc::new(
+            ScriptedLLMProvider::new(["Final Answer: done"])
+                .with_capabilities(ModelCapabilities::default()),
+        );
+        let context = Context::new(llm.clone(), None).with_tools(vec![Box::new(AddTool)]);
+
+        let agent = ReActAgent::new(MockAgentImpl::new("test", "desc"));
+        let output = agent
+            .execute(&Task::new("What is 1 + 2?"), Arc::new(context))
+            .await
+            .unwrap();
+
+        assert_eq!(output.response, "done");
+        assert!(llm.requests()[0][0]
+            .content
+            .contains("- add: Add two numbers"));
+    }
 }
@@ -1,4 +1,3 @@
-use autoagents_llm::chat::ChatMessage;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,13 +35,3 @@ pub enum AgentProtocol {
         final_turn: bool,
     },
 }
-
-/// Messages from the agent - used for A2A communication
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct AgentMessage {
-    /// The content of the message
-    pub content: String,
-
-    /// Optional chat messages for a full conversation history
-    pub chat_messages: Option<Vec<ChatMessage>>,
-}
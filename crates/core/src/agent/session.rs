@@ -0,0 +1,365 @@
+use crate::agent::constants::DEFAULT_SESSION_WINDOW_SIZE;
+use crate::agent::error::RunnableAgentError;
+use crate::agent::memory::{MemoryProvider, SlidingWindowMemory};
+use crate::agent::task::Task;
+use crate::agent::{AgentDeriveT, AgentExecutor, AgentHooks, BaseAgent, DirectAgent};
+use crate::protocol::SubmissionId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{hash_map::Entry, HashMap};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::sync::Mutex;
+
+#[cfg(target_arch = "wasm32")]
+use futures::lock::Mutex;
+
+/// Creates a fresh memory instance for every new session
+pub type MemoryFactory = Arc<dyn Fn() -> Box<dyn MemoryProvider> + Send + Sync>;
+
+#[derive(Debug, Error)]
+pub enum SessionError {
+    #[error("Session not found: {0}")]
+    NotFound(String),
+
+    #[error("Session already exists: {0}")]
+    AlreadyExists(String),
+
+    #[error("Session memory error: {0}")]
+    MemoryError(String),
+}
+
+/// A completed exchange in a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTurn {
+    pub submission_id: SubmissionId,
+    pub prompt: String,
+    pub output: Value,
+}
+
+/// State of a single session
+struct SessionState {
+    memory: Arc<Mutex<Box<dyn MemoryProvider>>>,
+    history: Mutex<Vec<SessionTurn>>,
+    metadata: Mutex<HashMap<String, Value>>,
+    /// Held for the duration of a run so concurrent runs on one session don't interleave
+    run_lock: Mutex<()>,
+}
+
+impl SessionState {
+    fn new(memory: Box<dyn MemoryProvider>) -> Self {
+        Self {
+            memory: Arc::new(Mutex::new(memory)),
+            history: Mutex::new(Vec::new()),
+            metadata: Mutex::new(HashMap::new()),
+            run_lock: Mutex::new(()),
+        }
+    }
+}
+
+/// Registry of the sessions of an agent
+#[derive(Default)]
+pub(crate) struct SessionStore {
+    sessions: Mutex<HashMap<String, Arc<SessionState>>>,
+}
+
+/// Handle to a conversation with an agent.
+///
+/// Each session has its own memory, history and metadata, so concurrent sessions on the
+/// same agent never see each other's messages. Handles are cheap to clone and all handles
+/// for the same session id share the same state.
+pub struct Session<T: AgentDeriveT + AgentExecutor + AgentHooks> {
+    id: String,
+    agent: BaseAgent<T, DirectAgent>,
+    state: Arc<SessionState>,
+}
+
+impl<T: AgentDeriveT + AgentExecutor + AgentHooks> Clone for Session<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id.clone(),
+            agent: self.agent.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T: AgentDeriveT + AgentExecutor + AgentHooks> Session<T> {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Run a task within this session.
+    ///
+    /// The session metadata is merged into the task metadata, with the task taking precedence.
+    pub async fn run(
+        &self,
+        mut task: Task,
+    ) -> Result<<T as AgentDeriveT>::Output, RunnableAgentError>
+    where
+        <T as AgentDeriveT>::Output: From<<T as AgentExecutor>::Output>,
+    {
+        let _guard = self.state.run_lock.lock().await;
+
+        let mut metadata = self.state.metadata.lock().await.clone();
+        metadata.extend(std::mem::take(&mut task.metadata));
+        task.metadata = metadata;
+
+        let context = self
+            .agent
+            .create_context_with_memory(Some(self.state.memory.clone()))?;
+        let submission_id = task.submission_id;
+        let prompt = task.prompt.clone();
+        let output = self.agent.run_with_context(task, context).await?;
+
+        self.state.history.lock().await.push(SessionTurn {
+            submission_id,
+            prompt,
+            output: serde_json::to_value(&output).unwrap_or(Value::Null),
+        });
+        Ok(output)
+    }
+
+    /// Completed turns, oldest first
+    pub async fn history(&self) -> Vec<SessionTurn> {
+        self.state.history.lock().await.clone()
+    }
+
+    /// The session memory
+    pub fn memory(&self) -> Arc<Mutex<Box<dyn MemoryProvider>>> {
+        self.state.memory.clone()
+    }
+
+    pub async fn metadata(&self) -> HashMap<String, Value> {
+        self.state.metadata.lock().await.clone()
+    }
+
+    /// Set a metadata entry, passed to every task run in this session
+    pub async fn set_metadata(&self, key: impl Into<String>, value: impl Into<Value>) {
+        self.state
+            .metadata
+            .lock()
+            .await
+            .insert(key.into(), value.into());
+    }
+
+    /// Clear the memory and history, keeping the metadata
+    pub async fn clear(&self) -> Result<(), SessionError> {
+        let _guard = self.state.run_lock.lock().await;
+        self.state
+            .memory
+            .lock()
+            .await
+            .clear()
+            .await
+            .map_err(|e| SessionError::MemoryError(e.to_string()))?;
+        self.state.history.lock().await.clear();
+        Ok(())
+    }
+}
+
+impl<T: AgentDeriveT + AgentExecutor + AgentHooks> BaseAgent<T, DirectAgent> {
+    /// Get the session with the given id, creating it if it doesn't exist
+    pub async fn session(&self, id: impl Into<String>) -> Session<T> {
+        let id = id.into();
+        let state = self
+            .sessions
+            .sessions
+            .lock()
+            .await
+            .entry(id.clone())
+            .or_insert_with(|| Arc::new(SessionState::new(self.new_session_memory())))
+            .clone();
+        self.session_handle(id, state)
+    }
+
+    /// Ids of all sessions, sorted
+    pub async fn sessions(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .sessions
+            .sessions
+            .lock()
+            .await
+            .keys()
+            .cloned()
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Copy a session into a new one. Later runs in either session don't affect the other.
+    pub async fn fork_session(
+        &self,
+        from: &str,
+        to: impl Into<String>,
+    ) -> Result<Session<T>, SessionError> {
+        let to = to.into();
+        // Don't hold the session map while waiting for a run in the source session
+        let source = {
+            let sessions = self.sessions.sessions.lock().await;
+            if sessions.contains_key(&to) {
+                return Err(SessionError::AlreadyExists(to));
+            }
+            sessions
+                .get(from)
+                .cloned()
+                .ok_or_else(|| SessionError::NotFound(from.to_string()))?
+        };
+
+        let _guard = source.run_lock.lock().await;
+        let messages = source
+            .memory
+            .lock()
+            .await
+            .recall("", None)
+            .await
+            .map_err(|e| SessionError::MemoryError(e.to_string()))?;
+        let mut memory = self.new_session_memory();
+        for message in &messages {
+            memory
+                .remember(message)
+                .await
+                .map_err(|e| SessionError::MemoryError(e.to_string()))?;
+        }
+
+        let state = Arc::new(SessionState {
+            memory: Arc::new(Mutex::new(memory)),
+            history: Mutex::new(source.history.lock().await.clone()),
+            metadata: Mutex::new(source.metadata.lock().await.clone()),
+            run_lock: Mutex::new(()),
+        });
+        match self.sessions.sessions.lock().await.entry(to.clone()) {
+            Entry::Occupied(_) => return Err(SessionError::AlreadyExists(to)),
+            Entry::Vacant(entry) => {
+                entry.insert(state.clone());
+            }
+        }
+        Ok(self.session_handle(to, state))
+    }
+
+    /// Delete a session. Returns `false` if it didn't exist.
+    pub async fn delete_session(&self, id: &str) -> bool {
+        self.sessions.sessions.lock().await.remove(id).is_some()
+    }
+
+    fn new_session_memory(&self) -> Box<dyn MemoryProvider> {
+        match &self.memory_factory {
+            Some(factory) => factory(),
+            None => Box::new(SlidingWindowMemory::new(DEFAULT_SESSION_WINDOW_SIZE)),
+        }
+    }
+
+    fn session_handle(&self, id: String, state: Arc<SessionState>) -> Session<T> {
+        Session {
+            id,
+            agent: self.clone(),
+            state,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::prebuilt::executor::ReActAgent;
+    use crate::tests::agent::MockAgentImpl;
+    use autoagents_llm::chat::ChatRole;
+    use autoagents_test_utils::llm::ScriptedLLMProvider;
+    use tokio::sync::mpsc::channel;
+
+    async fn agent(
+        llm: Arc<ScriptedLLMProvider>,
+    ) -> BaseAgent<ReActAgent<MockAgentImpl>, DirectAgent> {
+        let (tx, _rx) = channel(10);
+        BaseAgent::new(
+            ReActAgent::new(MockAgentImpl::new("chat", "A chat agent")),
+            llm,
+            None,
+            tx,
+            false,
+        )
+        .await
+        .unwrap()
+        .with_memory_factory(Some(Arc::new(|| {
+            Box::new(SlidingWindowMemory::new(10)) as Box<dyn MemoryProvider>
+        })))
+    }
+
+    #[tokio::test]
+    async fn test_sessions_have_separate_memory() {
+        let llm = Arc::new(ScriptedLLMProvider::new([r#"{"result": "ok"}"#]));
+        let agent = agent(llm.clone()).await;
+
+        let alice = agent.session("alice").await;
+        let bob = agent.session("bob").await;
+        alice.run(Task::new("I am Alice")).await.unwrap();
+        bob.run(Task::new("I am Bob")).await.unwrap();
+        alice.run(Task::new("Who am I?")).await.unwrap();
+
+        let requests = llm.requests();
+        let contents: Vec<&str> = requests[2]
+            .iter()
+            .filter(|m| m.role == ChatRole::User)
+            .map(|m| m.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["I am Alice", "Who am I?"]);
+
+        assert_eq!(alice.history().await.len(), 2);
+        assert_eq!(bob.history().await[0].prompt, "I am Bob");
+        assert_eq!(agent.sessions().await, vec!["alice", "bob"]);
+    }
+
+    #[tokio::test]
+    async fn test_fork_and_delete_session() {
+        let llm = Arc::new(ScriptedLLMProvider::new([r#"{"result": "ok"}"#]));
+        let agent = agent(llm).await;
+
+        let main = agent.session("main").await;
+        main.set_metadata("tenant", "acme").await;
+        main.run(Task::new("hello")).await.unwrap();
+
+        let fork = agent.fork_session("main", "fork").await.unwrap();
+        fork.run(Task::new("only in fork")).await.unwrap();
+
+        assert_eq!(main.history().await.len(), 1);
+        assert_eq!(fork.history().await.len(), 2);
+        assert_eq!(main.memory().lock().await.size(), 2);
+        assert_eq!(fork.memory().lock().await.size(), 4);
+        assert_eq!(fork.metadata().await["tenant"], "acme");
+
+        assert!(matches!(
+            agent.fork_session("main", "fork").await,
+            Err(SessionError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            agent.fork_session("missing", "other").await,
+            Err(SessionError::NotFound(_))
+        ));
+
+        assert!(agent.delete_session("fork").await);
+        assert!(!agent.delete_session("fork").await);
+        assert_eq!(agent.sessions().await, vec!["main"]);
+    }
+
+    #[tokio::test]
+    async fn test_fork_waits_for_run_without_blocking_other_sessions() {
+        use std::time::Duration;
+        use tokio::time::timeout;
+
+        let llm = Arc::new(ScriptedLLMProvider::new([r#"{"result": "ok"}"#]));
+        let agent = agent(llm).await;
+        let main = agent.session("main").await;
+
+        let run = main.state.run_lock.lock().await;
+        let mut fork = std::pin::pin!(agent.fork_session("main", "fork"));
+        assert!(timeout(Duration::from_millis(50), &mut fork).await.is_err());
+        let other = timeout(Duration::from_secs(1), agent.session("other")).await;
+        assert!(other.is_ok());
+
+        drop(run);
+        assert!(fork.await.is_ok());
+        assert_eq!(agent.sessions().await, vec!["fork", "main", "other"]);
+    }
+}
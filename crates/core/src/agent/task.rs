@@ -1,18 +1,111 @@
 #[cfg(not(target_arch = "wasm32"))]
 use crate::actor::{ActorMessage, CloneableMessage};
 use crate::protocol::SubmissionId;
-use autoagents_llm::chat::ImageMime;
+use autoagents_llm::chat::{ChatMessage, ImageMime};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// A file or media item sent to the agent along with the task prompt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Attachment {
+    /// Raw image bytes
+    Image { mime: ImageMime, data: Vec<u8> },
+    /// Image referenced by URL
+    ImageUrl { url: String },
+    /// PDF document
+    Pdf { name: String, data: Vec<u8> },
+    /// Text file, sent to the model inline
+    Text { name: String, content: String },
+}
+
+impl Attachment {
+    pub fn image(mime: ImageMime, data: Vec<u8>) -> Self {
+        Attachment::Image { mime, data }
+    }
+
+    pub fn image_url(url: impl Into<String>) -> Self {
+        Attachment::ImageUrl { url: url.into() }
+    }
+
+    pub fn pdf(name: impl Into<String>, data: Vec<u8>) -> Self {
+        Attachment::Pdf {
+            name: name.into(),
+            data,
+        }
+    }
+
+    pub fn text(name: impl Into<String>, content: impl Into<String>) -> Self {
+        Attachment::Text {
+            name: name.into(),
+            content: content.into(),
+        }
+    }
+
+    /// The user message carrying this attachment
+    pub fn to_chat_message(&self) -> ChatMessage {
+        match self {
+            Attachment::Image { mime, data } => ChatMessage::user()
+                .image(mime.clone(), data.clone())
+                .build(),
+            Attachment::ImageUrl { url } => ChatMessage::user().image_url(url.clone()).build(),
+            Attachment::Pdf { name, data } => ChatMessage::user()
+                .pdf_file(name.clone(), data.clone())
+                .build(),
+            Attachment::Text { name, content } => ChatMessage::user()
+                .content(format!("Attached file: {name}\n\n{content}"))
+                .build(),
+        }
+    }
+}
+
+/// Spoken input, transcribed into the prompt before the agent runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioInput {
+    /// File name; providers infer the audio container from its extension
+    pub name: String,
+    pub data: Vec<u8>,
+    /// ISO-639-1 language of the audio, if known
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+impl AudioInput {
+    pub fn new(name: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            name: name.into(),
+            data,
+            language: None,
+        }
+    }
+
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
     pub prompt: String,
     pub image: Option<(ImageMime, Vec<u8>)>,
+    /// Files and media sent along with the prompt
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    /// Audio transcribed by the agent's speech-to-text provider before the run
+    #[serde(default)]
+    pub audio: Option<AudioInput>,
+    /// Arbitrary key/value metadata, such as user id, tenant or locale
+    #[serde(default)]
+    pub metadata: HashMap<String, Value>,
     pub submission_id: SubmissionId,
     pub completed: bool,
     pub result: Option<Value>,
+    /// Variables available to the agent's instruction template
+    #[serde(default)]
+    pub vars: HashMap<String, Value>,
 }
 
 impl Task {
@@ -20,9 +113,13 @@ impl Task {
         Self {
             prompt: task.into(),
             image: None,
+            attachments: Vec::new(),
+            audio: None,
+            metadata: HashMap::new(),
             submission_id: Uuid::new_v4(),
             completed: false,
             result: None,
+            vars: HashMap::new(),
         }
     }
 
@@ -34,11 +131,61 @@ impl Task {
         Self {
             prompt: task.into(),
             image: Some((image_mime, image_data)),
+            attachments: Vec::new(),
+            audio: None,
+            metadata: HashMap::new(),
             submission_id: Uuid::new_v4(),
             completed: false,
             result: None,
+            vars: HashMap::new(),
         }
     }
+
+    /// Set a variable available to the agent's instruction template
+    pub fn with_var(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.vars.insert(key.into(), value.into());
+        self
+    }
+
+    /// Add an attachment to the task
+    pub fn with_attachment(mut self, attachment: Attachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
+    /// Add several attachments to the task
+    pub fn with_attachments(mut self, attachments: impl IntoIterator<Item = Attachment>) -> Self {
+        self.attachments.extend(attachments);
+        self
+    }
+
+    /// Attach spoken input. Its transcript is appended to the prompt before the run,
+    /// so the prompt may be left empty.
+    pub fn with_audio(mut self, audio: AudioInput) -> Self {
+        self.audio = Some(audio);
+        self
+    }
+
+    /// Set a metadata entry
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// The user messages for this task: one message per attachment, followed by the prompt
+    pub fn to_chat_messages(&self) -> Vec<ChatMessage> {
+        let mut messages: Vec<ChatMessage> = self
+            .attachments
+            .iter()
+            .map(Attachment::to_chat_message)
+            .collect();
+        let prompt = match &self.image {
+            Some((mime, data)) => ChatMessage::user().image(mime.clone(), data.clone()),
+            None => ChatMessage::user(),
+        };
+        messages.push(prompt.content(self.prompt.clone()).build());
+        messages
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -166,4 +313,62 @@ mod tests {
         assert_eq!(deserialized.image, task.image);
         assert_eq!(deserialized.submission_id, task.submission_id);
     }
+
+    #[test]
+    fn test_task_attachments_and_metadata_serialization() {
+        let task = Task::new("Summarize these")
+            .with_attachments([
+                Attachment::image(ImageMime::PNG, vec![1, 2, 3]),
+                Attachment::pdf("report.pdf", vec![0x25, 0x50, 0x44, 0x46]),
+                Attachment::image_url("https://example.com/chart.png"),
+                Attachment::text("notes.txt", "remember the milk"),
+            ])
+            .with_metadata("tenant", "acme")
+            .with_metadata("user_id", 42);
+
+        let serialized = serde_json::to_string(&task).unwrap();
+        assert!(serialized.contains(r#""type":"image_url""#));
+        let deserialized: Task = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.attachments, task.attachments);
+        assert_eq!(deserialized.metadata["tenant"], json!("acme"));
+        assert_eq!(deserialized.metadata["user_id"], json!(42));
+    }
+
+    #[test]
+    fn test_task_without_attachments_deserializes() {
+        let task: Task = serde_json::from_value(json!({
+            "prompt": "legacy",
+            "image": null,
+            "submission_id": Uuid::new_v4(),
+            "completed": false,
+            "result": null
+        }))
+        .unwrap();
+        assert!(task.attachments.is_empty());
+        assert!(task.metadata.is_empty());
+    }
+
+    #[test]
+    fn test_task_to_chat_messages() {
+        use autoagents_llm::chat::MessageType;
+
+        let task = Task::new("What is in the report?")
+            .with_attachment(Attachment::pdf("report.pdf", vec![1]))
+            .with_attachment(Attachment::text("notes.txt", "draft"));
+        let messages = task.to_chat_messages();
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages[0].message_type,
+            MessageType::Pdf {
+                name: "report.pdf".to_string(),
+                data: vec![1]
+            }
+        );
+        assert!(messages[0].content.is_empty());
+        assert_eq!(messages[1].message_type, MessageType::Text);
+        assert_eq!(messages[1].content, "Attached file: notes.txt\n\ndraft");
+        assert_eq!(messages[2].content, "What is in the report?");
+        assert_eq!(messages[2].message_type, MessageType::Text);
+    }
 }
@@ -0,0 +1,451 @@
+use super::Document;
+#[cfg(all(feature = "tokenizers", not(target_arch = "wasm32")))]
+use super::DocumentError;
+use regex::Regex;
+use std::collections::VecDeque;
+use std::sync::LazyLock;
+
+static MARKDOWN_HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(#{1,6})\s+(.+?)\s*#*\s*$").unwrap());
+
+/// Separators tried in order by [`RecursiveChunker`]
+const DEFAULT_SEPARATORS: [&str; 4] = ["\n\n", "\n", ". ", " "];
+
+/// Splits documents into chunks ready for embedding.
+///
+/// Chunks keep the metadata of their document, plus `parent_id` and `chunk_index`, and
+/// get the id `{parent_id}:{chunk_index}`.
+pub trait Chunker: Send + Sync {
+    fn split_text(&self, text: &str) -> Vec<String>;
+
+    fn split_document(&self, document: &Document) -> Vec<Document> {
+        self.split_text(&document.text)
+            .into_iter()
+            .enumerate()
+            .map(|(index, text)| chunk_of(document, index, text))
+            .collect()
+    }
+
+    fn split_documents(&self, documents: &[Document]) -> Vec<Document> {
+        documents
+            .iter()
+            .flat_map(|d| self.split_document(d))
+            .collect()
+    }
+}
+
+fn chunk_of(parent: &Document, index: usize, text: String) -> Document {
+    let mut chunk = Document::with_id(format!("{}:{index}", parent.id), text);
+    chunk.metadata = parent.metadata.clone();
+    chunk
+        .with_metadata("parent_id", parent.id.clone())
+        .with_metadata("chunk_index", index)
+}
+
+fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Raise the chunk size to at least 1 and halve an overlap that isn't below it, so every
+/// chunk makes progress without repeating most of the previous one
+fn clamp(chunk_size: usize, overlap: usize) -> (usize, usize) {
+    let chunk_size = chunk_size.max(1);
+    if overlap < chunk_size {
+        return (chunk_size, overlap);
+    }
+    log::warn!(
+        "Chunk overlap {overlap} must be smaller than the chunk size {chunk_size}, using {}",
+        chunk_size / 2
+    );
+    (chunk_size, chunk_size / 2)
+}
+
+/// Splits text into windows of `chunk_size` characters, each overlapping the previous one
+/// by `overlap` characters
+#[derive(Debug, Clone)]
+pub struct FixedSizeChunker {
+    chunk_size: usize,
+    overlap: usize,
+}
+
+impl FixedSizeChunker {
+    /// `chunk_size` is raised to at least 1, and an `overlap` that isn't below it is
+    /// lowered to half of it
+    pub fn new(chunk_size: usize, overlap: usize) -> Self {
+        let (chunk_size, overlap) = clamp(chunk_size, overlap);
+        Self {
+            chunk_size,
+            overlap,
+        }
+    }
+}
+
+impl Chunker for FixedSizeChunker {
+    fn split_text(&self, text: &str) -> Vec<String> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            let end = (start + self.chunk_size).min(chars.len());
+            chunks.push(chars[start..end].iter().collect());
+            if end == chars.len() {
+                break;
+            }
+            start += self.chunk_size - self.overlap;
+        }
+        chunks
+    }
+}
+
+/// Splits text on the first separator that occurs in it, recursing with the next
+/// separators into pieces that are still too long, then merges the pieces back into
+/// chunks of at most `chunk_size` characters.
+///
+/// With the default separators, paragraphs are kept together when possible, then lines,
+/// sentences and words. Consecutive chunks share up to `overlap` characters of whole
+/// pieces.
+#[derive(Debug, Clone)]
+pub struct RecursiveChunker {
+    chunk_size: usize,
+    overlap: usize,
+    separators: Vec<String>,
+}
+
+impl RecursiveChunker {
+    /// `chunk_size` is raised to at least 1, and an `overlap` that isn't below it is
+    /// lowered to half of it
+    pub fn new(chunk_size: usize, overlap: usize) -> Self {
+        let (chunk_size, overlap) = clamp(chunk_size, overlap);
+        Self {
+            chunk_size,
+            overlap,
+            separators: DEFAULT_SEPARATORS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    pub fn with_separators<I, S>(mut self, separators: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.separators = separators
+            .into_iter()
+            .map(Into::into)
+            .filter(|s: &String| !s.is_empty())
+            .collect();
+        self
+    }
+
+    /// Split into pieces of at most `chunk_size` characters, keeping the separators
+    fn split_pieces(&self, text: &str, separators: &[String]) -> Vec<String> {
+        if char_len(text) <= self.chunk_size {
+            return vec![text.to_string()];
+        }
+        let Some(position) = separators.iter().position(|s| text.contains(s.as_str())) else {
+            // No separator left, cut at the chunk size
+            return FixedSizeChunker::new(self.chunk_size, 0).split_text(text);
+        };
+        let remaining = &separators[position + 1..];
+        text.split_inclusive(separators[position].as_str())
+            .flat_map(|piece| self.split_pieces(piece, remaining))
+            .collect()
+    }
+
+    fn merge(&self, pieces: Vec<String>) -> Vec<String> {
+        let mut chunks = Vec::new();
+        let mut current: VecDeque<(String, usize)> = VecDeque::new();
+        let mut current_len = 0;
+
+        for piece in pieces {
+            let piece_len = char_len(&piece);
+            if current_len + piece_len > self.chunk_size && !current.is_empty() {
+                chunks.push(current.iter().map(|(p, _)| p.as_str()).collect::<String>());
+                while current_len > self.overlap
+                    || (current_len + piece_len > self.chunk_size && current_len > 0)
+                {
+                    let (_, len) = current.pop_front().unwrap_or_default();
+                    current_len -= len;
+                }
+            }
+            current_len += piece_len;
+            current.push_back((piece, piece_len));
+        }
+        if !current.is_empty() {
+            chunks.push(current.iter().map(|(p, _)| p.as_str()).collect());
+        }
+
+        chunks
+            .into_iter()
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect()
+    }
+}
+
+impl Chunker for RecursiveChunker {
+    fn split_text(&self, text: &str) -> Vec<String> {
+        self.merge(self.split_pieces(text, &self.separators))
+    }
+}
+
+/// Splits Markdown into one chunk per section, starting at each heading outside code
+/// blocks.
+///
+/// Sections longer than `chunk_size` characters are split further with a
+/// [`RecursiveChunker`]. Each chunk's heading path, e.g. `Guide > Install`, is stored in
+/// the `headers` metadata.
+#[derive(Debug, Clone)]
+pub struct MarkdownChunker {
+    chunk_size: usize,
+    splitter: RecursiveChunker,
+}
+
+impl MarkdownChunker {
+    /// `chunk_size` is raised to at least 1, and an `overlap` that isn't below it is
+    /// lowered to half of it
+    pub fn new(chunk_size: usize, overlap: usize) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+            splitter: RecursiveChunker::new(chunk_size, overlap),
+        }
+    }
+
+    /// Sections of a Markdown text with the heading path leading to each
+    fn sections(text: &str) -> Vec<(String, String)> {
+        let mut sections = Vec::new();
+        let mut headings: Vec<(usize, String)> = Vec::new();
+        let mut current = String::new();
+        let mut path = String::new();
+        let mut in_code = false;
+
+        for line in text.split_inclusive('\n') {
+            let trimmed = line.trim_end();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_code = !in_code;
+            }
+            let heading = (!in_code)
+                .then(|| MARKDOWN_HEADING.captures(trimmed))
+                .flatten();
+            if let Some(caps) = heading {
+                if !current.trim().is_empty() {
+                    sections.push((path.clone(), std::mem::take(&mut current)));
+                }
+                current.clear();
+                let level = caps[1].len();
+                headings.retain(|(l, _)| *l < level);
+                headings.push((level, caps[2].to_string()));
+                path = headings
+                    .iter()
+                    .map(|(_, h)| h.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" > ");
+            }
+            current.push_str(line);
+        }
+        if !current.trim().is_empty() {
+            sections.push((path, current));
+        }
+        sections
+    }
+
+    fn split_section(&self, section: &str) -> Vec<String> {
+        if char_len(section) <= self.chunk_size {
+            vec![section.trim().to_string()]
+        } else {
+            self.splitter.split_text(section)
+        }
+    }
+}
+
+impl Chunker for MarkdownChunker {
+    fn split_text(&self, text: &str) -> Vec<String> {
+        Self::sections(text)
+            .iter()
+            .flat_map(|(_, section)| self.split_section(section))
+            .collect()
+    }
+
+    fn split_document(&self, document: &Document) -> Vec<Document> {
+        Self::sections(&document.text)
+            .iter()
+            .flat_map(|(path, section)| {
+                self.split_section(section)
+                    .into_iter()
+                    .map(move |text| (path.clone(), text))
+            })
+            .enumerate()
+            .map(|(index, (path, text))| {
+                let chunk = chunk_of(document, index, text);
+                if path.is_empty() {
+                    chunk
+                } else {
+                    chunk.with_metadata("headers", path)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Splits text into windows of `chunk_tokens` tokens of a HuggingFace tokenizer, each
+/// overlapping the previous one by `overlap` tokens
+#[cfg(all(feature = "tokenizers", not(target_arch = "wasm32")))]
+pub struct TokenChunker {
+    tokenizer: tokenizers::Tokenizer,
+    chunk_tokens: usize,
+    overlap: usize,
+}
+
+#[cfg(all(feature = "tokenizers", not(target_arch = "wasm32")))]
+impl TokenChunker {
+    /// `chunk_tokens` is raised to at least 1, and an `overlap` that isn't below it is
+    /// lowered to half of it
+    pub fn new(tokenizer: tokenizers::Tokenizer, chunk_tokens: usize, overlap: usize) -> Self {
+        let (chunk_tokens, overlap) = clamp(chunk_tokens, overlap);
+        Self {
+            tokenizer,
+            chunk_tokens,
+            overlap,
+        }
+    }
+
+    /// Load a `tokenizer.json` file
+    pub fn from_file(
+        path: impl AsRef<std::path::Path>,
+        chunk_tokens: usize,
+        overlap: usize,
+    ) -> Result<Self, DocumentError> {
+        let tokenizer = tokenizers::Tokenizer::from_file(path)
+            .map_err(|e| DocumentError::Tokenizer(e.to_string()))?;
+        Ok(Self::new(tokenizer, chunk_tokens, overlap))
+    }
+}
+
+#[cfg(all(feature = "tokenizers", not(target_arch = "wasm32")))]
+impl Chunker for TokenChunker {
+    fn split_text(&self, text: &str) -> Vec<String> {
+        let encoding = match self.tokenizer.encode(text, false) {
+            Ok(encoding) => encoding,
+            Err(e) => {
+                log::warn!("Failed to tokenize text, keeping it as one chunk: {e}");
+                return vec![text.to_string()];
+            }
+        };
+        let offsets = encoding.get_offsets();
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while start < offsets.len() {
+            let end = (start + self.chunk_tokens).min(offsets.len());
+            if let Some(chunk) = text.get(offsets[start].0..offsets[end - 1].1) {
+                chunks.push(chunk.to_string());
+            }
+            if end == offsets.len() {
+                break;
+            }
+            start += self.chunk_tokens - self.overlap;
+        }
+        chunks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_size_overlap() {
+        let chunker = FixedSizeChunker::new(4, 1);
+        assert_eq!(
+            chunker.split_text("abcdefghij"),
+            vec!["abcd", "defg", "ghij"]
+        );
+        assert!(chunker.split_text("").is_empty());
+    }
+
+    #[test]
+    fn test_invalid_sizes_are_clamped() {
+        assert_eq!(
+            FixedSizeChunker::new(4, 4).split_text("abcdefg"),
+            vec!["abcd", "cdef", "efg"]
+        );
+        assert_eq!(
+            FixedSizeChunker::new(4, 10).split_text("abcdefg"),
+            vec!["abcd", "cdef", "efg"]
+        );
+        assert_eq!(
+            FixedSizeChunker::new(0, 0).split_text("abc"),
+            vec!["a", "b", "c"]
+        );
+        assert_eq!(RecursiveChunker::new(0, 5).split_text("ab").len(), 2);
+    }
+
+    #[test]
+    fn test_recursive_prefers_paragraphs() {
+        let text = "First paragraph here.\n\nSecond one is a bit longer.\n\nThird.";
+        let chunker = RecursiveChunker::new(30, 0);
+        assert_eq!(
+            chunker.split_text(text),
+            vec![
+                "First paragraph here.",
+                "Second one is a bit longer.",
+                "Third."
+            ]
+        );
+
+        let words = RecursiveChunker::new(12, 6).split_text("one two three four five");
+        assert_eq!(
+            words,
+            vec!["one two", "two three", "three four", "four five"]
+        );
+        assert!(words.iter().all(|c| c.len() <= 12));
+
+        let unbroken = RecursiveChunker::new(4, 0).split_text("abcdefghij");
+        assert_eq!(unbroken, vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn test_markdown_sections_and_metadata() {
+        let text = "Intro\n# Guide\nWelcome\n## Install\n```sh\n# not a heading\n```\n## Usage\nRun it\n# Other\nEnd";
+        let document = Document::with_id("doc", text).with_metadata("source", "guide.md");
+        let chunks = MarkdownChunker::new(100, 0).split_document(&document);
+
+        let headers: Vec<Option<&str>> = chunks
+            .iter()
+            .map(|c| c.metadata.get("headers").and_then(|h| h.as_str()))
+            .collect();
+        assert_eq!(
+            headers,
+            vec![
+                None,
+                Some("Guide"),
+                Some("Guide > Install"),
+                Some("Guide > Usage"),
+                Some("Other")
+            ]
+        );
+        assert_eq!(chunks[2].text, "## Install\n```sh\n# not a heading\n```");
+        assert_eq!(chunks[3].id, "doc:3");
+        assert_eq!(chunks[3].metadata["parent_id"], "doc");
+        assert_eq!(chunks[3].metadata["source"], "guide.md");
+    }
+
+    #[cfg(all(feature = "tokenizers", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_token_chunker() {
+        use std::str::FromStr;
+
+        let tokenizer = tokenizers::Tokenizer::from_str(
+            r#"{
+                "version": "1.0", "truncation": null, "padding": null, "added_tokens": [],
+                "normalizer": null, "pre_tokenizer": {"type": "Whitespace"},
+                "post_processor": null, "decoder": null,
+                "model": {"type": "WordLevel", "vocab": {"[UNK]": 0}, "unk_token": "[UNK]"}
+            }"#,
+        )
+        .unwrap();
+        let chunker = TokenChunker::new(tokenizer, 3, 1);
+        assert_eq!(
+            chunker.split_text("a b c d e f"),
+            vec!["a b c", "c d e", "e f"]
+        );
+    }
+}
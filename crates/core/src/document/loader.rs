@@ -0,0 +1,647 @@
+use super::{Document, DocumentError};
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+static COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+static HIDDEN_ELEMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<(script|style|noscript|head|template)\b[^>]*>.*?</(script|style|noscript|head|template)\s*>").unwrap()
+});
+static TITLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title\b[^>]*>(.*?)</title\s*>").unwrap());
+static BLOCK_BREAK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)<\s*(br|hr|/p|/div|/h[1-6]|/li|/tr|/section|/article|/blockquote|/pre|/table|/ul|/ol|/header|/footer)\b[^>]*>").unwrap()
+});
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+static ENTITY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap());
+
+/// Loads documents from a source
+pub trait DocumentLoader: Send + Sync {
+    fn load(&self) -> Result<Vec<Document>, DocumentError>;
+}
+
+fn read(path: &Path) -> Result<String, DocumentError> {
+    std::fs::read_to_string(path).map_err(|source| DocumentError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn parse_error(path: &Path, message: impl Into<String>) -> DocumentError {
+    DocumentError::Parse {
+        path: path.to_path_buf(),
+        message: message.into(),
+    }
+}
+
+fn source_document(id: String, path: &Path, text: String) -> Document {
+    Document::with_id(id, text).with_metadata("source", path.display().to_string())
+}
+
+/// Loads a plain text file as a single document
+pub struct TextLoader {
+    path: PathBuf,
+}
+
+impl TextLoader {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl DocumentLoader for TextLoader {
+    fn load(&self) -> Result<Vec<Document>, DocumentError> {
+        let text = read(&self.path)?;
+        Ok(vec![source_document(
+            self.path.display().to_string(),
+            &self.path,
+            text,
+        )])
+    }
+}
+
+/// Loads a Markdown file as a single document.
+///
+/// `key: value` pairs of a YAML front matter block are moved into the metadata, and the
+/// `title` is taken from the front matter or the first `# ` heading.
+pub struct MarkdownLoader {
+    path: PathBuf,
+}
+
+impl MarkdownLoader {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Split a Markdown text into its front matter entries and body
+    pub fn parse(text: &str) -> (Map<String, Value>, String) {
+        let mut front_matter = Map::new();
+        let mut body = text;
+        if let Some(rest) = text
+            .strip_prefix("---\n")
+            .or_else(|| text.strip_prefix("---\r\n"))
+        {
+            if let Some(end) = rest.find("\n---") {
+                for line in rest[..end].lines() {
+                    if let Some((key, value)) = line.split_once(':') {
+                        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+                        front_matter.insert(key.trim().to_string(), Value::from(value));
+                    }
+                }
+                let after = &rest[end + 4..];
+                body = after.split_once('\n').map_or("", |(_, body)| body);
+            }
+        }
+
+        if !front_matter.contains_key("title") {
+            if let Some(title) = body.lines().find_map(|l| l.strip_prefix("# ")) {
+                front_matter.insert("title".to_string(), Value::from(title.trim()));
+            }
+        }
+        (
+            front_matter,
+            body.trim_start_matches(['\r', '\n']).to_string(),
+        )
+    }
+}
+
+impl DocumentLoader for MarkdownLoader {
+    fn load(&self) -> Result<Vec<Document>, DocumentError> {
+        let (front_matter, body) = Self::parse(&read(&self.path)?);
+        let mut document = source_document(self.path.display().to_string(), &self.path, body);
+        document.metadata.extend(front_matter);
+        Ok(vec![document])
+    }
+}
+
+/// Loads the visible text of an HTML file as a single document
+pub struct HtmlLoader {
+    path: PathBuf,
+}
+
+impl HtmlLoader {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Content of the `<title>` element
+    pub fn extract_title(html: &str) -> Option<String> {
+        TITLE
+            .captures(html)
+            .map(|c| Self::decode_entities(c[1].trim()))
+            .filter(|t| !t.is_empty())
+    }
+
+    /// Visible text of an HTML page, with block elements on separate lines
+    pub fn extract_text(html: &str) -> String {
+        let html = COMMENT.replace_all(html, "");
+        let html = HIDDEN_ELEMENT.replace_all(&html, "");
+        let html = BLOCK_BREAK.replace_all(&html, "\n");
+        let text = Self::decode_entities(&TAG.replace_all(&html, ""));
+
+        text.lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn decode_entities(text: &str) -> String {
+        ENTITY
+            .replace_all(text, |caps: &regex::Captures| {
+                let entity = &caps[1];
+                let decoded = match entity {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    _ => entity
+                        .strip_prefix("#x")
+                        .or_else(|| entity.strip_prefix("#X"))
+                        .map(|hex| u32::from_str_radix(hex, 16))
+                        .or_else(|| entity.strip_prefix('#').map(str::parse))
+                        .and_then(Result::ok)
+                        .and_then(char::from_u32),
+                };
+                decoded.map_or_else(|| caps[0].to_string(), String::from)
+            })
+            .into_owned()
+    }
+}
+
+impl DocumentLoader for HtmlLoader {
+    fn load(&self) -> Result<Vec<Document>, DocumentError> {
+        let html = read(&self.path)?;
+        let mut document = source_document(
+            self.path.display().to_string(),
+            &self.path,
+            Self::extract_text(&html),
+        );
+        if let Some(title) = Self::extract_title(&html) {
+            document = document.with_metadata("title", title);
+        }
+        Ok(vec![document])
+    }
+}
+
+/// Loads JSON or JSON Lines records, one document per record.
+///
+/// A JSON array yields one document per element, any other JSON value a single document.
+/// Files ending in `.jsonl` or `.ndjson` are read as JSON Lines. With a text field, the
+/// field becomes the document text and the other fields of the record become metadata;
+/// otherwise the whole record is the text.
+pub struct JsonLoader {
+    path: PathBuf,
+    text_field: Option<String>,
+    json_lines: Option<bool>,
+}
+
+impl JsonLoader {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            text_field: None,
+            json_lines: None,
+        }
+    }
+
+    pub fn with_text_field(mut self, field: impl Into<String>) -> Self {
+        self.text_field = Some(field.into());
+        self
+    }
+
+    /// Read the file as JSON Lines regardless of its extension
+    pub fn json_lines(mut self, json_lines: bool) -> Self {
+        self.json_lines = Some(json_lines);
+        self
+    }
+
+    fn is_json_lines(&self) -> bool {
+        self.json_lines.unwrap_or_else(|| {
+            matches!(
+                self.path.extension().and_then(|e| e.to_str()),
+                Some("jsonl" | "ndjson")
+            )
+        })
+    }
+
+    fn records(&self, text: &str) -> Result<Vec<Value>, DocumentError> {
+        if self.is_json_lines() {
+            return text
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(n, line)| {
+                    serde_json::from_str(line)
+                        .map_err(|e| parse_error(&self.path, format!("line {}: {e}", n + 1)))
+                })
+                .collect();
+        }
+        match serde_json::from_str(text).map_err(|e| parse_error(&self.path, e.to_string()))? {
+            Value::Array(records) => Ok(records),
+            record => Ok(vec![record]),
+        }
+    }
+
+    fn to_document(&self, index: usize, record: Value) -> Result<Document, DocumentError> {
+        let id = format!("{}#{index}", self.path.display());
+        let Some(field) = &self.text_field else {
+            let text = match record {
+                Value::String(text) => text,
+                other => serde_json::to_string_pretty(&other).unwrap_or_default(),
+            };
+            return Ok(source_document(id, &self.path, text).with_metadata("index", index));
+        };
+
+        let Value::Object(mut fields) = record else {
+            return Err(parse_error(
+                &self.path,
+                format!("record {index} is not an object"),
+            ));
+        };
+        let text = match fields.remove(field) {
+            Some(Value::String(text)) => text,
+            Some(other) => other.to_string(),
+            None => {
+                return Err(parse_error(
+                    &self.path,
+                    format!("record {index} has no field '{field}'"),
+                ))
+            }
+        };
+        let mut document = source_document(id, &self.path, text).with_metadata("index", index);
+        document.metadata.extend(fields);
+        Ok(document)
+    }
+}
+
+impl DocumentLoader for JsonLoader {
+    fn load(&self) -> Result<Vec<Document>, DocumentError> {
+        let records = self.records(&read(&self.path)?)?;
+        records
+            .into_iter()
+            .enumerate()
+            .map(|(index, record)| self.to_document(index, record))
+            .collect()
+    }
+}
+
+/// Loads a CSV file with a header row, one document per row.
+///
+/// The text is made of `column: value` lines for the text columns (all columns by
+/// default), and every column is also stored in the metadata.
+pub struct CsvLoader {
+    path: PathBuf,
+    delimiter: char,
+    text_columns: Option<Vec<String>>,
+}
+
+impl CsvLoader {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            delimiter: ',',
+            text_columns: None,
+        }
+    }
+
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn with_text_columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.text_columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Parse CSV text into rows, handling quoted fields with escaped quotes and newlines
+    pub fn parse(text: &str, delimiter: char) -> Result<Vec<Vec<String>>, String> {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if in_quotes {
+                match c {
+                    '"' if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    '"' => in_quotes = false,
+                    _ => field.push(c),
+                }
+            } else if c == '"' && field.is_empty() {
+                in_quotes = true;
+            } else if c == delimiter {
+                row.push(std::mem::take(&mut field));
+            } else if c == '\n' || c == '\r' {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            } else {
+                field.push(c);
+            }
+        }
+        if in_quotes {
+            return Err("unterminated quoted field".to_string());
+        }
+        if !field.is_empty() || !row.is_empty() {
+            row.push(field);
+            rows.push(row);
+        }
+        rows.retain(|r| !(r.len() == 1 && r[0].is_empty()));
+        Ok(rows)
+    }
+}
+
+impl DocumentLoader for CsvLoader {
+    fn load(&self) -> Result<Vec<Document>, DocumentError> {
+        let rows = Self::parse(&read(&self.path)?, self.delimiter)
+            .map_err(|e| parse_error(&self.path, e))?;
+        let mut rows = rows.into_iter();
+        let Some(header) = rows.next() else {
+            return Ok(Vec::new());
+        };
+        let text_columns = self.text_columns.clone().unwrap_or_else(|| header.clone());
+        if let Some(missing) = text_columns.iter().find(|c| !header.contains(c)) {
+            return Err(parse_error(&self.path, format!("no column '{missing}'")));
+        }
+
+        rows.enumerate()
+            .map(|(index, row)| {
+                if row.len() != header.len() {
+                    return Err(parse_error(
+                        &self.path,
+                        format!(
+                            "row {} has {} fields, expected {}",
+                            index + 1,
+                            row.len(),
+                            header.len()
+                        ),
+                    ));
+                }
+                let text = header
+                    .iter()
+                    .zip(&row)
+                    .filter(|(column, _)| text_columns.contains(column))
+                    .map(|(column, value)| format!("{column}: {value}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let mut document =
+                    source_document(format!("{}#{index}", self.path.display()), &self.path, text)
+                        .with_metadata("row", index);
+                for (column, value) in header.iter().zip(row) {
+                    document
+                        .metadata
+                        .insert(column.clone(), Value::String(value));
+                }
+                Ok(document)
+            })
+            .collect()
+    }
+}
+
+/// Loads the text files of a directory tree, such as a source code repository.
+///
+/// `.gitignore`, `.ignore` and hidden files are skipped. Each file becomes a document whose
+/// id is its path relative to the root, with its `extension` and `language` in the
+/// metadata. Files that aren't valid UTF-8 or exceed the size limit are skipped.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct DirectoryLoader {
+    root: PathBuf,
+    extensions: Option<Vec<String>>,
+    max_file_size: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DirectoryLoader {
+    /// Files larger than this are skipped unless configured otherwise
+    const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            extensions: None,
+            max_file_size: Self::DEFAULT_MAX_FILE_SIZE,
+        }
+    }
+
+    /// Only load files with one of these extensions, e.g. `["rs", "md"]`
+    pub fn with_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extensions = Some(
+            extensions
+                .into_iter()
+                .map(|e| e.into().trim_start_matches('.').to_string())
+                .collect(),
+        );
+        self
+    }
+
+    pub fn with_max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = bytes;
+        self
+    }
+
+    /// Language name for a file extension
+    pub fn language(extension: &str) -> &str {
+        match extension {
+            "rs" => "rust",
+            "py" => "python",
+            "js" | "mjs" | "cjs" | "jsx" => "javascript",
+            "ts" | "tsx" => "typescript",
+            "go" => "go",
+            "java" => "java",
+            "kt" | "kts" => "kotlin",
+            "c" | "h" => "c",
+            "cc" | "cpp" | "cxx" | "hpp" => "cpp",
+            "cs" => "csharp",
+            "rb" => "ruby",
+            "php" => "php",
+            "swift" => "swift",
+            "sh" | "bash" => "shell",
+            "md" | "markdown" => "markdown",
+            "yml" | "yaml" => "yaml",
+            "toml" => "toml",
+            "json" => "json",
+            "html" | "htm" => "html",
+            "css" => "css",
+            "sql" => "sql",
+            other => other,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DocumentLoader for DirectoryLoader {
+    fn load(&self) -> Result<Vec<Document>, DocumentError> {
+        let walker = ignore::WalkBuilder::new(&self.root)
+            .require_git(false)
+            .sort_by_file_path(|a, b| a.cmp(b))
+            .build();
+
+        let mut documents = Vec::new();
+        for entry in walker {
+            let entry = entry.map_err(|e| parse_error(&self.root, e.to_string()))?;
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let path = entry.path();
+            let extension = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or_default()
+                .to_string();
+            if let Some(extensions) = &self.extensions {
+                if !extensions.contains(&extension) {
+                    continue;
+                }
+            }
+            if entry.metadata().is_ok_and(|m| m.len() > self.max_file_size) {
+                log::debug!("Skipping {} as it is too large", path.display());
+                continue;
+            }
+            let text = match std::fs::read(path).map(String::from_utf8) {
+                Ok(Ok(text)) => text,
+                Ok(Err(_)) => {
+                    log::debug!("Skipping {} as it isn't valid UTF-8", path.display());
+                    continue;
+                }
+                Err(source) => {
+                    return Err(DocumentError::Io {
+                        path: path.to_path_buf(),
+                        source,
+                    })
+                }
+            };
+
+            let relative = path.strip_prefix(&self.root).unwrap_or(path);
+            let language = Self::language(&extension).to_string();
+            documents.push(
+                source_document(relative.display().to_string(), path, text)
+                    .with_metadata("extension", extension)
+                    .with_metadata("language", language),
+            );
+        }
+        Ok(documents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_markdown_front_matter_and_title() {
+        let (meta, body) =
+            MarkdownLoader::parse("---\nauthor: \"Ada\"\ntags: rag\n---\n\n# Guide\n\nBody");
+        assert_eq!(meta["author"], "Ada");
+        assert_eq!(meta["title"], "Guide");
+        assert_eq!(body, "# Guide\n\nBody");
+
+        let (meta, body) = MarkdownLoader::parse("No heading");
+        assert!(meta.is_empty());
+        assert_eq!(body, "No heading");
+    }
+
+    #[test]
+    fn test_html_text_extraction() {
+        let html = r#"<html><head><title>Docs &amp; more</title><style>p{}</style></head>
+            <body><h1>Hello</h1><!-- hidden --><p>Fish &lt;3 <b>chips</b>&#33;</p>
+            <script>alert(1)</script><ul><li>one</li><li>two</li></ul></body></html>"#;
+        assert_eq!(
+            HtmlLoader::extract_title(html).as_deref(),
+            Some("Docs & more")
+        );
+        assert_eq!(
+            HtmlLoader::extract_text(html),
+            "Hello\nFish <3 chips!\none\ntwo"
+        );
+    }
+
+    #[test]
+    fn test_csv_parsing() {
+        let rows = CsvLoader::parse("a,b\r\n\"x, y\",\"say \"\"hi\"\"\nline\"\n", ',').unwrap();
+        assert_eq!(rows, vec![vec!["a", "b"], vec!["x, y", "say \"hi\"\nline"]]);
+        assert!(CsvLoader::parse("\"open", ',').is_err());
+    }
+
+    #[test]
+    fn test_csv_and_json_loaders() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("people.csv");
+        fs::write(&csv, "name;city\nAda;London\nAlan;Wilmslow\n").unwrap();
+        let docs = CsvLoader::new(&csv)
+            .with_delimiter(';')
+            .with_text_columns(["name"])
+            .load()
+            .unwrap();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[1].text, "name: Alan");
+        assert_eq!(docs[1].metadata["city"], "Wilmslow");
+
+        let jsonl = dir.path().join("notes.jsonl");
+        fs::write(
+            &jsonl,
+            "{\"body\": \"first\", \"tag\": \"a\"}\n\n{\"body\": \"second\"}\n",
+        )
+        .unwrap();
+        let docs = JsonLoader::new(&jsonl)
+            .with_text_field("body")
+            .load()
+            .unwrap();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].text, "first");
+        assert_eq!(docs[0].metadata["tag"], "a");
+        assert!(!docs[0].metadata.contains_key("body"));
+
+        let json = dir.path().join("notes.json");
+        fs::write(&json, r#"[{"title": "x"}, "plain"]"#).unwrap();
+        let docs = JsonLoader::new(&json).load().unwrap();
+        assert_eq!(docs[1].text, "plain");
+        assert!(JsonLoader::new(&json)
+            .with_text_field("body")
+            .load()
+            .is_err());
+    }
+
+    #[test]
+    fn test_directory_loader_respects_gitignore() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "pub fn a() {}").unwrap();
+        fs::write(dir.path().join("README.md"), "# Readme").unwrap();
+        fs::write(dir.path().join("target/out.rs"), "ignored").unwrap();
+        fs::write(dir.path().join("logo.png"), [0xff, 0xfe, 0x00]).unwrap();
+
+        let docs = DirectoryLoader::new(dir.path()).load().unwrap();
+        let ids: Vec<&str> = docs.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["README.md", "src/lib.rs"]);
+        assert_eq!(docs[1].metadata["language"], "rust");
+
+        let docs = DirectoryLoader::new(dir.path())
+            .with_extensions([".rs"])
+            .load()
+            .unwrap();
+        assert_eq!(docs.len(), 1);
+    }
+}
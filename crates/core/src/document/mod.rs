@@ -0,0 +1,65 @@
+//! Documents for retrieval augmented generation.
+//!
+//! Loaders read files into [`Document`]s and chunkers split them into pieces small
+//! enough to embed.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use thiserror::Error;
+use uuid::Uuid;
+
+mod chunker;
+mod loader;
+
+#[cfg(all(feature = "tokenizers", not(target_arch = "wasm32")))]
+pub use chunker::TokenChunker;
+pub use chunker::{Chunker, FixedSizeChunker, MarkdownChunker, RecursiveChunker};
+#[cfg(not(target_arch = "wasm32"))]
+pub use loader::DirectoryLoader;
+pub use loader::{CsvLoader, DocumentLoader, HtmlLoader, JsonLoader, MarkdownLoader, TextLoader};
+
+#[derive(Debug, Error)]
+pub enum DocumentError {
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+
+    #[error("Tokenizer error: {0}")]
+    Tokenizer(String),
+}
+
+/// A piece of text with metadata, as loaded from a source or produced by a chunker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub metadata: HashMap<String, Value>,
+}
+
+impl Document {
+    /// Create a document with a random id
+    pub fn new(text: impl Into<String>) -> Self {
+        Self::with_id(Uuid::new_v4().to_string(), text)
+    }
+
+    pub fn with_id(id: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            text: text.into(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+}
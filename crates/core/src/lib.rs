@@ -11,10 +11,12 @@ pub mod agent;
 
 // Common modules available on all platforms
 mod channel;
+pub mod document;
 pub mod error;
 pub mod protocol;
 pub mod tool;
 pub mod utils;
+pub mod vector_store;
 
 #[cfg(test)]
 mod tests;
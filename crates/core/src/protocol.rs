@@ -1,4 +1,5 @@
 use crate::agent::task::Task;
+use crate::agent::{ModerationAction, ModerationStage};
 use crate::tool::ToolCallResult;
 use autoagents_llm::chat::StreamChoice;
 use serde::{Deserialize, Serialize};
@@ -25,7 +26,7 @@ pub enum Event {
     // /// A new task has been submitted to an agent
     NewTask {
         actor_id: ActorID,
-        task: Task,
+        task: Box<Task>,
     },
 
     /// A task has started execution
@@ -102,6 +103,12 @@ pub enum Event {
         chunk: StreamChoice,
     },
 
+    /// Streaming reasoning chunk, sent apart from the answer and never stored in memory
+    StreamReasoning {
+        sub_id: SubmissionId,
+        reasoning: String,
+    },
+
     /// Streaming tool call chunk
     StreamToolCall {
         sub_id: SubmissionId,
@@ -112,6 +119,15 @@ pub enum Event {
     StreamComplete {
         sub_id: SubmissionId,
     },
+
+    /// The moderation guard flagged the task prompt or the final output
+    ModerationViolation {
+        sub_id: SubmissionId,
+        actor_id: ActorID,
+        stage: ModerationStage,
+        action: ModerationAction,
+        categories: Vec<String>,
+    },
 }
 
 /// Internal events that are processed within the runtime
@@ -139,7 +155,7 @@ mod tests {
         let _ = Uuid::new_v4();
         let event = Event::NewTask {
             actor_id: Default::default(),
-            task: Task::new(String::from("test")),
+            task: Box::new(Task::new(String::from("test"))),
         };
 
         //Check if serialization and deserilization works properly
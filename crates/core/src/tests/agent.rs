@@ -48,6 +48,14 @@ impl From<TestAgentOutput> for Value {
     }
 }
 
+impl From<crate::agent::prebuilt::executor::ReActAgentOutput> for TestAgentOutput {
+    fn from(output: crate::agent::prebuilt::executor::ReActAgentOutput) -> Self {
+        Self {
+            result: output.response,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct MockAgentImpl {
     pub name: String,
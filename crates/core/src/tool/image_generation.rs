@@ -0,0 +1,251 @@
+use super::{block_on, with_tool_images, ToolCallError, ToolRuntime, ToolT};
+use autoagents_llm::images::{
+    GeneratedImage, ImageGenerationProvider, ImageGenerationRequest, ImageResponseFormat,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const DEFAULT_NAME: &str = "generate_image";
+const DEFAULT_DESCRIPTION: &str = "Generate images from a detailed text description. \
+The generated images are shown to you after the call.";
+/// Upper bound on the number of images the LLM can ask for in one call
+const DEFAULT_MAX_IMAGES: u32 = 4;
+
+#[derive(Debug, Deserialize)]
+struct ImageGenerationArgs {
+    prompt: String,
+    #[serde(default)]
+    size: Option<String>,
+    #[serde(default)]
+    n: Option<u32>,
+}
+
+/// Tool that lets an agent generate images with any [`ImageGenerationProvider`].
+///
+/// The images are attached to the tool result with [`with_tool_images`], so they are
+/// stored in memory as image messages and a vision model can critique them on the next
+/// turn. Like [`RetrieverTool`](crate::vector_store::RetrieverTool), the tool is cheap to
+/// clone and needs a multi-threaded Tokio runtime.
+#[derive(Clone)]
+pub struct ImageGenerationTool {
+    provider: Arc<dyn ImageGenerationProvider>,
+    name: &'static str,
+    description: &'static str,
+    model: Option<String>,
+    size: Option<String>,
+    max_images: u32,
+}
+
+impl std::fmt::Debug for ImageGenerationTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImageGenerationTool")
+            .field("name", &self.name)
+            .field("model", &self.model)
+            .field("size", &self.size)
+            .field("max_images", &self.max_images)
+            .finish()
+    }
+}
+
+impl ImageGenerationTool {
+    pub fn new(provider: Arc<dyn ImageGenerationProvider>) -> Self {
+        Self {
+            provider,
+            name: DEFAULT_NAME,
+            description: DEFAULT_DESCRIPTION,
+            model: None,
+            size: None,
+            max_images: DEFAULT_MAX_IMAGES,
+        }
+    }
+
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_description(mut self, description: &'static str) -> Self {
+        self.description = description;
+        self
+    }
+
+    /// Image model used instead of the provider's default
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Size used when the LLM doesn't ask for one, e.g. `1024x1024`
+    pub fn with_size(mut self, size: impl Into<String>) -> Self {
+        self.size = Some(size.into());
+        self
+    }
+
+    /// Most images the LLM can ask for in one call
+    pub fn with_max_images(mut self, max_images: u32) -> Self {
+        self.max_images = max_images.max(1);
+        self
+    }
+
+    fn request(&self, args: ImageGenerationArgs) -> ImageGenerationRequest {
+        let mut request = ImageGenerationRequest::new(args.prompt)
+            .n(args.n.unwrap_or(1).min(self.max_images))
+            .format(ImageResponseFormat::Bytes);
+        request.model = self.model.clone();
+        request.size = args.size.or_else(|| self.size.clone());
+        request
+    }
+}
+
+impl ToolRuntime for ImageGenerationTool {
+    fn execute(&self, args: Value) -> Result<Value, ToolCallError> {
+        let request = self.request(serde_json::from_value(args)?);
+        let images = block_on(self.provider.generate_images(&request))?
+            .map_err(|e| ToolCallError::RuntimeError(Box::new(e)))?;
+
+        let mut urls = Vec::new();
+        let mut encoded = Vec::new();
+        for image in images {
+            match image {
+                GeneratedImage::Bytes { mime, data } => encoded.push((mime, data)),
+                GeneratedImage::Url { url } => urls.push(url),
+            }
+        }
+        let mut result = json!({ "prompt": request.prompt, "count": encoded.len() + urls.len() });
+        if !urls.is_empty() {
+            result["urls"] = json!(urls);
+        }
+        Ok(with_tool_images(result, encoded))
+    }
+}
+
+impl ToolT for ImageGenerationTool {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn args_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "prompt": {
+                    "type": "string",
+                    "description": "Detailed description of the image to generate"
+                },
+                "size": {
+                    "type": "string",
+                    "description": "Image size as WIDTHxHEIGHT, e.g. 1024x1024"
+                },
+                "n": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": self.max_images,
+                    "description": "Number of images to generate, 1 by default"
+                }
+            },
+            "required": ["prompt"]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::memory::{MemoryProvider, SlidingWindowMemory};
+    use crate::agent::prebuilt::executor::ReActAgent;
+    use crate::agent::task::Task;
+    use crate::agent::Context;
+    use crate::agent::{AgentExecutor, ToolCallingMode};
+    use crate::tests::agent::MockAgentImpl;
+    use async_trait::async_trait;
+    use autoagents_llm::chat::{ImageMime, MessageType};
+    use autoagents_llm::error::LLMError;
+    use autoagents_test_utils::llm::ScriptedLLMProvider;
+    use std::sync::Mutex;
+
+    const PNG: [u8; 4] = [0x89, b'P', b'N', b'G'];
+
+    #[derive(Default)]
+    struct FakePainter {
+        requests: Mutex<Vec<ImageGenerationRequest>>,
+    }
+
+    #[async_trait]
+    impl ImageGenerationProvider for FakePainter {
+        async fn generate_images(
+            &self,
+            request: &ImageGenerationRequest,
+        ) -> Result<Vec<GeneratedImage>, LLMError> {
+            self.requests.lock().unwrap().push(request.clone());
+            Ok((0..request.n)
+                .map(|_| GeneratedImage::Bytes {
+                    mime: ImageMime::PNG,
+                    data: PNG.to_vec(),
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_attaches_images() {
+        let painter = Arc::new(FakePainter::default());
+        let tool = ImageGenerationTool::new(painter.clone())
+            .with_size("512x512")
+            .with_max_images(2);
+
+        let mut output = tool
+            .execute(json!({"prompt": "a red fox", "n": 5}))
+            .unwrap();
+        assert_eq!(output["count"], json!(2));
+        let request = painter.requests.lock().unwrap()[0].clone();
+        assert_eq!(request.n, 2);
+        assert_eq!(request.size.as_deref(), Some("512x512"));
+
+        let mut result = crate::tool::ToolCallResult {
+            tool_name: "generate_image".to_string(),
+            success: true,
+            arguments: json!({}),
+            result: output.take(),
+        };
+        assert_eq!(result.take_images().len(), 2);
+        assert!(tool.execute(json!({"n": 1})).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_generated_images_reach_the_next_turn() {
+        let llm = Arc::new(ScriptedLLMProvider::new([
+            "Action: generate_image\nAction Input: {\"prompt\": \"a red fox\"}",
+            "Final Answer: The fox looks good.",
+        ]));
+        let memory: Box<dyn MemoryProvider> = Box::new(SlidingWindowMemory::new(10));
+        let context = Context::new(llm.clone(), None)
+            .with_memory(Some(Arc::new(tokio::sync::Mutex::new(memory))))
+            .with_tools(vec![Box::new(ImageGenerationTool::new(Arc::new(
+                FakePainter::default(),
+            )))]);
+
+        let agent = ReActAgent::new(MockAgentImpl::new("artist", "Draws pictures"))
+            .with_tool_calling_mode(ToolCallingMode::Text);
+        let output = agent
+            .execute(&Task::new("Draw a fox"), Arc::new(context))
+            .await
+            .unwrap();
+        assert_eq!(output.response, "The fox looks good.");
+
+        let second = &llm.requests()[1];
+        let observation = &second[second.len() - 2];
+        assert!(observation.content.starts_with("Observation:"));
+        assert!(!observation.content.contains(crate::tool::TOOL_IMAGES_KEY));
+        let image = second.last().unwrap();
+        assert_eq!(
+            image.message_type,
+            MessageType::Image((ImageMime::PNG, PNG.to_vec()))
+        );
+        assert_eq!(image.content, "Image returned by the generate_image tool");
+    }
+}
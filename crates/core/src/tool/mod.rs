@@ -1,9 +1,18 @@
-use autoagents_llm::chat::{FunctionTool, Tool};
+use autoagents_llm::chat::{FunctionTool, ImageMime, Tool};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::fmt::Debug;
+#[cfg(not(target_arch = "wasm32"))]
+use std::future::Future;
 use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::runtime::{Handle, RuntimeFlavor};
+#[cfg(not(target_arch = "wasm32"))]
+mod image_generation;
 mod runtime;
+#[cfg(not(target_arch = "wasm32"))]
+pub use image_generation::ImageGenerationTool;
 pub use runtime::ToolRuntime;
 
 #[cfg(feature = "wasmtime")]
@@ -17,6 +26,82 @@ pub struct ToolCallResult {
     pub result: Value,
 }
 
+/// Key of the images carried by a tool result, see [`with_tool_images`]
+pub const TOOL_IMAGES_KEY: &str = "_images";
+
+#[derive(Serialize, Deserialize)]
+struct EncodedImage {
+    mime: ImageMime,
+    data: String,
+}
+
+/// Attach images to a tool result.
+///
+/// Executors move the images out of the result into image messages in memory, so vision
+/// models can look at them on the next turn while the tool result itself stays small.
+/// A result that is not a JSON object is kept under `result`.
+pub fn with_tool_images(
+    result: Value,
+    images: impl IntoIterator<Item = (ImageMime, Vec<u8>)>,
+) -> Value {
+    let mut result = match result {
+        Value::Object(map) => map,
+        other => serde_json::Map::from_iter([("result".to_string(), other)]),
+    };
+    let images: Vec<Value> = images
+        .into_iter()
+        .map(|(mime, data)| {
+            json!(EncodedImage {
+                mime,
+                data: BASE64.encode(data),
+            })
+        })
+        .collect();
+    result.insert(TOOL_IMAGES_KEY.to_string(), Value::Array(images));
+    Value::Object(result)
+}
+
+impl ToolCallResult {
+    /// Remove the images attached with [`with_tool_images`] and return them
+    pub fn take_images(&mut self) -> Vec<(ImageMime, Vec<u8>)> {
+        let Some(Value::Array(images)) = self
+            .result
+            .as_object_mut()
+            .and_then(|map| map.remove(TOOL_IMAGES_KEY))
+        else {
+            return Vec::new();
+        };
+        images
+            .into_iter()
+            .filter_map(|image| serde_json::from_value::<EncodedImage>(image).ok())
+            .filter_map(|image| Some((image.mime, BASE64.decode(image.data).ok()?)))
+            .collect()
+    }
+}
+
+/// Run a future to completion from the synchronous tool runtime.
+///
+/// Tools run on the agent's runtime, so this blocks the calling worker thread until the
+/// future completes. That needs a multi-threaded Tokio runtime, where the worker hands its
+/// other tasks off first; on a current-thread runtime the future could never make progress
+/// and an error is returned instead. Outside of any runtime a temporary one is used.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn block_on<F: Future>(future: F) -> Result<F::Output, ToolCallError> {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            Ok(tokio::task::block_in_place(|| handle.block_on(future)))
+        }
+        Ok(_) => Err(ToolCallError::RuntimeError(
+            "This tool needs a multi-threaded Tokio runtime".into(),
+        )),
+        Err(_) => tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map(|runtime| runtime.block_on(future))
+            .map_err(|e| ToolCallError::RuntimeError(Box::new(e))),
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ToolCallError {
     #[error("Runtime Error {0}")]
@@ -708,4 +793,41 @@ mod tests {
         assert_eq!(deserialized.result["valid"], false);
         assert_eq!(deserialized.result["error"], true);
     }
+
+    #[test]
+    fn test_tool_images_round_trip() {
+        let result = with_tool_images(json!({"count": 1}), [(ImageMime::PNG, vec![1, 2, 3])]);
+        assert_eq!(result[TOOL_IMAGES_KEY][0]["data"], json!("AQID"));
+
+        let mut call = ToolCallResult {
+            tool_name: "draw".to_string(),
+            success: true,
+            arguments: json!({}),
+            result,
+        };
+        assert_eq!(call.take_images(), vec![(ImageMime::PNG, vec![1, 2, 3])]);
+        assert_eq!(call.result, json!({"count": 1}));
+        assert!(call.take_images().is_empty());
+
+        let wrapped = with_tool_images(json!("done"), []);
+        assert_eq!(wrapped["result"], json!("done"));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_block_on_outside_runtime() {
+        assert_eq!(block_on(async { 42 }).unwrap(), 42);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_block_on_rejects_current_thread_runtime() {
+        assert!(block_on(async { 42 }).is_err());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_block_on_multi_thread_runtime() {
+        assert_eq!(block_on(async { 42 }).unwrap(), 42);
+    }
 }
@@ -52,3 +52,13 @@ where
 {
     wasm_bindgen_futures::spawn_local(fut)
 }
+
+/// Strip the Markdown code fence some models wrap structured responses in
+pub(crate) fn strip_code_fence(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_prefix("```json")
+        .or_else(|| text.strip_prefix("```"))
+        .and_then(|t| t.strip_suffix("```"))
+        .map(str::trim)
+        .unwrap_or(text)
+}
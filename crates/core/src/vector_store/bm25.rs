@@ -0,0 +1,313 @@
+use super::{Filter, Retriever, SearchResult, VectorStoreError};
+use crate::document::Document;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::RwLock;
+
+/// Split text into lowercase keyword terms.
+///
+/// Terms are runs of letters, digits and underscores, so identifiers such as `E0382` or
+/// `parse_config` stay whole. The parts of snake_case identifiers are indexed as well so
+/// `parse config` still matches `parse_config`.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .map(|w| w.trim_matches('_'))
+        .filter(|w| !w.is_empty())
+    {
+        let word = word.to_lowercase();
+        if word.contains('_') {
+            terms.extend(
+                word.split('_')
+                    .filter(|part| !part.is_empty())
+                    .map(str::to_string),
+            );
+        }
+        terms.push(word);
+    }
+    terms
+}
+
+/// Parameters of the BM25 ranking function
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bm25Config {
+    /// How quickly repeated terms stop increasing the score
+    pub k1: f32,
+    /// How much long documents are penalized, from 0 (not at all) to 1
+    pub b: f32,
+}
+
+impl Default for Bm25Config {
+    fn default() -> Self {
+        Self { k1: 1.2, b: 0.75 }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    document: Document,
+    terms: HashMap<String, u32>,
+    length: usize,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct State {
+    config: Bm25Config,
+    entries: HashMap<String, Entry>,
+    /// Number of documents containing each term
+    document_frequency: HashMap<String, usize>,
+    total_length: usize,
+}
+
+impl State {
+    fn remove(&mut self, id: &str) -> bool {
+        let Some(entry) = self.entries.remove(id) else {
+            return false;
+        };
+        self.total_length -= entry.length;
+        for term in entry.terms.keys() {
+            if let Some(count) = self.document_frequency.get_mut(term) {
+                *count -= 1;
+                if *count == 0 {
+                    self.document_frequency.remove(term);
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Keyword index ranking documents with BM25.
+///
+/// Embeddings capture meaning but often miss exact identifiers like error codes or
+/// function names; a keyword index finds them reliably. Combine both with a
+/// [`HybridRetriever`](super::HybridRetriever).
+#[derive(Default)]
+pub struct Bm25Index {
+    state: RwLock<State>,
+}
+
+impl Bm25Index {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: Bm25Config) -> Self {
+        Self {
+            state: RwLock::new(State {
+                config,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Index documents, replacing documents with the same id
+    pub fn add(&self, documents: Vec<Document>) {
+        let mut state = self.state.write().unwrap();
+        for document in documents {
+            state.remove(&document.id);
+            let tokens = tokenize(&document.text);
+            let mut terms: HashMap<String, u32> = HashMap::new();
+            for token in tokens.iter() {
+                *terms.entry(token.clone()).or_default() += 1;
+            }
+            for term in terms.keys() {
+                *state.document_frequency.entry(term.clone()).or_default() += 1;
+            }
+            state.total_length += tokens.len();
+            state.entries.insert(
+                document.id.clone(),
+                Entry {
+                    document,
+                    terms,
+                    length: tokens.len(),
+                },
+            );
+        }
+    }
+
+    /// Remove documents by id, returning how many existed
+    pub fn delete(&self, ids: &[String]) -> usize {
+        let mut state = self.state.write().unwrap();
+        ids.iter().filter(|id| state.remove(id)).count()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Document> {
+        self.state
+            .read()
+            .unwrap()
+            .entries
+            .get(id)
+            .map(|e| e.document.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.read().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The `top_k` documents matching `filter` with the highest BM25 score for `query`.
+    ///
+    /// Documents sharing no term with the query are not returned.
+    pub fn search(&self, query: &str, top_k: usize, filter: Option<&Filter>) -> Vec<SearchResult> {
+        let state = self.state.read().unwrap();
+        if state.entries.is_empty() {
+            return Vec::new();
+        }
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+
+        let count = state.entries.len() as f32;
+        let average_length = (state.total_length as f32 / count).max(1.0);
+        let idf: Vec<(&String, f32)> = terms
+            .iter()
+            .filter_map(|term| {
+                let frequency = *state.document_frequency.get(term)? as f32;
+                Some((
+                    term,
+                    (1.0 + (count - frequency + 0.5) / (frequency + 0.5)).ln(),
+                ))
+            })
+            .collect();
+        let Bm25Config { k1, b } = state.config;
+
+        let mut results: Vec<SearchResult> = state
+            .entries
+            .values()
+            .filter(|e| filter.is_none_or(|f| f.matches(&e.document.metadata)))
+            .filter_map(|entry| {
+                let normalization = k1 * (1.0 - b + b * entry.length as f32 / average_length);
+                let score: f32 = idf
+                    .iter()
+                    .filter_map(|(term, idf)| {
+                        let tf = *entry.terms.get(*term)? as f32;
+                        Some(idf * tf * (k1 + 1.0) / (tf + normalization))
+                    })
+                    .sum();
+                (score > 0.0).then(|| SearchResult {
+                    document: entry.document.clone(),
+                    score,
+                })
+            })
+            .collect();
+        results.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.document.id.cmp(&b.document.id))
+        });
+        results.truncate(top_k);
+        results
+    }
+
+    /// Write the index to a JSON file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), VectorStoreError> {
+        let data = serde_json::to_vec(&*self.state.read().unwrap())?;
+        super::hnsw::write_atomic(path.as_ref(), &data)
+    }
+
+    /// Load an index written by [`Bm25Index::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, VectorStoreError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|source| VectorStoreError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Self {
+            state: RwLock::new(serde_json::from_slice(&data)?),
+        })
+    }
+}
+
+#[async_trait]
+impl Retriever for Bm25Index {
+    async fn retrieve(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        Ok(self.search(query, top_k, filter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> Bm25Index {
+        let index = Bm25Index::new();
+        index.add(vec![
+            Document::with_id("e1", "Error E1234 means the connection pool is exhausted")
+                .with_metadata("product", "db"),
+            Document::with_id("e2", "A connection error is retried three times")
+                .with_metadata("product", "db"),
+            Document::with_id("cfg", "Call parse_config before starting the server")
+                .with_metadata("product", "server"),
+        ]);
+        index
+    }
+
+    fn ids(results: Vec<SearchResult>) -> Vec<String> {
+        results.into_iter().map(|r| r.document.id).collect()
+    }
+
+    #[test]
+    fn test_tokenize_keeps_identifiers() {
+        assert_eq!(
+            tokenize("Call parse_config, see E1234!"),
+            vec!["call", "parse", "config", "parse_config", "see", "e1234"]
+        );
+        assert!(tokenize(" -- ").is_empty());
+    }
+
+    #[test]
+    fn test_exact_identifiers_rank_first() {
+        let index = index();
+        assert_eq!(ids(index.search("e1234", 5, None)), vec!["e1"]);
+        assert_eq!(ids(index.search("parse config", 5, None)), vec!["cfg"]);
+        // The rare identifier outweighs the common words
+        assert_eq!(
+            ids(index.search("connection error E1234", 2, None)),
+            vec!["e1", "e2"]
+        );
+        assert!(index.search("unrelated", 5, None).is_empty());
+
+        let filter = Filter::eq("product", "server");
+        assert!(index.search("connection", 5, Some(&filter)).is_empty());
+    }
+
+    #[test]
+    fn test_replace_and_delete_update_statistics() {
+        let index = index();
+        index.add(vec![Document::with_id("e2", "Timeouts are retried")]);
+        assert_eq!(index.len(), 3);
+        assert_eq!(ids(index.search("connection", 5, None)), vec!["e1"]);
+
+        assert_eq!(index.delete(&["e1".to_string(), "missing".to_string()]), 1);
+        assert!(index.search("connection", 5, None).is_empty());
+        assert!(index.get("e1").is_none());
+        assert_eq!(
+            index.state.read().unwrap().total_length,
+            tokenize("Timeouts are retried").len()
+                + tokenize("Call parse_config before starting the server").len()
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bm25.json");
+        index().save(&path).unwrap();
+
+        let loaded = Bm25Index::load(&path).unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(ids(loaded.search("E1234", 1, None)), vec!["e1"]);
+    }
+}
@@ -0,0 +1,137 @@
+use super::{Filter, SearchResult, VectorRecord, VectorStore, VectorStoreError};
+use crate::document::Document;
+use autoagents_llm::embedding::EmbeddingProvider;
+use autoagents_llm::LLMProvider;
+use std::sync::Arc;
+
+/// Number of texts sent to the provider per request unless configured otherwise
+const DEFAULT_BATCH_SIZE: usize = 64;
+
+/// Embeds documents and queries with an [`EmbeddingProvider`], splitting large inputs
+/// into batches the provider accepts.
+#[derive(Clone)]
+pub struct Embedder {
+    provider: Arc<dyn EmbeddingProvider + Send + Sync>,
+    batch_size: usize,
+}
+
+impl Embedder {
+    pub fn new(provider: Arc<dyn EmbeddingProvider + Send + Sync>) -> Self {
+        Self {
+            provider,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Use the embedding endpoint of an LLM provider
+    pub fn from_llm(llm: Arc<dyn LLMProvider>) -> Self {
+        Self::new(llm)
+    }
+
+    /// Send at most `batch_size` texts per request
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Embed texts, returning one embedding per text in the same order
+    pub async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, VectorStoreError> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            let batch_embeddings = self
+                .provider
+                .embed(batch.to_vec())
+                .await
+                .map_err(|e| VectorStoreError::EmbeddingError(e.to_string()))?;
+            if batch_embeddings.len() != batch.len() {
+                return Err(VectorStoreError::EmbeddingError(format!(
+                    "provider returned {} embeddings for {} texts",
+                    batch_embeddings.len(),
+                    batch.len()
+                )));
+            }
+            embeddings.extend(batch_embeddings);
+        }
+        Ok(embeddings)
+    }
+
+    pub async fn embed_query(&self, query: &str) -> Result<Vec<f32>, VectorStoreError> {
+        self.embed(vec![query.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| VectorStoreError::EmbeddingError("no embedding returned".to_string()))
+    }
+
+    pub async fn embed_documents(
+        &self,
+        documents: Vec<Document>,
+    ) -> Result<Vec<VectorRecord>, VectorStoreError> {
+        let texts = documents.iter().map(|d| d.text.clone()).collect();
+        let embeddings = self.embed(texts).await?;
+        Ok(documents
+            .into_iter()
+            .zip(embeddings)
+            .map(|(document, embedding)| VectorRecord::new(document, embedding))
+            .collect())
+    }
+
+    /// Embed documents and upsert them into a store, returning how many were stored
+    pub async fn index(
+        &self,
+        store: &dyn VectorStore,
+        documents: Vec<Document>,
+    ) -> Result<usize, VectorStoreError> {
+        let records = self.embed_documents(documents).await?;
+        let count = records.len();
+        store.upsert(records).await?;
+        Ok(count)
+    }
+
+    /// Embed a query and search a store with it
+    pub async fn search(
+        &self,
+        store: &dyn VectorStore,
+        query: &str,
+        top_k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let embedding = self.embed_query(query).await?;
+        store.search(&embedding, top_k, filter).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector_store::InMemoryVectorStore;
+    use autoagents_test_utils::llm::{MockEmbeddingProvider, MockLLMProvider};
+
+    #[tokio::test]
+    async fn test_batches_and_searches() {
+        let provider = Arc::new(MockEmbeddingProvider::new(64));
+        let embedder = Embedder::new(provider.clone()).with_batch_size(2);
+        let store = InMemoryVectorStore::new();
+
+        let documents = vec![
+            Document::with_id("rust", "Rust is a systems programming language"),
+            Document::with_id("python", "Python is popular for data science"),
+            Document::with_id("tea", "Green tea is brewed at low temperature"),
+        ];
+        assert_eq!(embedder.index(&store, documents).await.unwrap(), 3);
+        assert_eq!(provider.batches(), vec![2, 1]);
+
+        let results = embedder
+            .search(&store, "which programming language is fast", 1, None)
+            .await
+            .unwrap();
+        assert_eq!(results[0].document.id, "rust");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_mismatched_embedding_count() {
+        // The mock LLM returns a single embedding whatever the input
+        let embedder = Embedder::from_llm(Arc::new(MockLLMProvider));
+        let result = embedder.embed(vec!["a".to_string(), "b".to_string()]).await;
+        assert!(matches!(result, Err(VectorStoreError::EmbeddingError(_))));
+    }
+}
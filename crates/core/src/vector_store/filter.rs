@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Condition on document metadata used to restrict a search
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Filter {
+    /// The field equals the value
+    Eq(String, Value),
+    /// The field is missing or differs from the value
+    Ne(String, Value),
+    /// The field equals one of the values
+    In(String, Vec<Value>),
+    /// The field is a number greater than the value
+    Gt(String, f64),
+    /// The field is a number greater than or equal to the value
+    Gte(String, f64),
+    /// The field is a number less than the value
+    Lt(String, f64),
+    /// The field is a number less than or equal to the value
+    Lte(String, f64),
+    /// The field is a string containing the value, or an array containing it
+    Contains(String, Value),
+    /// The field is present
+    Exists(String),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn eq(field: impl Into<String>, value: impl Into<Value>) -> Self {
+        Filter::Eq(field.into(), value.into())
+    }
+
+    pub fn ne(field: impl Into<String>, value: impl Into<Value>) -> Self {
+        Filter::Ne(field.into(), value.into())
+    }
+
+    pub fn is_in<I, V>(field: impl Into<String>, values: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        Filter::In(field.into(), values.into_iter().map(Into::into).collect())
+    }
+
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Filter::And(mut filters) => {
+                filters.push(other);
+                Filter::And(filters)
+            }
+            filter => Filter::And(vec![filter, other]),
+        }
+    }
+
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Filter::Or(mut filters) => {
+                filters.push(other);
+                Filter::Or(filters)
+            }
+            filter => Filter::Or(vec![filter, other]),
+        }
+    }
+
+    pub fn matches(&self, metadata: &HashMap<String, Value>) -> bool {
+        let number = |field: &str| metadata.get(field).and_then(Value::as_f64);
+        match self {
+            Filter::Eq(field, value) => metadata.get(field) == Some(value),
+            Filter::Ne(field, value) => metadata.get(field) != Some(value),
+            Filter::In(field, values) => metadata.get(field).is_some_and(|v| values.contains(v)),
+            Filter::Gt(field, n) => number(field).is_some_and(|v| v > *n),
+            Filter::Gte(field, n) => number(field).is_some_and(|v| v >= *n),
+            Filter::Lt(field, n) => number(field).is_some_and(|v| v < *n),
+            Filter::Lte(field, n) => number(field).is_some_and(|v| v <= *n),
+            Filter::Contains(field, value) => match (metadata.get(field), value) {
+                (Some(Value::String(s)), Value::String(needle)) => s.contains(needle.as_str()),
+                (Some(Value::Array(items)), value) => items.contains(value),
+                _ => false,
+            },
+            Filter::Exists(field) => metadata.contains_key(field),
+            Filter::And(filters) => filters.iter().all(|f| f.matches(metadata)),
+            Filter::Or(filters) => filters.iter().any(|f| f.matches(metadata)),
+            Filter::Not(filter) => !filter.matches(metadata),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn metadata() -> HashMap<String, Value> {
+        serde_json::from_value(json!({
+            "lang": "rust",
+            "year": 2024,
+            "tags": ["async", "agents"],
+            "path": "src/agent/mod.rs"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_filter_matching() {
+        let meta = metadata();
+        assert!(Filter::eq("lang", "rust").matches(&meta));
+        assert!(Filter::ne("lang", "go").matches(&meta));
+        assert!(Filter::ne("missing", "x").matches(&meta));
+        assert!(Filter::is_in("lang", ["go", "rust"]).matches(&meta));
+        assert!(Filter::Gte("year".to_string(), 2024.0).matches(&meta));
+        assert!(!Filter::Lt("year".to_string(), 2024.0).matches(&meta));
+        assert!(!Filter::Gt("lang".to_string(), 0.0).matches(&meta));
+        assert!(Filter::Contains("tags".to_string(), json!("agents")).matches(&meta));
+        assert!(Filter::Contains("path".to_string(), json!("agent/")).matches(&meta));
+        assert!(Filter::Exists("tags".to_string()).matches(&meta));
+        assert!(Filter::Not(Box::new(Filter::Exists("missing".to_string()))).matches(&meta));
+    }
+
+    #[test]
+    fn test_filter_combinators() {
+        let meta = metadata();
+        let filter = Filter::eq("lang", "rust").and(Filter::Gt("year".to_string(), 2000.0));
+        assert!(filter.matches(&meta));
+        assert!(matches!(&filter, Filter::And(filters) if filters.len() == 2));
+        assert!(!filter.and(Filter::eq("lang", "go")).matches(&meta));
+        assert!(Filter::eq("lang", "go")
+            .or(Filter::eq("lang", "rust"))
+            .matches(&meta));
+
+        let json = serde_json::to_value(Filter::eq("lang", "rust")).unwrap();
+        assert_eq!(json, json!({"eq": ["lang", "rust"]}));
+    }
+}
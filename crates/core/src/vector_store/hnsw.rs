@@ -0,0 +1,557 @@
+use super::{
+    check_dimensions, DistanceMetric, Filter, SearchResult, VectorRecord, VectorStore,
+    VectorStoreError,
+};
+use crate::document::Document;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Parameters of the HNSW graph
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HnswConfig {
+    /// Neighbors kept per node on the upper layers, twice as many on the bottom layer
+    pub m: usize,
+    /// Size of the candidate list when inserting; higher builds a better graph, slower
+    pub ef_construction: usize,
+    /// Size of the candidate list when searching; higher improves recall, slower
+    pub ef_search: usize,
+    pub metric: DistanceMetric,
+}
+
+impl Default for HnswConfig {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+            metric: DistanceMetric::Cosine,
+        }
+    }
+}
+
+/// Distance and node index, ordered by distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scored(f32, usize);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Node {
+    record: VectorRecord,
+    /// Neighbor indices for each layer the node is on
+    neighbors: Vec<Vec<usize>>,
+    deleted: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Graph {
+    config: HnswConfig,
+    dimension: Option<usize>,
+    nodes: Vec<Node>,
+    entry: Option<usize>,
+    max_level: usize,
+    rng_state: u64,
+    /// Index of the live node for each document id
+    #[serde(skip)]
+    ids: HashMap<String, usize>,
+}
+
+impl Graph {
+    fn new(config: HnswConfig) -> Self {
+        Self {
+            config,
+            dimension: None,
+            nodes: Vec::new(),
+            entry: None,
+            max_level: 0,
+            rng_state: 0x9E37_79B9_7F4A_7C15,
+            ids: HashMap::new(),
+        }
+    }
+
+    fn rebuild_ids(&mut self) {
+        self.ids = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| !n.deleted)
+            .map(|(i, n)| (n.record.document.id.clone(), i))
+            .collect();
+    }
+
+    fn deleted_count(&self) -> usize {
+        self.nodes.len() - self.ids.len()
+    }
+
+    /// Draw a level from an exponential distribution with xorshift, so graphs are
+    /// reproducible
+    fn random_level(&mut self) -> usize {
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng_state = x;
+        let uniform = ((x >> 11) as f64 / (1u64 << 53) as f64).max(f64::MIN_POSITIVE);
+        let level_multiplier = 1.0 / (self.config.m.max(2) as f64).ln();
+        (-uniform.ln() * level_multiplier) as usize
+    }
+
+    fn max_neighbors(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.config.m * 2
+        } else {
+            self.config.m
+        }
+    }
+
+    fn distance(&self, query: &[f32], node: usize) -> f32 {
+        -self
+            .config
+            .metric
+            .similarity(query, &self.nodes[node].record.embedding)
+    }
+
+    /// The `ef` nodes closest to `query` reachable on `layer` from `entries`, closest first
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entries: &[usize],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Scored> {
+        let mut visited: HashSet<usize> = entries.iter().copied().collect();
+        let mut candidates = BinaryHeap::new();
+        let mut found = BinaryHeap::new();
+        for &entry in entries {
+            let scored = Scored(self.distance(query, entry), entry);
+            candidates.push(Reverse(scored));
+            found.push(scored);
+        }
+
+        while let Some(Reverse(Scored(distance, node))) = candidates.pop() {
+            if found.len() >= ef && found.peek().is_some_and(|w: &Scored| distance > w.0) {
+                break;
+            }
+            let neighbors = self.nodes[node].neighbors.get(layer);
+            for &neighbor in neighbors.into_iter().flatten() {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let scored = Scored(self.distance(query, neighbor), neighbor);
+                if found.len() < ef || found.peek().is_some_and(|w| scored < *w) {
+                    candidates.push(Reverse(scored));
+                    found.push(scored);
+                    if found.len() > ef {
+                        found.pop();
+                    }
+                }
+            }
+        }
+        found.into_sorted_vec()
+    }
+
+    /// Descend the upper layers greedily to the closest entry point on `layer`
+    fn descend(&self, query: &[f32], mut entry: usize, layer: usize) -> usize {
+        for l in (layer + 1..=self.max_level).rev() {
+            entry = self.search_layer(query, &[entry], 1, l)[0].1;
+        }
+        entry
+    }
+
+    fn insert(&mut self, record: VectorRecord) {
+        let id = record.document.id.clone();
+        if let Some(old) = self.ids.remove(&id) {
+            self.nodes[old].deleted = true;
+        }
+
+        let level = self.random_level();
+        let index = self.nodes.len();
+        let query = record.embedding.clone();
+        self.nodes.push(Node {
+            record,
+            neighbors: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.ids.insert(id, index);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(index);
+            self.max_level = level;
+            return;
+        };
+
+        let mut entries = vec![self.descend(&query, entry, level)];
+        for layer in (0..=level.min(self.max_level)).rev() {
+            let candidates =
+                self.search_layer(&query, &entries, self.config.ef_construction, layer);
+            let max = self.max_neighbors(layer);
+            let neighbors: Vec<usize> = candidates.iter().take(max).map(|s| s.1).collect();
+            for &neighbor in &neighbors {
+                self.nodes[neighbor].neighbors[layer].push(index);
+                if self.nodes[neighbor].neighbors[layer].len() > max {
+                    self.prune(neighbor, layer, max);
+                }
+            }
+            self.nodes[index].neighbors[layer] = neighbors;
+            entries = candidates.into_iter().map(|s| s.1).collect();
+        }
+
+        if level > self.max_level {
+            self.max_level = level;
+            self.entry = Some(index);
+        }
+    }
+
+    /// Keep only the `max` closest neighbors of `node` on `layer`
+    fn prune(&mut self, node: usize, layer: usize, max: usize) {
+        let embedding = self.nodes[node].record.embedding.clone();
+        let mut neighbors: Vec<Scored> = self.nodes[node].neighbors[layer]
+            .iter()
+            .map(|&n| Scored(self.distance(&embedding, n), n))
+            .collect();
+        neighbors.sort();
+        self.nodes[node].neighbors[layer] = neighbors.into_iter().take(max).map(|s| s.1).collect();
+    }
+
+    fn result(&self, scored: Scored) -> SearchResult {
+        SearchResult {
+            document: self.nodes[scored.1].record.document.clone(),
+            score: -scored.0,
+        }
+    }
+
+    fn search(&self, query: &[f32], top_k: usize, filter: Option<&Filter>) -> Vec<SearchResult> {
+        let Some(entry) = self.entry else {
+            return Vec::new();
+        };
+        let accept = |i: usize| {
+            let node = &self.nodes[i];
+            !node.deleted && filter.is_none_or(|f| f.matches(&node.record.document.metadata))
+        };
+
+        let entry = self.descend(query, entry, 0);
+        let ef = self.config.ef_search.max(top_k);
+        let mut found: Vec<Scored> = self
+            .search_layer(query, &[entry], ef, 0)
+            .into_iter()
+            .filter(|s| accept(s.1))
+            .take(top_k)
+            .collect();
+
+        // Restrictive filters and deleted nodes can leave the candidate list short, in
+        // which case the matching nodes are scanned exactly
+        if found.len() < top_k && found.len() < self.ids.len() {
+            found = self
+                .ids
+                .values()
+                .filter(|&&i| accept(i))
+                .map(|&i| Scored(self.distance(query, i), i))
+                .collect();
+            found.sort();
+            found.truncate(top_k);
+        }
+        found.into_iter().map(|s| self.result(s)).collect()
+    }
+
+    /// Rebuild the graph from the live nodes, dropping deleted ones
+    fn compact(&mut self) {
+        let mut graph = Graph::new(self.config);
+        graph.dimension = self.dimension;
+        for node in std::mem::take(&mut self.nodes) {
+            if !node.deleted {
+                graph.insert(node.record);
+            }
+        }
+        *self = graph;
+    }
+}
+
+pub(super) fn write_atomic(path: &Path, data: &[u8]) -> Result<(), VectorStoreError> {
+    let io_error = |source| VectorStoreError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, data).map_err(io_error)?;
+    std::fs::rename(&tmp, path).map_err(io_error)
+}
+
+/// [`write_atomic`] on the blocking pool, so async callers don't stall their runtime
+#[cfg(not(target_arch = "wasm32"))]
+async fn write_atomic_blocking(path: PathBuf, data: Vec<u8>) -> Result<(), VectorStoreError> {
+    let task_path = path.clone();
+    tokio::task::spawn_blocking(move || write_atomic(&task_path, &data))
+        .await
+        .map_err(|e| VectorStoreError::Io {
+            path,
+            source: std::io::Error::other(e),
+        })?
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn write_atomic_blocking(path: PathBuf, data: Vec<u8>) -> Result<(), VectorStoreError> {
+    write_atomic(&path, &data)
+}
+
+/// Vector store with an approximate nearest neighbor index (HNSW).
+///
+/// Searches visit a small part of the graph, so they stay fast on large collections at
+/// the cost of occasionally missing a close match. Deleted documents are kept in the
+/// graph until [`HnswVectorStore::compact`] is called or more than half of the nodes are
+/// deleted when persisting.
+///
+/// A store opened with [`HnswVectorStore::open`] is loaded from its file if it exists and
+/// written back by [`VectorStore::persist`].
+pub struct HnswVectorStore {
+    path: Option<PathBuf>,
+    graph: RwLock<Graph>,
+}
+
+impl HnswVectorStore {
+    /// Create a store that only lives in memory
+    pub fn new(config: HnswConfig) -> Self {
+        Self {
+            path: None,
+            graph: RwLock::new(Graph::new(config)),
+        }
+    }
+
+    /// Open the store persisted at `path`, or create an empty one using `config`.
+    ///
+    /// The configuration of an existing store is kept. The file is read synchronously, so
+    /// async code should open large stores on a blocking thread.
+    pub fn open(path: impl Into<PathBuf>, config: HnswConfig) -> Result<Self, VectorStoreError> {
+        let path = path.into();
+        let graph = match std::fs::read(&path) {
+            Ok(data) => {
+                let mut graph: Graph = serde_json::from_slice(&data)?;
+                graph.rebuild_ids();
+                graph
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Graph::new(config),
+            Err(source) => return Err(VectorStoreError::Io { path, source }),
+        };
+        Ok(Self {
+            path: Some(path),
+            graph: RwLock::new(graph),
+        })
+    }
+
+    pub fn config(&self) -> HnswConfig {
+        self.graph.read().unwrap().config
+    }
+
+    /// Set the candidate list size used by searches
+    pub fn set_ef_search(&self, ef_search: usize) {
+        self.graph.write().unwrap().config.ef_search = ef_search.max(1);
+    }
+
+    /// Rebuild the index without the deleted documents
+    pub fn compact(&self) {
+        self.graph.write().unwrap().compact();
+    }
+}
+
+#[async_trait]
+impl VectorStore for HnswVectorStore {
+    async fn upsert(&self, records: Vec<VectorRecord>) -> Result<(), VectorStoreError> {
+        let mut graph = self.graph.write().unwrap();
+        check_dimensions(
+            &mut graph.dimension,
+            records.iter().map(|r| r.embedding.as_slice()),
+        )?;
+        for record in records {
+            graph.insert(record);
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, ids: &[String]) -> Result<usize, VectorStoreError> {
+        let mut graph = self.graph.write().unwrap();
+        let mut deleted = 0;
+        for id in ids {
+            if let Some(index) = graph.ids.remove(id) {
+                graph.nodes[index].deleted = true;
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    async fn search(
+        &self,
+        query: &[f32],
+        top_k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let graph = self.graph.read().unwrap();
+        if let Some(expected) = graph.dimension {
+            if query.len() != expected {
+                return Err(VectorStoreError::DimensionMismatch {
+                    expected,
+                    actual: query.len(),
+                });
+            }
+        }
+        Ok(graph.search(query, top_k, filter))
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Document>, VectorStoreError> {
+        let graph = self.graph.read().unwrap();
+        Ok(graph
+            .ids
+            .get(id)
+            .map(|&i| graph.nodes[i].record.document.clone()))
+    }
+
+    async fn len(&self) -> Result<usize, VectorStoreError> {
+        Ok(self.graph.read().unwrap().ids.len())
+    }
+
+    async fn persist(&self) -> Result<(), VectorStoreError> {
+        let Some(path) = self.path.clone() else {
+            return Ok(());
+        };
+        // Snapshot under the lock, then write the file without holding it
+        let data = {
+            let mut graph = self.graph.write().unwrap();
+            if graph.deleted_count() * 2 > graph.nodes.len() {
+                graph.compact();
+            }
+            serde_json::to_vec(&*graph)?
+        };
+        write_atomic_blocking(path, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector_store::InMemoryVectorStore;
+
+    /// Deterministic pseudo-random embeddings
+    fn embeddings(count: usize, dimension: usize) -> Vec<Vec<f32>> {
+        let mut state = 42u64;
+        (0..count)
+            .map(|_| {
+                (0..dimension)
+                    .map(|_| {
+                        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                        ((state >> 33) as f32 / u32::MAX as f32) - 0.25
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn records(embeddings: &[Vec<f32>]) -> Vec<VectorRecord> {
+        embeddings
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                let document = Document::with_id(format!("doc-{i}"), format!("text {i}"))
+                    .with_metadata("parity", i % 2);
+                VectorRecord::new(document, e.clone())
+            })
+            .collect()
+    }
+
+    fn ids(results: &[SearchResult]) -> HashSet<String> {
+        results.iter().map(|r| r.document.id.clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_recall_matches_exact_search() {
+        let vectors = embeddings(500, 16);
+        let hnsw = HnswVectorStore::new(HnswConfig::default());
+        let exact = InMemoryVectorStore::new();
+        hnsw.upsert(records(&vectors)).await.unwrap();
+        exact.upsert(records(&vectors)).await.unwrap();
+
+        let mut hits = 0;
+        for query in embeddings(520, 16).iter().skip(500) {
+            let approximate = hnsw.search(query, 10, None).await.unwrap();
+            let expected = exact.search(query, 10, None).await.unwrap();
+            hits += ids(&approximate).intersection(&ids(&expected)).count();
+            assert!(approximate.windows(2).all(|w| w[0].score >= w[1].score));
+        }
+        assert!(hits >= 190, "recall too low: {hits}/200");
+    }
+
+    #[tokio::test]
+    async fn test_filters_deletes_and_upserts() {
+        let vectors = embeddings(100, 8);
+        let store = HnswVectorStore::new(HnswConfig::default());
+        store.upsert(records(&vectors)).await.unwrap();
+
+        let filter = Filter::eq("parity", 1);
+        let results = store.search(&vectors[4], 5, Some(&filter)).await.unwrap();
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(|r| r.document.metadata["parity"] == 1));
+
+        let results = store.search(&vectors[4], 1, None).await.unwrap();
+        assert_eq!(results[0].document.id, "doc-4");
+        assert_eq!(store.delete(&["doc-4".to_string()]).await.unwrap(), 1);
+        let results = store.search(&vectors[4], 1, None).await.unwrap();
+        assert_ne!(results[0].document.id, "doc-4");
+
+        let replaced = Document::with_id("doc-5", "new text");
+        store
+            .upsert(vec![VectorRecord::new(replaced, vectors[4].clone())])
+            .await
+            .unwrap();
+        assert_eq!(store.len().await.unwrap(), 99);
+        let results = store.search(&vectors[4], 1, None).await.unwrap();
+        assert_eq!(results[0].document.text, "new text");
+
+        store.compact();
+        assert_eq!(store.graph.read().unwrap().nodes.len(), 99);
+        assert_eq!(
+            store.search(&vectors[4], 1, None).await.unwrap()[0]
+                .document
+                .id,
+            "doc-5"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_persist_and_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index").join("store.json");
+        let vectors = embeddings(50, 8);
+        let config = HnswConfig {
+            m: 8,
+            ..Default::default()
+        };
+
+        let store = HnswVectorStore::open(&path, config).unwrap();
+        store.upsert(records(&vectors)).await.unwrap();
+        store.persist().await.unwrap();
+
+        let reopened = HnswVectorStore::open(&path, HnswConfig::default()).unwrap();
+        assert_eq!(reopened.config().m, 8);
+        assert_eq!(reopened.len().await.unwrap(), 50);
+        let results = reopened.search(&vectors[7], 1, None).await.unwrap();
+        assert_eq!(results[0].document.id, "doc-7");
+        assert!(reopened.get("doc-3").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,281 @@
+use super::{Bm25Index, Embedder, Filter, Retriever, SearchResult, VectorStore, VectorStoreError};
+use crate::document::Document;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Minimum number of candidates each search contributes before fusion
+const DEFAULT_CANDIDATES: usize = 20;
+
+/// Retriever embedding the query and searching a [`VectorStore`]
+#[derive(Clone)]
+pub struct VectorRetriever {
+    store: Arc<dyn VectorStore>,
+    embedder: Embedder,
+}
+
+impl VectorRetriever {
+    pub fn new(store: Arc<dyn VectorStore>, embedder: Embedder) -> Self {
+        Self { store, embedder }
+    }
+
+    pub fn store(&self) -> &Arc<dyn VectorStore> {
+        &self.store
+    }
+
+    pub fn embedder(&self) -> &Embedder {
+        &self.embedder
+    }
+}
+
+#[async_trait]
+impl Retriever for VectorRetriever {
+    async fn retrieve(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        self.embedder
+            .search(self.store.as_ref(), query, top_k, filter)
+            .await
+    }
+}
+
+/// How the rankings of the vector and keyword searches are combined
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Fusion {
+    /// Reciprocal rank fusion: a document scores `1 / (k + rank)` in each ranking it
+    /// appears in. Only ranks matter, so the different score scales don't need tuning.
+    ReciprocalRank { k: f32 },
+    /// Scores of each ranking are min-max normalized to `[0, 1]`, then weighted by
+    /// `vector_weight` for the vector search and `1 - vector_weight` for the keyword search
+    Weighted { vector_weight: f32 },
+}
+
+impl Default for Fusion {
+    fn default() -> Self {
+        Fusion::ReciprocalRank { k: 60.0 }
+    }
+}
+
+impl Fusion {
+    /// Merge rankings, each paired with its weight, into the `top_k` best documents
+    fn fuse(&self, rankings: Vec<(Vec<SearchResult>, f32)>, top_k: usize) -> Vec<SearchResult> {
+        let mut fused: HashMap<String, SearchResult> = HashMap::new();
+        for (ranking, weight) in rankings {
+            let (min, max) = ranking.iter().fold((f32::MAX, f32::MIN), |(min, max), r| {
+                (min.min(r.score), max.max(r.score))
+            });
+            for (rank, result) in ranking.into_iter().enumerate() {
+                let score = match *self {
+                    Fusion::ReciprocalRank { k } => weight / (k + rank as f32 + 1.0),
+                    Fusion::Weighted { .. } if max > min => {
+                        weight * (result.score - min) / (max - min)
+                    }
+                    Fusion::Weighted { .. } => weight,
+                };
+                fused
+                    .entry(result.document.id.clone())
+                    .or_insert(SearchResult {
+                        document: result.document,
+                        score: 0.0,
+                    })
+                    .score += score;
+            }
+        }
+        let mut results: Vec<SearchResult> = fused.into_values().collect();
+        results.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.document.id.cmp(&b.document.id))
+        });
+        results.truncate(top_k);
+        results
+    }
+
+    fn weights(&self) -> (f32, f32) {
+        match *self {
+            Fusion::ReciprocalRank { .. } => (1.0, 1.0),
+            Fusion::Weighted { vector_weight } => {
+                let vector_weight = vector_weight.clamp(0.0, 1.0);
+                (vector_weight, 1.0 - vector_weight)
+            }
+        }
+    }
+}
+
+/// Retriever combining a vector search with a BM25 keyword search.
+///
+/// Both searches run for every query and their rankings are merged with a [`Fusion`]
+/// strategy, so documents found by meaning and documents containing exact identifiers
+/// both surface. Index documents through [`HybridRetriever::index`] to keep the store and
+/// the keyword index in sync.
+#[derive(Clone)]
+pub struct HybridRetriever {
+    vector: VectorRetriever,
+    keywords: Arc<Bm25Index>,
+    fusion: Fusion,
+    candidates: usize,
+}
+
+impl HybridRetriever {
+    pub fn new(store: Arc<dyn VectorStore>, embedder: Embedder, keywords: Arc<Bm25Index>) -> Self {
+        Self {
+            vector: VectorRetriever::new(store, embedder),
+            keywords,
+            fusion: Fusion::default(),
+            candidates: DEFAULT_CANDIDATES,
+        }
+    }
+
+    pub fn with_fusion(mut self, fusion: Fusion) -> Self {
+        self.fusion = fusion;
+        self
+    }
+
+    /// Number of results each search contributes before fusion, at least `top_k`
+    pub fn with_candidates(mut self, candidates: usize) -> Self {
+        self.candidates = candidates.max(1);
+        self
+    }
+
+    pub fn keywords(&self) -> &Arc<Bm25Index> {
+        &self.keywords
+    }
+
+    /// Embed documents into the vector store and add them to the keyword index,
+    /// returning how many were indexed
+    pub async fn index(&self, documents: Vec<Document>) -> Result<usize, VectorStoreError> {
+        let count = self
+            .vector
+            .embedder
+            .index(self.vector.store.as_ref(), documents.clone())
+            .await?;
+        self.keywords.add(documents);
+        Ok(count)
+    }
+
+    /// Delete documents from both indexes, returning how many existed in the vector store
+    pub async fn delete(&self, ids: &[String]) -> Result<usize, VectorStoreError> {
+        self.keywords.delete(ids);
+        self.vector.store.delete(ids).await
+    }
+}
+
+#[async_trait]
+impl Retriever for HybridRetriever {
+    async fn retrieve(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let candidates = self.candidates.max(top_k);
+        let vector = self.vector.retrieve(query, candidates, filter).await?;
+        let keyword = self.keywords.search(query, candidates, filter);
+        let (vector_weight, keyword_weight) = self.fusion.weights();
+        Ok(self.fusion.fuse(
+            vec![(vector, vector_weight), (keyword, keyword_weight)],
+            top_k,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector_store::InMemoryVectorStore;
+    use autoagents_test_utils::llm::MockEmbeddingProvider;
+
+    fn result(id: &str, score: f32) -> SearchResult {
+        SearchResult {
+            document: Document::with_id(id, id),
+            score,
+        }
+    }
+
+    fn ids(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.document.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let vector = vec![
+            result("a", 0.9),
+            result("b", 0.8),
+            result("c", 0.5),
+            result("d", 0.1),
+        ];
+        let keyword = vec![result("d", 12.0), result("b", 3.0)];
+        let fused = Fusion::ReciprocalRank { k: 60.0 }.fuse(vec![(vector, 1.0), (keyword, 1.0)], 3);
+        // b is second in both rankings, d is first in one but last in the other
+        assert_eq!(ids(&fused), vec!["b", "d", "a"]);
+        assert!((fused[0].score - 2.0 / 62.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_weighted_fusion() {
+        let rankings = || {
+            vec![
+                vec![result("a", 0.9), result("b", 0.5)],
+                vec![result("b", 8.0), result("a", 2.0)],
+            ]
+        };
+        let fuse = |vector_weight: f32| {
+            let fusion = Fusion::Weighted { vector_weight };
+            let (v, k) = fusion.weights();
+            let mut rankings = rankings().into_iter();
+            fusion.fuse(
+                vec![(rankings.next().unwrap(), v), (rankings.next().unwrap(), k)],
+                2,
+            )
+        };
+        assert_eq!(ids(&fuse(1.0)), vec!["a", "b"]);
+        assert_eq!(ids(&fuse(0.2)), vec!["b", "a"]);
+        assert_eq!(fuse(0.2)[0].score, 0.8);
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_finds_identifiers_and_meaning() {
+        let store = Arc::new(InMemoryVectorStore::new());
+        let embedder = Embedder::new(Arc::new(MockEmbeddingProvider::new(64)));
+        let keywords = Arc::new(Bm25Index::new());
+        let retriever = HybridRetriever::new(store.clone(), embedder, keywords.clone());
+        retriever
+            .index(vec![
+                Document::with_id("code", "Error E4021 is returned by the billing service")
+                    .with_metadata("team", "billing"),
+                Document::with_id("refund", "How to refund a customer payment")
+                    .with_metadata("team", "billing"),
+                Document::with_id("login", "Reset a password when login fails")
+                    .with_metadata("team", "auth"),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(keywords.len(), 3);
+
+        let results = retriever
+            .retrieve("what does E4021 mean", 1, None)
+            .await
+            .unwrap();
+        assert_eq!(ids(&results), vec!["code"]);
+        let results = retriever
+            .retrieve("refund a payment", 1, None)
+            .await
+            .unwrap();
+        assert_eq!(ids(&results), vec!["refund"]);
+
+        let filter = Filter::eq("team", "auth");
+        let results = retriever
+            .retrieve("refund a payment", 3, Some(&filter))
+            .await
+            .unwrap();
+        assert!(results.iter().all(|r| r.document.id == "login"));
+
+        assert_eq!(retriever.delete(&["code".to_string()]).await.unwrap(), 1);
+        assert!(keywords.get("code").is_none());
+        assert!(store.get("code").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,211 @@
+use super::{
+    check_dimensions, DistanceMetric, Filter, SearchResult, VectorRecord, VectorStore,
+    VectorStoreError,
+};
+use crate::document::Document;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::RwLock;
+
+#[derive(Default, Serialize, Deserialize)]
+struct State {
+    metric: DistanceMetric,
+    dimension: Option<usize>,
+    records: HashMap<String, VectorRecord>,
+}
+
+/// Vector store that compares the query with every record.
+///
+/// Searches are exact and take linear time, which is fast enough for tens of thousands of
+/// documents. The store can be saved to and loaded from a JSON file.
+#[derive(Default)]
+pub struct InMemoryVectorStore {
+    state: RwLock<State>,
+}
+
+impl InMemoryVectorStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_metric(metric: DistanceMetric) -> Self {
+        Self {
+            state: RwLock::new(State {
+                metric,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Write all records to a JSON file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), VectorStoreError> {
+        let data = serde_json::to_vec(&*self.state.read().unwrap())?;
+        super::hnsw::write_atomic(path.as_ref(), &data)
+    }
+
+    /// Load a store written by [`InMemoryVectorStore::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, VectorStoreError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|source| VectorStoreError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Self {
+            state: RwLock::new(serde_json::from_slice(&data)?),
+        })
+    }
+}
+
+#[async_trait]
+impl VectorStore for InMemoryVectorStore {
+    async fn upsert(&self, records: Vec<VectorRecord>) -> Result<(), VectorStoreError> {
+        let mut state = self.state.write().unwrap();
+        check_dimensions(
+            &mut state.dimension,
+            records.iter().map(|r| r.embedding.as_slice()),
+        )?;
+        for record in records {
+            state.records.insert(record.document.id.clone(), record);
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, ids: &[String]) -> Result<usize, VectorStoreError> {
+        let mut state = self.state.write().unwrap();
+        Ok(ids
+            .iter()
+            .filter(|id| state.records.remove(*id).is_some())
+            .count())
+    }
+
+    async fn search(
+        &self,
+        query: &[f32],
+        top_k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let state = self.state.read().unwrap();
+        if let Some(expected) = state.dimension {
+            if query.len() != expected {
+                return Err(VectorStoreError::DimensionMismatch {
+                    expected,
+                    actual: query.len(),
+                });
+            }
+        }
+        let mut results: Vec<SearchResult> = state
+            .records
+            .values()
+            .filter(|r| filter.is_none_or(|f| f.matches(&r.document.metadata)))
+            .map(|r| SearchResult {
+                document: r.document.clone(),
+                score: state.metric.similarity(query, &r.embedding),
+            })
+            .collect();
+        results.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.document.id.cmp(&b.document.id))
+        });
+        results.truncate(top_k);
+        Ok(results)
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Document>, VectorStoreError> {
+        Ok(self
+            .state
+            .read()
+            .unwrap()
+            .records
+            .get(id)
+            .map(|r| r.document.clone()))
+    }
+
+    async fn len(&self) -> Result<usize, VectorStoreError> {
+        Ok(self.state.read().unwrap().records.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, lang: &str, embedding: Vec<f32>) -> VectorRecord {
+        VectorRecord::new(
+            Document::with_id(id, format!("text of {id}")).with_metadata("lang", lang),
+            embedding,
+        )
+    }
+
+    async fn store() -> InMemoryVectorStore {
+        let store = InMemoryVectorStore::new();
+        store
+            .upsert(vec![
+                record("a", "rust", vec![1.0, 0.0]),
+                record("b", "go", vec![0.9, 0.1]),
+                record("c", "rust", vec![0.0, 1.0]),
+            ])
+            .await
+            .unwrap();
+        store
+    }
+
+    #[tokio::test]
+    async fn test_search_orders_and_filters() {
+        let store = store().await;
+        let ids = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.document.id).collect()
+        };
+
+        let results = store.search(&[1.0, 0.0], 2, None).await.unwrap();
+        assert_eq!(ids(results), vec!["a", "b"]);
+
+        let filter = Filter::eq("lang", "rust");
+        let results = store.search(&[0.8, 0.2], 5, Some(&filter)).await.unwrap();
+        assert_eq!(ids(results), vec!["a", "c"]);
+
+        assert!(matches!(
+            store.search(&[1.0], 1, None).await,
+            Err(VectorStoreError::DimensionMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_upsert_replaces_and_delete() {
+        let store = store().await;
+        store
+            .upsert(vec![record("a", "python", vec![0.0, 1.0])])
+            .await
+            .unwrap();
+        assert_eq!(store.len().await.unwrap(), 3);
+        assert_eq!(
+            store.get("a").await.unwrap().unwrap().metadata["lang"],
+            "python"
+        );
+        assert!(store
+            .upsert(vec![record("d", "rust", vec![1.0])])
+            .await
+            .is_err());
+
+        let deleted = store
+            .delete(&["a".to_string(), "missing".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(deleted, 1);
+        assert!(store.get("a").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        store().await.save(&path).unwrap();
+
+        let loaded = InMemoryVectorStore::load(&path).unwrap();
+        assert_eq!(loaded.len().await.unwrap(), 3);
+        let results = loaded.search(&[0.0, 1.0], 1, None).await.unwrap();
+        assert_eq!(results[0].document.id, "c");
+    }
+}
@@ -0,0 +1,222 @@
+//! Vector stores for retrieval augmented generation.
+//!
+//! A [`VectorStore`] keeps [`Document`]s with their embeddings and finds the documents
+//! closest to a query embedding. The [`Embedder`] turns documents and queries into
+//! embeddings with any [`EmbeddingProvider`](autoagents_llm::embedding::EmbeddingProvider).
+//!
+//! A [`Retriever`] answers text queries, either from a vector store alone, from a
+//! [`Bm25Index`] keyword index, or from both with a [`HybridRetriever`]. A
+//! [`RerankingRetriever`] reorders another retriever's candidates with a [`Reranker`].
+
+use crate::document::Document;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use thiserror::Error;
+
+mod bm25;
+mod embedder;
+mod filter;
+mod hnsw;
+mod hybrid;
+mod memory;
+mod rerank;
+#[cfg(not(target_arch = "wasm32"))]
+mod retriever;
+
+pub use bm25::{tokenize, Bm25Config, Bm25Index};
+pub use embedder::Embedder;
+pub use filter::Filter;
+pub use hnsw::{HnswConfig, HnswVectorStore};
+pub use hybrid::{Fusion, HybridRetriever, VectorRetriever};
+pub use memory::InMemoryVectorStore;
+#[cfg(feature = "cohere")]
+pub use rerank::CohereReranker;
+#[cfg(all(feature = "liquid_edge", not(target_arch = "wasm32")))]
+pub use rerank::CrossEncoderReranker;
+pub use rerank::{LlmReranker, Reranker, RerankingRetriever};
+#[cfg(not(target_arch = "wasm32"))]
+pub use retriever::RetrieverTool;
+
+#[derive(Debug, Error)]
+pub enum VectorStoreError {
+    #[error("Embedding dimension mismatch: expected {expected}, got {actual}")]
+    DimensionMismatch { expected: usize, actual: usize },
+
+    #[error("Embedding error: {0}")]
+    EmbeddingError(String),
+
+    #[error("Failed to access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Rerank error: {0}")]
+    RerankError(String),
+
+    #[error("Serialization error: {0}")]
+    SerializationError(#[from] serde_json::Error),
+}
+
+/// A document and its embedding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VectorRecord {
+    pub document: Document,
+    pub embedding: Vec<f32>,
+}
+
+impl VectorRecord {
+    pub fn new(document: Document, embedding: Vec<f32>) -> Self {
+        Self {
+            document,
+            embedding,
+        }
+    }
+}
+
+/// A document found by a search, with its similarity to the query. Higher is closer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub document: Document,
+    pub score: f32,
+}
+
+/// How the similarity between two embeddings is measured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DistanceMetric {
+    /// Cosine of the angle between the embeddings, in `[-1, 1]`
+    #[default]
+    Cosine,
+    /// Dot product, for embeddings that are already normalized
+    DotProduct,
+    /// Negated Euclidean distance
+    Euclidean,
+}
+
+impl DistanceMetric {
+    /// Similarity of two embeddings of the same dimension, higher is closer
+    pub fn similarity(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            DistanceMetric::Cosine => {
+                let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+                let denominator = norm(a) * norm(b);
+                if denominator == 0.0 {
+                    0.0
+                } else {
+                    dot(a, b) / denominator
+                }
+            }
+            DistanceMetric::DotProduct => dot(a, b),
+            DistanceMetric::Euclidean => -a
+                .iter()
+                .zip(b)
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f32>()
+                .sqrt(),
+        }
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Check that every embedding has the expected dimension, fixing it from the first one
+/// when unknown
+fn check_dimensions<'a>(
+    dimension: &mut Option<usize>,
+    embeddings: impl IntoIterator<Item = &'a [f32]>,
+) -> Result<(), VectorStoreError> {
+    for embedding in embeddings {
+        let expected = *dimension.get_or_insert(embedding.len());
+        if embedding.len() != expected {
+            return Err(VectorStoreError::DimensionMismatch {
+                expected,
+                actual: embedding.len(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Storage of embedded documents with similarity search.
+///
+/// Stores use interior mutability so they can be shared between agents and tools.
+#[async_trait]
+pub trait VectorStore: Send + Sync {
+    /// Insert records, replacing documents with the same id
+    async fn upsert(&self, records: Vec<VectorRecord>) -> Result<(), VectorStoreError>;
+
+    /// Delete documents by id, returning how many existed
+    async fn delete(&self, ids: &[String]) -> Result<usize, VectorStoreError>;
+
+    /// The `top_k` documents most similar to `query` that match `filter`, best first
+    async fn search(
+        &self,
+        query: &[f32],
+        top_k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError>;
+
+    /// Get a document by id
+    async fn get(&self, id: &str) -> Result<Option<Document>, VectorStoreError>;
+
+    /// Number of documents in the store
+    async fn len(&self) -> Result<usize, VectorStoreError>;
+
+    async fn is_empty(&self) -> Result<bool, VectorStoreError> {
+        Ok(self.len().await? == 0)
+    }
+
+    /// Write pending changes to durable storage. Stores without persistence do nothing.
+    async fn persist(&self) -> Result<(), VectorStoreError> {
+        Ok(())
+    }
+}
+
+/// Finds the documents relevant to a text query.
+///
+/// Vector, keyword and hybrid retrievers implement it so any of them can back the
+/// retriever tool given to agents.
+#[async_trait]
+pub trait Retriever: Send + Sync {
+    /// The `top_k` documents most relevant to `query` that match `filter`, best first
+    async fn retrieve(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_similarity_metrics() {
+        let a = [1.0, 0.0];
+        let b = [2.0, 0.0];
+        let c = [0.0, 1.0];
+        assert!((DistanceMetric::Cosine.similarity(&a, &b) - 1.0).abs() < 1e-6);
+        assert_eq!(DistanceMetric::Cosine.similarity(&a, &c), 0.0);
+        assert_eq!(DistanceMetric::Cosine.similarity(&a, &[0.0, 0.0]), 0.0);
+        assert_eq!(DistanceMetric::DotProduct.similarity(&a, &b), 2.0);
+        assert_eq!(DistanceMetric::Euclidean.similarity(&a, &b), -1.0);
+    }
+
+    #[test]
+    fn test_check_dimensions() {
+        let mut dimension = None;
+        check_dimensions(&mut dimension, [[1.0, 2.0].as_slice()]).unwrap();
+        assert_eq!(dimension, Some(2));
+        assert!(matches!(
+            check_dimensions(&mut dimension, [[1.0].as_slice()]),
+            Err(VectorStoreError::DimensionMismatch {
+                expected: 2,
+                actual: 1
+            })
+        ));
+    }
+}
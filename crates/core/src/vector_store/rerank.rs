@@ -0,0 +1,386 @@
+use super::{Filter, Retriever, SearchResult, VectorStoreError};
+use crate::utils::strip_code_fence;
+use async_trait::async_trait;
+use autoagents_llm::chat::{
+    ChatMessage, ChatProvider, ChatRole, MessageType, StructuredOutputFormat,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Passages scored per LLM request unless configured otherwise
+const DEFAULT_BATCH_SIZE: usize = 10;
+/// Passages longer than this are truncated before being sent for scoring
+const DEFAULT_MAX_PASSAGE_CHARS: usize = 2000;
+/// Minimum number of candidates fetched from the inner retriever before reranking
+const DEFAULT_CANDIDATES: usize = 20;
+/// Highest relevance score the LLM can give, scores are divided by it
+const MAX_RELEVANCE: f32 = 10.0;
+
+const RERANK_PROMPT: &str = "You judge how relevant passages are to a search query. \
+Score every passage from 0 (unrelated) to 10 (directly answers the query), \
+referring to each passage by the number in brackets before it.";
+
+/// Reorders search results by their relevance to the query.
+///
+/// Retrieval ranks candidates cheaply; a reranker then spends more effort on the short
+/// list, e.g. by asking an LLM to judge each candidate.
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// Score `results` against `query` and return the best `top_k`, best first
+    async fn rerank(
+        &self,
+        query: &str,
+        results: Vec<SearchResult>,
+        top_k: usize,
+    ) -> Result<Vec<SearchResult>, VectorStoreError>;
+}
+
+#[derive(Debug, Deserialize)]
+struct PassageScore {
+    index: usize,
+    score: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelevanceScores {
+    scores: Vec<PassageScore>,
+}
+
+/// Reranker asking a chat model for relevance scores with structured output.
+///
+/// Candidates are sent in batches so long lists fit the model's context. The new score
+/// of each result is the model's rating divided by 10, in `[0, 1]`; passages the model
+/// doesn't rate score 0.
+pub struct LlmReranker {
+    llm: Arc<dyn ChatProvider>,
+    batch_size: usize,
+    max_passage_chars: usize,
+}
+
+impl LlmReranker {
+    pub fn new(llm: Arc<dyn ChatProvider>) -> Self {
+        Self {
+            llm,
+            batch_size: DEFAULT_BATCH_SIZE,
+            max_passage_chars: DEFAULT_MAX_PASSAGE_CHARS,
+        }
+    }
+
+    /// Score at most `batch_size` passages per request
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Truncate passages to `max_passage_chars` characters before scoring
+    pub fn with_max_passage_chars(mut self, max_passage_chars: usize) -> Self {
+        self.max_passage_chars = max_passage_chars.max(1);
+        self
+    }
+
+    async fn score_batch(
+        &self,
+        query: &str,
+        batch: &[SearchResult],
+    ) -> Result<Vec<f32>, VectorStoreError> {
+        let passages: Vec<String> = batch
+            .iter()
+            .enumerate()
+            .map(|(i, r)| format!("[{i}] {}", self.truncate(&r.document.text)))
+            .collect();
+        let messages = vec![
+            ChatMessage {
+                role: ChatRole::System,
+                message_type: MessageType::Text,
+                content: RERANK_PROMPT.to_string(),
+            },
+            ChatMessage::user()
+                .content(format!(
+                    "Query: {query}\n\nPassages:\n\n{}",
+                    passages.join("\n\n")
+                ))
+                .build(),
+        ];
+        let response = self
+            .llm
+            .chat(&messages, None, Some(Self::output_format()))
+            .await
+            .map_err(|e| VectorStoreError::RerankError(e.to_string()))?;
+        let text = response.text().unwrap_or_default();
+        let rated: RelevanceScores = serde_json::from_str(strip_code_fence(&text))
+            .map_err(|e| VectorStoreError::RerankError(format!("Invalid scores {text:?}: {e}")))?;
+
+        let mut scores = vec![0.0; batch.len()];
+        for rating in rated.scores {
+            if let Some(score) = scores.get_mut(rating.index) {
+                *score = rating.score.clamp(0.0, MAX_RELEVANCE) / MAX_RELEVANCE;
+            }
+        }
+        Ok(scores)
+    }
+
+    fn truncate<'a>(&self, text: &'a str) -> &'a str {
+        match text.char_indices().nth(self.max_passage_chars) {
+            Some((end, _)) => &text[..end],
+            None => text,
+        }
+    }
+
+    fn output_format() -> StructuredOutputFormat {
+        StructuredOutputFormat {
+            name: "RelevanceScores".to_string(),
+            description: Some("Relevance of each passage to the query".to_string()),
+            schema: Some(json!({
+                "type": "object",
+                "properties": {
+                    "scores": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "index": {"type": "integer"},
+                                "score": {"type": "number"}
+                            },
+                            "required": ["index", "score"],
+                            "additionalProperties": false
+                        }
+                    }
+                },
+                "required": ["scores"],
+                "additionalProperties": false
+            })),
+            strict: Some(true),
+        }
+    }
+}
+
+#[async_trait]
+impl Reranker for LlmReranker {
+    async fn rerank(
+        &self,
+        query: &str,
+        results: Vec<SearchResult>,
+        top_k: usize,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let mut scores = Vec::with_capacity(results.len());
+        for batch in results.chunks(self.batch_size) {
+            scores.extend(self.score_batch(query, batch).await?);
+        }
+        Ok(apply_scores(results, scores, top_k))
+    }
+}
+
+/// Replace the scores of `results` and keep the best `top_k`
+fn apply_scores(
+    mut results: Vec<SearchResult>,
+    scores: Vec<f32>,
+    top_k: usize,
+) -> Vec<SearchResult> {
+    for (result, score) in results.iter_mut().zip(scores) {
+        result.score = score;
+    }
+    // Stable sort, so ties keep the retrieval order
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.truncate(top_k);
+    results
+}
+
+/// Reranker scoring candidates with a local ONNX cross-encoder, for fully offline
+/// retrieval. Scores are the model's relevance probabilities in `[0, 1]`.
+#[cfg(all(feature = "liquid_edge", not(target_arch = "wasm32")))]
+pub struct CrossEncoderReranker {
+    encoder: Arc<autoagents_llm::backends::liquid_edge::LiquidEdgeCrossEncoder>,
+}
+
+#[cfg(all(feature = "liquid_edge", not(target_arch = "wasm32")))]
+impl CrossEncoderReranker {
+    pub fn new(
+        encoder: Arc<autoagents_llm::backends::liquid_edge::LiquidEdgeCrossEncoder>,
+    ) -> Self {
+        Self { encoder }
+    }
+}
+
+#[cfg(all(feature = "liquid_edge", not(target_arch = "wasm32")))]
+#[async_trait]
+impl Reranker for CrossEncoderReranker {
+    async fn rerank(
+        &self,
+        query: &str,
+        results: Vec<SearchResult>,
+        top_k: usize,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let passages: Vec<&str> = results.iter().map(|r| r.document.text.as_str()).collect();
+        let scores = self
+            .encoder
+            .score(query, &passages)
+            .await
+            .map_err(|e| VectorStoreError::RerankError(e.to_string()))?;
+        Ok(apply_scores(results, scores, top_k))
+    }
+}
+
+/// Reranker scoring candidates with Cohere's hosted rerank API, using the backend's
+/// rerank model. Scores are Cohere's relevance scores in `[0, 1]`.
+#[cfg(feature = "cohere")]
+pub struct CohereReranker {
+    cohere: Arc<autoagents_llm::backends::cohere::Cohere>,
+}
+
+#[cfg(feature = "cohere")]
+impl CohereReranker {
+    pub fn new(cohere: Arc<autoagents_llm::backends::cohere::Cohere>) -> Self {
+        Self { cohere }
+    }
+}
+
+#[cfg(feature = "cohere")]
+#[async_trait]
+impl Reranker for CohereReranker {
+    async fn rerank(
+        &self,
+        query: &str,
+        results: Vec<SearchResult>,
+        top_k: usize,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let passages: Vec<&str> = results.iter().map(|r| r.document.text.as_str()).collect();
+        let ranked = self
+            .cohere
+            .rerank(query, &passages, None)
+            .await
+            .map_err(|e| VectorStoreError::RerankError(e.to_string()))?;
+        let mut scores = vec![0.0; results.len()];
+        for result in ranked {
+            if let Some(score) = scores.get_mut(result.index) {
+                *score = result.relevance_score;
+            }
+        }
+        Ok(apply_scores(results, scores, top_k))
+    }
+}
+
+/// Retriever fetching candidates from another retriever and reranking them
+#[derive(Clone)]
+pub struct RerankingRetriever {
+    retriever: Arc<dyn Retriever>,
+    reranker: Arc<dyn Reranker>,
+    candidates: usize,
+}
+
+impl RerankingRetriever {
+    pub fn new(retriever: Arc<dyn Retriever>, reranker: Arc<dyn Reranker>) -> Self {
+        Self {
+            retriever,
+            reranker,
+            candidates: DEFAULT_CANDIDATES,
+        }
+    }
+
+    /// Number of candidates fetched for reranking, at least `top_k`
+    pub fn with_candidates(mut self, candidates: usize) -> Self {
+        self.candidates = candidates.max(1);
+        self
+    }
+}
+
+#[async_trait]
+impl Retriever for RerankingRetriever {
+    async fn retrieve(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let candidates = self
+            .retriever
+            .retrieve(query, self.candidates.max(top_k), filter)
+            .await?;
+        if candidates.is_empty() {
+            return Ok(candidates);
+        }
+        self.reranker.rerank(query, candidates, top_k).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::Document;
+    use crate::vector_store::Bm25Index;
+    use autoagents_test_utils::llm::ScriptedLLMProvider;
+
+    fn results(ids: &[&str]) -> Vec<SearchResult> {
+        ids.iter()
+            .map(|id| SearchResult {
+                document: Document::with_id(*id, format!("passage {id}")),
+                score: 1.0,
+            })
+            .collect()
+    }
+
+    fn ids(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.document.id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_rerank_in_batches() {
+        let llm = Arc::new(ScriptedLLMProvider::new([
+            r#"{"scores": [{"index": 0, "score": 2}, {"index": 1, "score": 9}]}"#,
+            "```json\n{\"scores\": [{\"index\": 0, \"score\": 7}, {\"index\": 5, \"score\": 10}]}\n```",
+        ]));
+        let reranker = LlmReranker::new(llm.clone()).with_batch_size(2);
+
+        let reranked = reranker
+            .rerank("query", results(&["a", "b", "c"]), 2)
+            .await
+            .unwrap();
+        assert_eq!(ids(&reranked), vec!["b", "c"]);
+        assert_eq!(reranked[0].score, 0.9);
+
+        let requests = llm.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[1][1].content,
+            "Query: query\n\nPassages:\n\n[0] passage c"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_response_is_an_error() {
+        let reranker = LlmReranker::new(Arc::new(ScriptedLLMProvider::new(["not json"])));
+        assert!(matches!(
+            reranker.rerank("query", results(&["a"]), 1).await,
+            Err(VectorStoreError::RerankError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reranking_retriever() {
+        let index = Bm25Index::new();
+        index.add(vec![
+            Document::with_id("short", "rust agents"),
+            Document::with_id("long", "rust agents are built with tools and memory"),
+        ]);
+        let llm = Arc::new(ScriptedLLMProvider::new([
+            r#"{"scores": [{"index": 0, "score": 1}, {"index": 1, "score": 8}]}"#,
+        ]));
+        let retriever = RerankingRetriever::new(
+            Arc::new(index),
+            Arc::new(LlmReranker::new(llm.clone()).with_max_passage_chars(4)),
+        );
+
+        let results = retriever.retrieve("rust agents", 1, None).await.unwrap();
+        assert_eq!(ids(&results), vec!["long"]);
+        assert!(llm.requests()[0][1]
+            .content
+            .ends_with("[0] rust\n\n[1] rust"));
+
+        assert!(retriever
+            .retrieve("python", 1, None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(llm.requests().len(), 1);
+    }
+}
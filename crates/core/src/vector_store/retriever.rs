@@ -0,0 +1,410 @@
+use super::{
+    Embedder, Filter, Reranker, RerankingRetriever, Retriever, SearchResult, VectorRetriever,
+    VectorStore, VectorStoreError,
+};
+use crate::agent::task::{Attachment, Task};
+use crate::tool::{block_on, ToolCallError, ToolRuntime, ToolT};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+const DEFAULT_NAME: &str = "retrieve_documents";
+const DEFAULT_DESCRIPTION: &str = "Search the knowledge base for passages relevant to a query. \
+Returns the most relevant chunks with their source and a relevance score.";
+const DEFAULT_TOP_K: usize = 4;
+/// Upper bound on the number of chunks the LLM can ask for
+const MAX_TOP_K: usize = 50;
+/// Name of the attachment added by [`RetrieverTool::inject`]
+const CONTEXT_ATTACHMENT: &str = "retrieved_context";
+
+#[derive(Debug, Deserialize)]
+struct RetrieverArgs {
+    query: String,
+    #[serde(default)]
+    top_k: Option<usize>,
+    #[serde(default)]
+    filter: Option<Map<String, Value>>,
+}
+
+/// Tool that lets an agent search a [`VectorStore`] or any other [`Retriever`].
+///
+/// The LLM calls it with a `query`, an optional `top_k` and an optional `filter` object
+/// whose fields must equal the chunk metadata. The tool is cheap to clone, so an agent can
+/// keep it in a field and list it with `#[agent(tools = [self.retriever.clone()])]` or
+/// share it through [`shared_tools_to_boxes`](crate::tool::shared_tools_to_boxes).
+///
+/// To give the agent relevant context without a tool call, call
+/// [`RetrieverTool::inject`] from the `on_prepare_task` hook instead.
+///
+/// As a tool it needs a multi-threaded Tokio runtime.
+#[derive(Clone)]
+pub struct RetrieverTool {
+    retriever: Arc<dyn Retriever>,
+    name: &'static str,
+    description: &'static str,
+    top_k: usize,
+    min_score: Option<f32>,
+    filter: Option<Filter>,
+}
+
+impl std::fmt::Debug for RetrieverTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetrieverTool")
+            .field("name", &self.name)
+            .field("top_k", &self.top_k)
+            .field("min_score", &self.min_score)
+            .field("filter", &self.filter)
+            .finish()
+    }
+}
+
+impl RetrieverTool {
+    /// Search a vector store with query embeddings
+    pub fn new(store: Arc<dyn VectorStore>, embedder: Embedder) -> Self {
+        Self::from_retriever(Arc::new(VectorRetriever::new(store, embedder)))
+    }
+
+    /// Search with a custom retriever, e.g. a [`HybridRetriever`](super::HybridRetriever)
+    pub fn from_retriever(retriever: Arc<dyn Retriever>) -> Self {
+        Self {
+            retriever,
+            name: DEFAULT_NAME,
+            description: DEFAULT_DESCRIPTION,
+            top_k: DEFAULT_TOP_K,
+            min_score: None,
+            filter: None,
+        }
+    }
+
+    /// Tool name shown to the LLM, useful when an agent has several knowledge bases
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_description(mut self, description: &'static str) -> Self {
+        self.description = description;
+        self
+    }
+
+    /// Number of chunks returned when the LLM doesn't ask for a specific number
+    pub fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k.max(1);
+        self
+    }
+
+    /// Rerank the retrieved candidates before returning them.
+    ///
+    /// Each agent can use its own reranker; `with_min_score` then applies to the
+    /// reranker's scores.
+    pub fn with_reranker(mut self, reranker: Arc<dyn Reranker>) -> Self {
+        self.retriever = Arc::new(RerankingRetriever::new(self.retriever, reranker));
+        self
+    }
+
+    /// Drop chunks scoring below `min_score`
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
+    /// Filter applied to every search on top of the LLM's filter, e.g. to restrict a
+    /// tenant's agent to its own documents
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Search the store for chunks relevant to `query`
+    pub async fn retrieve(
+        &self,
+        query: &str,
+        top_k: Option<usize>,
+        filter: Option<Filter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let filter = match (self.filter.clone(), filter) {
+            (Some(base), Some(filter)) => Some(base.and(filter)),
+            (base, filter) => base.or(filter),
+        };
+        let top_k = top_k.unwrap_or(self.top_k).clamp(1, MAX_TOP_K);
+        let mut results = self
+            .retriever
+            .retrieve(query, top_k, filter.as_ref())
+            .await?;
+        if let Some(min_score) = self.min_score {
+            results.retain(|r| r.score >= min_score);
+        }
+        Ok(results)
+    }
+
+    /// Retrieve chunks for the task prompt and attach them to the task as text.
+    ///
+    /// Returns the number of chunks attached.
+    pub async fn inject(&self, task: &mut Task) -> Result<usize, VectorStoreError> {
+        let results = self.retrieve(&task.prompt, None, None).await?;
+        if !results.is_empty() {
+            task.attachments
+                .push(Attachment::text(CONTEXT_ATTACHMENT, Self::format(&results)));
+        }
+        Ok(results.len())
+    }
+
+    /// Render results as numbered passages with their source
+    pub fn format(results: &[SearchResult]) -> String {
+        results
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let source = Self::source(r).unwrap_or(&r.document.id);
+                format!(
+                    "[{}] {} (score {:.2})\n{}",
+                    i + 1,
+                    source,
+                    r.score,
+                    r.document.text
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn source(result: &SearchResult) -> Option<&str> {
+        result
+            .document
+            .metadata
+            .get("source")
+            .and_then(Value::as_str)
+    }
+
+    fn to_value(results: &[SearchResult]) -> Value {
+        let results: Vec<Value> = results
+            .iter()
+            .map(|r| {
+                json!({
+                    "id": r.document.id,
+                    "text": r.document.text,
+                    "score": r.score,
+                    "source": Self::source(r),
+                    "metadata": r.document.metadata,
+                })
+            })
+            .collect();
+        json!({ "results": results })
+    }
+}
+
+impl ToolRuntime for RetrieverTool {
+    fn execute(&self, args: Value) -> Result<Value, ToolCallError> {
+        let args: RetrieverArgs = serde_json::from_value(args)?;
+        let filter = args.filter.filter(|f| !f.is_empty()).map(|fields| {
+            Filter::And(
+                fields
+                    .into_iter()
+                    .map(|(field, value)| Filter::Eq(field, value))
+                    .collect(),
+            )
+        });
+        let results = block_on(self.retrieve(&args.query, args.top_k, filter))?
+            .map_err(|e| ToolCallError::RuntimeError(Box::new(e)))?;
+        Ok(Self::to_value(&results))
+    }
+}
+
+impl ToolT for RetrieverTool {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn args_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "What to search for, phrased as a question or keywords"
+                },
+                "top_k": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": MAX_TOP_K,
+                    "description": format!("Number of chunks to return, {} by default", self.top_k)
+                },
+                "filter": {
+                    "type": "object",
+                    "description": "Only return chunks whose metadata fields equal these values",
+                    "additionalProperties": true
+                }
+            },
+            "required": ["query"]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::memory::SlidingWindowMemory;
+    use crate::agent::prebuilt::executor::ReActAgent;
+    use crate::agent::{AgentDeriveT, AgentHooks, BaseAgent, Context, DirectAgent};
+    use crate::document::Document;
+    use crate::tool::shared_tools_to_boxes;
+    use crate::vector_store::{Bm25Index, HybridRetriever, InMemoryVectorStore};
+    use async_trait::async_trait;
+    use autoagents_llm::chat::ChatRole;
+    use autoagents_test_utils::llm::MockEmbeddingProvider;
+    use autoagents_test_utils::llm::ScriptedLLMProvider;
+    use tokio::sync::mpsc::channel;
+
+    async fn retriever() -> RetrieverTool {
+        let embedder = Embedder::new(Arc::new(MockEmbeddingProvider::new(64)));
+        let store = Arc::new(InMemoryVectorStore::new());
+        let documents = vec![
+            Document::with_id("a", "Agents call tools to act on the world")
+                .with_metadata("source", "agents.md")
+                .with_metadata("lang", "en"),
+            Document::with_id("b", "Les agents utilisent des outils")
+                .with_metadata("source", "agents_fr.md")
+                .with_metadata("lang", "fr"),
+            Document::with_id("c", "Tea should steep for three minutes")
+                .with_metadata("source", "tea.md")
+                .with_metadata("lang", "en"),
+        ];
+        embedder.index(store.as_ref(), documents).await.unwrap();
+        RetrieverTool::new(store, embedder).with_top_k(1)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_returns_chunks_with_sources() {
+        let tool = retriever().await;
+        let output = tool
+            .execute(json!({"query": "how do agents use tools"}))
+            .unwrap();
+        let results = output["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["id"], "a");
+        assert_eq!(results[0]["source"], "agents.md");
+        assert!(results[0]["score"].as_f64().unwrap() > 0.0);
+
+        let output = tool
+            .execute(json!({"query": "agents tools", "top_k": 5, "filter": {"lang": "fr"}}))
+            .unwrap();
+        assert_eq!(output["results"].as_array().unwrap().len(), 1);
+        assert_eq!(output["results"][0]["id"], "b");
+
+        assert!(tool.execute(json!({"top_k": 2})).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_hybrid_retriever_backs_tool() {
+        let store = Arc::new(InMemoryVectorStore::new());
+        let embedder = Embedder::new(Arc::new(MockEmbeddingProvider::new(64)));
+        let hybrid = HybridRetriever::new(store, embedder, Arc::new(Bm25Index::new()));
+        hybrid
+            .index(vec![
+                Document::with_id("code", "Error E4021 is returned by the billing service"),
+                Document::with_id("refund", "How to refund a customer payment"),
+            ])
+            .await
+            .unwrap();
+
+        let tool = RetrieverTool::from_retriever(Arc::new(hybrid)).with_top_k(1);
+        let output = tool.execute(json!({"query": "E4021"})).unwrap();
+        assert_eq!(output["results"][0]["id"], "code");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_shared_tool_on_multi_thread_runtime() {
+        let tool: Arc<dyn ToolT> = Arc::new(retriever().await.with_name("kb"));
+        let tools = shared_tools_to_boxes(&[tool]);
+        assert_eq!(tools[0].name(), "kb");
+        let output = tools[0].execute(json!({"query": "tea minutes"})).unwrap();
+        assert_eq!(output["results"][0]["id"], "c");
+    }
+
+    #[tokio::test]
+    async fn test_inject_attaches_context() {
+        let tool = retriever().await.with_filter(Filter::eq("lang", "en"));
+        let mut task = Task::new("how long should tea steep");
+        assert_eq!(tool.inject(&mut task).await.unwrap(), 1);
+        match &task.attachments[0] {
+            Attachment::Text { name, content } => {
+                assert_eq!(name, CONTEXT_ATTACHMENT);
+                assert!(content.starts_with("[1] tea.md (score "));
+                assert!(content.ends_with("Tea should steep for three minutes"));
+            }
+            other => panic!("unexpected attachment {other:?}"),
+        }
+
+        let strict = retriever().await.with_min_score(1.1);
+        let mut task = Task::new("tea");
+        assert_eq!(strict.inject(&mut task).await.unwrap(), 0);
+        assert!(task.attachments.is_empty());
+    }
+
+    #[derive(Debug)]
+    struct KnowledgeAgent {
+        retriever: RetrieverTool,
+    }
+
+    impl AgentDeriveT for KnowledgeAgent {
+        type Output = String;
+
+        fn description(&self) -> &'static str {
+            "Answers questions from the knowledge base"
+        }
+
+        fn output_schema(&self) -> Option<Value> {
+            None
+        }
+
+        fn name(&self) -> &'static str {
+            "knowledge"
+        }
+
+        fn tools(&self) -> Vec<Box<dyn ToolT>> {
+            vec![Box::new(self.retriever.clone())]
+        }
+    }
+
+    #[async_trait]
+    impl AgentHooks for KnowledgeAgent {
+        async fn on_prepare_task(&self, task: &mut Task, _ctx: &Context) {
+            self.retriever.inject(task).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prepare_task_hook_injects_context() {
+        let llm = Arc::new(ScriptedLLMProvider::new(["Three minutes"]));
+        let (tx, _rx) = channel(10);
+        let agent = BaseAgent::<_, DirectAgent>::new(
+            ReActAgent::new(KnowledgeAgent {
+                retriever: retriever().await,
+            }),
+            llm.clone(),
+            Some(Box::new(SlidingWindowMemory::new(10))),
+            tx,
+            false,
+        )
+        .await
+        .unwrap();
+
+        agent
+            .run(Task::new("how long should tea steep"))
+            .await
+            .unwrap();
+
+        let request = &llm.requests()[0];
+        let user: Vec<&str> = request
+            .iter()
+            .filter(|m| m.role == ChatRole::User)
+            .map(|m| m.content.as_str())
+            .collect();
+        assert!(user[0].starts_with("Attached file: retrieved_context\n\n[1] tea.md"));
+        assert_eq!(user[1], "how long should tea steep");
+    }
+}
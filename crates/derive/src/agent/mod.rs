@@ -2,8 +2,8 @@ use proc_macro::TokenStream;
 use quote::quote;
 use strum::{Display, EnumString};
 use syn::{
-    bracketed, parse::Parse, parse_macro_input, punctuated::Punctuated, Ident, ItemStruct, LitStr,
-    Token, Type,
+    bracketed, parse::Parse, parse_macro_input, punctuated::Punctuated, Expr, Ident, ItemStruct,
+    LitStr, Token, Type,
 };
 
 pub(crate) mod output;
@@ -11,16 +11,27 @@ pub(crate) mod output;
 pub(crate) struct AgentAttributes {
     pub(crate) name: LitStr,
     pub(crate) description: LitStr,
-    pub(crate) tools: Option<Vec<Ident>>,
+    pub(crate) instructions: Option<AgentInstructions>,
+    pub(crate) tools: Option<Vec<Expr>>,
     pub(crate) output: Option<Type>,
 }
 
+/// Instructions given inline or loaded from a file at build time
+pub(crate) enum AgentInstructions {
+    Inline(LitStr),
+    File(LitStr),
+}
+
 #[derive(EnumString, Display)]
 pub(crate) enum AgentAttributeKeys {
     #[strum(serialize = "name")]
     Name,
     #[strum(serialize = "description")]
     Description,
+    #[strum(serialize = "instructions")]
+    Instructions,
+    #[strum(serialize = "instructions_file")]
+    InstructionsFile,
     #[strum(serialize = "tools")]
     Tools,
     #[strum(serialize = "output")]
@@ -33,6 +44,8 @@ impl From<Ident> for AgentAttributeKeys {
         match value.to_string().as_str() {
             "name" => Self::Name,
             "description" => Self::Description,
+            "instructions" => Self::Instructions,
+            "instructions_file" => Self::InstructionsFile,
             "tools" => Self::Tools,
             "output" => Self::Output,
             other => Self::Unknown(other.to_string()),
@@ -44,6 +57,7 @@ impl Parse for AgentAttributes {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut name = None;
         let mut description = None;
+        let mut instructions = None;
         let mut tools = None;
         let mut output = None;
 
@@ -61,16 +75,29 @@ impl Parse for AgentAttributes {
                 AgentAttributeKeys::Description => {
                     description = Some(input.parse::<LitStr>()?);
                 }
+                AgentAttributeKeys::Instructions | AgentAttributeKeys::InstructionsFile => {
+                    if instructions.is_some() {
+                        return Err(syn::Error::new(
+                            key_span,
+                            "Only one of `instructions` and `instructions_file` can be set",
+                        ));
+                    }
+                    let value = input.parse::<LitStr>()?;
+                    instructions = Some(match agent_attr_key {
+                        AgentAttributeKeys::Instructions => AgentInstructions::Inline(value),
+                        _ => AgentInstructions::File(value),
+                    });
+                }
                 AgentAttributeKeys::Output => {
                     output = Some(input.parse::<Type>()?);
                 }
                 AgentAttributeKeys::Tools => {
-                    // Parse a bracketed list of identifiers
+                    // Parse a bracketed list of tool types or expressions
                     let content;
                     bracketed!(content in input);
-                    let punctuated_idents: Punctuated<Ident, Token![,]> =
-                        content.parse_terminated(Ident::parse, Token![,])?;
-                    tools = Some(punctuated_idents.into_iter().collect::<Vec<Ident>>());
+                    let punctuated_tools: Punctuated<Expr, Token![,]> =
+                        content.parse_terminated(Expr::parse, Token![,])?;
+                    tools = Some(punctuated_tools.into_iter().collect::<Vec<Expr>>());
                 }
                 AgentAttributeKeys::Unknown(other) => {
                     return Err(syn::Error::new(
@@ -96,6 +123,7 @@ impl Parse for AgentAttributes {
                     format!("Missing attribute: {}", AgentAttributeKeys::Description),
                 )
             })?,
+            instructions,
             output,
             tools,
         })
@@ -112,9 +140,40 @@ impl AgentParser {
         let struct_name = &input_struct.ident;
         let agent_name_literal = agent_attrs.name;
         let agent_description = agent_attrs.description;
-        let tool_idents = agent_attrs.tools.unwrap_or_default();
+        // A bare identifier is a unit tool struct, anything else an expression evaluated in
+        // `tools(&self)`, e.g. `self.retriever.clone()`
+        let tool_values = agent_attrs
+            .tools
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tool| match &tool {
+                Expr::Path(path) if path.path.get_ident().is_some() => quote! { #tool{} },
+                _ => quote! { #tool },
+            })
+            .collect::<Vec<_>>();
         let output_type = agent_attrs.output;
 
+        let instructions_impl = match &agent_attrs.instructions {
+            Some(AgentInstructions::Inline(instructions)) => quote! {
+                fn instructions(&self) -> &'static str {
+                    #instructions
+                }
+            },
+            Some(AgentInstructions::File(path)) => {
+                let resolved = match resolve_instructions_file(path) {
+                    Ok(resolved) => resolved,
+                    Err(err) => return err.to_compile_error().into(),
+                };
+                // include_str! makes cargo rebuild the agent when the file changes
+                quote! {
+                    fn instructions(&self) -> &'static str {
+                        include_str!(#resolved)
+                    }
+                }
+            }
+            None => quote! {},
+        };
+
         let quoted_output_type = match &output_type {
             Some(output_ty) => quote! { #output_ty },
             None => quote! { String },
@@ -153,10 +212,12 @@ impl AgentParser {
                     #agent_description
                 }
 
+                #instructions_impl
+
                 fn tools(&self) -> Vec<Box<dyn ToolT>> {
                     vec![
                         #(
-                            Box::new(#tool_idents{}) as Box<dyn ToolT>
+                            Box::new(#tool_values) as Box<dyn ToolT>
                         ),*
                     ]
                 }
@@ -171,3 +232,23 @@ impl AgentParser {
         expanded.into()
     }
 }
+
+/// Resolve an `instructions_file` path relative to the crate root (`CARGO_MANIFEST_DIR`)
+/// and check that it exists, so a missing file is reported on the attribute itself.
+fn resolve_instructions_file(path: &LitStr) -> syn::Result<String> {
+    let relative = std::path::PathBuf::from(path.value());
+    let full = if relative.is_absolute() {
+        relative
+    } else {
+        let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+            .map_err(|_| syn::Error::new(path.span(), "CARGO_MANIFEST_DIR is not set"))?;
+        std::path::Path::new(&manifest_dir).join(relative)
+    };
+    if !full.is_file() {
+        return Err(syn::Error::new(
+            path.span(),
+            format!("Instructions file not found: {}", full.display()),
+        ));
+    }
+    Ok(full.to_string_lossy().into_owned())
+}
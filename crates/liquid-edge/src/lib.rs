@@ -6,6 +6,8 @@
 pub mod device;
 pub mod error;
 pub mod model;
+#[cfg(all(feature = "pipelines", not(target_arch = "wasm32")))]
+pub mod pipeline;
 pub mod runtime;
 
 // Re-exports
@@ -15,4 +17,6 @@ pub use device::{cpu, cpu_with_threads, cuda, cuda_default, Device};
 pub use device::webgpu;
 pub use error::{EdgeError, EdgeResult};
 pub use model::Model;
+#[cfg(all(feature = "pipelines", not(target_arch = "wasm32")))]
+pub use pipeline::{CrossEncoderPipeline, EmbeddingPipeline, Pooling};
 pub use runtime::{InferenceInput, InferenceOutput, InferenceRuntime, RuntimeBackend};
@@ -0,0 +1,125 @@
+//! Query-passage relevance scores from an ONNX cross-encoder
+
+use super::{
+    load, output_f32, set_max_length, tensor_names, EncodedBatch, DEFAULT_BATCH_SIZE,
+    DEFAULT_MAX_LENGTH,
+};
+use crate::error::{EdgeError, EdgeResult};
+use crate::runtime::InferenceRuntime;
+use std::path::Path;
+use tokenizers::Tokenizer;
+
+/// Output holding the classification logits
+const LOGITS_OUTPUT: &str = "logits";
+
+/// Pipeline scoring query-passage pairs with a cross-encoder such as
+/// `cross-encoder/ms-marco-MiniLM-L-6-v2` or `BAAI/bge-reranker-base`.
+///
+/// Unlike embeddings, the query and passage are read together by the model, which is
+/// slower but much more accurate, so cross-encoders are used to rerank a short list of
+/// retrieved candidates. Scores are in `[0, 1]`, higher is more relevant.
+pub struct CrossEncoderPipeline {
+    runtime: InferenceRuntime,
+    tokenizer: Tokenizer,
+    input_names: Vec<String>,
+    batch_size: usize,
+}
+
+impl CrossEncoderPipeline {
+    /// Load `model.onnx` and `tokenizer.json` from a model directory
+    pub async fn from_directory<P: AsRef<Path>>(path: P) -> EdgeResult<Self> {
+        let (runtime, tokenizer) = load(path.as_ref()).await?;
+        Self::new(runtime, tokenizer)
+    }
+
+    /// Create a pipeline from a loaded runtime and tokenizer
+    pub fn new(runtime: InferenceRuntime, mut tokenizer: Tokenizer) -> EdgeResult<Self> {
+        set_max_length(&mut tokenizer, DEFAULT_MAX_LENGTH)?;
+        let input_names = tensor_names(&runtime, "inputs");
+        Ok(Self {
+            runtime,
+            tokenizer,
+            input_names,
+            batch_size: DEFAULT_BATCH_SIZE,
+        })
+    }
+
+    /// Number of pairs run through the model at once
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Truncate query-passage pairs to `max_length` tokens, 512 by default
+    pub fn with_max_length(mut self, max_length: usize) -> EdgeResult<Self> {
+        set_max_length(&mut self.tokenizer, max_length)?;
+        Ok(self)
+    }
+
+    /// Relevance of each passage to the query, in the order of the passages
+    pub fn score(&mut self, query: &str, passages: &[&str]) -> EdgeResult<Vec<f32>> {
+        let mut scores = Vec::with_capacity(passages.len());
+        for batch in passages.chunks(self.batch_size) {
+            let pairs: Vec<(&str, &str)> = batch.iter().map(|p| (query, *p)).collect();
+            let encodings = self
+                .tokenizer
+                .encode_batch(pairs, true)
+                .map_err(|e| EdgeError::tokenizer(format!("Tokenization failed: {e}")))?;
+            let encoded = EncodedBatch::new(&encodings);
+            let output = self.runtime.infer(encoded.to_input(&self.input_names)?)?;
+            let (logits, shape) = output_f32(&output, LOGITS_OUTPUT)?;
+            scores.extend(relevance(&logits, &shape)?);
+        }
+        Ok(scores)
+    }
+}
+
+/// Turn `[batch, labels]` logits into relevance probabilities.
+///
+/// Models with a single label get its sigmoid; models with several labels get the
+/// softmax probability of the last one, the "relevant" class.
+fn relevance(logits: &[f32], shape: &[usize]) -> EdgeResult<Vec<f32>> {
+    match *shape {
+        [_, 1] | [_] => Ok(logits.iter().map(|x| 1.0 / (1.0 + (-x).exp())).collect()),
+        [_, labels] if labels > 1 => Ok(logits
+            .chunks(labels)
+            .map(|row| {
+                let max = row.iter().copied().fold(f32::MIN, f32::max);
+                let sum: f32 = row.iter().map(|x| (x - max).exp()).sum();
+                (row[labels - 1] - max).exp() / sum
+            })
+            .collect()),
+        _ => Err(EdgeError::inference(format!(
+            "Unexpected cross-encoder output shape {shape:?}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::tests::tokenizer;
+
+    #[test]
+    fn test_relevance_from_logits() {
+        let scores = relevance(&[0.0, 2.0, -2.0], &[3, 1]).unwrap();
+        assert_eq!(scores[0], 0.5);
+        assert!(scores[1] > 0.88 && scores[2] < 0.12);
+
+        let scores = relevance(&[0.0, 0.0, 1.0, 3.0], &[2, 2]).unwrap();
+        assert_eq!(scores[0], 0.5);
+        assert!(scores[1] > 0.88);
+
+        assert!(relevance(&[0.0; 8], &[2, 2, 2]).is_err());
+    }
+
+    #[test]
+    fn test_pairs_are_encoded_as_two_segments() {
+        let encodings = tokenizer()
+            .encode_batch(vec![("rust", "agents tools")], false)
+            .unwrap();
+        let encoded = EncodedBatch::new(&encodings);
+        assert_eq!(encoded.input_ids, vec![2, 3, 4]);
+        assert_eq!(encoded.token_type_ids, vec![0, 1, 1]);
+    }
+}
@@ -0,0 +1,204 @@
+//! Sentence embeddings from an ONNX encoder
+
+use super::{
+    load, output_f32, set_max_length, tensor_names, EncodedBatch, DEFAULT_BATCH_SIZE,
+    DEFAULT_MAX_LENGTH,
+};
+use crate::error::{EdgeError, EdgeResult};
+use crate::runtime::InferenceRuntime;
+use std::path::Path;
+use tokenizers::Tokenizer;
+
+/// Outputs holding token embeddings, in order of preference
+const TOKEN_OUTPUTS: [&str; 2] = ["last_hidden_state", "token_embeddings"];
+/// Outputs holding already pooled embeddings, in order of preference
+const POOLED_OUTPUTS: [&str; 2] = ["sentence_embedding", "pooler_output"];
+
+/// How token embeddings are combined into a single sentence embedding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Pooling {
+    /// Average of the token embeddings, ignoring padding. Used by most
+    /// sentence-transformers models.
+    #[default]
+    Mean,
+    /// Embedding of the first (`[CLS]`) token, used by BGE and similar models
+    Cls,
+}
+
+/// Pipeline turning texts into sentence embeddings with an ONNX encoder.
+///
+/// Texts are tokenized, run through the model in batches, pooled and, unless disabled,
+/// L2-normalized so cosine similarity is a dot product.
+pub struct EmbeddingPipeline {
+    runtime: InferenceRuntime,
+    tokenizer: Tokenizer,
+    input_names: Vec<String>,
+    output: String,
+    pooling: Pooling,
+    normalize: bool,
+    batch_size: usize,
+}
+
+impl EmbeddingPipeline {
+    /// Load `model.onnx` and `tokenizer.json` from a model directory
+    pub async fn from_directory<P: AsRef<Path>>(path: P) -> EdgeResult<Self> {
+        let (runtime, tokenizer) = load(path.as_ref()).await?;
+        Self::new(runtime, tokenizer)
+    }
+
+    /// Create a pipeline from a loaded runtime and tokenizer
+    pub fn new(runtime: InferenceRuntime, mut tokenizer: Tokenizer) -> EdgeResult<Self> {
+        set_max_length(&mut tokenizer, DEFAULT_MAX_LENGTH)?;
+        let input_names = tensor_names(&runtime, "inputs");
+        let outputs = tensor_names(&runtime, "outputs");
+        let output = TOKEN_OUTPUTS
+            .iter()
+            .chain(POOLED_OUTPUTS.iter())
+            .find(|name| outputs.iter().any(|o| o == *name))
+            .map(|name| name.to_string())
+            .or_else(|| outputs.first().cloned())
+            .ok_or_else(|| EdgeError::model("Embedding model has no outputs"))?;
+
+        Ok(Self {
+            runtime,
+            tokenizer,
+            input_names,
+            output,
+            pooling: Pooling::default(),
+            normalize: true,
+            batch_size: DEFAULT_BATCH_SIZE,
+        })
+    }
+
+    pub fn with_pooling(mut self, pooling: Pooling) -> Self {
+        self.pooling = pooling;
+        self
+    }
+
+    /// Whether embeddings are L2-normalized, enabled by default
+    pub fn with_normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Number of texts run through the model at once
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Truncate texts to `max_length` tokens, 512 by default
+    pub fn with_max_length(mut self, max_length: usize) -> EdgeResult<Self> {
+        set_max_length(&mut self.tokenizer, max_length)?;
+        Ok(self)
+    }
+
+    /// Read embeddings from the named model output instead of the detected one
+    pub fn with_output(mut self, output: impl Into<String>) -> Self {
+        self.output = output.into();
+        self
+    }
+
+    /// Embed texts, returning one embedding per text in the same order
+    pub fn embed(&mut self, texts: &[&str]) -> EdgeResult<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            let encodings = self
+                .tokenizer
+                .encode_batch(batch.to_vec(), true)
+                .map_err(|e| EdgeError::tokenizer(format!("Tokenization failed: {e}")))?;
+            let encoded = EncodedBatch::new(&encodings);
+            let output = self.runtime.infer(encoded.to_input(&self.input_names)?)?;
+            let (values, shape) = output_f32(&output, &self.output)?;
+            let mut pooled = pool(&values, &shape, &encoded.attention_mask, self.pooling)?;
+            if self.normalize {
+                pooled.iter_mut().for_each(|e| normalize(e));
+            }
+            embeddings.extend(pooled);
+        }
+        Ok(embeddings)
+    }
+}
+
+/// Pool a `[batch, seq_len, dim]` output into one embedding per text. A `[batch, dim]`
+/// output is already pooled and returned as is.
+fn pool(
+    values: &[f32],
+    shape: &[usize],
+    attention_mask: &[i64],
+    pooling: Pooling,
+) -> EdgeResult<Vec<Vec<f32>>> {
+    match *shape {
+        [_, dim] if dim > 0 => Ok(values.chunks(dim).map(<[f32]>::to_vec).collect()),
+        [batch, seq_len, dim] if attention_mask.len() == batch * seq_len => Ok((0..batch)
+            .map(|b| {
+                let tokens = &values[b * seq_len * dim..(b + 1) * seq_len * dim];
+                let mask = &attention_mask[b * seq_len..(b + 1) * seq_len];
+                match pooling {
+                    Pooling::Mean => mean_pool(tokens, mask, dim),
+                    Pooling::Cls => tokens[..dim].to_vec(),
+                }
+            })
+            .collect()),
+        _ => Err(EdgeError::inference(format!(
+            "Unexpected embedding output shape {shape:?}"
+        ))),
+    }
+}
+
+/// Average of the token embeddings whose attention mask is set
+fn mean_pool(tokens: &[f32], mask: &[i64], dim: usize) -> Vec<f32> {
+    let mut sum = vec![0.0; dim];
+    let mut count = 0.0;
+    for (token, _) in tokens.chunks(dim).zip(mask).filter(|(_, m)| **m != 0) {
+        sum.iter_mut().zip(token).for_each(|(s, t)| *s += t);
+        count += 1.0;
+    }
+    if count > 0.0 {
+        sum.iter_mut().for_each(|s| *s /= count);
+    }
+    sum
+}
+
+fn normalize(embedding: &mut [f32]) {
+    let norm = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        embedding.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mean_pool_ignores_padding() {
+        // Two texts, three tokens, dimension 2; the second text has one padding token
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 2.0, 2.0, 4.0, 0.0, 9.0, 9.0];
+        let mask = [1, 1, 1, 1, 1, 0];
+        let pooled = pool(&values, &[2, 3, 2], &mask, Pooling::Mean).unwrap();
+        assert_eq!(pooled, vec![vec![3.0, 4.0], vec![3.0, 1.0]]);
+
+        let pooled = pool(&values, &[2, 3, 2], &mask, Pooling::Cls).unwrap();
+        assert_eq!(pooled, vec![vec![1.0, 2.0], vec![2.0, 2.0]]);
+    }
+
+    #[test]
+    fn test_pooled_output_and_bad_shapes() {
+        let pooled = pool(&[1.0, 0.0, 0.0, 1.0], &[2, 2], &[], Pooling::Mean).unwrap();
+        assert_eq!(pooled, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+        assert!(pool(&[1.0; 4], &[4], &[], Pooling::Mean).is_err());
+        assert!(pool(&[1.0; 8], &[2, 2, 2], &[1], Pooling::Mean).is_err());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut embedding = vec![3.0, 4.0];
+        normalize(&mut embedding);
+        assert_eq!(embedding, vec![0.6, 0.8]);
+
+        let mut zero = vec![0.0, 0.0];
+        normalize(&mut zero);
+        assert_eq!(zero, vec![0.0, 0.0]);
+    }
+}
@@ -0,0 +1,200 @@
+//! Encoder pipelines for retrieval
+//!
+//! Pipelines combine a HuggingFace tokenizer with an ONNX encoder model run by the
+//! [`InferenceRuntime`]: [`EmbeddingPipeline`] turns texts into sentence embeddings and
+//! [`CrossEncoderPipeline`] scores how relevant passages are to a query. Both expect a
+//! model directory containing `model.onnx` and `tokenizer.json`, as exported by
+//! `optimum-cli export onnx --task feature-extraction` or `--task text-classification`.
+
+use crate::error::{EdgeError, EdgeResult};
+use crate::runtime::{InferenceInput, InferenceRuntime};
+use serde_json::Value;
+use std::path::Path;
+use tokenizers::{Encoding, Tokenizer, TruncationParams};
+
+mod cross_encoder;
+mod embedding;
+
+pub use cross_encoder::CrossEncoderPipeline;
+pub use embedding::{EmbeddingPipeline, Pooling};
+
+/// Longest input, in tokens, unless configured otherwise
+const DEFAULT_MAX_LENGTH: usize = 512;
+/// Texts run through the model at once unless configured otherwise
+const DEFAULT_BATCH_SIZE: usize = 16;
+
+/// Load the ONNX model and tokenizer of a model directory
+async fn load(path: &Path) -> EdgeResult<(InferenceRuntime, Tokenizer)> {
+    let model = crate::runtime::onnx::OnnxModel::from_directory(path)?;
+    let runtime = InferenceRuntime::from_model(Box::new(model)).await?;
+    let tokenizer = Tokenizer::from_file(path.join("tokenizer.json"))
+        .map_err(|e| EdgeError::tokenizer(format!("Failed to load tokenizer: {e}")))?;
+    Ok((runtime, tokenizer))
+}
+
+/// Truncate encodings to `max_length` tokens
+fn set_max_length(tokenizer: &mut Tokenizer, max_length: usize) -> EdgeResult<()> {
+    tokenizer
+        .with_truncation(Some(TruncationParams {
+            max_length,
+            ..Default::default()
+        }))
+        .map_err(|e| EdgeError::tokenizer(format!("Invalid max length {max_length}: {e}")))?;
+    Ok(())
+}
+
+/// Names of a runtime's inputs or outputs, from its model info
+fn tensor_names(runtime: &InferenceRuntime, kind: &str) -> Vec<String> {
+    runtime
+        .model_info()
+        .get(kind)
+        .and_then(Value::as_array)
+        .map(|tensors| {
+            tensors
+                .iter()
+                .filter_map(|t| t.get("name").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Encodings padded to the longest one, flattened row-major into `[batch, seq_len]`
+#[derive(Debug, Clone, PartialEq)]
+struct EncodedBatch {
+    input_ids: Vec<i64>,
+    attention_mask: Vec<i64>,
+    token_type_ids: Vec<i64>,
+    batch: usize,
+    seq_len: usize,
+}
+
+impl EncodedBatch {
+    fn new(encodings: &[Encoding]) -> Self {
+        let batch = encodings.len();
+        let seq_len = encodings.iter().map(Encoding::len).max().unwrap_or(0);
+        let mut encoded = Self {
+            input_ids: Vec::with_capacity(batch * seq_len),
+            attention_mask: Vec::with_capacity(batch * seq_len),
+            token_type_ids: Vec::with_capacity(batch * seq_len),
+            batch,
+            seq_len,
+        };
+        for encoding in encodings {
+            encoded
+                .input_ids
+                .extend(Self::padded(encoding.get_ids(), seq_len));
+            encoded
+                .attention_mask
+                .extend(Self::padded(encoding.get_attention_mask(), seq_len));
+            encoded
+                .token_type_ids
+                .extend(Self::padded(encoding.get_type_ids(), seq_len));
+        }
+        encoded
+    }
+
+    fn padded(values: &[u32], seq_len: usize) -> impl Iterator<Item = i64> + '_ {
+        values
+            .iter()
+            .map(|&v| v as i64)
+            .chain(std::iter::repeat(0))
+            .take(seq_len)
+    }
+
+    /// Build the inputs the model expects, by name
+    fn to_input(&self, input_names: &[String]) -> EdgeResult<InferenceInput> {
+        let tensor = |values: &[i64]| Value::Array(values.iter().map(|&v| v.into()).collect());
+        let shape = vec![self.batch, self.seq_len];
+        let mut input = InferenceInput::new();
+        for name in input_names {
+            let values = match name.as_str() {
+                "input_ids" => &self.input_ids,
+                "attention_mask" => &self.attention_mask,
+                "token_type_ids" => &self.token_type_ids,
+                other => {
+                    return Err(EdgeError::model(format!(
+                        "Unsupported encoder input: {other}"
+                    )))
+                }
+            };
+            input = input.add_tensor(name.clone(), tensor(values), shape.clone());
+        }
+        Ok(input)
+    }
+}
+
+/// Flat `f32` values of a named output and its shape
+fn output_f32(
+    output: &crate::runtime::InferenceOutput,
+    name: &str,
+) -> EdgeResult<(Vec<f32>, Vec<usize>)> {
+    let values: Vec<f32> = output
+        .get_output(name)
+        .and_then(Value::as_array)
+        .ok_or_else(|| EdgeError::inference(format!("Missing output: {name}")))?
+        .iter()
+        .map(|v| v.as_f64().unwrap_or(0.0) as f32)
+        .collect();
+    let shape = output
+        .get_shape(name)
+        .ok_or_else(|| EdgeError::inference(format!("Missing shape of output: {name}")))?
+        .to_vec();
+    if values.len() != shape.iter().product::<usize>() {
+        return Err(EdgeError::inference(format!(
+            "Output {name} has {} values for shape {shape:?}",
+            values.len()
+        )));
+    }
+    Ok((values, shape))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tokenizers::models::wordlevel::WordLevel;
+    use tokenizers::pre_tokenizers::whitespace::Whitespace;
+
+    /// Word-level tokenizer over a tiny vocabulary
+    pub(crate) fn tokenizer() -> Tokenizer {
+        let vocab = ["[PAD]", "[UNK]", "rust", "agents", "tools", "tea"]
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (w.to_string(), i as u32));
+        let model = WordLevel::builder()
+            .vocab(vocab.collect())
+            .unk_token("[UNK]".to_string())
+            .build()
+            .unwrap();
+        let mut tokenizer = Tokenizer::new(model);
+        tokenizer.with_pre_tokenizer(Some(Whitespace {}));
+        tokenizer
+    }
+
+    #[test]
+    fn test_encoded_batch_pads_to_longest() {
+        let tokenizer = tokenizer();
+        let encodings = tokenizer
+            .encode_batch(vec!["rust agents tools", "tea"], false)
+            .unwrap();
+        let batch = EncodedBatch::new(&encodings);
+
+        assert_eq!((batch.batch, batch.seq_len), (2, 3));
+        assert_eq!(batch.input_ids, vec![2, 3, 4, 5, 0, 0]);
+        assert_eq!(batch.attention_mask, vec![1, 1, 1, 1, 0, 0]);
+
+        let names = vec!["input_ids".to_string(), "attention_mask".to_string()];
+        let input = batch.to_input(&names).unwrap();
+        assert_eq!(input.shapes["input_ids"], vec![2, 3]);
+        assert!(!input.inputs.contains_key("token_type_ids"));
+        assert!(batch.to_input(&["pixel_values".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_max_length_truncates() {
+        let mut tokenizer = tokenizer();
+        set_max_length(&mut tokenizer, 2).unwrap();
+        let encoding = tokenizer.encode("rust agents tools", false).unwrap();
+        assert_eq!(encoding.get_ids(), &[2, 3]);
+    }
+}
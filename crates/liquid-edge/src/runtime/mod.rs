@@ -11,21 +11,32 @@ use std::collections::HashMap;
 pub mod onnx;
 
 /// Generic input for inference operations
+///
+/// Built with [`InferenceInput::new`] and the `add_*` methods, so fields can be added later.
 #[derive(Debug, Clone, Default)]
+#[non_exhaustive]
 pub struct InferenceInput {
     /// Named tensor inputs as key-value pairs
     /// Keys are input names, values are the tensor data as JSON
     pub inputs: HashMap<String, Value>,
+    /// Shapes of the inputs, which are given as flat arrays
+    /// Inputs without a shape are treated as a batch of one sequence, `[1, len]`
+    pub shapes: HashMap<String, Vec<usize>>,
     /// Input metadata
     pub metadata: HashMap<String, Value>,
 }
 
 /// Generic output from inference operations
+///
+/// Built with [`InferenceOutput::new`] and the `add_*` methods, so fields can be added later.
 #[derive(Debug, Clone, Default)]
+#[non_exhaustive]
 pub struct InferenceOutput {
     /// Named tensor outputs as key-value pairs
     /// Keys are output names, values are the tensor data as JSON
     pub outputs: HashMap<String, Value>,
+    /// Shapes of the outputs, which are returned as flat arrays in row-major order
+    pub shapes: HashMap<String, Vec<usize>>,
     /// Output metadata
     pub metadata: HashMap<String, Value>,
 }
@@ -140,6 +151,13 @@ impl InferenceInput {
         self
     }
 
+    /// Add a tensor input with an explicit shape, e.g. `[batch, seq_len]`
+    pub fn add_tensor(mut self, name: String, data: Value, shape: Vec<usize>) -> Self {
+        self.shapes.insert(name.clone(), shape);
+        self.inputs.insert(name, data);
+        self
+    }
+
     /// Add metadata
     pub fn add_metadata(mut self, key: String, value: Value) -> Self {
         self.metadata.insert(key, value);
@@ -153,11 +171,35 @@ impl InferenceOutput {
         Self::default()
     }
 
+    /// Add a tensor output
+    pub fn add_output(mut self, name: String, data: Value) -> Self {
+        self.outputs.insert(name, data);
+        self
+    }
+
+    /// Add a tensor output with its shape
+    pub fn add_tensor(mut self, name: String, data: Value, shape: Vec<usize>) -> Self {
+        self.shapes.insert(name.clone(), shape);
+        self.outputs.insert(name, data);
+        self
+    }
+
+    /// Add metadata
+    pub fn add_metadata(mut self, key: String, value: Value) -> Self {
+        self.metadata.insert(key, value);
+        self
+    }
+
     /// Get output by name
     pub fn get_output(&self, name: &str) -> Option<&Value> {
         self.outputs.get(name)
     }
 
+    /// Get the shape of an output by name
+    pub fn get_shape(&self, name: &str) -> Option<&[usize]> {
+        self.shapes.get(name).map(Vec::as_slice)
+    }
+
     /// Get metadata by key
     pub fn get_metadata(&self, key: &str) -> Option<&Value> {
         self.metadata.get(key)
@@ -180,4 +222,31 @@ mod tests {
         assert!(input.inputs.contains_key("input_ids"));
         assert!(input.metadata.contains_key("batch_size"));
     }
+
+    #[test]
+    fn test_inference_input_with_shape() {
+        let input = InferenceInput::new().add_tensor(
+            "input_ids".to_string(),
+            serde_json::json!([1, 2, 3, 4]),
+            vec![2, 2],
+        );
+
+        assert_eq!(input.shapes["input_ids"], vec![2, 2]);
+        assert!(input.inputs.contains_key("input_ids"));
+    }
+
+    #[test]
+    fn test_inference_output_with_shape() {
+        let output = InferenceOutput::new()
+            .add_tensor(
+                "logits".to_string(),
+                serde_json::json!([0.1, 0.9]),
+                vec![1, 2],
+            )
+            .add_metadata("backend".to_string(), Value::String("test".to_string()));
+
+        assert_eq!(output.get_shape("logits"), Some([1, 2].as_slice()));
+        assert!(output.get_output("logits").is_some());
+        assert!(output.get_metadata("backend").is_some());
+    }
 }
@@ -198,22 +198,24 @@ impl OnnxBackend {
         })
     }
 
-    /// Convert JSON value to ONNX tensor
+    /// Convert JSON value to ONNX tensor, shaped `[1, len]` unless a shape is given
     fn json_to_tensor(
         &self,
         name: &str,
         data: &Value,
+        shape: Option<&[usize]>,
     ) -> EdgeResult<ort::value::Value<ort::value::DynValueTypeMarker>> {
         match data {
             Value::Array(arr) => {
+                let len = arr.len();
+                let shape = shape.map(<[usize]>::to_vec).unwrap_or_else(|| vec![1, len]);
                 if let Ok(i64_values) = arr
                     .iter()
                     .map(|v| v.as_i64().ok_or("Invalid i64"))
                     .collect::<Result<Vec<_>, _>>()
                 {
-                    let len = i64_values.len();
-                    let array = ArrayD::<i64>::from_shape_vec(IxDyn(&[1, len]), i64_values)
-                        .map_err(|e| {
+                    let array =
+                        ArrayD::<i64>::from_shape_vec(IxDyn(&shape), i64_values).map_err(|e| {
                             EdgeError::inference(format!(
                                 "Failed to create i64 tensor for {name}: {e}"
                             ))
@@ -233,9 +235,8 @@ impl OnnxBackend {
                     .map(|v| v.as_f64().map(|f| f as f32).ok_or("Invalid f32"))
                     .collect::<Result<Vec<_>, _>>()
                 {
-                    let len = f32_values.len();
-                    let array = ArrayD::<f32>::from_shape_vec(IxDyn(&[1, len]), f32_values)
-                        .map_err(|e| {
+                    let array =
+                        ArrayD::<f32>::from_shape_vec(IxDyn(&shape), f32_values).map_err(|e| {
                             EdgeError::inference(format!(
                                 "Failed to create f32 tensor for {name}: {e}"
                             ))
@@ -260,12 +261,14 @@ impl OnnxBackend {
         }
     }
 
-    /// Convert ONNX tensor to JSON value
+    /// Convert ONNX tensor to a flat JSON array and its shape
     fn tensor_to_json_static(
         tensor: &ort::value::Value<ort::value::DynValueTypeMarker>,
-    ) -> EdgeResult<Value> {
+    ) -> EdgeResult<(Value, Vec<usize>)> {
+        let dims = |shape: &ort::tensor::Shape| shape.iter().map(|&d| d.max(0) as usize).collect();
+
         // Try to extract as f32 first
-        if let Ok((_, data)) = tensor.try_extract_tensor::<f32>() {
+        if let Ok((shape, data)) = tensor.try_extract_tensor::<f32>() {
             let values: Vec<Value> = data
                 .iter()
                 .map(|&x| {
@@ -275,13 +278,13 @@ impl OnnxBackend {
                     )
                 })
                 .collect();
-            return Ok(Value::Array(values));
+            return Ok((Value::Array(values), dims(shape)));
         }
 
         // Try to extract as i64
-        if let Ok((_, data)) = tensor.try_extract_tensor::<i64>() {
+        if let Ok((shape, data)) = tensor.try_extract_tensor::<i64>() {
             let values: Vec<Value> = data.iter().map(|&x| Value::Number(x.into())).collect();
-            return Ok(Value::Array(values));
+            return Ok((Value::Array(values), dims(shape)));
         }
 
         Err(EdgeError::inference(
@@ -297,7 +300,8 @@ impl RuntimeBackend for OnnxBackend {
 
         for input_info in &self.input_info {
             if let Some(data) = input.inputs.get(&input_info.name) {
-                let tensor = self.json_to_tensor(&input_info.name, data)?;
+                let shape = input.shapes.get(&input_info.name).map(Vec::as_slice);
+                let tensor = self.json_to_tensor(&input_info.name, data, shape)?;
                 onnx_inputs.insert(input_info.name.clone(), tensor);
             } else {
                 return Err(EdgeError::inference(format!(
@@ -315,10 +319,12 @@ impl RuntimeBackend for OnnxBackend {
 
         // Convert outputs back to JSON
         let mut result_outputs = HashMap::new();
+        let mut shapes = HashMap::new();
         for output_info in &self.output_info {
             if let Some(tensor) = outputs.get(&output_info.name) {
-                let json_data = Self::tensor_to_json_static(tensor)?;
+                let (json_data, shape) = Self::tensor_to_json_static(tensor)?;
                 result_outputs.insert(output_info.name.clone(), json_data);
+                shapes.insert(output_info.name.clone(), shape);
             }
         }
 
@@ -328,6 +334,7 @@ impl RuntimeBackend for OnnxBackend {
 
         Ok(InferenceOutput {
             outputs: result_outputs,
+            shapes,
             metadata,
         })
     }
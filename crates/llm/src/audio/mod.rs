@@ -0,0 +1,294 @@
+use std::fmt;
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+
+use crate::error::LLMError;
+
+/// Audio to transcribe, along with the hints passed to the speech-to-text model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptionRequest {
+    /// Raw audio bytes
+    pub audio: Vec<u8>,
+    /// File name sent with the audio; providers infer the container from its extension
+    pub filename: String,
+    /// Model override; each provider falls back to its default transcription model
+    pub model: Option<String>,
+    /// ISO-639-1 language of the audio, if known
+    pub language: Option<String>,
+    /// Text that guides the style or vocabulary of the transcript
+    pub prompt: Option<String>,
+    /// Whether to return segment-level timestamps
+    pub timestamps: bool,
+}
+
+impl TranscriptionRequest {
+    pub fn new(audio: impl Into<Vec<u8>>, filename: impl Into<String>) -> Self {
+        Self {
+            audio: audio.into(),
+            filename: filename.into(),
+            model: None,
+            language: None,
+            prompt: None,
+            timestamps: false,
+        }
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    pub fn timestamps(mut self, timestamps: bool) -> Self {
+        self.timestamps = timestamps;
+        self
+    }
+}
+
+/// A timed span of a transcript.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionSegment {
+    /// Start of the segment in seconds
+    pub start: f32,
+    /// End of the segment in seconds
+    pub end: f32,
+    pub text: String,
+}
+
+/// The result of a speech-to-text request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcription {
+    pub text: String,
+    /// Language detected or confirmed by the provider
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Duration of the audio in seconds
+    #[serde(default)]
+    pub duration: Option<f32>,
+    /// Empty unless timestamps were requested
+    #[serde(default)]
+    pub segments: Vec<TranscriptionSegment>,
+}
+
+/// Encoding of synthesized audio.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    #[default]
+    Mp3,
+    Opus,
+    Aac,
+    Flac,
+    Wav,
+    /// Raw 24kHz 16-bit little-endian samples
+    Pcm,
+}
+
+impl AudioFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Opus => "opus",
+            AudioFormat::Aac => "aac",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Wav => "wav",
+            AudioFormat::Pcm => "pcm",
+        }
+    }
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Text to synthesize into speech.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeechRequest {
+    pub text: String,
+    /// Model override; each provider falls back to its default speech model
+    pub model: Option<String>,
+    /// Voice override; falls back to the provider's configured voice
+    pub voice: Option<String>,
+    pub format: AudioFormat,
+    /// Playback speed multiplier
+    pub speed: Option<f32>,
+}
+
+impl SpeechRequest {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            model: None,
+            voice: None,
+            format: AudioFormat::default(),
+            speed: None,
+        }
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn voice(mut self, voice: impl Into<String>) -> Self {
+        self.voice = Some(voice.into());
+        self
+    }
+
+    pub fn format(mut self, format: AudioFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+}
+
+/// Chunks of encoded audio, in playback order.
+pub type AudioStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>, LLMError>> + Send>>;
+
+/// Providers that can turn audio into text.
+#[async_trait]
+pub trait SpeechToTextProvider: Send + Sync {
+    async fn transcribe(&self, request: &TranscriptionRequest) -> Result<Transcription, LLMError>;
+}
+
+/// Providers that can turn text into audio.
+#[async_trait]
+pub trait TextToSpeechProvider: Send + Sync {
+    /// Synthesizes the whole clip and returns the encoded audio.
+    async fn synthesize(&self, request: &SpeechRequest) -> Result<Vec<u8>, LLMError>;
+
+    /// Streams the audio as the provider produces it.
+    ///
+    /// Defaults to a single chunk holding the result of [`synthesize`](Self::synthesize).
+    async fn synthesize_stream(&self, request: &SpeechRequest) -> Result<AudioStream, LLMError> {
+        let audio = self.synthesize(request).await?;
+        Ok(Box::pin(futures::stream::once(async move { Ok(audio) })))
+    }
+}
+
+/// Multipart body for the OpenAI-style `audio/transcriptions` endpoint, shared by
+/// OpenAI, Azure OpenAI and Groq.
+#[cfg(any(feature = "openai", feature = "azure_openai", feature = "groq"))]
+pub(crate) fn transcription_form(
+    request: &TranscriptionRequest,
+    default_model: &str,
+) -> Result<reqwest::multipart::Form, LLMError> {
+    let file = reqwest::multipart::Part::bytes(request.audio.clone())
+        .file_name(request.filename.clone())
+        .mime_str("application/octet-stream")
+        .map_err(|e| LLMError::InvalidRequest(e.to_string()))?;
+    let mut form = reqwest::multipart::Form::new().part("file", file).text(
+        "model",
+        request
+            .model
+            .clone()
+            .unwrap_or_else(|| default_model.to_string()),
+    );
+    if let Some(language) = &request.language {
+        form = form.text("language", language.clone());
+    }
+    if let Some(prompt) = &request.prompt {
+        form = form.text("prompt", prompt.clone());
+    }
+    if request.timestamps {
+        form = form
+            .text("response_format", "verbose_json")
+            .text("timestamp_granularities[]", "segment");
+    } else {
+        form = form.text("response_format", "json");
+    }
+    Ok(form)
+}
+
+/// JSON body for the OpenAI-style `audio/speech` endpoint.
+#[cfg(any(feature = "openai", feature = "azure_openai"))]
+#[derive(Serialize, Debug)]
+pub(crate) struct OpenAISpeechRequest<'a> {
+    pub model: &'a str,
+    pub input: &'a str,
+    pub voice: &'a str,
+    pub response_format: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+}
+
+/// Streams the body of an `audio/speech` response.
+#[cfg(any(feature = "openai", feature = "azure_openai"))]
+pub(crate) fn audio_byte_stream(response: reqwest::Response) -> AudioStream {
+    use futures::StreamExt;
+    Box::pin(response.bytes_stream().map(|chunk| {
+        chunk
+            .map(|bytes| bytes.to_vec())
+            .map_err(|e| LLMError::HttpError(e.to_string()))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    struct EchoSpeech;
+
+    #[async_trait]
+    impl TextToSpeechProvider for EchoSpeech {
+        async fn synthesize(&self, request: &SpeechRequest) -> Result<Vec<u8>, LLMError> {
+            Ok(request.text.as_bytes().to_vec())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_synthesize_stream_yields_one_chunk() {
+        let request = SpeechRequest::new("hello").format(AudioFormat::Wav);
+        let chunks: Vec<_> = EchoSpeech
+            .synthesize_stream(&request)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].as_ref().unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_transcription_deserializes_verbose_json() {
+        let transcription: Transcription = serde_json::from_value(serde_json::json!({
+            "task": "transcribe",
+            "language": "english",
+            "duration": 1.5,
+            "text": "Hi there",
+            "segments": [{"id": 0, "start": 0.0, "end": 1.5, "text": "Hi there"}]
+        }))
+        .unwrap();
+        assert_eq!(transcription.text, "Hi there");
+        assert_eq!(transcription.language.as_deref(), Some("english"));
+        assert_eq!(transcription.segments[0].end, 1.5);
+
+        let plain: Transcription = serde_json::from_str(r#"{"text": "Hi"}"#).unwrap();
+        assert!(plain.segments.is_empty());
+    }
+
+    #[test]
+    fn test_audio_format_names() {
+        assert_eq!(AudioFormat::Opus.to_string(), "opus");
+        assert_eq!(serde_json::to_string(&AudioFormat::Pcm).unwrap(), "\"pcm\"");
+    }
+}
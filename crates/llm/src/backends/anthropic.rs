@@ -4,10 +4,11 @@
 
 use crate::chat::utils::check_response_status;
 use crate::{
+    batch::{parse_jsonl, BatchJob, BatchProvider, BatchRequest, BatchResult, BatchStatus},
     builder::{LLMBackend, LLMBuilder},
     chat::{
-        ChatMessage, ChatProvider, ChatResponse, ChatRole, MessageType, StructuredOutputFormat,
-        Tool, ToolChoice,
+        ChatMessage, ChatProvider, ChatResponse, ChatRole, MessageType, PromptTokensDetails,
+        StreamLineDecoder, StreamResponse, StructuredOutputFormat, Tool, ToolChoice, Usage,
     },
     completion::{CompletionProvider, CompletionRequest, CompletionResponse},
     embedding::EmbeddingProvider,
@@ -19,17 +20,24 @@ use async_trait::async_trait;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use chrono::{DateTime, Utc};
 use futures::stream::Stream;
-use reqwest::Client;
+use reqwest::{Client, Method};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{collections::HashMap, sync::Arc};
 
+/// Default base URL of Anthropic's API
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com/v1";
+/// Anthropic accepts at most this many `cache_control` blocks per request
+const MAX_CACHE_BREAKPOINTS: usize = 4;
+
 /// Client for interacting with Anthropic's API.
 ///
 /// Provides methods for chat and completion requests using Anthropic's models.
 #[derive(Debug)]
 pub struct Anthropic {
     pub api_key: String,
+    /// Base URL of the API, `https://api.anthropic.com/v1` unless set on the builder
+    pub base_url: String,
     pub model: String,
     pub max_tokens: u32,
     pub temperature: f32,
@@ -40,6 +48,8 @@ pub struct Anthropic {
     pub tool_choice: Option<ToolChoice>,
     pub reasoning: bool,
     pub thinking_budget_tokens: Option<u32>,
+    /// Cache the system prompt, tools and conversation prefix between requests
+    pub prompt_caching: bool,
     client: Client,
 }
 
@@ -50,6 +60,23 @@ struct AnthropicTool<'a> {
     description: &'a str,
     #[serde(rename = "input_schema")]
     schema: &'a serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
+}
+
+/// Prompt caching marker; the prompt is cached up to and including the marked block
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum CacheControl {
+    Ephemeral,
+}
+
+/// System prompt, sent as content blocks when it has several parts or a cache breakpoint
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+enum AnthropicSystem<'a> {
+    Text(&'a str),
+    Blocks(Vec<MessageContent<'a>>),
 }
 
 /// Configuration for the thinking feature
@@ -70,7 +97,7 @@ struct AnthropicCompleteRequest<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    system: Option<&'a str>,
+    system: Option<AnthropicSystem<'a>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -92,16 +119,17 @@ struct AnthropicMessage<'a> {
     content: Vec<MessageContent<'a>>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Default)]
 struct MessageContent<'a> {
     #[serde(rename = "type")]
     message_type: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
     text: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    image_url: Option<ImageUrlContent<'a>>,
+    source: Option<MediaSource<'a>>,
+    /// File name of a document
     #[serde(skip_serializing_if = "Option::is_none")]
-    source: Option<ImageSource<'a>>,
+    title: Option<&'a str>,
     // tool use
     #[serde(skip_serializing_if = "Option::is_none", rename = "id")]
     tool_use_id: Option<String>,
@@ -114,25 +142,28 @@ struct MessageContent<'a> {
     tool_result_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "content")]
     tool_output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
 }
 
+/// Source of an image or document content block.
 #[derive(Serialize, Debug)]
-struct ImageUrlContent<'a> {
-    url: &'a str,
-}
-
-#[derive(Serialize, Debug)]
-struct ImageSource<'a> {
+struct MediaSource<'a> {
     #[serde(rename = "type")]
     source_type: &'a str,
-    media_type: &'a str,
-    data: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    media_type: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<&'a str>,
 }
 
 /// Response from Anthropic's messages API endpoint.
 #[derive(Deserialize, Debug)]
 struct AnthropicCompleteResponse {
     content: Vec<AnthropicContent>,
+    usage: Option<AnthropicUsage>,
 }
 
 /// Content block within an Anthropic API response.
@@ -147,21 +178,90 @@ struct AnthropicContent {
     id: Option<String>,
 }
 
-/// Response from Anthropic's streaming messages API endpoint.
+/// Event from Anthropic's streaming messages API endpoint.
 #[derive(Deserialize, Debug)]
-struct AnthropicStreamResponse {
-    #[serde(rename = "type")]
-    response_type: String,
-    delta: Option<AnthropicDelta>,
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicStreamEvent {
+    MessageStart {
+        message: AnthropicStreamMessage,
+    },
+    ContentBlockStart {
+        index: usize,
+        content_block: AnthropicContent,
+    },
+    ContentBlockDelta {
+        index: usize,
+        delta: AnthropicDelta,
+    },
+    MessageDelta {
+        usage: Option<AnthropicUsage>,
+    },
+    Error {
+        error: AnthropicError,
+    },
+    /// `ping`, `content_block_stop`, `message_stop` and future event types
+    #[serde(other)]
+    Other,
+}
+
+/// Message metadata sent at the start of a stream.
+#[derive(Deserialize, Debug)]
+struct AnthropicStreamMessage {
+    usage: Option<AnthropicUsage>,
 }
 
 /// Delta content within an Anthropic streaming response.
 #[derive(Deserialize, Debug)]
 struct AnthropicDelta {
-    #[allow(dead_code)]
     #[serde(rename = "type")]
     delta_type: Option<String>,
     text: Option<String>,
+    /// Fragment of the model's reasoning, for `thinking_delta`
+    thinking: Option<String>,
+    /// Fragment of a tool call's input, for `input_json_delta`
+    partial_json: Option<String>,
+}
+
+/// Token usage, split between the `message_start` and `message_delta` events when streaming.
+#[derive(Deserialize, Debug, Default, Clone)]
+struct AnthropicUsage {
+    /// Input tokens after the last cache breakpoint
+    input_tokens: Option<u32>,
+    output_tokens: Option<u32>,
+    cache_creation_input_tokens: Option<u32>,
+    cache_read_input_tokens: Option<u32>,
+}
+
+impl AnthropicUsage {
+    /// Converts to [`Usage`], counting cached input as prompt tokens like OpenAI does
+    fn into_usage(self) -> Usage {
+        let prompt_tokens = self.input_tokens.unwrap_or(0)
+            + self.cache_creation_input_tokens.unwrap_or(0)
+            + self.cache_read_input_tokens.unwrap_or(0);
+        let completion_tokens = self.output_tokens.unwrap_or(0);
+        let prompt_tokens_details = (self.cache_creation_input_tokens.is_some()
+            || self.cache_read_input_tokens.is_some())
+        .then_some(PromptTokensDetails {
+            cached_tokens: self.cache_read_input_tokens,
+            cache_creation_tokens: self.cache_creation_input_tokens,
+            audio_tokens: None,
+        });
+        Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            completion_tokens_details: None,
+            prompt_tokens_details,
+        }
+    }
+}
+
+/// Error reported in the middle of a stream, e.g. when the API is overloaded.
+#[derive(Deserialize, Debug)]
+struct AnthropicError {
+    #[serde(rename = "type")]
+    error_type: String,
+    message: String,
 }
 
 impl std::fmt::Display for AnthropicCompleteResponse {
@@ -216,6 +316,10 @@ impl ChatResponse for AnthropicCompleteResponse {
             .and_then(|c| c.thinking.clone())
     }
 
+    fn usage(&self) -> Option<Usage> {
+        self.usage.clone().map(AnthropicUsage::into_usage)
+    }
+
     fn tool_calls(&self) -> Option<Vec<ToolCall>> {
         match self
             .content
@@ -245,6 +349,19 @@ impl ChatResponse for AnthropicCompleteResponse {
     }
 }
 
+/// Content blocks for a multimodal message, with the message text (if any) after the media block
+fn with_text_block<'a>(text: &'a str, media: MessageContent<'a>) -> Vec<MessageContent<'a>> {
+    let mut blocks = vec![media];
+    if !text.is_empty() {
+        blocks.push(MessageContent {
+            message_type: Some("text"),
+            text: Some(text),
+            ..Default::default()
+        });
+    }
+    blocks
+}
+
 impl Anthropic {
     /// Creates a new Anthropic client with the specified configuration.
     ///
@@ -279,6 +396,7 @@ impl Anthropic {
         }
         Self {
             api_key: api_key.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
             model: model.unwrap_or_else(|| "claude-3-sonnet-20240229".to_string()),
             max_tokens: max_tokens.unwrap_or(300),
             temperature: temperature.unwrap_or(0.7),
@@ -289,6 +407,7 @@ impl Anthropic {
             tool_choice,
             reasoning: reasoning.unwrap_or(false),
             thinking_budget_tokens,
+            prompt_caching: false,
             client: builder.build().expect("Failed to build reqwest Client"),
         }
     }
@@ -300,8 +419,30 @@ impl Anthropic {
         _json_schema: Option<StructuredOutputFormat>,
         stream: bool,
     ) -> Result<AnthropicCompleteRequest<'a>, LLMError> {
-        let anthropic_messages: Vec<AnthropicMessage> = messages
+        // Breakpoint markers carry no content, they mark the message right before them
+        let mut kept: Vec<&'a ChatMessage> = Vec::with_capacity(messages.len());
+        let mut marked: Vec<usize> = Vec::new();
+        for m in messages {
+            if !m.is_cache_breakpoint() {
+                kept.push(m);
+            } else if let Some(previous) = kept.len().checked_sub(1) {
+                if marked.last() != Some(&previous) {
+                    marked.push(previous);
+                }
+            }
+        }
+
+        // Anthropic takes the system prompt outside the messages, so leading system
+        // messages such as agent instructions are sent after the client's system prompt
+        let leading_system = kept
+            .iter()
+            .take_while(|m| m.role == ChatRole::System)
+            .count();
+        let (system_messages, messages) = kept.split_at(leading_system);
+
+        let mut anthropic_messages: Vec<AnthropicMessage> = messages
             .iter()
+            .copied()
             .map(|m| AnthropicMessage {
                 role: match m.role {
                     ChatRole::User => "user",
@@ -313,50 +454,55 @@ impl Anthropic {
                     MessageType::Text => vec![MessageContent {
                         message_type: Some("text"),
                         text: Some(&m.content),
-                        image_url: None,
-                        source: None,
-                        tool_use_id: None,
-                        tool_input: None,
-                        tool_name: None,
-                        tool_result_id: None,
-                        tool_output: None,
+                        ..Default::default()
                     }],
-                    MessageType::Pdf(_) => unimplemented!(),
-                    MessageType::Image((image_mime, raw_bytes)) => {
-                        vec![MessageContent {
+                    MessageType::Pdf { name, data } => with_text_block(
+                        &m.content,
+                        MessageContent {
+                            message_type: Some("document"),
+                            source: Some(MediaSource {
+                                source_type: "base64",
+                                media_type: Some("application/pdf"),
+                                data: Some(BASE64.encode(data)),
+                                url: None,
+                            }),
+                            title: Some(name),
+                            ..Default::default()
+                        },
+                    ),
+                    MessageType::Image((image_mime, raw_bytes)) => with_text_block(
+                        &m.content,
+                        MessageContent {
                             message_type: Some("image"),
-                            text: None,
-                            image_url: None,
-                            source: Some(ImageSource {
+                            source: Some(MediaSource {
                                 source_type: "base64",
-                                media_type: image_mime.mime_type(),
-                                data: BASE64.encode(raw_bytes),
+                                media_type: Some(image_mime.mime_type()),
+                                data: Some(BASE64.encode(raw_bytes)),
+                                url: None,
                             }),
-                            tool_use_id: None,
-                            tool_input: None,
-                            tool_name: None,
-                            tool_result_id: None,
-                            tool_output: None,
-                        }]
-                    }
-                    MessageType::ImageURL(ref url) => vec![MessageContent {
-                        message_type: Some("image_url"),
-                        text: None,
-                        image_url: Some(ImageUrlContent { url }),
-                        source: None,
-                        tool_use_id: None,
-                        tool_input: None,
-                        tool_name: None,
-                        tool_result_id: None,
-                        tool_output: None,
-                    }],
+                            ..Default::default()
+                        },
+                    ),
+                    MessageType::ImageURL(ref url) => with_text_block(
+                        &m.content,
+                        MessageContent {
+                            message_type: Some("image"),
+                            source: Some(MediaSource {
+                                source_type: "url",
+                                media_type: None,
+                                data: None,
+                                url: Some(url),
+                            }),
+                            ..Default::default()
+                        },
+                    ),
                     MessageType::ToolUse(calls) => calls
                         .iter()
                         .map(|c| MessageContent {
                             message_type: Some("tool_use"),
                             text: None,
-                            image_url: None,
                             source: None,
+                            title: None,
                             tool_use_id: Some(c.id.clone()),
                             tool_input: Some(
                                 serde_json::from_str(&c.function.arguments)
@@ -365,6 +511,7 @@ impl Anthropic {
                             tool_name: Some(c.function.name.clone()),
                             tool_result_id: None,
                             tool_output: None,
+                            cache_control: None,
                         })
                         .collect(),
                     MessageType::ToolResult(responses) => responses
@@ -372,30 +519,89 @@ impl Anthropic {
                         .map(|r| MessageContent {
                             message_type: Some("tool_result"),
                             text: None,
-                            image_url: None,
                             source: None,
+                            title: None,
                             tool_use_id: None,
                             tool_input: None,
                             tool_name: None,
                             tool_result_id: Some(r.id.clone()),
                             tool_output: Some(r.function.arguments.clone()),
+                            cache_control: None,
                         })
                         .collect(),
+                    MessageType::CacheBreakpoint => Vec::new(),
                 },
             })
             .collect();
 
-        let anthropic_tools = tools.map(|slice| {
+        let mut anthropic_tools = tools.map(|slice| {
             slice
                 .iter()
                 .map(|tool| AnthropicTool {
                     name: &tool.function.name,
                     description: &tool.function.description,
                     schema: &tool.function.parameters,
+                    cache_control: None,
                 })
                 .collect::<Vec<_>>()
         });
 
+        // Breakpoints cache everything before them: the system prompt, then the tools, then
+        // the conversation up to the latest message
+        let mut system_blocks = (!system_messages.is_empty() || self.prompt_caching).then(|| {
+            std::iter::once(self.system.as_str())
+                .chain(system_messages.iter().map(|m| m.content.as_str()))
+                .map(|text| MessageContent {
+                    message_type: Some("text"),
+                    text: Some(text),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>()
+        });
+        let mut breakpoints = 0;
+        if self.prompt_caching {
+            let last_system = system_blocks.as_mut().and_then(|b| b.last_mut());
+            let last_tool = anthropic_tools.as_mut().and_then(|t| t.last_mut());
+            let last_block = anthropic_messages
+                .last_mut()
+                .and_then(|m| m.content.last_mut());
+            for cache_control in [
+                last_system.map(|b| &mut b.cache_control),
+                last_tool.map(|t| &mut t.cache_control),
+                last_block.map(|b| &mut b.cache_control),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                *cache_control = Some(CacheControl::Ephemeral);
+                breakpoints += 1;
+            }
+        }
+        // Explicit breakpoints fill what's left of the limit, keeping the latest ones
+        for &index in marked.iter().rev() {
+            let block = if index < leading_system {
+                // The first system block is the client's own system prompt
+                system_blocks.as_mut().and_then(|b| b.get_mut(index + 1))
+            } else {
+                anthropic_messages
+                    .get_mut(index - leading_system)
+                    .and_then(|m| m.content.last_mut())
+            };
+            let Some(block) = block else { continue };
+            if block.cache_control.is_some() {
+                continue;
+            }
+            if breakpoints >= MAX_CACHE_BREAKPOINTS {
+                break;
+            }
+            block.cache_control = Some(CacheControl::Ephemeral);
+            breakpoints += 1;
+        }
+        let system = match system_blocks {
+            Some(blocks) => AnthropicSystem::Blocks(blocks),
+            None => AnthropicSystem::Text(&self.system),
+        };
+
         let tool_choice = match self.tool_choice {
             Some(ToolChoice::Auto) => {
                 Some(HashMap::from([("type".to_string(), "auto".to_string())]))
@@ -431,7 +637,7 @@ impl Anthropic {
             model: &self.model,
             max_tokens: Some(self.max_tokens),
             temperature: Some(self.temperature),
-            system: Some(&self.system),
+            system: Some(system),
             stream: Some(stream),
             top_p: self.top_p,
             top_k: self.top_k,
@@ -441,6 +647,27 @@ impl Anthropic {
         })
     }
 
+    /// Builds a POST request to the messages endpoint with authentication headers.
+    fn messages_request(&self, body: &AnthropicCompleteRequest) -> reqwest::RequestBuilder {
+        self.api_request(Method::POST, "messages").json(body)
+    }
+
+    /// Builds a request to an API path with authentication headers.
+    fn api_request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        let url = format!("{}/{path}", self.base_url.trim_end_matches('/'));
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("x-api-key", &self.api_key)
+            .header("Content-Type", "application/json")
+            .header("anthropic-version", "2023-06-01");
+
+        if self.timeout_seconds > 0 {
+            request = request.timeout(std::time::Duration::from_secs(self.timeout_seconds));
+        }
+        request
+    }
+
     /// Sends a chat request to Anthropic's API.
     ///
     /// # Arguments
@@ -463,17 +690,7 @@ impl Anthropic {
 
         let req_body = self.build_completion_request(messages, tools, json_schema, false)?;
 
-        let mut request = self
-            .client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", &self.api_key)
-            .header("Content-Type", "application/json")
-            .header("anthropic-version", "2023-06-01")
-            .json(&req_body);
-
-        if self.timeout_seconds > 0 {
-            request = request.timeout(std::time::Duration::from_secs(self.timeout_seconds));
-        }
+        let request = self.messages_request(&req_body);
 
         if log::log_enabled!(log::Level::Trace) {
             if let Ok(json) = serde_json::to_string(&req_body) {
@@ -534,30 +751,35 @@ impl ChatProvider for Anthropic {
         json_schema: Option<StructuredOutputFormat>,
     ) -> Result<std::pin::Pin<Box<dyn Stream<Item = Result<String, LLMError>> + Send>>, LLMError>
     {
+        let struct_stream = self
+            .chat_stream_struct(messages, tools, json_schema)
+            .await?;
+        Ok(crate::chat::content_stream(struct_stream))
+    }
+
+    /// Sends a streaming chat request to Anthropic's API, yielding text, tool call
+    /// deltas and, once the message is complete, token usage.
+    async fn chat_stream_struct(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<
+        std::pin::Pin<Box<dyn Stream<Item = Result<StreamResponse, LLMError>> + Send>>,
+        LLMError,
+    > {
         if self.api_key.is_empty() {
             return Err(LLMError::AuthError("Missing Anthropic API key".to_string()));
         }
 
         let req_body = self.build_completion_request(messages, tools, json_schema, true)?;
 
-        let mut request = self
-            .client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", &self.api_key)
-            .header("Content-Type", "application/json")
-            .header("anthropic-version", "2023-06-01")
-            .json(&req_body);
-
-        if self.timeout_seconds > 0 {
-            request = request.timeout(std::time::Duration::from_secs(self.timeout_seconds));
-        }
-
-        let response = request.send().await?;
+        let response = self.messages_request(&req_body).send().await?;
         let response = check_response_status(response).await?;
 
-        Ok(crate::chat::create_sse_stream(
+        Ok(crate::chat::create_line_stream(
             response,
-            parse_anthropic_sse_chunk,
+            AnthropicStreamDecoder::default(),
         ))
     }
 }
@@ -591,6 +813,165 @@ impl EmbeddingProvider for Anthropic {
     }
 }
 
+/// Request body for the message batches endpoint
+#[derive(Serialize, Debug)]
+struct AnthropicBatchCreateRequest<'a> {
+    requests: Vec<AnthropicBatchRequest<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+struct AnthropicBatchRequest<'a> {
+    custom_id: &'a str,
+    params: AnthropicCompleteRequest<'a>,
+}
+
+#[derive(Deserialize, Debug)]
+struct AnthropicBatch {
+    id: String,
+    processing_status: String,
+    request_counts: AnthropicBatchRequestCounts,
+}
+
+#[derive(Deserialize, Debug)]
+struct AnthropicBatchRequestCounts {
+    processing: usize,
+    succeeded: usize,
+    errored: usize,
+    canceled: usize,
+    expired: usize,
+}
+
+impl AnthropicBatch {
+    fn job(&self) -> BatchJob {
+        let counts = &self.request_counts;
+        let failed = counts.errored + counts.canceled + counts.expired;
+        BatchJob {
+            id: self.id.clone(),
+            // Anthropic reports `ended` however the batch finished; the outcome of
+            // each request is in its result
+            status: match self.processing_status.as_str() {
+                "canceling" => BatchStatus::Cancelling,
+                "ended" => BatchStatus::Completed,
+                _ => BatchStatus::InProgress,
+            },
+            total: counts.processing + counts.succeeded + failed,
+            completed: counts.succeeded,
+            failed,
+        }
+    }
+}
+
+/// One line of the batch results file
+#[derive(Deserialize, Debug)]
+struct AnthropicBatchResultLine {
+    custom_id: String,
+    result: AnthropicBatchOutcome,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicBatchOutcome {
+    Succeeded { message: AnthropicCompleteResponse },
+    Errored { error: AnthropicErrorResponse },
+    Canceled,
+    Expired,
+}
+
+#[derive(Deserialize, Debug)]
+struct AnthropicErrorResponse {
+    error: AnthropicError,
+}
+
+impl AnthropicBatchResultLine {
+    fn into_result(self) -> BatchResult {
+        BatchResult {
+            custom_id: self.custom_id,
+            response: match self.result {
+                AnthropicBatchOutcome::Succeeded { message } => Ok(Box::new(message)),
+                AnthropicBatchOutcome::Errored { error } => Err(LLMError::ProviderError(format!(
+                    "{}: {}",
+                    error.error.error_type, error.error.message
+                ))),
+                AnthropicBatchOutcome::Canceled => {
+                    Err(LLMError::ProviderError("Request was canceled".to_string()))
+                }
+                AnthropicBatchOutcome::Expired => Err(LLMError::ProviderError(
+                    "Request expired before it was processed".to_string(),
+                )),
+            },
+        }
+    }
+}
+
+impl Anthropic {
+    async fn batch_object(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<AnthropicBatch, LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing Anthropic API key".to_string()));
+        }
+        let response = check_response_status(request.send().await?).await?;
+        Ok(response.json().await?)
+    }
+}
+
+#[async_trait]
+impl BatchProvider for Anthropic {
+    async fn submit_batch(&self, requests: &[BatchRequest]) -> Result<BatchJob, LLMError> {
+        let requests = requests
+            .iter()
+            .map(|request| {
+                let mut params = self.build_completion_request(
+                    &request.messages,
+                    request.tools.as_deref(),
+                    request.json_schema.clone(),
+                    false,
+                )?;
+                // Batched requests can't stream
+                params.stream = None;
+                Ok(AnthropicBatchRequest {
+                    custom_id: &request.custom_id,
+                    params,
+                })
+            })
+            .collect::<Result<Vec<_>, LLMError>>()?;
+        let body = AnthropicBatchCreateRequest { requests };
+        let batch = self
+            .batch_object(
+                self.api_request(Method::POST, "messages/batches")
+                    .json(&body),
+            )
+            .await?;
+        Ok(batch.job())
+    }
+
+    async fn batch_status(&self, batch_id: &str) -> Result<BatchJob, LLMError> {
+        let request = self.api_request(Method::GET, &format!("messages/batches/{batch_id}"));
+        Ok(self.batch_object(request).await?.job())
+    }
+
+    async fn batch_results(&self, batch_id: &str) -> Result<Vec<BatchResult>, LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing Anthropic API key".to_string()));
+        }
+        let request =
+            self.api_request(Method::GET, &format!("messages/batches/{batch_id}/results"));
+        let response = check_response_status(request.send().await?).await?;
+        let lines: Vec<AnthropicBatchResultLine> = parse_jsonl(&response.text().await?)?;
+        Ok(lines
+            .into_iter()
+            .map(AnthropicBatchResultLine::into_result)
+            .collect())
+    }
+
+    async fn cancel_batch(&self, batch_id: &str) -> Result<BatchJob, LLMError> {
+        let request =
+            self.api_request(Method::POST, &format!("messages/batches/{batch_id}/cancel"));
+        Ok(self.batch_object(request).await?.job())
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct AnthropicModelListResponse {
     data: Vec<AnthropicModelEntry>,
@@ -645,50 +1026,96 @@ impl ModelsProvider for Anthropic {
     }
 }
 
-impl crate::LLMProvider for Anthropic {}
+impl crate::LLMProvider for Anthropic {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&LLMBackend::Anthropic, &self.model)
+    }
+}
 
-/// Parses a Server-Sent Events (SSE) chunk from Anthropic's streaming API.
-///
-/// # Arguments
-///
-/// * `chunk` - The raw SSE chunk text
-///
-/// # Returns
+/// Decodes the events of Anthropic's streaming messages API.
 ///
-/// * `Ok(Some(String))` - Content token if found
-/// * `Ok(None)` - If chunk should be skipped (e.g., ping, done signal)
-/// * `Err(LLMError)` - If parsing fails
-fn parse_anthropic_sse_chunk(chunk: &str) -> Result<Option<String>, LLMError> {
-    for line in chunk.lines() {
-        let line = line.trim();
-
-        if let Some(data) = line.strip_prefix("data: ") {
-            match serde_json::from_str::<AnthropicStreamResponse>(data) {
-                Ok(response) => {
-                    if response.response_type == "content_block_delta" {
-                        if let Some(delta) = response.delta {
-                            if let Some(text) = delta.text {
-                                return Ok(Some(text));
+/// Tool calls are numbered in the order their `tool_use` blocks start, and usage is
+/// emitted with the `message_delta` event that carries the final output token count.
+#[derive(Default)]
+struct AnthropicStreamDecoder {
+    /// Tool call index of each `tool_use` content block, by block index
+    tool_blocks: HashMap<usize, usize>,
+    /// Input and cache token counts from `message_start`
+    start_usage: AnthropicUsage,
+}
+
+impl StreamLineDecoder for AnthropicStreamDecoder {
+    fn decode_line(&mut self, line: &str) -> Vec<Result<StreamResponse, LLMError>> {
+        let Some(data) = crate::chat::sse_data(line) else {
+            return Vec::new();
+        };
+        let Ok(event) = serde_json::from_str::<AnthropicStreamEvent>(data) else {
+            return Vec::new();
+        };
+        match event {
+            AnthropicStreamEvent::MessageStart { message } => {
+                self.start_usage = message.usage.unwrap_or_default();
+                Vec::new()
+            }
+            AnthropicStreamEvent::ContentBlockStart {
+                index,
+                content_block,
+            } if content_block.content_type.as_deref() == Some("tool_use") => {
+                let tool_index = self.tool_blocks.len();
+                self.tool_blocks.insert(index, tool_index);
+                vec![Ok(StreamResponse::from_tool_call(
+                    tool_index,
+                    content_block.name.unwrap_or_default(),
+                    "",
+                ))]
+            }
+            AnthropicStreamEvent::ContentBlockDelta { index, delta } => {
+                match delta.delta_type.as_deref() {
+                    Some("text_delta") => delta
+                        .text
+                        .map(|text| Ok(StreamResponse::from_content(text)))
+                        .into_iter()
+                        .collect(),
+                    Some("thinking_delta") => delta
+                        .thinking
+                        .map(|thinking| Ok(StreamResponse::from_reasoning(thinking)))
+                        .into_iter()
+                        .collect(),
+                    Some("input_json_delta") => {
+                        match (self.tool_blocks.get(&index), delta.partial_json) {
+                            (Some(tool_index), Some(json)) if !json.is_empty() => {
+                                vec![Ok(StreamResponse::from_tool_call(*tool_index, "", json))]
                             }
+                            _ => Vec::new(),
                         }
                     }
-                    return Ok(None);
+                    _ => Vec::new(),
                 }
-                Err(_) => continue,
             }
+            AnthropicStreamEvent::MessageDelta { usage: Some(usage) } => {
+                let usage = AnthropicUsage {
+                    output_tokens: usage.output_tokens,
+                    ..self.start_usage.clone()
+                };
+                vec![Ok(StreamResponse::from_usage(usage.into_usage()))]
+            }
+            AnthropicStreamEvent::Error { error } => vec![Err(LLMError::ProviderError(format!(
+                "Anthropic stream error ({}): {}",
+                error.error_type, error.message
+            )))],
+            _ => Vec::new(),
         }
     }
-
-    Ok(None)
 }
 
 impl LLMBuilder<Anthropic> {
     pub fn build(self) -> Result<Arc<Anthropic>, LLMError> {
+        self.ensure_unvalidated()?;
         let api_key = self.api_key.ok_or_else(|| {
             LLMError::InvalidRequest("No API key provided for Anthropic".to_string())
         })?;
 
-        let anthro = Anthropic::new(
+        let mut anthro = Anthropic::new(
             api_key,
             self.model,
             self.max_tokens,
@@ -701,6 +1128,10 @@ impl LLMBuilder<Anthropic> {
             self.reasoning,
             self.reasoning_budget_tokens,
         );
+        if let Some(base_url) = self.base_url {
+            anthro.base_url = base_url;
+        }
+        anthro.prompt_caching = self.prompt_caching.unwrap_or(false);
 
         Ok(Arc::new(anthro))
     }
@@ -2,9 +2,19 @@
 //!
 //! This module provides integration with Azure OpenAI's GPT models through their API.
 
+use crate::chat::utils::check_response_status;
 use crate::{
+    audio::{
+        audio_byte_stream, transcription_form, AudioStream, OpenAISpeechRequest, SpeechRequest,
+        SpeechToTextProvider, TextToSpeechProvider, Transcription, TranscriptionRequest,
+    },
     builder::LLMBuilder,
-    chat::{ChatResponse, ToolChoice},
+    chat::{ChatResponse, StreamResponse, ToolChoice},
+    images::{
+        GeneratedImage, ImageGenerationProvider, ImageGenerationRequest, OpenAIImageRequest,
+        OpenAIImageResponse,
+    },
+    providers::openai_compatible::{create_sse_stream, OpenAIStreamOptions},
     FunctionCall, ToolCall,
 };
 use crate::{
@@ -19,8 +29,10 @@ use crate::{
 use async_trait::async_trait;
 use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
 use either::*;
+use futures::stream::Stream;
 use reqwest::{Client, Url};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::sync::Arc;
 
 /// Client for interacting with Azure OpenAI's API.
@@ -61,6 +73,23 @@ struct AzureOpenAIChatMessage<'a> {
     tool_call_id: Option<String>,
 }
 
+/// Content parts for a multimodal message, with the message text (if any) before the media part
+fn with_text_part<'a>(
+    text: &'a str,
+    media: AzureMessageContent<'a>,
+) -> Vec<AzureMessageContent<'a>> {
+    let mut parts = Vec::with_capacity(2);
+    if !text.is_empty() {
+        parts.push(AzureMessageContent {
+            message_type: Some("text"),
+            text: Some(text),
+            ..Default::default()
+        });
+    }
+    parts.push(media);
+    parts
+}
+
 impl<'a> From<&'a ChatMessage> for AzureOpenAIChatMessage<'a> {
     fn from(chat_msg: &'a ChatMessage) -> Self {
         Self {
@@ -78,29 +107,46 @@ impl<'a> From<&'a ChatMessage> for AzureOpenAIChatMessage<'a> {
                     let base64_data = BASE64_STANDARD.encode(raw_bytes);
                     let data_url =
                         format!("data:{};base64,{}", image_mime.mime_type(), base64_data);
-                    let data_url_str = Box::leak(data_url.into_boxed_str());
-                    Some(Left(vec![AzureMessageContent {
-                        message_type: Some("image_url"),
-                        text: None,
-                        image_url: Some(ImageUrlContent { url: data_url_str }),
-                        tool_output: None,
-                        tool_call_id: None,
-                    }]))
+                    Some(Left(with_text_part(
+                        &chat_msg.content,
+                        AzureMessageContent {
+                            message_type: Some("image_url"),
+                            image_url: Some(ImageUrlContent {
+                                url: Cow::Owned(data_url),
+                            }),
+                            ..Default::default()
+                        },
+                    )))
                 }
-                MessageType::Pdf(_) => unimplemented!(),
-                MessageType::ImageURL(url) => {
-                    // Clone the URL to create an owned version
-
-                    Some(Left(vec![AzureMessageContent {
-                        message_type: Some("image_url"),
-                        text: None,
-                        image_url: Some(ImageUrlContent { url }),
-                        tool_output: None,
-                        tool_call_id: None,
-                    }]))
+                MessageType::Pdf { name, data } => {
+                    let data_url = format!(
+                        "data:application/pdf;base64,{}",
+                        BASE64_STANDARD.encode(data)
+                    );
+                    Some(Left(with_text_part(
+                        &chat_msg.content,
+                        AzureMessageContent {
+                            message_type: Some("file"),
+                            file: Some(FileContent {
+                                filename: name,
+                                file_data: Cow::Owned(data_url),
+                            }),
+                            ..Default::default()
+                        },
+                    )))
                 }
+                MessageType::ImageURL(url) => Some(Left(with_text_part(
+                    &chat_msg.content,
+                    AzureMessageContent {
+                        message_type: Some("image_url"),
+                        image_url: Some(ImageUrlContent {
+                            url: Cow::Borrowed(url),
+                        }),
+                        ..Default::default()
+                    },
+                ))),
                 MessageType::ToolUse(_) => None,
-                MessageType::ToolResult(_) => None,
+                MessageType::ToolResult(_) | MessageType::CacheBreakpoint => None,
             },
             tool_calls: match &chat_msg.message_type {
                 MessageType::ToolUse(calls) => {
@@ -147,7 +193,7 @@ impl<'a> From<&'a ToolCall> for AzureOpenAIToolCall<'a> {
     }
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Default)]
 struct AzureMessageContent<'a> {
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     message_type: Option<&'a str>,
@@ -155,6 +201,8 @@ struct AzureMessageContent<'a> {
     text: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
     image_url: Option<ImageUrlContent<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<FileContent<'a>>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "tool_call_id")]
     tool_call_id: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "content")]
@@ -164,7 +212,14 @@ struct AzureMessageContent<'a> {
 /// Individual image message in an OpenAI chat conversation.
 #[derive(Serialize, Debug)]
 struct ImageUrlContent<'a> {
-    url: &'a str,
+    url: Cow<'a, str>,
+}
+
+/// Inline file (e.g. PDF) in an OpenAI chat conversation.
+#[derive(Serialize, Debug)]
+struct FileContent<'a> {
+    filename: &'a str,
+    file_data: Cow<'a, str>,
 }
 
 #[derive(Serialize)]
@@ -199,6 +254,8 @@ struct AzureOpenAIChatRequest<'a> {
     reasoning_effort: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     response_format: Option<OpenAIResponseFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<OpenAIStreamOptions>,
 }
 
 /// Response from OpenAI's chat API endpoint.
@@ -393,12 +450,12 @@ impl AzureOpenAI {
         messages: &'a [ChatMessage],
         tools: Option<&'a [Tool]>,
         json_schema: Option<StructuredOutputFormat>,
-        _stream: bool,
-        _stream_options: Option<AzureOpenAIChatRequest>,
+        stream: bool,
+        stream_options: Option<OpenAIStreamOptions>,
     ) -> Result<AzureOpenAIChatRequest<'a>, LLMError> {
         let mut openai_msgs: Vec<AzureOpenAIChatMessage> = vec![];
 
-        for msg in messages {
+        for msg in messages.iter().filter(|m| !m.is_cache_breakpoint()) {
             if let MessageType::ToolResult(ref results) = msg.message_type {
                 for result in results {
                     openai_msgs.push(
@@ -424,9 +481,7 @@ impl AzureOpenAI {
                     content: Some(Left(vec![AzureMessageContent {
                         message_type: Some("text"),
                         text: Some(system),
-                        image_url: None,
-                        tool_call_id: None,
-                        tool_output: None,
+                        ..Default::default()
                     }])),
                     tool_calls: None,
                     tool_call_id: None,
@@ -449,16 +504,42 @@ impl AzureOpenAI {
             messages: openai_msgs,
             max_tokens: self.max_tokens,
             temperature: self.temperature,
-            stream: false,
+            stream,
             top_p: self.top_p,
             top_k: self.top_k,
             tools: request_tools,
             tool_choice: request_tool_choice,
             reasoning_effort: self.reasoning_effort.clone(),
             response_format,
+            stream_options,
         })
     }
 
+    /// Builds a POST request to the deployment's chat completions endpoint
+    fn chat_request(
+        &self,
+        body: &AzureOpenAIChatRequest,
+    ) -> Result<reqwest::RequestBuilder, LLMError> {
+        let mut url = self
+            .base_url
+            .join("chat/completions")
+            .map_err(|e| LLMError::HttpError(e.to_string()))?;
+
+        url.query_pairs_mut()
+            .append_pair("api-version", &self.api_version);
+
+        let mut request = self
+            .client
+            .post(url)
+            .header("api-key", &self.api_key)
+            .json(body);
+
+        if let Some(timeout) = self.timeout_seconds {
+            request = request.timeout(std::time::Duration::from_secs(timeout));
+        }
+        Ok(request)
+    }
+
     /// Sends a chat request to OpenAI's API.
     ///
     /// # Arguments
@@ -488,26 +569,8 @@ impl AzureOpenAI {
             }
         }
 
-        let mut url = self
-            .base_url
-            .join("chat/completions")
-            .map_err(|e| LLMError::HttpError(e.to_string()))?;
-
-        url.query_pairs_mut()
-            .append_pair("api-version", &self.api_version);
-
-        let mut request = self
-            .client
-            .post(url)
-            .header("api-key", &self.api_key)
-            .json(&body);
-
-        if let Some(timeout) = self.timeout_seconds {
-            request = request.timeout(std::time::Duration::from_secs(timeout));
-        }
-
         // Send the request
-        let response = request.send().await?;
+        let response = self.chat_request(&body)?.send().await?;
 
         log::debug!("Azure OpenAI HTTP status: {}", response.status());
 
@@ -546,6 +609,60 @@ impl ChatProvider for AzureOpenAI {
     ) -> Result<Box<dyn ChatResponse>, LLMError> {
         self.chat_with_tools(messages, tools, json_schema).await
     }
+
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<std::pin::Pin<Box<dyn Stream<Item = Result<String, LLMError>> + Send>>, LLMError>
+    {
+        let struct_stream = self
+            .chat_stream_struct(messages, tools, json_schema)
+            .await?;
+        Ok(crate::chat::content_stream(struct_stream))
+    }
+
+    /// Sends a streaming chat request to the deployment, yielding text, tool call deltas
+    /// and, at the end of the stream, usage.
+    async fn chat_stream_struct(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<
+        std::pin::Pin<Box<dyn Stream<Item = Result<StreamResponse, LLMError>> + Send>>,
+        LLMError,
+    > {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError(
+                "Missing Azure OpenAI API key".to_string(),
+            ));
+        }
+
+        let body = self.build_chat_completion_request(
+            messages,
+            tools,
+            json_schema,
+            true,
+            Some(OpenAIStreamOptions {
+                include_usage: true,
+            }),
+        )?;
+
+        let response = self.chat_request(&body)?.send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(LLMError::ResponseFormatError {
+                message: format!("Azure OpenAI API returned error status: {status}"),
+                raw_response: error_text,
+            });
+        }
+
+        Ok(create_sse_stream(response, true))
+    }
 }
 
 #[async_trait]
@@ -608,13 +725,93 @@ impl EmbeddingProvider for AzureOpenAI {
     }
 }
 
-impl LLMProvider for AzureOpenAI {}
+impl AzureOpenAI {
+    /// A POST request to `path` under the deployment, with the API version and key applied
+    fn deployment_request(&self, path: &str) -> Result<reqwest::RequestBuilder, LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing Azure OpenAI API key".into()));
+        }
+        let mut url = self
+            .base_url
+            .join(path)
+            .map_err(|e| LLMError::HttpError(e.to_string()))?;
+        url.query_pairs_mut()
+            .append_pair("api-version", &self.api_version);
+        Ok(self.client.post(url).header("api-key", &self.api_key))
+    }
+
+    /// Builds the request for `audio/speech`. The deployment decides the model.
+    fn speech_request(&self, request: &SpeechRequest) -> Result<reqwest::RequestBuilder, LLMError> {
+        let body = OpenAISpeechRequest {
+            model: request.model.as_deref().unwrap_or(&self.model),
+            input: &request.text,
+            voice: request.voice.as_deref().unwrap_or("alloy"),
+            response_format: request.format.as_str(),
+            speed: request.speed,
+        };
+        Ok(self.deployment_request("audio/speech")?.json(&body))
+    }
+}
+
+/// Requires the client to point at a Whisper deployment.
+#[async_trait]
+impl SpeechToTextProvider for AzureOpenAI {
+    async fn transcribe(&self, request: &TranscriptionRequest) -> Result<Transcription, LLMError> {
+        let response = self
+            .deployment_request("audio/transcriptions")?
+            .multipart(transcription_form(request, &self.model)?)
+            .send()
+            .await?;
+        let response = check_response_status(response).await?;
+        Ok(response.json().await?)
+    }
+}
+
+/// Requires the client to point at a TTS deployment.
+#[async_trait]
+impl TextToSpeechProvider for AzureOpenAI {
+    async fn synthesize(&self, request: &SpeechRequest) -> Result<Vec<u8>, LLMError> {
+        let response = self.speech_request(request)?.send().await?;
+        let response = check_response_status(response).await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    async fn synthesize_stream(&self, request: &SpeechRequest) -> Result<AudioStream, LLMError> {
+        let response = self.speech_request(request)?.send().await?;
+        let response = check_response_status(response).await?;
+        Ok(audio_byte_stream(response))
+    }
+}
+
+/// Requires the client to point at an image model deployment.
+#[async_trait]
+impl ImageGenerationProvider for AzureOpenAI {
+    async fn generate_images(
+        &self,
+        request: &ImageGenerationRequest,
+    ) -> Result<Vec<GeneratedImage>, LLMError> {
+        let response = self
+            .deployment_request("images/generations")?
+            .json(&OpenAIImageRequest::new(request, None))
+            .send()
+            .await?;
+        let response = check_response_status(response).await?;
+        response.json::<OpenAIImageResponse>().await?.into_images()
+    }
+}
+
+impl LLMProvider for AzureOpenAI {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&crate::builder::LLMBackend::AzureOpenAI, &self.model)
+    }
+}
 
 #[async_trait]
 impl ModelsProvider for AzureOpenAI {}
 
 impl LLMBuilder<AzureOpenAI> {
     pub fn build(self) -> Result<Arc<AzureOpenAI>, LLMError> {
+        self.ensure_unvalidated()?;
         let endpoint = self.base_url.ok_or_else(|| {
             LLMError::InvalidRequest("No API endpoint provided for Azure OpenAI".into())
         })?;
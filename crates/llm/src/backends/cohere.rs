@@ -0,0 +1,768 @@
+//! Cohere API client implementation for chat, embedding, reranking and model listing.
+//!
+//! This module provides integration with Cohere's models through their v2 API.
+
+use crate::builder::LLMBuilder;
+use crate::chat::{StreamLineDecoder, StructuredOutputFormat, ToolChoice, Usage};
+use crate::models::{ModelListRawEntry, ModelListRequest, ModelListResponse};
+use crate::{
+    builder::LLMBackend,
+    chat::{ChatMessage, ChatProvider, ChatResponse, ChatRole, MessageType, StreamResponse, Tool},
+    completion::{CompletionProvider, CompletionRequest, CompletionResponse},
+    embedding::EmbeddingProvider,
+    error::LLMError,
+    models::ModelsProvider,
+    LLMProvider, ToolCall,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use futures::stream::Stream;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Default base URL of Cohere's API
+const DEFAULT_BASE_URL: &str = "https://api.cohere.com";
+const DEFAULT_MODEL: &str = "command-a-03-2025";
+const DEFAULT_RERANK_MODEL: &str = "rerank-v3.5";
+/// Embeddings are stored as documents unless configured otherwise
+const DEFAULT_EMBEDDING_INPUT_TYPE: &str = "search_document";
+
+/// Client for interacting with Cohere's API.
+pub struct Cohere {
+    pub api_key: String,
+    /// Base URL of the API, overridable through the builder
+    pub base_url: String,
+    pub model: String,
+    pub max_tokens: Option<u32>,
+    pub temperature: Option<f32>,
+    pub system: Option<String>,
+    pub timeout_seconds: Option<u64>,
+    pub top_p: Option<f32>,
+    pub top_k: Option<u32>,
+    pub tool_choice: Option<ToolChoice>,
+    /// Model used by [`Cohere::rerank`]
+    pub rerank_model: String,
+    /// `input_type` sent with embedding requests, e.g. `search_document` or `search_query`
+    pub embedding_input_type: String,
+    pub embedding_dimensions: Option<u32>,
+    client: Client,
+}
+
+#[derive(Serialize, Debug)]
+struct CohereChatMessage<'a> {
+    role: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<CohereContent<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<&'a [ToolCall]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<&'a str>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+enum CohereContent<'a> {
+    Text(&'a str),
+    Parts(Vec<CohereContentPart<'a>>),
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum CohereContentPart<'a> {
+    Text { text: &'a str },
+    ImageUrl { image_url: CohereImageUrl },
+}
+
+#[derive(Serialize, Debug)]
+struct CohereImageUrl {
+    url: String,
+}
+
+#[derive(Serialize, Debug)]
+struct CohereResponseFormat {
+    #[serde(rename = "type")]
+    format_type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    json_schema: Option<Value>,
+}
+
+#[derive(Serialize, Debug)]
+struct CohereChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<CohereChatMessage<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<&'a [Tool]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<CohereResponseFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    k: Option<u32>,
+    stream: bool,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct CohereTokens {
+    #[serde(default)]
+    input_tokens: Option<f64>,
+    #[serde(default)]
+    output_tokens: Option<f64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct CohereUsage {
+    #[serde(default)]
+    tokens: Option<CohereTokens>,
+}
+
+impl CohereUsage {
+    fn to_usage(&self) -> Option<Usage> {
+        let tokens = self.tokens.as_ref()?;
+        let prompt_tokens = tokens.input_tokens.unwrap_or(0.0) as u32;
+        let completion_tokens = tokens.output_tokens.unwrap_or(0.0) as u32;
+        Some(Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            completion_tokens_details: None,
+            prompt_tokens_details: None,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct CohereResponseContent {
+    #[serde(rename = "type")]
+    content_type: String,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CohereResponseMessage {
+    #[serde(default)]
+    content: Vec<CohereResponseContent>,
+    #[serde(default)]
+    tool_calls: Option<Vec<ToolCall>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CohereChatResponse {
+    message: CohereResponseMessage,
+    #[serde(default)]
+    usage: Option<CohereUsage>,
+}
+
+impl std::fmt::Display for CohereChatResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(tool_calls) = &self.message.tool_calls {
+            for tool_call in tool_calls {
+                write!(f, "{tool_call:?}")?;
+            }
+        }
+        write!(f, "{}", self.text().unwrap_or_default())
+    }
+}
+
+impl ChatResponse for CohereChatResponse {
+    fn text(&self) -> Option<String> {
+        let text: String = self
+            .message
+            .content
+            .iter()
+            .filter(|c| c.content_type == "text")
+            .filter_map(|c| c.text.as_deref())
+            .collect();
+        (!text.is_empty()).then_some(text)
+    }
+
+    fn tool_calls(&self) -> Option<Vec<ToolCall>> {
+        self.message
+            .tool_calls
+            .clone()
+            .filter(|calls| !calls.is_empty())
+    }
+
+    fn usage(&self) -> Option<Usage> {
+        self.usage.as_ref().and_then(CohereUsage::to_usage)
+    }
+}
+
+#[derive(Serialize)]
+struct CohereEmbedRequest<'a> {
+    model: &'a str,
+    texts: Vec<String>,
+    input_type: &'a str,
+    embedding_types: [&'static str; 1],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_dimension: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct CohereEmbeddings {
+    float: Vec<Vec<f32>>,
+}
+
+#[derive(Deserialize)]
+struct CohereEmbedResponse {
+    embeddings: CohereEmbeddings,
+}
+
+#[derive(Serialize)]
+struct CohereRerankRequest<'a> {
+    model: &'a str,
+    query: &'a str,
+    documents: &'a [&'a str],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_n: Option<usize>,
+}
+
+/// Relevance of one document returned by [`Cohere::rerank`]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CohereRerankResult {
+    /// Position of the document in the request
+    pub index: usize,
+    /// Relevance to the query, in `[0, 1]`
+    pub relevance_score: f32,
+}
+
+#[derive(Deserialize)]
+struct CohereRerankResponse {
+    results: Vec<CohereRerankResult>,
+}
+
+/// Model entry of Cohere's model list
+#[derive(Clone, Debug, Deserialize)]
+pub struct CohereModelEntry {
+    pub name: String,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+impl ModelListRawEntry for CohereModelEntry {
+    fn get_id(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_created_at(&self) -> DateTime<Utc> {
+        DateTime::default()
+    }
+
+    fn get_raw(&self) -> Value {
+        self.extra.clone()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CohereModelListResponse {
+    pub models: Vec<CohereModelEntry>,
+}
+
+impl ModelListResponse for CohereModelListResponse {
+    fn get_models(&self) -> Vec<String> {
+        self.models.iter().map(|m| m.name.clone()).collect()
+    }
+
+    fn get_models_raw(&self) -> Vec<Box<dyn ModelListRawEntry>> {
+        self.models
+            .iter()
+            .map(|m| Box::new(m.clone()) as Box<dyn ModelListRawEntry>)
+            .collect()
+    }
+
+    fn get_backend(&self) -> LLMBackend {
+        LLMBackend::Cohere
+    }
+}
+
+impl Cohere {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        api_key: impl Into<String>,
+        model: Option<String>,
+        max_tokens: Option<u32>,
+        temperature: Option<f32>,
+        timeout_seconds: Option<u64>,
+        system: Option<String>,
+        top_p: Option<f32>,
+        top_k: Option<u32>,
+        tool_choice: Option<ToolChoice>,
+        embedding_dimensions: Option<u32>,
+    ) -> Self {
+        let mut builder = Client::builder();
+        if let Some(sec) = timeout_seconds {
+            builder = builder.timeout(std::time::Duration::from_secs(sec));
+        }
+        Self {
+            api_key: api_key.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            model: model.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            max_tokens,
+            temperature,
+            system,
+            timeout_seconds,
+            top_p,
+            top_k,
+            tool_choice,
+            rerank_model: DEFAULT_RERANK_MODEL.to_string(),
+            embedding_input_type: DEFAULT_EMBEDDING_INPUT_TYPE.to_string(),
+            embedding_dimensions,
+            client: builder.build().expect("Failed to build reqwest Client"),
+        }
+    }
+
+    /// Scores `documents` by relevance to `query`, best first, keeping at most `top_n`.
+    pub async fn rerank(
+        &self,
+        query: &str,
+        documents: &[&str],
+        top_n: Option<usize>,
+    ) -> Result<Vec<CohereRerankResult>, LLMError> {
+        self.check_api_key()?;
+        let body = CohereRerankRequest {
+            model: &self.rerank_model,
+            query,
+            documents,
+            top_n,
+        };
+        let resp = self
+            .request(reqwest::Method::POST, "v2/rerank")
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+        let json_resp: CohereRerankResponse = resp.json().await?;
+        Ok(json_resp.results)
+    }
+
+    fn check_api_key(&self) -> Result<(), LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing Cohere API key".to_string()));
+        }
+        Ok(())
+    }
+
+    /// A request to `path` under the base URL, with auth and timeout applied
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let url = format!("{}/{path}", self.base_url.trim_end_matches('/'));
+        let mut request = self.client.request(method, url).bearer_auth(&self.api_key);
+        if let Some(timeout) = self.timeout_seconds {
+            request = request.timeout(std::time::Duration::from_secs(timeout));
+        }
+        request
+    }
+
+    /// Convert chat messages to Cohere's format, after the system prompt if one is set
+    fn chat_messages<'a>(&'a self, messages: &'a [ChatMessage]) -> Vec<CohereChatMessage<'a>> {
+        let mut cohere_msgs = Vec::with_capacity(messages.len() + 1);
+        if let Some(system) = &self.system {
+            cohere_msgs.push(CohereChatMessage {
+                role: "system",
+                content: Some(CohereContent::Text(system)),
+                tool_calls: None,
+                tool_call_id: None,
+            });
+        }
+        for msg in messages.iter().filter(|m| !m.is_cache_breakpoint()) {
+            let role = match msg.role {
+                ChatRole::User => "user",
+                ChatRole::Assistant => "assistant",
+                ChatRole::System => "system",
+                ChatRole::Tool => "tool",
+            };
+            match &msg.message_type {
+                MessageType::ToolResult(results) => {
+                    cohere_msgs.extend(results.iter().map(|result| CohereChatMessage {
+                        role: "tool",
+                        content: Some(CohereContent::Text(&result.function.arguments)),
+                        tool_calls: None,
+                        tool_call_id: Some(&result.id),
+                    }));
+                }
+                MessageType::ToolUse(calls) => cohere_msgs.push(CohereChatMessage {
+                    role,
+                    content: None,
+                    tool_calls: Some(calls),
+                    tool_call_id: None,
+                }),
+                MessageType::Image((mime, data)) => cohere_msgs.push(CohereChatMessage {
+                    role,
+                    content: Some(CohereContent::Parts(vec![
+                        CohereContentPart::Text { text: &msg.content },
+                        CohereContentPart::ImageUrl {
+                            image_url: CohereImageUrl {
+                                url: format!(
+                                    "data:{};base64,{}",
+                                    mime.mime_type(),
+                                    BASE64.encode(data)
+                                ),
+                            },
+                        },
+                    ])),
+                    tool_calls: None,
+                    tool_call_id: None,
+                }),
+                MessageType::ImageURL(url) => cohere_msgs.push(CohereChatMessage {
+                    role,
+                    content: Some(CohereContent::Parts(vec![
+                        CohereContentPart::Text { text: &msg.content },
+                        CohereContentPart::ImageUrl {
+                            image_url: CohereImageUrl { url: url.clone() },
+                        },
+                    ])),
+                    tool_calls: None,
+                    tool_call_id: None,
+                }),
+                MessageType::Text | MessageType::Pdf { .. } => {
+                    cohere_msgs.push(CohereChatMessage {
+                        role,
+                        content: Some(CohereContent::Text(&msg.content)),
+                        tool_calls: None,
+                        tool_call_id: None,
+                    })
+                }
+                MessageType::CacheBreakpoint => {}
+            }
+        }
+        cohere_msgs
+    }
+
+    fn chat_request<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
+        tools: Option<&'a [Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+        stream: bool,
+    ) -> CohereChatRequest<'a> {
+        // Cohere can only require some tool call, not a specific one
+        let tool_choice = tools.and(match self.tool_choice {
+            Some(ToolChoice::Any) | Some(ToolChoice::Tool(_)) => Some("REQUIRED"),
+            Some(ToolChoice::None) => Some("NONE"),
+            Some(ToolChoice::Auto) | None => None,
+        });
+        CohereChatRequest {
+            model: &self.model,
+            messages: self.chat_messages(messages),
+            tools,
+            tool_choice,
+            response_format: json_schema.map(|format| CohereResponseFormat {
+                format_type: "json_object",
+                json_schema: format.schema,
+            }),
+            max_tokens: self.max_tokens,
+            temperature: self.temperature,
+            p: self.top_p,
+            k: self.top_k,
+            stream,
+        }
+    }
+
+    async fn send_chat(&self, body: &CohereChatRequest<'_>) -> Result<reqwest::Response, LLMError> {
+        self.check_api_key()?;
+        if log::log_enabled!(log::Level::Trace) {
+            if let Ok(json) = serde_json::to_string(body) {
+                log::trace!("Cohere request payload: {json}");
+            }
+        }
+        let response = self
+            .request(reqwest::Method::POST, "v2/chat")
+            .json(body)
+            .send()
+            .await?;
+        log::debug!("Cohere HTTP status: {}", response.status());
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(LLMError::ResponseFormatError {
+                message: format!("Cohere API returned error status: {status}"),
+                raw_response: error_text,
+            });
+        }
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl ChatProvider for Cohere {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<Box<dyn ChatResponse>, LLMError> {
+        let body = self.chat_request(messages, tools, json_schema, false);
+        let response = self.send_chat(&body).await?;
+        let resp_text = response.text().await?;
+        match serde_json::from_str::<CohereChatResponse>(&resp_text) {
+            Ok(response) => Ok(Box::new(response)),
+            Err(e) => Err(LLMError::ResponseFormatError {
+                message: format!("Failed to decode Cohere API response: {e}"),
+                raw_response: resp_text,
+            }),
+        }
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<std::pin::Pin<Box<dyn Stream<Item = Result<String, LLMError>> + Send>>, LLMError>
+    {
+        let struct_stream = self
+            .chat_stream_struct(messages, tools, json_schema)
+            .await?;
+        Ok(crate::chat::content_stream(struct_stream))
+    }
+
+    /// Sends a streaming chat request to Cohere's API, yielding text, tool calls and, at
+    /// the end of the stream, usage.
+    async fn chat_stream_struct(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<
+        std::pin::Pin<Box<dyn Stream<Item = Result<StreamResponse, LLMError>> + Send>>,
+        LLMError,
+    > {
+        let body = self.chat_request(messages, tools, json_schema, true);
+        let response = self.send_chat(&body).await?;
+        Ok(crate::chat::create_line_stream(
+            response,
+            CohereStreamDecoder,
+        ))
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct CohereStreamContent {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct CohereStreamFunction {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct CohereStreamToolCall {
+    #[serde(default)]
+    function: Option<CohereStreamFunction>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct CohereStreamMessage {
+    #[serde(default)]
+    content: Option<CohereStreamContent>,
+    #[serde(default)]
+    tool_calls: Option<CohereStreamToolCall>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct CohereStreamDelta {
+    #[serde(default)]
+    message: Option<CohereStreamMessage>,
+    #[serde(default)]
+    usage: Option<CohereUsage>,
+}
+
+/// Event of Cohere's streaming chat API
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum CohereStreamEvent {
+    ContentDelta {
+        #[serde(default)]
+        delta: CohereStreamDelta,
+    },
+    ToolCallStart {
+        #[serde(default)]
+        index: usize,
+        #[serde(default)]
+        delta: CohereStreamDelta,
+    },
+    ToolCallDelta {
+        #[serde(default)]
+        index: usize,
+        #[serde(default)]
+        delta: CohereStreamDelta,
+    },
+    MessageEnd {
+        #[serde(default)]
+        delta: CohereStreamDelta,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Decodes the events of Cohere's streaming chat API.
+///
+/// Tool calls keep the index Cohere gives them, and usage is emitted with the final
+/// `message-end` event.
+struct CohereStreamDecoder;
+
+impl StreamLineDecoder for CohereStreamDecoder {
+    fn decode_line(&mut self, line: &str) -> Vec<Result<StreamResponse, LLMError>> {
+        let Some(data) = crate::chat::sse_data(line) else {
+            return Vec::new();
+        };
+        let Ok(event) = serde_json::from_str::<CohereStreamEvent>(data) else {
+            return Vec::new();
+        };
+        let function = |delta: CohereStreamDelta| {
+            delta
+                .message
+                .and_then(|m| m.tool_calls)
+                .and_then(|c| c.function)
+                .unwrap_or_default()
+        };
+        match event {
+            CohereStreamEvent::ContentDelta { delta } => delta
+                .message
+                .and_then(|m| m.content)
+                .and_then(|c| c.text)
+                .filter(|text| !text.is_empty())
+                .map(|text| Ok(StreamResponse::from_content(text)))
+                .into_iter()
+                .collect(),
+            CohereStreamEvent::ToolCallStart { index, delta } => {
+                let function = function(delta);
+                vec![Ok(StreamResponse::from_tool_call(
+                    index,
+                    function.name.unwrap_or_default(),
+                    function.arguments.unwrap_or_default(),
+                ))]
+            }
+            CohereStreamEvent::ToolCallDelta { index, delta } => match function(delta).arguments {
+                Some(arguments) if !arguments.is_empty() => {
+                    vec![Ok(StreamResponse::from_tool_call(index, "", arguments))]
+                }
+                _ => Vec::new(),
+            },
+            CohereStreamEvent::MessageEnd { delta } => delta
+                .usage
+                .as_ref()
+                .and_then(CohereUsage::to_usage)
+                .map(|usage| Ok(StreamResponse::from_usage(usage)))
+                .into_iter()
+                .collect(),
+            CohereStreamEvent::Other => Vec::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl CompletionProvider for Cohere {
+    async fn complete(
+        &self,
+        _req: &CompletionRequest,
+        _json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<CompletionResponse, LLMError> {
+        Err(LLMError::ProviderError(
+            "Cohere completion not implemented".into(),
+        ))
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for Cohere {
+    async fn embed(&self, input: Vec<String>) -> Result<Vec<Vec<f32>>, LLMError> {
+        self.check_api_key()?;
+        let body = CohereEmbedRequest {
+            model: &self.model,
+            texts: input,
+            input_type: &self.embedding_input_type,
+            embedding_types: ["float"],
+            output_dimension: self.embedding_dimensions,
+        };
+        let resp = self
+            .request(reqwest::Method::POST, "v2/embed")
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+        let json_resp: CohereEmbedResponse = resp.json().await?;
+        Ok(json_resp.embeddings.float)
+    }
+}
+
+#[async_trait]
+impl ModelsProvider for Cohere {
+    async fn list_models(
+        &self,
+        _request: Option<&ModelListRequest>,
+    ) -> Result<Box<dyn ModelListResponse>, LLMError> {
+        self.check_api_key()?;
+        let resp = self
+            .request(reqwest::Method::GET, "v1/models")
+            .send()
+            .await?
+            .error_for_status()?;
+        let result: CohereModelListResponse = resp.json().await?;
+        Ok(Box::new(result))
+    }
+}
+
+impl LLMProvider for Cohere {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&LLMBackend::Cohere, &self.model)
+    }
+}
+
+impl LLMBuilder<Cohere> {
+    /// Set the model used by [`Cohere::rerank`].
+    pub fn rerank_model(mut self, model: impl Into<String>) -> Self {
+        self.cohere_rerank_model = Some(model.into());
+        self
+    }
+
+    /// Set the `input_type` of embedding requests, e.g. `search_query` for queries.
+    pub fn embedding_input_type(mut self, input_type: impl Into<String>) -> Self {
+        self.cohere_embedding_input_type = Some(input_type.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Arc<Cohere>, LLMError> {
+        self.ensure_unvalidated()?;
+        let api_key = self.api_key.ok_or_else(|| {
+            LLMError::InvalidRequest("No API key provided for Cohere".to_string())
+        })?;
+
+        let mut cohere = Cohere::new(
+            api_key,
+            self.model,
+            self.max_tokens,
+            self.temperature,
+            self.timeout_seconds,
+            self.system,
+            self.top_p,
+            self.top_k,
+            self.tool_choice,
+            self.embedding_dimensions,
+        );
+        if let Some(base_url) = self.base_url {
+            cohere.base_url = base_url;
+        }
+        if let Some(model) = self.cohere_rerank_model {
+            cohere.rerank_model = model;
+        }
+        if let Some(input_type) = self.cohere_embedding_input_type {
+            cohere.embedding_input_type = input_type;
+        }
+
+        Ok(Arc::new(cohere))
+    }
+}
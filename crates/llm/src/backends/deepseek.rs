@@ -6,7 +6,8 @@ use crate::chat::StructuredOutputFormat;
 use crate::ToolCall;
 use crate::{
     builder::LLMBuilder,
-    chat::{ChatResponse, Tool},
+    chat::{ChatResponse, StreamResponse, Tool},
+    providers::openai_compatible::{create_sse_stream, OpenAIStreamOptions},
 };
 use crate::{
     chat::{ChatMessage, ChatProvider, ChatRole},
@@ -17,12 +18,18 @@ use crate::{
     LLMProvider,
 };
 use async_trait::async_trait;
+use futures::stream::Stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+/// Default base URL of DeepSeek's API
+const DEFAULT_BASE_URL: &str = "https://api.deepseek.com/v1";
+
 pub struct DeepSeek {
     pub api_key: String,
+    /// Base URL of the API, overridable through the builder
+    pub base_url: String,
     pub model: String,
     pub max_tokens: Option<u32>,
     pub temperature: Option<f32>,
@@ -44,6 +51,8 @@ struct DeepSeekChatRequest<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
     stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<OpenAIStreamOptions>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -97,6 +106,7 @@ impl DeepSeek {
         }
         Self {
             api_key: api_key.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
             model: model.unwrap_or("deepseek-chat".to_string()),
             max_tokens,
             temperature,
@@ -105,6 +115,46 @@ impl DeepSeek {
             client: builder.build().expect("Failed to build reqwest Client"),
         }
     }
+
+    /// Convert chat messages to DeepSeek's format, after the system prompt if one is set
+    fn chat_messages<'a>(&'a self, messages: &'a [ChatMessage]) -> Vec<DeepSeekChatMessage<'a>> {
+        let mut deepseek_msgs: Vec<DeepSeekChatMessage> = messages
+            .iter()
+            .filter(|m| !m.is_cache_breakpoint())
+            .map(|m| DeepSeekChatMessage {
+                role: match m.role {
+                    ChatRole::User => "user",
+                    ChatRole::Assistant => "assistant",
+                    ChatRole::Tool => "tool",
+                    ChatRole::System => "system",
+                },
+                content: &m.content,
+            })
+            .collect();
+
+        if let Some(system) = &self.system {
+            deepseek_msgs.insert(
+                0,
+                DeepSeekChatMessage {
+                    role: "system",
+                    content: system,
+                },
+            );
+        }
+
+        deepseek_msgs
+    }
+
+    /// Builds a POST request to the chat completions endpoint
+    fn chat_request(&self, body: &DeepSeekChatRequest) -> reqwest::RequestBuilder {
+        let url = format!("{}/chat/completions", self.base_url.trim_end_matches('/'));
+        let mut request = self.client.post(url).bearer_auth(&self.api_key).json(body);
+
+        if let Some(timeout) = self.timeout_seconds {
+            request = request.timeout(std::time::Duration::from_secs(timeout));
+        }
+        request
+    }
 }
 
 #[async_trait]
@@ -134,34 +184,14 @@ impl ChatProvider for DeepSeek {
             ));
         }
 
-        let mut deepseek_msgs: Vec<DeepSeekChatMessage> = messages
-            .iter()
-            .map(|m| DeepSeekChatMessage {
-                role: match m.role {
-                    ChatRole::User => "user",
-                    ChatRole::Assistant => "assistant",
-                    ChatRole::Tool => "tool",
-                    ChatRole::System => "system",
-                },
-                content: &m.content,
-            })
-            .collect();
-
-        if let Some(system) = &self.system {
-            deepseek_msgs.insert(
-                0,
-                DeepSeekChatMessage {
-                    role: "system",
-                    content: system,
-                },
-            );
-        }
+        let deepseek_msgs = self.chat_messages(messages);
 
         let body = DeepSeekChatRequest {
             model: &self.model,
             messages: deepseek_msgs,
             temperature: self.temperature,
             stream: false,
+            stream_options: None,
         };
 
         if log::log_enabled!(log::Level::Trace) {
@@ -170,17 +200,7 @@ impl ChatProvider for DeepSeek {
             }
         }
 
-        let mut request = self
-            .client
-            .post("https://api.deepseek.com/v1/chat/completions")
-            .bearer_auth(&self.api_key)
-            .json(&body);
-
-        if let Some(timeout) = self.timeout_seconds {
-            request = request.timeout(std::time::Duration::from_secs(timeout));
-        }
-
-        let resp = request.send().await?;
+        let resp = self.chat_request(&body).send().await?;
 
         log::debug!("DeepSeek HTTP status: {}", resp.status());
 
@@ -190,6 +210,73 @@ impl ChatProvider for DeepSeek {
 
         Ok(Box::new(json_resp))
     }
+
+    /// Sends a streaming chat request to DeepSeek's API.
+    ///
+    /// # Arguments
+    ///
+    /// * `messages` - The conversation history as a slice of chat messages
+    ///
+    /// # Returns
+    ///
+    /// A stream of text tokens or an error
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<std::pin::Pin<Box<dyn Stream<Item = Result<String, LLMError>> + Send>>, LLMError>
+    {
+        let struct_stream = self
+            .chat_stream_struct(messages, tools, json_schema)
+            .await?;
+        Ok(crate::chat::content_stream(struct_stream))
+    }
+
+    /// Sends a streaming chat request to DeepSeek's API, yielding text and, at the end
+    /// of the stream, usage.
+    async fn chat_stream_struct(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        _json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<
+        std::pin::Pin<Box<dyn Stream<Item = Result<StreamResponse, LLMError>> + Send>>,
+        LLMError,
+    > {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing DeepSeek API key".to_string()));
+        }
+
+        if tools.is_some() {
+            return Err(LLMError::NoToolSupport(
+                "Currently Dont Support Tools".to_string(),
+            ));
+        }
+
+        let body = DeepSeekChatRequest {
+            model: &self.model,
+            messages: self.chat_messages(messages),
+            temperature: self.temperature,
+            stream: true,
+            stream_options: Some(OpenAIStreamOptions {
+                include_usage: true,
+            }),
+        };
+
+        let response = self.chat_request(&body).send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(LLMError::ResponseFormatError {
+                message: format!("DeepSeek API returned error status: {status}"),
+                raw_response: error_text,
+            });
+        }
+
+        Ok(create_sse_stream(response, true))
+    }
 }
 
 #[async_trait]
@@ -220,15 +307,20 @@ impl EmbeddingProvider for DeepSeek {
 #[async_trait]
 impl ModelsProvider for DeepSeek {}
 
-impl LLMProvider for DeepSeek {}
+impl LLMProvider for DeepSeek {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&crate::builder::LLMBackend::DeepSeek, &self.model)
+    }
+}
 
 impl LLMBuilder<DeepSeek> {
     pub fn build(self) -> Result<Arc<DeepSeek>, LLMError> {
+        self.ensure_unvalidated()?;
         let api_key = self.api_key.ok_or_else(|| {
             LLMError::InvalidRequest("No API key provided for DeepSeek".to_string())
         })?;
 
-        let deepseek = DeepSeek::new(
+        let mut deepseek = DeepSeek::new(
             api_key,
             self.model,
             self.max_tokens,
@@ -236,6 +328,9 @@ impl LLMBuilder<DeepSeek> {
             self.timeout_seconds,
             self.system,
         );
+        if let Some(base_url) = self.base_url {
+            deepseek.base_url = base_url;
+        }
 
         Ok(Arc::new(deepseek))
     }
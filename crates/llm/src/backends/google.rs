@@ -16,12 +16,16 @@
 use crate::{
     builder::LLMBuilder,
     chat::{
-        ChatMessage, ChatProvider, ChatResponse, ChatRole, MessageType, StructuredOutputFormat,
-        Tool,
+        ChatMessage, ChatProvider, ChatResponse, ChatRole, MessageType, StreamLineDecoder,
+        StreamResponse, StructuredOutputFormat, Tool, Usage,
     },
     completion::{CompletionProvider, CompletionRequest, CompletionResponse},
     embedding::EmbeddingProvider,
     error::LLMError,
+    images::{
+        decode_image, sniff_image_mime, GeneratedImage, ImageGenerationProvider,
+        ImageGenerationRequest,
+    },
     models::ModelsProvider,
     FunctionCall, LLMProvider, ToolCall,
 };
@@ -33,6 +37,21 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
 
+/// Default base URL of the Gemini API
+const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// Imagen model used when an image request doesn't name one
+const DEFAULT_IMAGE_MODEL: &str = "imagen-3.0-generate-002";
+
+/// Aspect ratios accepted by Imagen
+const IMAGEN_ASPECT_RATIOS: [(&str, f32); 5] = [
+    ("1:1", 1.0),
+    ("3:4", 0.75),
+    ("4:3", 4.0 / 3.0),
+    ("9:16", 9.0 / 16.0),
+    ("16:9", 16.0 / 9.0),
+];
+
 /// Client for interacting with Google's Gemini API.
 ///
 /// This struct holds the configuration and state needed to make requests to the Gemini API.
@@ -41,6 +60,8 @@ use std::sync::Arc;
 pub struct Google {
     /// API key for authentication with Google's API
     pub api_key: String,
+    /// Base URL of the Gemini API, overridable through the builder
+    pub base_url: String,
     /// Model identifier (e.g. "gemini-1.5-flash")
     pub model: String,
     /// Maximum number of tokens to generate in responses
@@ -89,6 +110,7 @@ enum GoogleContentPart<'a> {
     #[serde(rename = "text")]
     Text(&'a str),
     InlineData(GoogleInlineData),
+    FileData(GoogleFileData),
     FunctionCall(GoogleFunctionCall),
     #[serde(rename = "functionResponse")]
     FunctionResponse(GoogleFunctionResponse),
@@ -100,6 +122,13 @@ struct GoogleInlineData {
     data: String,
 }
 
+/// Media referenced by URI instead of being sent inline
+#[derive(Serialize)]
+struct GoogleFileData {
+    mime_type: String,
+    file_uri: String,
+}
+
 /// Configuration parameters for text generation
 #[derive(Serialize)]
 struct GoogleGenerationConfig {
@@ -135,6 +164,21 @@ struct GoogleChatResponse {
 struct GoogleStreamResponse {
     /// Generated completion candidates
     candidates: Option<Vec<GoogleCandidate>>,
+    /// Token counts so far, repeated in every chunk
+    #[serde(rename = "usageMetadata")]
+    usage_metadata: Option<GoogleUsageMetadata>,
+}
+
+/// Token counts of a request
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct GoogleUsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+    #[serde(default)]
+    total_token_count: u32,
 }
 
 impl std::fmt::Display for GoogleChatResponse {
@@ -161,12 +205,13 @@ impl std::fmt::Display for GoogleChatResponse {
 /// Individual completion candidate
 #[derive(Deserialize, Debug)]
 struct GoogleCandidate {
-    /// Content of the candidate response
+    /// Content of the candidate response, absent from the final chunk of some streams
+    #[serde(default)]
     content: GoogleResponseContent,
 }
 
 /// Content block within a response
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
 struct GoogleResponseContent {
     /// Parts making up the content (might be absent when only function calls are present)
     #[serde(default)]
@@ -394,6 +439,95 @@ struct GoogleEmbedding {
     values: Vec<f32>,
 }
 
+/// Request body for Imagen's `predict` endpoint
+#[derive(Serialize)]
+struct ImagenRequest<'a> {
+    instances: Vec<ImagenInstance<'a>>,
+    parameters: ImagenParameters,
+}
+
+#[derive(Serialize)]
+struct ImagenInstance<'a> {
+    prompt: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImagenParameters {
+    sample_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aspect_ratio: Option<&'static str>,
+}
+
+#[derive(Deserialize)]
+struct ImagenResponse {
+    #[serde(default)]
+    predictions: Vec<ImagenPrediction>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImagenPrediction {
+    bytes_base64_encoded: String,
+    mime_type: Option<String>,
+}
+
+/// The supported Imagen aspect ratio closest to `size`, given as `WIDTHxHEIGHT` or `W:H`
+fn imagen_aspect_ratio(size: &str) -> Option<&'static str> {
+    let (width, height) = size.split_once(['x', 'X', ':'])?;
+    let ratio = width.trim().parse::<f32>().ok()? / height.trim().parse::<f32>().ok()?;
+    IMAGEN_ASPECT_RATIOS
+        .iter()
+        .min_by(|a, b| (a.1 - ratio).abs().total_cmp(&(b.1 - ratio).abs()))
+        .map(|(name, _)| *name)
+}
+
+/// Content parts for an image or PDF message, with the message text (if any) after the media
+fn media_parts(msg: &ChatMessage) -> Vec<GoogleContentPart<'_>> {
+    let media = match &msg.message_type {
+        MessageType::Image((image_mime, raw_bytes)) => {
+            GoogleContentPart::InlineData(GoogleInlineData {
+                mime_type: image_mime.mime_type().to_string(),
+                data: BASE64.encode(raw_bytes),
+            })
+        }
+        MessageType::Pdf {
+            data: raw_bytes, ..
+        } => GoogleContentPart::InlineData(GoogleInlineData {
+            mime_type: "application/pdf".to_string(),
+            data: BASE64.encode(raw_bytes),
+        }),
+        MessageType::ImageURL(url) => GoogleContentPart::FileData(GoogleFileData {
+            mime_type: image_mime_from_url(url).to_string(),
+            file_uri: url.clone(),
+        }),
+        _ => return vec![GoogleContentPart::Text(&msg.content)],
+    };
+    let mut parts = vec![media];
+    if !msg.content.is_empty() {
+        parts.push(GoogleContentPart::Text(&msg.content));
+    }
+    parts
+}
+
+/// Guess the MIME type of an image from its URL, since Gemini requires one for file data
+fn image_mime_from_url(url: &str) -> &'static str {
+    let path = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or(url)
+        .to_ascii_lowercase();
+    if path.ends_with(".png") {
+        "image/png"
+    } else if path.ends_with(".gif") {
+        "image/gif"
+    } else if path.ends_with(".webp") {
+        "image/webp"
+    } else {
+        "image/jpeg"
+    }
+}
+
 impl Google {
     /// Creates a new Google Gemini client with the specified configuration.
     ///
@@ -431,6 +565,7 @@ impl Google {
         }
         Self {
             api_key: api_key.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
             model: model.unwrap_or_else(|| "gemini-1.5-flash".to_string()),
             max_tokens,
             temperature,
@@ -442,26 +577,22 @@ impl Google {
         }
     }
 
-    /// Sends a chat request to Google's Gemini API with tools.
-    ///
-    /// # Arguments
-    ///
-    /// * `messages` - The conversation history as a slice of chat messages
-    /// * `tools` - Optional slice of tools to use in the chat
-    ///
-    /// # Returns
-    ///
-    /// The provider's response text or an error
-    async fn chat_with_tools(
-        &self,
-        messages: &[ChatMessage],
+    /// URL of a method of the configured model, e.g. `generateContent`
+    fn model_url(&self, method: &str) -> String {
+        format!(
+            "{}/models/{}:{method}",
+            self.base_url.trim_end_matches('/'),
+            self.model
+        )
+    }
+
+    /// Builds the request body shared by regular and streaming chat requests.
+    fn build_chat_request<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
         tools: Option<&[Tool]>,
         json_schema: Option<StructuredOutputFormat>,
-    ) -> Result<Box<dyn ChatResponse>, LLMError> {
-        if self.api_key.is_empty() {
-            return Err(LLMError::AuthError("Missing Google API key".to_string()));
-        }
-
+    ) -> GoogleChatRequest<'a> {
         let mut chat_contents = Vec::with_capacity(messages.len());
 
         // Add system message if present
@@ -473,7 +604,7 @@ impl Google {
         }
 
         // Add conversation messages in pairs to maintain context
-        for msg in messages {
+        for msg in messages.iter().filter(|m| !m.is_cache_breakpoint()) {
             // For tool results, we need to use "function" role
             let role = match &msg.message_type {
                 MessageType::ToolResult(_) => "function",
@@ -489,18 +620,8 @@ impl Google {
                 role,
                 parts: match &msg.message_type {
                     MessageType::Text => vec![GoogleContentPart::Text(&msg.content)],
-                    MessageType::Image((image_mime, raw_bytes)) => {
-                        vec![GoogleContentPart::InlineData(GoogleInlineData {
-                            mime_type: image_mime.mime_type().to_string(),
-                            data: BASE64.encode(raw_bytes),
-                        })]
-                    }
-                    MessageType::ImageURL(_) => unimplemented!(),
-                    MessageType::Pdf(raw_bytes) => {
-                        vec![GoogleContentPart::InlineData(GoogleInlineData {
-                            mime_type: "application/pdf".to_string(),
-                            data: BASE64.encode(raw_bytes),
-                        })]
+                    MessageType::Image(_) | MessageType::ImageURL(_) | MessageType::Pdf { .. } => {
+                        media_parts(msg)
                     }
                     MessageType::ToolUse(calls) => calls
                         .iter()
@@ -528,6 +649,7 @@ impl Google {
                             })
                         })
                         .collect(),
+                    MessageType::CacheBreakpoint => Vec::new(),
                 },
             });
         }
@@ -570,11 +692,34 @@ impl Google {
             })
         };
 
-        let req_body = GoogleChatRequest {
+        GoogleChatRequest {
             contents: chat_contents,
             generation_config,
             tools: google_tools,
-        };
+        }
+    }
+
+    /// Sends a chat request to Google's Gemini API with tools.
+    ///
+    /// # Arguments
+    ///
+    /// * `messages` - The conversation history as a slice of chat messages
+    /// * `tools` - Optional slice of tools to use in the chat
+    ///
+    /// # Returns
+    ///
+    /// The provider's response text or an error
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<Box<dyn ChatResponse>, LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing Google API key".to_string()));
+        }
+
+        let req_body = self.build_chat_request(messages, tools, json_schema);
 
         if log::log_enabled!(log::Level::Trace) {
             if let Ok(json) = serde_json::to_string(&req_body) {
@@ -583,8 +728,8 @@ impl Google {
         }
 
         let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{model}:generateContent?key={key}",
-            model = self.model,
+            "{}?key={key}",
+            self.model_url("generateContent"),
             key = self.api_key
         );
 
@@ -652,78 +797,36 @@ impl ChatProvider for Google {
     async fn chat_stream(
         &self,
         messages: &[ChatMessage],
-        _tools: Option<&[Tool]>,
-        _json_schema: Option<StructuredOutputFormat>,
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
     ) -> Result<std::pin::Pin<Box<dyn Stream<Item = Result<String, LLMError>> + Send>>, LLMError>
     {
+        let struct_stream = self
+            .chat_stream_struct(messages, tools, json_schema)
+            .await?;
+        Ok(crate::chat::content_stream(struct_stream))
+    }
+
+    /// Sends a streaming chat request to Google's Gemini API, yielding text, function
+    /// calls and, once the stream ends, token usage.
+    async fn chat_stream_struct(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<
+        std::pin::Pin<Box<dyn Stream<Item = Result<StreamResponse, LLMError>> + Send>>,
+        LLMError,
+    > {
         if self.api_key.is_empty() {
             return Err(LLMError::AuthError("Missing Google API key".to_string()));
         }
 
-        let mut chat_contents = Vec::with_capacity(messages.len());
-
-        if let Some(system) = &self.system {
-            chat_contents.push(GoogleChatContent {
-                role: "user",
-                parts: vec![GoogleContentPart::Text(system)],
-            });
-        }
-
-        for msg in messages {
-            let role = match msg.role {
-                ChatRole::User => "user",
-                ChatRole::Assistant => "model",
-                ChatRole::Tool => "tool",
-                ChatRole::System => "system",
-            };
-
-            chat_contents.push(GoogleChatContent {
-                role,
-                parts: match &msg.message_type {
-                    MessageType::Text => vec![GoogleContentPart::Text(&msg.content)],
-                    MessageType::Image((image_mime, raw_bytes)) => {
-                        vec![GoogleContentPart::InlineData(GoogleInlineData {
-                            mime_type: image_mime.mime_type().to_string(),
-                            data: BASE64.encode(raw_bytes),
-                        })]
-                    }
-                    MessageType::Pdf(raw_bytes) => {
-                        vec![GoogleContentPart::InlineData(GoogleInlineData {
-                            mime_type: "application/pdf".to_string(),
-                            data: BASE64.encode(raw_bytes),
-                        })]
-                    }
-                    _ => vec![GoogleContentPart::Text(&msg.content)],
-                },
-            });
-        }
-
-        let generation_config = if self.max_tokens.is_none()
-            && self.temperature.is_none()
-            && self.top_p.is_none()
-            && self.top_k.is_none()
-        {
-            None
-        } else {
-            Some(GoogleGenerationConfig {
-                max_output_tokens: self.max_tokens,
-                temperature: self.temperature,
-                top_p: self.top_p,
-                top_k: self.top_k,
-                response_mime_type: None,
-                response_schema: None,
-            })
-        };
-
-        let req_body = GoogleChatRequest {
-            contents: chat_contents,
-            generation_config,
-            tools: None,
-        };
+        let req_body = self.build_chat_request(messages, tools, json_schema);
 
         let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{model}:streamGenerateContent?alt=sse&key={key}",
-            model = self.model,
+            "{}?alt=sse&key={key}",
+            self.model_url("streamGenerateContent"),
             key = self.api_key
         );
 
@@ -744,9 +847,9 @@ impl ChatProvider for Google {
             });
         }
 
-        Ok(crate::chat::create_sse_stream(
+        Ok(crate::chat::create_line_stream(
             response,
-            parse_google_sse_chunk,
+            GoogleStreamDecoder::default(),
         ))
     }
 }
@@ -797,7 +900,8 @@ impl EmbeddingProvider for Google {
             };
 
             let url = format!(
-                "https://generativelanguage.googleapis.com/v1beta/models/text-embedding-004:embedContent?key={}",
+                "{}/models/text-embedding-004:embedContent?key={}",
+                self.base_url.trim_end_matches('/'),
                 self.api_key
             );
 
@@ -817,43 +921,125 @@ impl EmbeddingProvider for Google {
     }
 }
 
-impl LLMProvider for Google {}
+/// Image generation with Imagen. Imagen doesn't host images, so bytes are returned
+/// whatever [`ImageResponseFormat`](crate::images::ImageResponseFormat) is requested.
+#[async_trait]
+impl ImageGenerationProvider for Google {
+    async fn generate_images(
+        &self,
+        request: &ImageGenerationRequest,
+    ) -> Result<Vec<GeneratedImage>, LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing Google API key".to_string()));
+        }
+        let body = ImagenRequest {
+            instances: vec![ImagenInstance {
+                prompt: &request.prompt,
+            }],
+            parameters: ImagenParameters {
+                sample_count: request.n,
+                aspect_ratio: request.size.as_deref().and_then(imagen_aspect_ratio),
+            },
+        };
+        let url = format!(
+            "{}/models/{}:predict?key={}",
+            self.base_url.trim_end_matches('/'),
+            request.model.as_deref().unwrap_or(DEFAULT_IMAGE_MODEL),
+            self.api_key
+        );
+        let mut http_request = self.client.post(&url).json(&body);
+        if let Some(timeout) = self.timeout_seconds {
+            http_request = http_request.timeout(std::time::Duration::from_secs(timeout));
+        }
+        let response: ImagenResponse = http_request
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        response
+            .predictions
+            .into_iter()
+            .map(|prediction| {
+                let data = decode_image(&prediction.bytes_base64_encoded)?;
+                let mime = prediction
+                    .mime_type
+                    .as_deref()
+                    .and_then(crate::chat::ImageMime::from_mime_type)
+                    .unwrap_or_else(|| sniff_image_mime(&data));
+                Ok(GeneratedImage::Bytes { mime, data })
+            })
+            .collect()
+    }
+}
+
+impl LLMProvider for Google {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&crate::builder::LLMBackend::Google, &self.model)
+    }
+}
 
-/// Parses a Server-Sent Events (SSE) chunk from Google's streaming API.
-///
-/// # Arguments
-///
-/// * `chunk` - The raw SSE chunk text
+/// Decodes the events of Gemini's `streamGenerateContent` SSE API.
 ///
-/// # Returns
-///
-/// * `Ok(Some(String))` - Content token if found
-/// * `Ok(None)` - If chunk should be skipped (e.g., ping, done signal)
-/// * `Err(LLMError)` - If parsing fails
-fn parse_google_sse_chunk(chunk: &str) -> Result<Option<String>, LLMError> {
-    for line in chunk.lines() {
-        let line = line.trim();
-
-        if let Some(data) = line.strip_prefix("data: ") {
-            match serde_json::from_str::<GoogleStreamResponse>(data) {
-                Ok(response) => {
-                    if let Some(candidates) = response.candidates {
-                        if let Some(candidate) = candidates.first() {
-                            if let Some(part) = candidate.content.parts.first() {
-                                if !part.text.is_empty() {
-                                    return Ok(Some(part.text.clone()));
-                                }
-                            }
-                        }
-                    }
-                    return Ok(None);
-                }
-                Err(_) => continue,
+/// Gemini sends each function call whole, so every call gets the next tool call index.
+/// Usage is repeated in every chunk and emitted once, when the stream ends.
+#[derive(Default)]
+struct GoogleStreamDecoder {
+    tool_calls: usize,
+    usage: Option<GoogleUsageMetadata>,
+}
+
+impl StreamLineDecoder for GoogleStreamDecoder {
+    fn decode_line(&mut self, line: &str) -> Vec<Result<StreamResponse, LLMError>> {
+        let Some(data) = crate::chat::sse_data(line) else {
+            return Vec::new();
+        };
+        let Ok(response) = serde_json::from_str::<GoogleStreamResponse>(data) else {
+            return Vec::new();
+        };
+        if response.usage_metadata.is_some() {
+            self.usage = response.usage_metadata;
+        }
+
+        let mut results = Vec::new();
+        let parts = response
+            .candidates
+            .into_iter()
+            .flatten()
+            .take(1)
+            .flat_map(|c| c.content.parts);
+        for part in parts {
+            if let Some(call) = part.function_call {
+                let arguments = serde_json::to_string(&call.args).unwrap_or_default();
+                results.push(Ok(StreamResponse::from_tool_call(
+                    self.tool_calls,
+                    call.name,
+                    arguments,
+                )));
+                self.tool_calls += 1;
+            } else if !part.text.is_empty() {
+                results.push(Ok(StreamResponse::from_content(part.text)));
             }
         }
+        results
     }
 
-    Ok(None)
+    fn finish(&mut self) -> Vec<Result<StreamResponse, LLMError>> {
+        self.usage
+            .take()
+            .map(|usage| {
+                Ok(StreamResponse::from_usage(Usage {
+                    prompt_tokens: usage.prompt_token_count,
+                    completion_tokens: usage.candidates_token_count,
+                    total_tokens: usage.total_token_count,
+                    completion_tokens_details: None,
+                    prompt_tokens_details: None,
+                }))
+            })
+            .into_iter()
+            .collect()
+    }
 }
 
 #[async_trait]
@@ -861,11 +1047,12 @@ impl ModelsProvider for Google {}
 
 impl LLMBuilder<Google> {
     pub fn build(self) -> Result<Arc<Google>, LLMError> {
+        self.ensure_unvalidated()?;
         let api_key = self.api_key.ok_or_else(|| {
             LLMError::InvalidRequest("No API key provided for Google".to_string())
         })?;
 
-        let google = Google::new(
+        let mut google = Google::new(
             api_key,
             self.model,
             self.max_tokens,
@@ -875,6 +1062,9 @@ impl LLMBuilder<Google> {
             self.top_p,
             self.top_k,
         );
+        if let Some(base_url) = self.base_url {
+            google.base_url = base_url;
+        }
 
         Ok(Arc::new(google))
     }
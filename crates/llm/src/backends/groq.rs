@@ -4,6 +4,7 @@
 
 use crate::builder::LLMBuilder;
 use crate::{
+    audio::{transcription_form, SpeechToTextProvider, Transcription, TranscriptionRequest},
     builder::LLMBackend,
     chat::{StructuredOutputFormat, ToolChoice},
     completion::{CompletionProvider, CompletionRequest, CompletionResponse},
@@ -85,7 +86,26 @@ impl Groq {
     }
 }
 
-impl LLMProvider for Groq {}
+/// Whisper transcription. Groq does not offer speech synthesis.
+#[async_trait]
+impl SpeechToTextProvider for Groq {
+    async fn transcribe(&self, request: &TranscriptionRequest) -> Result<Transcription, LLMError> {
+        self.check_api_key()?;
+        let response = self
+            .request(reqwest::Method::POST, "audio/transcriptions")?
+            .multipart(transcription_form(request, "whisper-large-v3-turbo")?)
+            .send()
+            .await?;
+        let response = crate::chat::utils::check_response_status(response).await?;
+        Ok(response.json().await?)
+    }
+}
+
+impl LLMProvider for Groq {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&LLMBackend::Groq, &self.model)
+    }
+}
 
 #[async_trait]
 impl CompletionProvider for Groq {
@@ -139,6 +159,7 @@ impl ModelsProvider for Groq {
 
 impl LLMBuilder<Groq> {
     pub fn build(self) -> Result<Arc<Groq>, LLMError> {
+        self.ensure_unvalidated()?;
         let api_key = self
             .api_key
             .ok_or_else(|| LLMError::InvalidRequest("No API key provided for Groq".to_string()))?;
@@ -2,11 +2,14 @@
 //!
 //! This backend uses the liquid-edge inference runtime to run LLM models locally.
 //! It handles tokenization, text generation, and sampling specifically for LLMs.
+//! [`LiquidEdgeEmbedder`] and [`LiquidEdgeCrossEncoder`] run encoder models for
+//! offline embeddings and reranking.
 
 use crate::{
     builder::LLMBuilder,
     chat::{
-        ChatMessage, ChatProvider, ChatResponse, ChatRole, MessageType, StructuredOutputFormat,
+        structured_output::with_schema_instructions, ChatMessage, ChatProvider, ChatResponse,
+        ChatRole, MessageType, StructuredOutputFormat,
     },
     completion::{CompletionProvider, CompletionRequest, CompletionResponse},
     embedding::EmbeddingProvider,
@@ -15,11 +18,16 @@ use crate::{
     LLMProvider, ToolCall,
 };
 use async_trait::async_trait;
-use liquid_edge::{EdgeError, InferenceInput, InferenceRuntime};
+use liquid_edge::{
+    CrossEncoderPipeline, EdgeError, EmbeddingPipeline, InferenceInput, InferenceRuntime,
+};
 use minijinja::{context, Environment};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::{path::Path, sync::Arc};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
 use tokenizers::Tokenizer;
 
 /// LiquidEdge backend for local LLM inference
@@ -259,7 +267,12 @@ impl LiquidEdge {
         }
 
         // Add provided messages
-        all_messages.extend_from_slice(messages);
+        all_messages.extend(
+            messages
+                .iter()
+                .filter(|m| !m.is_cache_breakpoint())
+                .cloned(),
+        );
 
         // Use Jinja2 chat template if available
         match self.apply_jinja_template(&all_messages) {
@@ -522,31 +535,19 @@ impl ChatProvider for LiquidEdge {
     async fn chat(
         &self,
         messages: &[ChatMessage],
-        _tools: Option<&[crate::chat::Tool]>,
+        tools: Option<&[crate::chat::Tool]>,
         json_schema: Option<StructuredOutputFormat>,
     ) -> Result<Box<dyn ChatResponse>, LLMError> {
-        let mut modified_messages = messages.to_vec();
-
-        // Add JSON format instruction if schema is provided
-        if let Some(schema) = &json_schema {
-            let default_schema = serde_json::json!({});
-            let schema_json = schema.schema.as_ref().unwrap_or(&default_schema);
-            let schema_str =
-                serde_json::to_string_pretty(schema_json).unwrap_or_else(|_| "{}".to_string());
-
-            //TODO: Improve
-            let json_instruction = format!(
-                "You must respond with valid JSON that matches this schema: {schema_str}. Only return the JSON, no additional text.").to_string();
-
-            modified_messages.insert(
-                0,
-                ChatMessage {
-                    role: ChatRole::System,
-                    message_type: MessageType::Text,
-                    content: json_instruction,
-                },
-            );
+        if tools.is_some_and(|tools| !tools.is_empty()) {
+            return Err(LLMError::NoToolSupport(
+                "LiquidEdge models do not support native tool calling".into(),
+            ));
         }
+        // Add JSON format instruction if schema is provided
+        let modified_messages = match &json_schema {
+            Some(schema) => with_schema_instructions(messages, schema),
+            None => messages.to_vec(),
+        };
 
         let prompt = self.format_messages(&modified_messages);
 
@@ -593,11 +594,84 @@ impl CompletionProvider for LiquidEdge {
 impl EmbeddingProvider for LiquidEdge {
     async fn embed(&self, _input: Vec<String>) -> Result<Vec<Vec<f32>>, LLMError> {
         Err(LLMError::ProviderError(
-            "Embedding not supported by LiquidEdge backend".to_string(),
+            "Embedding not supported by LiquidEdge generation models, use LiquidEdgeEmbedder"
+                .to_string(),
         ))
     }
 }
 
+/// Local sentence embeddings with an ONNX encoder model such as `all-MiniLM-L6-v2`
+pub struct LiquidEdgeEmbedder {
+    pipeline: Arc<Mutex<EmbeddingPipeline>>,
+}
+
+impl LiquidEdgeEmbedder {
+    pub fn new(pipeline: EmbeddingPipeline) -> Self {
+        Self {
+            pipeline: Arc::new(Mutex::new(pipeline)),
+        }
+    }
+
+    /// Load `model.onnx` and `tokenizer.json` from a model directory
+    pub async fn from_directory<P: AsRef<Path>>(path: P) -> Result<Self, LLMError> {
+        Ok(Self::new(EmbeddingPipeline::from_directory(path).await?))
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for LiquidEdgeEmbedder {
+    async fn embed(&self, input: Vec<String>) -> Result<Vec<Vec<f32>>, LLMError> {
+        let pipeline = self.pipeline.clone();
+        run_blocking(move || {
+            let texts: Vec<&str> = input.iter().map(String::as_str).collect();
+            pipeline.lock().unwrap().embed(&texts)
+        })
+        .await
+    }
+}
+
+/// Local query-passage relevance scores with an ONNX cross-encoder model
+pub struct LiquidEdgeCrossEncoder {
+    pipeline: Arc<Mutex<CrossEncoderPipeline>>,
+}
+
+impl LiquidEdgeCrossEncoder {
+    pub fn new(pipeline: CrossEncoderPipeline) -> Self {
+        Self {
+            pipeline: Arc::new(Mutex::new(pipeline)),
+        }
+    }
+
+    /// Load `model.onnx` and `tokenizer.json` from a model directory
+    pub async fn from_directory<P: AsRef<Path>>(path: P) -> Result<Self, LLMError> {
+        Ok(Self::new(CrossEncoderPipeline::from_directory(path).await?))
+    }
+
+    /// Relevance of each passage to the query in `[0, 1]`, in the order of the passages
+    pub async fn score(&self, query: &str, passages: &[&str]) -> Result<Vec<f32>, LLMError> {
+        let pipeline = self.pipeline.clone();
+        let query = query.to_string();
+        let passages: Vec<String> = passages.iter().map(|p| p.to_string()).collect();
+        run_blocking(move || {
+            let passages: Vec<&str> = passages.iter().map(String::as_str).collect();
+            pipeline.lock().unwrap().score(&query, &passages)
+        })
+        .await
+    }
+}
+
+/// Run CPU-bound ONNX inference on the blocking thread pool instead of an async worker
+async fn run_blocking<T, F>(f: F) -> Result<T, LLMError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, EdgeError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| LLMError::ProviderError(format!("Inference task failed: {e}")))?
+        .map_err(LLMError::from)
+}
+
 #[async_trait]
 impl ModelsProvider for LiquidEdge {
     async fn list_models(
@@ -614,6 +688,7 @@ impl LLMProvider for LiquidEdge {}
 
 impl LLMBuilder<LiquidEdge> {
     pub async fn build(self) -> Result<Arc<LiquidEdge>, LLMError> {
+        self.ensure_unvalidated()?;
         let model_name = self
             .model
             .unwrap_or_else(|| "liquid-edge-model".to_string());
@@ -0,0 +1,193 @@
+//! Mistral API client implementation for chat, embedding and model listing.
+//!
+//! This module provides integration with Mistral's models through their
+//! OpenAI-compatible API.
+
+use crate::builder::LLMBuilder;
+use crate::{
+    builder::LLMBackend,
+    chat::{StructuredOutputFormat, ToolChoice},
+    completion::{CompletionProvider, CompletionRequest, CompletionResponse},
+    embedding::EmbeddingProvider,
+    error::LLMError,
+    models::{ModelListRequest, ModelListResponse, ModelsProvider, StandardModelListResponse},
+    providers::openai_compatible::{OpenAICompatibleProvider, OpenAIProviderConfig},
+    LLMProvider,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Mistral configuration for the generic provider
+pub struct MistralConfig;
+
+impl OpenAIProviderConfig for MistralConfig {
+    const PROVIDER_NAME: &'static str = "Mistral";
+    const DEFAULT_BASE_URL: &'static str = "https://api.mistral.ai/v1/";
+    const DEFAULT_MODEL: &'static str = "mistral-small-latest";
+    const SUPPORTS_REASONING_EFFORT: bool = false;
+    const SUPPORTS_STRUCTURED_OUTPUT: bool = true;
+    const SUPPORTS_PARALLEL_TOOL_CALLS: bool = true;
+    // Usage is always sent in the last chunk, and unknown fields are rejected
+    const SUPPORTS_STREAM_OPTIONS: bool = false;
+}
+
+pub type Mistral = OpenAICompatibleProvider<MistralConfig>;
+
+#[derive(Serialize)]
+struct MistralEmbeddingRequest<'a> {
+    model: &'a str,
+    input: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_dimension: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct MistralEmbeddingData {
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct MistralEmbeddingResponse {
+    data: Vec<MistralEmbeddingData>,
+}
+
+impl Mistral {
+    /// Creates a new Mistral client with the specified configuration.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_config(
+        api_key: impl Into<String>,
+        base_url: Option<String>,
+        model: Option<String>,
+        max_tokens: Option<u32>,
+        temperature: Option<f32>,
+        timeout_seconds: Option<u64>,
+        system: Option<String>,
+        top_p: Option<f32>,
+        tool_choice: Option<ToolChoice>,
+        embedding_dimensions: Option<u32>,
+        parallel_tool_calls: Option<bool>,
+        normalize_response: Option<bool>,
+    ) -> Self {
+        OpenAICompatibleProvider::<MistralConfig>::new(
+            api_key,
+            base_url,
+            model,
+            max_tokens,
+            temperature,
+            timeout_seconds,
+            system,
+            top_p,
+            None, // top_k - not supported by Mistral
+            tool_choice,
+            None, // reasoning_effort - not supported by Mistral
+            None, // voice - not supported by Mistral
+            parallel_tool_calls,
+            normalize_response,
+            None, // embedding_encoding_format - always float
+            embedding_dimensions,
+        )
+    }
+}
+
+impl LLMProvider for Mistral {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&LLMBackend::Mistral, &self.model)
+    }
+}
+
+#[async_trait]
+impl CompletionProvider for Mistral {
+    async fn complete(
+        &self,
+        _req: &CompletionRequest,
+        _json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<CompletionResponse, LLMError> {
+        Err(LLMError::ProviderError(
+            "Mistral completion not implemented".into(),
+        ))
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for Mistral {
+    async fn embed(&self, input: Vec<String>) -> Result<Vec<Vec<f32>>, LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing Mistral API key".into()));
+        }
+
+        let body = MistralEmbeddingRequest {
+            model: &self.model,
+            input,
+            output_dimension: self.embedding_dimensions,
+        };
+
+        let resp = self
+            .request(reqwest::Method::POST, "embeddings")?
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let json_resp: MistralEmbeddingResponse = resp.json().await?;
+        Ok(json_resp.data.into_iter().map(|d| d.embedding).collect())
+    }
+}
+
+#[async_trait]
+impl ModelsProvider for Mistral {
+    async fn list_models(
+        &self,
+        _request: Option<&ModelListRequest>,
+    ) -> Result<Box<dyn ModelListResponse>, LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing Mistral API key".to_string()));
+        }
+
+        let resp = self
+            .request(reqwest::Method::GET, "models")?
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let result = StandardModelListResponse {
+            inner: resp.json().await?,
+            backend: LLMBackend::Mistral,
+        };
+        Ok(Box::new(result))
+    }
+}
+
+impl LLMBuilder<Mistral> {
+    pub fn build(self) -> Result<Arc<Mistral>, LLMError> {
+        self.ensure_unvalidated()?;
+        let api_key = self.api_key.ok_or_else(|| {
+            LLMError::InvalidRequest("No API key provided for Mistral".to_string())
+        })?;
+        // Relative endpoint paths replace the last segment unless the base ends with a slash
+        let base_url = self.base_url.map(|url| {
+            if url.ends_with('/') {
+                url
+            } else {
+                format!("{url}/")
+            }
+        });
+
+        let mistral = Mistral::with_config(
+            api_key,
+            base_url,
+            self.model,
+            self.max_tokens,
+            self.temperature,
+            self.timeout_seconds,
+            self.system,
+            self.top_p,
+            self.tool_choice,
+            self.embedding_dimensions,
+            self.enable_parallel_tool_use,
+            self.normalize_response,
+        );
+
+        Ok(Arc::new(mistral))
+    }
+}
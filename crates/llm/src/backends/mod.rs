@@ -30,3 +30,12 @@ pub mod liquid_edge;
 
 #[cfg(feature = "openrouter")]
 pub mod openrouter;
+
+#[cfg(feature = "mistral")]
+pub mod mistral;
+
+#[cfg(feature = "cohere")]
+pub mod cohere;
+
+#[cfg(feature = "openai_compatible")]
+pub mod openai_compatible;
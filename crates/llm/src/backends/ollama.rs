@@ -4,7 +4,11 @@
 
 use crate::{
     builder::LLMBuilder,
-    chat::{ChatMessage, ChatProvider, ChatResponse, ChatRole, StructuredOutputFormat, Tool},
+    chat::{
+        structured_output::with_schema_instructions, ChatMessage, ChatProvider, ChatResponse,
+        ChatRole, MessageType, StreamLineDecoder, StreamResponse, StructuredOutputFormat, Tool,
+        Usage,
+    },
     completion::{CompletionProvider, CompletionRequest, CompletionResponse},
     embedding::EmbeddingProvider,
     error::LLMError,
@@ -12,6 +16,8 @@ use crate::{
     FunctionCall, ToolCall,
 };
 use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use futures::stream::Stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -56,6 +62,9 @@ struct OllamaOptions {
 struct OllamaChatMessage<'a> {
     role: &'a str,
     content: &'a str,
+    /// Base64-encoded images for multimodal models
+    #[serde(skip_serializing_if = "Option::is_none")]
+    images: Option<Vec<String>>,
 }
 
 /// Response from Ollama's API endpoints.
@@ -125,10 +134,22 @@ impl ChatResponse for OllamaResponse {
 /// Message content within an Ollama chat API response.
 #[derive(Deserialize, Debug)]
 struct OllamaChatResponseMessage {
+    #[serde(default)]
     content: String,
     tool_calls: Option<Vec<OllamaToolCall>>,
 }
 
+/// One line of a streaming chat response. The last one has `done` set and the token counts.
+#[derive(Deserialize, Debug)]
+struct OllamaStreamChunk {
+    message: Option<OllamaChatResponseMessage>,
+    #[serde(default)]
+    done: bool,
+    prompt_eval_count: Option<u32>,
+    eval_count: Option<u32>,
+    error: Option<String>,
+}
+
 /// Request payload for Ollama's generate API endpoint.
 #[derive(Serialize)]
 struct OllamaGenerateRequest<'a> {
@@ -289,28 +310,44 @@ impl Ollama {
         }
     }
 
-    async fn chat_with_tools(
-        &self,
-        messages: &[ChatMessage],
+    /// Builds a chat request from messages already carrying any schema instructions.
+    fn build_chat_request<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
         tools: Option<&[Tool]>,
-        json_schema: Option<StructuredOutputFormat>,
-    ) -> Result<Box<dyn ChatResponse>, LLMError> {
-        if self.base_url.is_empty() {
-            return Err(LLMError::InvalidRequest("Missing base_url".to_string()));
-        }
-
+        json_schema: Option<&StructuredOutputFormat>,
+        stream: bool,
+    ) -> Result<OllamaChatRequest<'a>, LLMError> {
         let mut chat_messages: Vec<OllamaChatMessage> = messages
             .iter()
-            .map(|msg| OllamaChatMessage {
-                role: match msg.role {
-                    ChatRole::User => "user",
-                    ChatRole::Assistant => "assistant",
-                    ChatRole::Tool => "tool",
-                    ChatRole::System => "system",
-                },
-                content: &msg.content,
+            .filter(|m| !m.is_cache_breakpoint())
+            .map(|msg| {
+                let images = match &msg.message_type {
+                    MessageType::Image((_, raw_bytes)) => Some(vec![BASE64.encode(raw_bytes)]),
+                    MessageType::ImageURL(_) => {
+                        return Err(LLMError::InvalidRequest(
+                            "Ollama only accepts inline images, not image URLs".to_string(),
+                        ))
+                    }
+                    MessageType::Pdf { .. } => {
+                        return Err(LLMError::InvalidRequest(
+                            "Ollama does not support PDF attachments".to_string(),
+                        ))
+                    }
+                    _ => None,
+                };
+                Ok(OllamaChatMessage {
+                    role: match msg.role {
+                        ChatRole::User => "user",
+                        ChatRole::Assistant => "assistant",
+                        ChatRole::Tool => "tool",
+                        ChatRole::System => "system",
+                    },
+                    content: &msg.content,
+                    images,
+                })
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
 
         if let Some(system) = &self.system {
             chat_messages.insert(
@@ -318,6 +355,7 @@ impl Ollama {
                 OllamaChatMessage {
                     role: "system",
                     content: system,
+                    images: None,
                 },
             );
         }
@@ -326,35 +364,39 @@ impl Ollama {
         let ollama_tools = tools.map(|t| t.iter().map(OllamaTool::from).collect());
 
         // Ollama doesn't require the "name" field in the schema, so we just use the schema itself
-        let format = if let Some(schema) = &json_schema {
+        let format = json_schema.and_then(|schema| {
             schema.schema.as_ref().map(|schema| OllamaResponseFormat {
                 format: OllamaResponseType::StructuredOutput(schema.clone()),
             })
-        } else {
-            None
-        };
+        });
 
-        let req_body = OllamaChatRequest {
+        Ok(OllamaChatRequest {
             model: self.model.clone(),
             messages: chat_messages,
-            stream: false,
+            stream,
             options: Some(OllamaOptions {
                 top_p: self.top_p,
                 top_k: self.top_k,
             }),
             format,
             tools: ollama_tools,
-        };
+        })
+    }
 
+    /// Posts a chat request, turning a rejected request into an error.
+    async fn send_chat_request(
+        &self,
+        req_body: &OllamaChatRequest<'_>,
+    ) -> Result<reqwest::Response, LLMError> {
         if log::log_enabled!(log::Level::Trace) {
-            if let Ok(json) = serde_json::to_string(&req_body) {
+            if let Ok(json) = serde_json::to_string(req_body) {
                 log::trace!("Ollama request payload (tools): {json}");
             }
         }
 
         let url = format!("{}/api/chat", self.base_url);
 
-        let mut request = self.client.post(&url).json(&req_body);
+        let mut request = self.client.post(&url).json(req_body);
 
         if let Some(timeout) = self.timeout_seconds {
             request = request.timeout(std::time::Duration::from_secs(timeout));
@@ -364,7 +406,36 @@ impl Ollama {
 
         log::debug!("Ollama HTTP status (tools): {}", resp.status());
 
-        let resp = resp.error_for_status()?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            // Models without a tool template are rejected by the server rather than ignoring tools
+            if body.contains("does not support tools") {
+                return Err(LLMError::NoToolSupport(body));
+            }
+            return Err(LLMError::HttpError(format!("HTTP {status}: {body}")));
+        }
+        Ok(resp)
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<Box<dyn ChatResponse>, LLMError> {
+        if self.base_url.is_empty() {
+            return Err(LLMError::InvalidRequest("Missing base_url".to_string()));
+        }
+
+        // Smaller local models often ignore `format`, so the schema is also spelled out in the prompt
+        let messages = match &json_schema {
+            Some(schema) => with_schema_instructions(messages, schema),
+            None => messages.to_vec(),
+        };
+
+        let req_body = self.build_chat_request(&messages, tools, json_schema.as_ref(), false)?;
+        let resp = self.send_chat_request(&req_body).await?;
         let json_resp = resp.json::<OllamaResponse>().await?;
 
         Ok(Box::new(json_resp))
@@ -390,6 +461,113 @@ impl ChatProvider for Ollama {
     ) -> Result<Box<dyn ChatResponse>, LLMError> {
         self.chat_with_tools(messages, tools, json_schema).await
     }
+
+    /// Sends a streaming chat request to Ollama's API.
+    ///
+    /// # Arguments
+    ///
+    /// * `messages` - Slice of chat messages representing the conversation
+    ///
+    /// # Returns
+    ///
+    /// A stream of text tokens or an error
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<std::pin::Pin<Box<dyn Stream<Item = Result<String, LLMError>> + Send>>, LLMError>
+    {
+        let struct_stream = self
+            .chat_stream_struct(messages, tools, json_schema)
+            .await?;
+        Ok(crate::chat::content_stream(struct_stream))
+    }
+
+    /// Sends a streaming chat request to Ollama's API, yielding text, tool calls and,
+    /// from the final line, token usage.
+    async fn chat_stream_struct(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<
+        std::pin::Pin<Box<dyn Stream<Item = Result<StreamResponse, LLMError>> + Send>>,
+        LLMError,
+    > {
+        if self.base_url.is_empty() {
+            return Err(LLMError::InvalidRequest("Missing base_url".to_string()));
+        }
+
+        let messages = match &json_schema {
+            Some(schema) => with_schema_instructions(messages, schema),
+            None => messages.to_vec(),
+        };
+
+        let req_body = self.build_chat_request(&messages, tools, json_schema.as_ref(), true)?;
+        let resp = self.send_chat_request(&req_body).await?;
+
+        Ok(crate::chat::create_line_stream(
+            resp,
+            OllamaStreamDecoder::default(),
+        ))
+    }
+}
+
+/// Decodes the newline-delimited JSON of Ollama's streaming chat API.
+///
+/// Ollama sends each tool call whole, so every call gets the next tool call index.
+#[derive(Default)]
+struct OllamaStreamDecoder {
+    tool_calls: usize,
+}
+
+impl StreamLineDecoder for OllamaStreamDecoder {
+    fn decode_line(&mut self, line: &str) -> Vec<Result<StreamResponse, LLMError>> {
+        if line.trim().is_empty() {
+            return Vec::new();
+        }
+        let chunk = match serde_json::from_str::<OllamaStreamChunk>(line) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                return vec![Err(LLMError::ResponseFormatError {
+                    message: format!("Failed to decode Ollama stream chunk: {e}"),
+                    raw_response: line.to_string(),
+                })]
+            }
+        };
+        if let Some(error) = chunk.error {
+            return vec![Err(LLMError::ProviderError(error))];
+        }
+
+        let mut results = Vec::new();
+        if let Some(message) = chunk.message {
+            if !message.content.is_empty() {
+                results.push(Ok(StreamResponse::from_content(message.content)));
+            }
+            for call in message.tool_calls.into_iter().flatten() {
+                let arguments = serde_json::to_string(&call.function.arguments).unwrap_or_default();
+                results.push(Ok(StreamResponse::from_tool_call(
+                    self.tool_calls,
+                    call.function.name,
+                    arguments,
+                )));
+                self.tool_calls += 1;
+            }
+        }
+        if chunk.done {
+            let prompt_tokens = chunk.prompt_eval_count.unwrap_or(0);
+            let completion_tokens = chunk.eval_count.unwrap_or(0);
+            results.push(Ok(StreamResponse::from_usage(Usage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+                completion_tokens_details: None,
+                prompt_tokens_details: None,
+            })));
+        }
+        results
+    }
 }
 
 #[async_trait]
@@ -468,10 +646,15 @@ impl EmbeddingProvider for Ollama {
 #[async_trait]
 impl ModelsProvider for Ollama {}
 
-impl crate::LLMProvider for Ollama {}
+impl crate::LLMProvider for Ollama {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&crate::builder::LLMBackend::Ollama, &self.model)
+    }
+}
 
 impl LLMBuilder<Ollama> {
     pub fn build(self) -> Result<Arc<Ollama>, LLMError> {
+        self.ensure_unvalidated()?;
         let url = self
             .base_url
             .unwrap_or("http://localhost:11434".to_string());
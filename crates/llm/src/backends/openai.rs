@@ -7,13 +7,23 @@ use crate::chat::{
     StreamChoice, StreamDelta, StreamResponse, StreamToolCallDelta, StreamToolCallFunction, Usage,
 };
 use crate::{
+    audio::{
+        audio_byte_stream, transcription_form, AudioStream, OpenAISpeechRequest, SpeechRequest,
+        SpeechToTextProvider, TextToSpeechProvider, Transcription, TranscriptionRequest,
+    },
+    batch::{parse_jsonl, BatchJob, BatchProvider, BatchRequest, BatchResult, BatchStatus},
     builder::LLMBackend,
     chat::Tool,
     chat::{ChatMessage, ChatProvider, ChatRole, MessageType, StructuredOutputFormat},
     completion::{CompletionProvider, CompletionRequest, CompletionResponse},
     embedding::EmbeddingProvider,
     error::LLMError,
+    images::{
+        GeneratedImage, ImageGenerationProvider, ImageGenerationRequest, OpenAIImageRequest,
+        OpenAIImageResponse,
+    },
     models::{ModelListRawEntry, ModelListRequest, ModelListResponse, ModelsProvider},
+    moderation::{ModerationProvider, ModerationResult},
     LLMProvider,
 };
 use crate::{
@@ -30,6 +40,7 @@ use futures::StreamExt;
 use reqwest::{Client, Url};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::sync::Arc;
 
 /// Client for interacting with OpenAI's API.
@@ -78,26 +89,28 @@ struct OpenAIChatMessage<'a> {
 
 #[derive(Serialize, Debug)]
 struct OpenAIFunctionPayload<'a> {
-    name: &'a str,
-    arguments: &'a str,
+    name: Cow<'a, str>,
+    arguments: Cow<'a, str>,
 }
 
 #[derive(Serialize, Debug)]
 struct OpenAIFunctionCall<'a> {
-    id: &'a str,
+    id: Cow<'a, str>,
     #[serde(rename = "type")]
     content_type: &'a str,
     function: OpenAIFunctionPayload<'a>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Default)]
 struct MessageContent<'a> {
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     message_type: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    text: Option<&'a str>,
+    text: Option<Cow<'a, str>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     image_url: Option<ImageUrlContent<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<FileContent<'a>>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "tool_call_id")]
     tool_call_id: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "content")]
@@ -107,7 +120,14 @@ struct MessageContent<'a> {
 /// Individual image message in an OpenAI chat conversation.
 #[derive(Serialize, Debug)]
 struct ImageUrlContent<'a> {
-    url: &'a str,
+    url: Cow<'a, str>,
+}
+
+/// Inline file (e.g. PDF) in an OpenAI chat conversation.
+#[derive(Serialize, Debug)]
+struct FileContent<'a> {
+    filename: Cow<'a, str>,
+    file_data: Cow<'a, str>,
 }
 
 #[derive(Serialize)]
@@ -172,6 +192,7 @@ impl std::fmt::Display for FunctionCall {
 #[derive(Deserialize, Debug)]
 struct OpenAIChatResponse {
     choices: Vec<OpenAIChatChoice>,
+    usage: Option<Usage>,
 }
 
 /// Individual choice within an OpenAI chat API response.
@@ -336,6 +357,10 @@ impl ChatResponse for OpenAIChatResponse {
             .first()
             .and_then(|c| c.message.tool_calls.clone())
     }
+
+    fn usage(&self) -> Option<Usage> {
+        self.usage.clone()
+    }
 }
 
 impl std::fmt::Display for OpenAIChatResponse {
@@ -450,7 +475,7 @@ impl OpenAI {
 
         let mut openai_msgs: Vec<OpenAIChatMessage> = vec![];
 
-        for msg in messages {
+        for msg in messages.into_iter().filter(|m| !m.is_cache_breakpoint()) {
             if let MessageType::ToolResult(ref results) = msg.message_type {
                 for result in results {
                     openai_msgs.push(
@@ -475,10 +500,8 @@ impl OpenAI {
                     role: "system",
                     content: Some(Left(vec![MessageContent {
                         message_type: Some("text"),
-                        text: Some(system),
-                        image_url: None,
-                        tool_call_id: None,
-                        tool_output: None,
+                        text: Some(Cow::Borrowed(system)),
+                        ..Default::default()
                     }])),
                     tool_calls: None,
                     tool_call_id: None,
@@ -777,6 +800,20 @@ fn create_struct_sse_stream(
     Box::pin(stream)
 }
 
+/// Content parts for a multimodal message, with the message text (if any) before the media part
+fn with_text_part(text: &str, media: MessageContent<'static>) -> Vec<MessageContent<'static>> {
+    let mut parts = Vec::with_capacity(2);
+    if !text.is_empty() {
+        parts.push(MessageContent {
+            message_type: Some("text"),
+            text: Some(Cow::Owned(text.to_string())),
+            ..Default::default()
+        });
+    }
+    parts.push(media);
+    parts
+}
+
 // Create an owned OpenAIChatMessage that doesn't borrow from any temporary variables
 fn chat_message_to_api_message(chat_msg: ChatMessage) -> OpenAIChatMessage<'static> {
     // For other message types, create an owned OpenAIChatMessage
@@ -794,56 +831,58 @@ fn chat_message_to_api_message(chat_msg: ChatMessage) -> OpenAIChatMessage<'stat
                 // Convert raw bytes to base64 data URL
                 let base64_data = BASE64_STANDARD.encode(raw_bytes);
                 let data_url = format!("data:{};base64,{}", image_mime.mime_type(), base64_data);
-                let data_url_str = Box::leak(data_url.into_boxed_str());
-                Some(Left(vec![MessageContent {
-                    message_type: Some("image_url"),
-                    text: None,
-                    image_url: Some(ImageUrlContent { url: data_url_str }),
-                    tool_output: None,
-                    tool_call_id: None,
-                }]))
+                Some(Left(with_text_part(
+                    &chat_msg.content,
+                    MessageContent {
+                        message_type: Some("image_url"),
+                        image_url: Some(ImageUrlContent {
+                            url: Cow::Owned(data_url),
+                        }),
+                        ..Default::default()
+                    },
+                )))
             }
-            MessageType::Pdf(_) => unimplemented!(),
-            MessageType::ImageURL(url) => {
-                // Clone the URL to create an owned version
-                let owned_url = url.clone();
-                // Leak the string to get a 'static reference
-                let url_str = Box::leak(owned_url.into_boxed_str());
-                Some(Left(vec![MessageContent {
-                    message_type: Some("image_url"),
-                    text: None,
-                    image_url: Some(ImageUrlContent { url: url_str }),
-                    tool_output: None,
-                    tool_call_id: None,
-                }]))
+            MessageType::Pdf { name, data } => {
+                let data_url = format!(
+                    "data:application/pdf;base64,{}",
+                    BASE64_STANDARD.encode(data)
+                );
+                Some(Left(with_text_part(
+                    &chat_msg.content,
+                    MessageContent {
+                        message_type: Some("file"),
+                        file: Some(FileContent {
+                            filename: Cow::Owned(name.clone()),
+                            file_data: Cow::Owned(data_url),
+                        }),
+                        ..Default::default()
+                    },
+                )))
             }
+            MessageType::ImageURL(url) => Some(Left(with_text_part(
+                &chat_msg.content,
+                MessageContent {
+                    message_type: Some("image_url"),
+                    image_url: Some(ImageUrlContent {
+                        url: Cow::Owned(url.clone()),
+                    }),
+                    ..Default::default()
+                },
+            ))),
             MessageType::ToolUse(_) => None,
-            MessageType::ToolResult(_) => None,
+            MessageType::ToolResult(_) | MessageType::CacheBreakpoint => None,
         },
         tool_calls: match &chat_msg.message_type {
             MessageType::ToolUse(calls) => {
                 let owned_calls: Vec<OpenAIFunctionCall<'static>> = calls
                     .iter()
-                    .map(|c| {
-                        let owned_id = c.id.clone();
-                        let owned_name = c.function.name.clone();
-                        let owned_args = c.function.arguments.clone();
-
-                        // Need to leak these strings to create 'static references
-                        // This is a deliberate choice to solve the lifetime issue
-                        // The small memory leak is acceptable in this context
-                        let id_str = Box::leak(owned_id.into_boxed_str());
-                        let name_str = Box::leak(owned_name.into_boxed_str());
-                        let args_str = Box::leak(owned_args.into_boxed_str());
-
-                        OpenAIFunctionCall {
-                            id: id_str,
-                            content_type: "function",
-                            function: OpenAIFunctionPayload {
-                                name: name_str,
-                                arguments: args_str,
-                            },
-                        }
+                    .map(|c| OpenAIFunctionCall {
+                        id: Cow::Owned(c.id.clone()),
+                        content_type: "function",
+                        function: OpenAIFunctionPayload {
+                            name: Cow::Owned(c.function.name.clone()),
+                            arguments: Cow::Owned(c.function.arguments.clone()),
+                        },
                     })
                     .collect();
                 Some(owned_calls)
@@ -914,6 +953,383 @@ impl EmbeddingProvider for OpenAI {
     }
 }
 
+impl OpenAI {
+    /// Builds the request for `audio/speech`, falling back to the configured voice.
+    fn speech_request(&self, request: &SpeechRequest) -> Result<reqwest::RequestBuilder, LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing OpenAI API key".into()));
+        }
+        let url = self
+            .base_url
+            .join("audio/speech")
+            .map_err(|e| LLMError::HttpError(e.to_string()))?;
+        let body = OpenAISpeechRequest {
+            model: request.model.as_deref().unwrap_or("tts-1"),
+            input: &request.text,
+            voice: request
+                .voice
+                .as_deref()
+                .or(self.voice.as_deref())
+                .unwrap_or("alloy"),
+            response_format: request.format.as_str(),
+            speed: request.speed,
+        };
+        Ok(self.client.post(url).bearer_auth(&self.api_key).json(&body))
+    }
+}
+
+#[async_trait]
+impl SpeechToTextProvider for OpenAI {
+    async fn transcribe(&self, request: &TranscriptionRequest) -> Result<Transcription, LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing OpenAI API key".into()));
+        }
+        let url = self
+            .base_url
+            .join("audio/transcriptions")
+            .map_err(|e| LLMError::HttpError(e.to_string()))?;
+        let response = self
+            .client
+            .post(url)
+            .bearer_auth(&self.api_key)
+            .multipart(transcription_form(request, "whisper-1")?)
+            .send()
+            .await?;
+        let response = check_response_status(response).await?;
+        Ok(response.json().await?)
+    }
+}
+
+#[async_trait]
+impl TextToSpeechProvider for OpenAI {
+    async fn synthesize(&self, request: &SpeechRequest) -> Result<Vec<u8>, LLMError> {
+        let response = self.speech_request(request)?.send().await?;
+        let response = check_response_status(response).await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    async fn synthesize_stream(&self, request: &SpeechRequest) -> Result<AudioStream, LLMError> {
+        let response = self.speech_request(request)?.send().await?;
+        let response = check_response_status(response).await?;
+        Ok(audio_byte_stream(response))
+    }
+}
+
+#[async_trait]
+impl ImageGenerationProvider for OpenAI {
+    async fn generate_images(
+        &self,
+        request: &ImageGenerationRequest,
+    ) -> Result<Vec<GeneratedImage>, LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing OpenAI API key".into()));
+        }
+        let url = self
+            .base_url
+            .join("images/generations")
+            .map_err(|e| LLMError::HttpError(e.to_string()))?;
+        let body = OpenAIImageRequest::new(
+            request,
+            Some(request.model.as_deref().unwrap_or("dall-e-3")),
+        );
+        let response = self
+            .client
+            .post(url)
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await?;
+        let response = check_response_status(response).await?;
+        response.json::<OpenAIImageResponse>().await?.into_images()
+    }
+}
+
+/// Request body for the `moderations` endpoint
+#[derive(Serialize, Debug)]
+struct OpenAIModerationRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenAIModerationResponse {
+    results: Vec<OpenAIModerationEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenAIModerationEntry {
+    flagged: bool,
+    #[serde(default)]
+    categories: std::collections::BTreeMap<String, bool>,
+    #[serde(default)]
+    category_scores: std::collections::BTreeMap<String, f32>,
+}
+
+#[async_trait]
+impl ModerationProvider for OpenAI {
+    async fn moderate(&self, input: &str) -> Result<ModerationResult, LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing OpenAI API key".into()));
+        }
+        let url = self
+            .base_url
+            .join("moderations")
+            .map_err(|e| LLMError::HttpError(e.to_string()))?;
+        let body = OpenAIModerationRequest {
+            model: "omni-moderation-latest",
+            input,
+        };
+        let response = self
+            .client
+            .post(url)
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await?;
+        let response = check_response_status(response).await?;
+        let entry = response
+            .json::<OpenAIModerationResponse>()
+            .await?
+            .results
+            .into_iter()
+            .next()
+            .ok_or_else(|| LLMError::ResponseFormatError {
+                message: "Moderation response without results".to_string(),
+                raw_response: String::new(),
+            })?;
+        Ok(ModerationResult {
+            flagged: entry.flagged,
+            categories: entry
+                .categories
+                .into_iter()
+                .filter_map(|(category, flagged)| flagged.then_some(category))
+                .collect(),
+            scores: entry.category_scores,
+        })
+    }
+}
+
+/// Endpoint every batch request is sent to
+const BATCH_ENDPOINT: &str = "/v1/chat/completions";
+
+/// One line of a batch input file
+#[derive(Serialize, Debug)]
+struct OpenAIBatchLine<'a> {
+    custom_id: &'a str,
+    method: &'static str,
+    url: &'static str,
+    body: OpenAIChatRequest<'a>,
+}
+
+#[derive(Serialize, Debug)]
+struct OpenAIBatchCreateRequest<'a> {
+    input_file_id: &'a str,
+    endpoint: &'static str,
+    completion_window: &'static str,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenAIFileObject {
+    id: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenAIBatchObject {
+    id: String,
+    status: BatchStatus,
+    #[serde(default)]
+    request_counts: Option<OpenAIBatchRequestCounts>,
+    output_file_id: Option<String>,
+    error_file_id: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct OpenAIBatchRequestCounts {
+    total: usize,
+    completed: usize,
+    failed: usize,
+}
+
+impl OpenAIBatchObject {
+    fn job(&self) -> BatchJob {
+        let counts = self.request_counts.as_ref();
+        BatchJob {
+            id: self.id.clone(),
+            status: self.status,
+            total: counts.map_or(0, |c| c.total),
+            completed: counts.map_or(0, |c| c.completed),
+            failed: counts.map_or(0, |c| c.failed),
+        }
+    }
+}
+
+/// One line of a batch output or error file
+#[derive(Deserialize, Debug)]
+struct OpenAIBatchOutputLine {
+    custom_id: String,
+    response: Option<OpenAIBatchResponse>,
+    error: Option<OpenAIBatchError>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenAIBatchResponse {
+    status_code: u16,
+    body: Value,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenAIBatchError {
+    message: String,
+}
+
+impl OpenAIBatchOutputLine {
+    fn into_result(self) -> BatchResult {
+        let response = match (self.response, self.error) {
+            (Some(response), _) if response.status_code == 200 => {
+                serde_json::from_value::<OpenAIChatResponse>(response.body.clone())
+                    .map(|chat| Box::new(chat) as Box<dyn ChatResponse>)
+                    .map_err(|e| LLMError::ResponseFormatError {
+                        message: format!("Failed to decode OpenAI API response: {e}"),
+                        raw_response: response.body.to_string(),
+                    })
+            }
+            (Some(response), _) => Err(LLMError::ProviderError(
+                response.body["error"]["message"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("HTTP {}", response.status_code)),
+            )),
+            (None, Some(error)) => Err(LLMError::ProviderError(error.message)),
+            (None, None) => Err(LLMError::ResponseFormatError {
+                message: "Batch result without response or error".to_string(),
+                raw_response: String::new(),
+            }),
+        };
+        BatchResult {
+            custom_id: self.custom_id,
+            response,
+        }
+    }
+}
+
+impl OpenAI {
+    /// Builds the JSONL input file of a batch, one chat completion request per line.
+    pub fn batch_file(&self, requests: &[BatchRequest]) -> Result<String, LLMError> {
+        let mut file = String::new();
+        for request in requests {
+            let line = OpenAIBatchLine {
+                custom_id: &request.custom_id,
+                method: "POST",
+                url: BATCH_ENDPOINT,
+                body: self.build_chat_completion_request(
+                    &request.messages,
+                    request.tools.as_deref(),
+                    request.json_schema.clone(),
+                    false,
+                    None,
+                )?,
+            };
+            file.push_str(&serde_json::to_string(&line)?);
+            file.push('\n');
+        }
+        Ok(file)
+    }
+
+    fn api_url(&self, path: &str) -> Result<Url, LLMError> {
+        if self.api_key.is_empty() {
+            return Err(LLMError::AuthError("Missing OpenAI API key".into()));
+        }
+        self.base_url
+            .join(path)
+            .map_err(|e| LLMError::HttpError(e.to_string()))
+    }
+
+    async fn batch_object(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<OpenAIBatchObject, LLMError> {
+        let response = request.bearer_auth(&self.api_key).send().await?;
+        let response = check_response_status(response).await?;
+        Ok(response.json().await?)
+    }
+
+    async fn file_content(&self, file_id: &str) -> Result<String, LLMError> {
+        let url = self.api_url(&format!("files/{file_id}/content"))?;
+        let response = self
+            .client
+            .get(url)
+            .bearer_auth(&self.api_key)
+            .send()
+            .await?;
+        let response = check_response_status(response).await?;
+        Ok(response.text().await?)
+    }
+}
+
+#[async_trait]
+impl BatchProvider for OpenAI {
+    async fn submit_batch(&self, requests: &[BatchRequest]) -> Result<BatchJob, LLMError> {
+        let file = reqwest::multipart::Part::text(self.batch_file(requests)?)
+            .file_name("batch.jsonl")
+            .mime_str("application/jsonl")
+            .map_err(|e| LLMError::InvalidRequest(e.to_string()))?;
+        let form = reqwest::multipart::Form::new()
+            .text("purpose", "batch")
+            .part("file", file);
+        let response = self
+            .client
+            .post(self.api_url("files")?)
+            .bearer_auth(&self.api_key)
+            .multipart(form)
+            .send()
+            .await?;
+        let input_file = check_response_status(response)
+            .await?
+            .json::<OpenAIFileObject>()
+            .await?;
+
+        let body = OpenAIBatchCreateRequest {
+            input_file_id: &input_file.id,
+            endpoint: BATCH_ENDPOINT,
+            completion_window: "24h",
+        };
+        let batch = self
+            .batch_object(self.client.post(self.api_url("batches")?).json(&body))
+            .await?;
+        Ok(batch.job())
+    }
+
+    async fn batch_status(&self, batch_id: &str) -> Result<BatchJob, LLMError> {
+        let url = self.api_url(&format!("batches/{batch_id}"))?;
+        Ok(self.batch_object(self.client.get(url)).await?.job())
+    }
+
+    async fn batch_results(&self, batch_id: &str) -> Result<Vec<BatchResult>, LLMError> {
+        let url = self.api_url(&format!("batches/{batch_id}"))?;
+        let batch = self.batch_object(self.client.get(url)).await?;
+        if !batch.status.is_terminal() {
+            return Err(LLMError::InvalidRequest(format!(
+                "Batch {batch_id} has not finished"
+            )));
+        }
+        let mut results = Vec::new();
+        for file_id in [&batch.output_file_id, &batch.error_file_id]
+            .into_iter()
+            .flatten()
+        {
+            let lines: Vec<OpenAIBatchOutputLine> =
+                parse_jsonl(&self.file_content(file_id).await?)?;
+            results.extend(lines.into_iter().map(OpenAIBatchOutputLine::into_result));
+        }
+        Ok(results)
+    }
+
+    async fn cancel_batch(&self, batch_id: &str) -> Result<BatchJob, LLMError> {
+        let url = self.api_url(&format!("batches/{batch_id}/cancel"))?;
+        Ok(self.batch_object(self.client.post(url)).await?.job())
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct OpenAIModelEntry {
     pub id: String,
@@ -988,7 +1404,11 @@ impl ModelsProvider for OpenAI {
     }
 }
 
-impl LLMProvider for OpenAI {}
+impl LLMProvider for OpenAI {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&LLMBackend::OpenAI, &self.model)
+    }
+}
 
 /// Parse SSE chunk and convert to StreamResponse format
 ///
@@ -1025,6 +1445,7 @@ fn parse_sse_chunk(chunk: &str) -> Result<Option<StreamResponse>, LLMError> {
                                 delta: StreamDelta {
                                     content: None,
                                     tool_calls: None,
+                                    reasoning_content: None,
                                 },
                             }],
                             usage: Some(usage),
@@ -1121,6 +1542,7 @@ fn parse_sse_chunk(chunk: &str) -> Result<Option<StreamResponse>, LLMError> {
                         None
                     },
                     tool_calls,
+                    reasoning_content: None,
                 },
             }],
             usage: None,
@@ -1138,6 +1560,7 @@ impl LLMBuilder<OpenAI> {
     }
 
     pub fn build(self) -> Result<Arc<OpenAI>, LLMError> {
+        self.ensure_unvalidated()?;
         let key = self.api_key.ok_or_else(|| {
             LLMError::InvalidRequest("No API key provided for OpenAI".to_string())
         })?;
@@ -0,0 +1,144 @@
+//! Generic client for self-hosted and third-party OpenAI-compatible servers.
+//!
+//! Works with any endpoint implementing `/chat/completions`, such as vLLM, the llama.cpp
+//! server, LM Studio or LocalAI. The base URL is required, the API key is optional, and
+//! optional request fields can be switched off per server with [`OpenAICompatibleQuirks`].
+
+use crate::builder::LLMBuilder;
+use crate::{
+    builder::LLMBackend,
+    chat::StructuredOutputFormat,
+    completion::{CompletionProvider, CompletionRequest, CompletionResponse},
+    embedding::EmbeddingProvider,
+    error::LLMError,
+    models::{ModelListRequest, ModelListResponse, ModelsProvider, StandardModelListResponse},
+    providers::openai_compatible::{OpenAICompatibleProvider, OpenAIProviderConfig},
+    LLMProvider,
+};
+use async_trait::async_trait;
+use reqwest::Url;
+use std::sync::Arc;
+
+pub use crate::providers::openai_compatible::OpenAICompatibleQuirks;
+
+/// Defaults for a generic OpenAI-compatible server
+pub struct OpenAICompatibleConfig;
+
+impl OpenAIProviderConfig for OpenAICompatibleConfig {
+    const PROVIDER_NAME: &'static str = "OpenAICompatible";
+    const DEFAULT_BASE_URL: &'static str = "http://localhost:8000/v1/";
+    const DEFAULT_MODEL: &'static str = "default";
+    const SUPPORTS_REASONING_EFFORT: bool = false;
+    const SUPPORTS_STRUCTURED_OUTPUT: bool = true;
+    const SUPPORTS_PARALLEL_TOOL_CALLS: bool = false;
+    const SUPPORTS_STREAM_OPTIONS: bool = true;
+    const REQUIRES_API_KEY: bool = false;
+}
+
+pub type OpenAICompatible = OpenAICompatibleProvider<OpenAICompatibleConfig>;
+
+impl Default for OpenAICompatibleQuirks {
+    fn default() -> Self {
+        Self::from_config::<OpenAICompatibleConfig>()
+    }
+}
+
+impl LLMProvider for OpenAICompatible {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&LLMBackend::OpenAICompatible, &self.model)
+    }
+}
+
+#[async_trait]
+impl CompletionProvider for OpenAICompatible {
+    async fn complete(
+        &self,
+        _req: &CompletionRequest,
+        _json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<CompletionResponse, LLMError> {
+        Err(LLMError::ProviderError(
+            "Completion not supported".to_string(),
+        ))
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAICompatible {
+    async fn embed(&self, _text: Vec<String>) -> Result<Vec<Vec<f32>>, LLMError> {
+        Err(LLMError::ProviderError(
+            "Embedding not supported".to_string(),
+        ))
+    }
+}
+
+#[async_trait]
+impl ModelsProvider for OpenAICompatible {
+    async fn list_models(
+        &self,
+        _request: Option<&ModelListRequest>,
+    ) -> Result<Box<dyn ModelListResponse>, LLMError> {
+        let resp = self
+            .request(reqwest::Method::GET, "models")?
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let result = StandardModelListResponse {
+            inner: resp.json().await?,
+            backend: LLMBackend::OpenAICompatible,
+        };
+        Ok(Box::new(result))
+    }
+}
+
+impl LLMBuilder<OpenAICompatible> {
+    /// Set which optional request fields the server accepts.
+    pub fn quirks(mut self, quirks: OpenAICompatibleQuirks) -> Self {
+        self.openai_compatible_quirks = Some(quirks);
+        self
+    }
+
+    pub fn build(self) -> Result<Arc<OpenAICompatible>, LLMError> {
+        self.ensure_unvalidated()?;
+        let base_url = self.base_url.ok_or_else(|| {
+            LLMError::InvalidRequest("No base URL provided for OpenAICompatible".to_string())
+        })?;
+        let model = self.model.ok_or_else(|| {
+            LLMError::InvalidRequest("No model provided for OpenAICompatible".to_string())
+        })?;
+        // Relative endpoint paths replace the last segment unless the base ends with a slash
+        let base_url = if base_url.ends_with('/') {
+            base_url
+        } else {
+            format!("{base_url}/")
+        };
+        Url::parse(&base_url).map_err(|e| {
+            LLMError::InvalidRequest(format!("Invalid base URL for OpenAICompatible: {e}"))
+        })?;
+
+        let mut provider = OpenAICompatible::new(
+            self.api_key.unwrap_or_default(),
+            Some(base_url),
+            Some(model),
+            self.max_tokens,
+            self.temperature,
+            self.timeout_seconds,
+            self.system,
+            self.top_p,
+            self.top_k,
+            self.tool_choice,
+            self.reasoning_effort,
+            None, // voice
+            self.enable_parallel_tool_use,
+            self.normalize_response,
+            None, // embedding_encoding_format
+            None, // embedding_dimensions
+        );
+        if let Some(quirks) = self.openai_compatible_quirks {
+            provider.quirks = quirks;
+        }
+        provider.headers.extend(self.headers);
+
+        Ok(Arc::new(provider))
+    }
+}
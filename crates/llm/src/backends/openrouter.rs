@@ -71,7 +71,11 @@ impl OpenRouter {
     }
 }
 
-impl LLMProvider for OpenRouter {}
+impl LLMProvider for OpenRouter {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&LLMBackend::OpenRouter, &self.model)
+    }
+}
 
 #[async_trait]
 impl CompletionProvider for OpenRouter {
@@ -127,6 +131,7 @@ impl ModelsProvider for OpenRouter {
 
 impl LLMBuilder<OpenRouter> {
     pub fn build(self) -> Result<Arc<OpenRouter>, LLMError> {
+        self.ensure_unvalidated()?;
         let api_key = self.api_key.ok_or_else(|| {
             LLMError::InvalidRequest("No API key provided for OpenRouter".to_string())
         })?;
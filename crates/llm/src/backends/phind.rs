@@ -6,7 +6,7 @@ use crate::{
     ToolCall,
 };
 use crate::{
-    chat::{ChatMessage, ChatProvider, ChatRole},
+    chat::{structured_output::with_schema_instructions, ChatMessage, ChatProvider, ChatRole},
     completion::{CompletionProvider, CompletionRequest, CompletionResponse},
     embedding::EmbeddingProvider,
     error::LLMError,
@@ -181,13 +181,18 @@ impl ChatProvider for Phind {
         &self,
         messages: &[ChatMessage],
         tools: Option<&[Tool]>,
-        _json_schema: Option<StructuredOutputFormat>,
+        json_schema: Option<StructuredOutputFormat>,
     ) -> Result<Box<dyn ChatResponse>, LLMError> {
         if tools.is_some() {
             return Err(LLMError::NoToolSupport("No Tool Support as of now.".into()));
         }
+        // Phind has no native structured output, so the schema is passed as instructions
+        let messages = match &json_schema {
+            Some(schema) => with_schema_instructions(messages, schema),
+            None => messages.to_vec(),
+        };
         let mut message_history = vec![];
-        for m in messages {
+        for m in messages.iter().filter(|m| !m.is_cache_breakpoint()) {
             let role_str = match m.role {
                 ChatRole::User => "user",
                 ChatRole::Assistant => "assistant",
@@ -289,10 +294,15 @@ impl EmbeddingProvider for Phind {
 impl ModelsProvider for Phind {}
 
 /// Implementation of the LLMProvider trait for Phind.
-impl LLMProvider for Phind {}
+impl LLMProvider for Phind {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&crate::builder::LLMBackend::Phind, &self.model)
+    }
+}
 
 impl LLMBuilder<Phind> {
     pub fn build(self) -> Result<Arc<Phind>, LLMError> {
+        self.ensure_unvalidated()?;
         let phind = crate::backends::phind::Phind::new(
             self.model,
             self.max_tokens,
@@ -5,11 +5,12 @@
 
 use crate::{
     builder::LLMBuilder,
-    chat::{ChatResponse, Tool},
+    chat::{ChatResponse, StreamResponse, Tool},
+    providers::openai_compatible::{create_sse_stream, OpenAIStreamOptions},
     ToolCall,
 };
 use crate::{
-    chat::{ChatMessage, ChatProvider, ChatRole, StructuredOutputFormat},
+    chat::{ChatMessage, ChatProvider, ChatRole, MessageType, StructuredOutputFormat},
     completion::{CompletionProvider, CompletionRequest, CompletionResponse},
     embedding::EmbeddingProvider,
     error::LLMError,
@@ -22,6 +23,9 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+/// Default base URL of X.AI's API
+const DEFAULT_BASE_URL: &str = "https://api.x.ai/v1";
+
 /// Client for interacting with X.AI's API.
 ///
 /// This struct provides methods for making chat and completion requests to X.AI's language models.
@@ -29,6 +33,8 @@ use std::sync::Arc;
 pub struct XAI {
     /// API key for authentication with X.AI services
     pub api_key: String,
+    /// Base URL of the API, overridable through the builder
+    pub base_url: String,
     /// Model identifier to use for requests (e.g. "grok-2-latest")
     pub model: String,
     /// Maximum number of tokens to generate in responses
@@ -95,6 +101,12 @@ struct XAIChatMessage<'a> {
     role: &'a str,
     /// Content of the message
     content: &'a str,
+    /// Tool calls made by the assistant
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<&'a [ToolCall]>,
+    /// Tool call answered by a `tool` message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<&'a str>,
 }
 
 /// Request payload for X.AI's chat API endpoint.
@@ -123,6 +135,12 @@ struct XAIChatRequest<'a> {
     /// Search parameters for search functionality
     #[serde(skip_serializing_if = "Option::is_none")]
     search_parameters: Option<&'a XaiSearchParameters>,
+    /// Tools the model may call, in OpenAI's format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<&'a [Tool]>,
+    /// Asks for usage in the final chunk of a stream
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<OpenAIStreamOptions>,
 }
 
 /// Response from X.AI's chat API endpoint.
@@ -177,27 +195,6 @@ struct XAIEmbeddingData {
     embedding: Vec<f32>,
 }
 
-/// Response from X.AI's streaming chat API endpoint.
-#[derive(Deserialize, Debug)]
-struct XAIStreamResponse {
-    /// Array of generated responses
-    choices: Vec<XAIStreamChoice>,
-}
-
-/// Individual response choice from the streaming chat API.
-#[derive(Deserialize, Debug)]
-struct XAIStreamChoice {
-    /// Delta content
-    delta: XAIStreamDelta,
-}
-
-/// Delta content from a streaming chat response.
-#[derive(Deserialize, Debug)]
-struct XAIStreamDelta {
-    /// Generated text content
-    content: Option<String>,
-}
-
 #[derive(Deserialize)]
 struct XAIEmbeddingResponse {
     data: Vec<XAIEmbeddingData>,
@@ -270,6 +267,7 @@ impl XAI {
         }
         Self {
             api_key: api_key.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
             model: model.unwrap_or("grok-2-latest".to_string()),
             max_tokens,
             temperature,
@@ -330,6 +328,51 @@ impl XAI {
         self.xai_search_to_date = Some(date.into());
         self
     }
+
+    /// Convert chat messages to X.AI's format, after the system prompt if one is set.
+    /// Tool results are expanded into one `tool` message per call.
+    fn chat_messages<'a>(&'a self, messages: &'a [ChatMessage]) -> Vec<XAIChatMessage<'a>> {
+        let system = self.system.as_deref().map(|system| XAIChatMessage {
+            role: "system",
+            content: system,
+            tool_calls: None,
+            tool_call_id: None,
+        });
+        let conversation = messages
+            .iter()
+            .filter(|m| !m.is_cache_breakpoint())
+            .flat_map(|m| match &m.message_type {
+                MessageType::ToolResult(results) => results
+                    .iter()
+                    .map(|result| XAIChatMessage {
+                        role: "tool",
+                        content: &result.function.arguments,
+                        tool_calls: None,
+                        tool_call_id: Some(&result.id),
+                    })
+                    .collect(),
+                message_type => vec![XAIChatMessage {
+                    role: match m.role {
+                        ChatRole::User => "user",
+                        ChatRole::Assistant => "assistant",
+                        ChatRole::Tool => "tool",
+                        ChatRole::System => "system",
+                    },
+                    content: &m.content,
+                    tool_calls: match message_type {
+                        MessageType::ToolUse(calls) => Some(calls.as_slice()),
+                        _ => None,
+                    },
+                    tool_call_id: None,
+                }],
+            });
+        system.into_iter().chain(conversation).collect()
+    }
+
+    /// URL of an API endpoint, e.g. `chat/completions`
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{path}", self.base_url.trim_end_matches('/'))
+    }
 }
 
 #[async_trait]
@@ -353,28 +396,7 @@ impl ChatProvider for XAI {
             return Err(LLMError::AuthError("Missing X.AI API key".to_string()));
         }
 
-        let mut xai_msgs: Vec<XAIChatMessage> = messages
-            .iter()
-            .map(|m| XAIChatMessage {
-                role: match m.role {
-                    ChatRole::User => "user",
-                    ChatRole::Assistant => "assistant",
-                    ChatRole::Tool => "tool",
-                    ChatRole::System => "system",
-                },
-                content: &m.content,
-            })
-            .collect();
-
-        if let Some(system) = &self.system {
-            xai_msgs.insert(
-                0,
-                XAIChatMessage {
-                    role: "system",
-                    content: system,
-                },
-            );
-        }
+        let xai_msgs = self.chat_messages(messages);
 
         // OpenAI's structured output has some [odd requirements](https://platform.openai.com/docs/guides/structured-outputs?api-mode=chat&lang=curl#supported-schemas).
         // There's currently no check for these, so we'll leave it up to the user to provide a valid schema.
@@ -409,6 +431,8 @@ impl ChatProvider for XAI {
             top_k: self.top_k,
             response_format,
             search_parameters: Some(&search_parameters),
+            tools: None,
+            stream_options: None,
         };
 
         if log::log_enabled!(log::Level::Trace) {
@@ -419,7 +443,7 @@ impl ChatProvider for XAI {
 
         let mut request = self
             .client
-            .post("https://api.x.ai/v1/chat/completions")
+            .post(self.endpoint("chat/completions"))
             .bearer_auth(&self.api_key)
             .json(&body);
 
@@ -449,36 +473,32 @@ impl ChatProvider for XAI {
     async fn chat_stream(
         &self,
         messages: &[ChatMessage],
-        _tools: Option<&[Tool]>,
-        _json_schema: Option<StructuredOutputFormat>,
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
     ) -> Result<std::pin::Pin<Box<dyn Stream<Item = Result<String, LLMError>> + Send>>, LLMError>
     {
+        let struct_stream = self
+            .chat_stream_struct(messages, tools, json_schema)
+            .await?;
+        Ok(crate::chat::content_stream(struct_stream))
+    }
+
+    /// Sends a streaming chat request to X.AI's API, yielding text, tool call deltas
+    /// and usage in the OpenAI-compatible format X.AI streams.
+    async fn chat_stream_struct(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        _json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<
+        std::pin::Pin<Box<dyn Stream<Item = Result<StreamResponse, LLMError>> + Send>>,
+        LLMError,
+    > {
         if self.api_key.is_empty() {
             return Err(LLMError::AuthError("Missing X.AI API key".to_string()));
         }
 
-        let mut xai_msgs: Vec<XAIChatMessage> = messages
-            .iter()
-            .map(|m| XAIChatMessage {
-                role: match m.role {
-                    ChatRole::User => "user",
-                    ChatRole::Assistant => "assistant",
-                    ChatRole::System => "system",
-                    ChatRole::Tool => "tool",
-                },
-                content: &m.content,
-            })
-            .collect();
-
-        if let Some(system) = &self.system {
-            xai_msgs.insert(
-                0,
-                XAIChatMessage {
-                    role: "system",
-                    content: system,
-                },
-            );
-        }
+        let xai_msgs = self.chat_messages(messages);
 
         let body = XAIChatRequest {
             model: &self.model,
@@ -490,11 +510,15 @@ impl ChatProvider for XAI {
             top_k: self.top_k,
             response_format: None,
             search_parameters: None,
+            tools,
+            stream_options: Some(OpenAIStreamOptions {
+                include_usage: true,
+            }),
         };
 
         let mut request = self
             .client
-            .post("https://api.x.ai/v1/chat/completions")
+            .post(self.endpoint("chat/completions"))
             .bearer_auth(&self.api_key)
             .json(&body);
 
@@ -513,10 +537,7 @@ impl ChatProvider for XAI {
             });
         }
 
-        Ok(crate::chat::create_sse_stream(
-            response,
-            parse_xai_sse_chunk,
-        ))
+        Ok(create_sse_stream(response, true))
     }
 }
 
@@ -568,7 +589,7 @@ impl EmbeddingProvider for XAI {
 
         let resp = self
             .client
-            .post("https://api.x.ai/v1/embeddings")
+            .post(self.endpoint("embeddings"))
             .bearer_auth(&self.api_key)
             .json(&body)
             .send()
@@ -595,52 +616,20 @@ impl ModelsProvider for XAI {
     }
 }
 
-impl LLMProvider for XAI {}
-
-/// Parses a Server-Sent Events (SSE) chunk from X.AI's streaming API.
-///
-/// # Arguments
-///
-/// * `chunk` - The raw SSE chunk text
-///
-/// # Returns
-///
-/// * `Ok(Some(String))` - Content token if found
-/// * `Ok(None)` - If chunk should be skipped (e.g., ping, done signal)
-/// * `Err(LLMError)` - If parsing fails
-fn parse_xai_sse_chunk(chunk: &str) -> Result<Option<String>, LLMError> {
-    for line in chunk.lines() {
-        let line = line.trim();
-
-        if let Some(data) = line.strip_prefix("data: ") {
-            if data == "[DONE]" {
-                return Ok(None);
-            }
-
-            match serde_json::from_str::<XAIStreamResponse>(data) {
-                Ok(response) => {
-                    if let Some(choice) = response.choices.first() {
-                        if let Some(content) = &choice.delta.content {
-                            return Ok(Some(content.clone()));
-                        }
-                    }
-                    return Ok(None);
-                }
-                Err(_) => continue,
-            }
-        }
+impl LLMProvider for XAI {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        crate::models::lookup_capabilities(&crate::builder::LLMBackend::XAI, &self.model)
     }
-
-    Ok(None)
 }
 
 impl LLMBuilder<XAI> {
     pub fn build(self) -> Result<Arc<XAI>, LLMError> {
+        self.ensure_unvalidated()?;
         let api_key = self
             .api_key
             .ok_or_else(|| LLMError::InvalidRequest("No API key provided for XAI".to_string()))?;
 
-        let xai = crate::backends::xai::XAI::new(
+        let mut xai = crate::backends::xai::XAI::new(
             api_key,
             self.model,
             self.max_tokens,
@@ -658,6 +647,9 @@ impl LLMBuilder<XAI> {
             None,
             None,
         );
+        if let Some(base_url) = self.base_url {
+            xai.base_url = base_url;
+        }
 
         Ok(Arc::new(xai))
     }
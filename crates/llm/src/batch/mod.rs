@@ -0,0 +1,370 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::chat::{ChatMessage, ChatProvider, ChatResponse, StructuredOutputFormat, Tool, Usage};
+use crate::error::LLMError;
+use crate::ToolCall;
+
+/// One chat request of a batch, matched to its result by `custom_id`.
+#[derive(Debug, Clone)]
+pub struct BatchRequest {
+    /// Caller-chosen id, unique within the batch
+    pub custom_id: String,
+    pub messages: Vec<ChatMessage>,
+    pub tools: Option<Vec<Tool>>,
+    pub json_schema: Option<StructuredOutputFormat>,
+}
+
+impl BatchRequest {
+    pub fn new(custom_id: impl Into<String>, messages: Vec<ChatMessage>) -> Self {
+        Self {
+            custom_id: custom_id.into(),
+            messages,
+            tools: None,
+            json_schema: None,
+        }
+    }
+
+    pub fn tools(mut self, tools: Vec<Tool>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    pub fn json_schema(mut self, json_schema: StructuredOutputFormat) -> Self {
+        self.json_schema = Some(json_schema);
+        self
+    }
+}
+
+/// Processing status of a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    Validating,
+    InProgress,
+    Finalizing,
+    Completed,
+    Failed,
+    Expired,
+    Cancelling,
+    Cancelled,
+}
+
+impl BatchStatus {
+    /// Whether the batch has stopped processing, so its results can be fetched
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            BatchStatus::Completed
+                | BatchStatus::Failed
+                | BatchStatus::Expired
+                | BatchStatus::Cancelled
+        )
+    }
+}
+
+/// A submitted batch and its progress.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchJob {
+    pub id: String,
+    pub status: BatchStatus,
+    /// Number of requests in the batch
+    pub total: usize,
+    /// Requests that finished successfully so far
+    pub completed: usize,
+    /// Requests that errored, expired or were cancelled so far
+    pub failed: usize,
+}
+
+/// The outcome of one request of a batch.
+#[derive(Debug)]
+pub struct BatchResult {
+    pub custom_id: String,
+    pub response: Result<Box<dyn ChatResponse>, LLMError>,
+}
+
+/// Providers that run chat requests asynchronously in bulk, usually at a discount and
+/// within a completion window rather than in real time.
+#[async_trait]
+pub trait BatchProvider: Send + Sync {
+    /// Submits the requests as one batch.
+    async fn submit_batch(&self, requests: &[BatchRequest]) -> Result<BatchJob, LLMError>;
+
+    /// Fetches the current status of a batch.
+    async fn batch_status(&self, batch_id: &str) -> Result<BatchJob, LLMError>;
+
+    /// Fetches the results of a finished batch, in no particular order.
+    async fn batch_results(&self, batch_id: &str) -> Result<Vec<BatchResult>, LLMError>;
+
+    /// Asks the provider to stop processing a batch.
+    async fn cancel_batch(&self, batch_id: &str) -> Result<BatchJob, LLMError>;
+
+    /// Polls the batch status until it stops processing.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn wait_for_batch(
+        &self,
+        batch_id: &str,
+        poll_interval: std::time::Duration,
+    ) -> Result<BatchJob, LLMError> {
+        loop {
+            let job = self.batch_status(batch_id).await?;
+            if job.status.is_terminal() {
+                return Ok(job);
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+}
+
+/// Parse a JSONL document, skipping blank lines
+#[cfg(any(feature = "openai", feature = "anthropic"))]
+pub(crate) fn parse_jsonl<T: serde::de::DeserializeOwned>(text: &str) -> Result<Vec<T>, LLMError> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|e| LLMError::ResponseFormatError {
+                message: format!("Invalid batch result line: {e}"),
+                raw_response: line.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Copy of a chat response that can be handed out more than once.
+#[derive(Debug, Clone)]
+struct StoredResponse {
+    text: Option<String>,
+    tool_calls: Option<Vec<ToolCall>>,
+    thinking: Option<String>,
+    usage: Option<Usage>,
+}
+
+impl StoredResponse {
+    fn new(response: &dyn ChatResponse) -> Self {
+        Self {
+            text: response.text(),
+            tool_calls: response.tool_calls(),
+            thinking: response.thinking(),
+            usage: response.usage(),
+        }
+    }
+}
+
+impl ChatResponse for StoredResponse {
+    fn text(&self) -> Option<String> {
+        self.text.clone()
+    }
+
+    fn tool_calls(&self) -> Option<Vec<ToolCall>> {
+        self.tool_calls.clone()
+    }
+
+    fn thinking(&self) -> Option<String> {
+        self.thinking.clone()
+    }
+
+    fn usage(&self) -> Option<Usage> {
+        self.usage.clone()
+    }
+}
+
+impl std::fmt::Display for StoredResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.text.as_deref().unwrap_or_default())
+    }
+}
+
+type StoredResults = Vec<(String, Result<StoredResponse, String>)>;
+
+/// Batch provider that runs each request sequentially through a [`ChatProvider`] when
+/// the batch is submitted.
+///
+/// Useful for testing batch jobs offline, or for providers without a batch API.
+pub struct LocalBatchProvider {
+    llm: Arc<dyn ChatProvider>,
+    batches: Mutex<HashMap<String, StoredResults>>,
+    next_id: AtomicUsize,
+}
+
+impl LocalBatchProvider {
+    pub fn new(llm: Arc<dyn ChatProvider>) -> Self {
+        Self {
+            llm,
+            batches: Mutex::new(HashMap::new()),
+            next_id: AtomicUsize::new(1),
+        }
+    }
+
+    fn job(id: &str, results: &StoredResults) -> BatchJob {
+        let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+        BatchJob {
+            id: id.to_string(),
+            status: BatchStatus::Completed,
+            total: results.len(),
+            completed: results.len() - failed,
+            failed,
+        }
+    }
+
+    fn with_batch<R>(
+        &self,
+        batch_id: &str,
+        f: impl FnOnce(&StoredResults) -> R,
+    ) -> Result<R, LLMError> {
+        let batches = self.batches.lock().unwrap();
+        batches
+            .get(batch_id)
+            .map(f)
+            .ok_or_else(|| LLMError::InvalidRequest(format!("Unknown batch: {batch_id}")))
+    }
+}
+
+#[async_trait]
+impl BatchProvider for LocalBatchProvider {
+    async fn submit_batch(&self, requests: &[BatchRequest]) -> Result<BatchJob, LLMError> {
+        let mut results = Vec::with_capacity(requests.len());
+        for request in requests {
+            let response = self
+                .llm
+                .chat(
+                    &request.messages,
+                    request.tools.as_deref(),
+                    request.json_schema.clone(),
+                )
+                .await
+                .map(|response| StoredResponse::new(response.as_ref()))
+                .map_err(|e| e.to_string());
+            results.push((request.custom_id.clone(), response));
+        }
+        let id = format!(
+            "local_batch_{}",
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        );
+        let job = Self::job(&id, &results);
+        self.batches.lock().unwrap().insert(id, results);
+        Ok(job)
+    }
+
+    async fn batch_status(&self, batch_id: &str) -> Result<BatchJob, LLMError> {
+        self.with_batch(batch_id, |results| Self::job(batch_id, results))
+    }
+
+    async fn batch_results(&self, batch_id: &str) -> Result<Vec<BatchResult>, LLMError> {
+        self.with_batch(batch_id, |results| {
+            results
+                .iter()
+                .map(|(custom_id, response)| BatchResult {
+                    custom_id: custom_id.clone(),
+                    response: match response {
+                        Ok(response) => Ok(Box::new(response.clone()) as Box<dyn ChatResponse>),
+                        Err(error) => Err(LLMError::ProviderError(error.clone())),
+                    },
+                })
+                .collect()
+        })
+    }
+
+    /// Local batches finish on submission, so there is nothing to cancel
+    async fn cancel_batch(&self, batch_id: &str) -> Result<BatchJob, LLMError> {
+        self.batch_status(batch_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Reply(String);
+
+    impl ChatResponse for Reply {
+        fn text(&self) -> Option<String> {
+            Some(self.0.clone())
+        }
+
+        fn tool_calls(&self) -> Option<Vec<ToolCall>> {
+            None
+        }
+    }
+
+    impl std::fmt::Display for Reply {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    /// Labels tickets by echoing them, failing on empty ones
+    struct Classifier;
+
+    #[async_trait]
+    impl ChatProvider for Classifier {
+        async fn chat(
+            &self,
+            messages: &[ChatMessage],
+            _tools: Option<&[Tool]>,
+            _json_schema: Option<StructuredOutputFormat>,
+        ) -> Result<Box<dyn ChatResponse>, LLMError> {
+            let ticket = &messages.last().unwrap().content;
+            if ticket.is_empty() {
+                return Err(LLMError::InvalidRequest("empty ticket".to_string()));
+            }
+            Ok(Box::new(Reply(format!("label: {ticket}"))))
+        }
+    }
+
+    fn ticket(id: &str, text: &str) -> BatchRequest {
+        BatchRequest::new(id, vec![ChatMessage::user().content(text).build()])
+    }
+
+    #[tokio::test]
+    async fn test_local_batch_runs_every_request() {
+        let provider = LocalBatchProvider::new(Arc::new(Classifier));
+        let job = provider
+            .submit_batch(&[ticket("t-1", "billing"), ticket("t-2", "")])
+            .await
+            .unwrap();
+        assert_eq!(job.status, BatchStatus::Completed);
+        assert_eq!((job.total, job.completed, job.failed), (2, 1, 1));
+
+        let done = provider
+            .wait_for_batch(&job.id, std::time::Duration::from_millis(1))
+            .await
+            .unwrap();
+        assert_eq!(done, job);
+
+        // Results can be fetched more than once
+        for _ in 0..2 {
+            let results = provider.batch_results(&job.id).await.unwrap();
+            let by_id: HashMap<_, _> = results
+                .into_iter()
+                .map(|r| (r.custom_id, r.response))
+                .collect();
+            assert_eq!(
+                by_id["t-1"].as_ref().unwrap().text().as_deref(),
+                Some("label: billing")
+            );
+            assert!(matches!(by_id["t-2"], Err(LLMError::ProviderError(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_batch_unknown_id() {
+        let provider = LocalBatchProvider::new(Arc::new(Classifier));
+        assert!(matches!(
+            provider.batch_status("missing").await,
+            Err(LLMError::InvalidRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_batch_status_is_terminal() {
+        assert!(BatchStatus::Cancelled.is_terminal());
+        assert!(!BatchStatus::Finalizing.is_terminal());
+        let status: BatchStatus = serde_json::from_str("\"in_progress\"").unwrap();
+        assert_eq!(status, BatchStatus::InProgress);
+    }
+}
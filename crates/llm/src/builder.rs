@@ -6,16 +6,17 @@
 use crate::{
     chat::{FunctionTool, ParameterProperty, ParametersSchema, ReasoningEffort, Tool, ToolChoice},
     error::LLMError,
+    validated_llm::ValidatedLLM,
     LLMProvider,
 };
-use std::{collections::HashMap, marker::PhantomData};
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
 
 /// A function type for validating LLM provider outputs.
 /// Takes a response string and returns Ok(()) if valid, or Err with an error message if invalid.
 pub type ValidatorFn = dyn Fn(&str) -> Result<(), String> + Send + Sync + 'static;
 
 /// Supported LLM backend providers.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum LLMBackend {
     /// OpenAI API provider (GPT-3, GPT-4, etc.)
     OpenAI,
@@ -37,6 +38,12 @@ pub enum LLMBackend {
     AzureOpenAI,
     /// OpenRouter API provider for various models
     OpenRouter,
+    /// Mistral API provider
+    Mistral,
+    /// Cohere API provider
+    Cohere,
+    /// Any OpenAI-compatible server, e.g. vLLM, llama.cpp server, LM Studio or LocalAI
+    OpenAICompatible,
 }
 
 /// Implements string parsing for LLMBackend enum.
@@ -80,6 +87,9 @@ impl std::str::FromStr for LLMBackend {
             "groq" => Ok(LLMBackend::Groq),
             "azure-openai" => Ok(LLMBackend::AzureOpenAI),
             "openrouter" => Ok(LLMBackend::OpenRouter),
+            "mistral" => Ok(LLMBackend::Mistral),
+            "cohere" => Ok(LLMBackend::Cohere),
+            "openai-compatible" => Ok(LLMBackend::OpenAICompatible),
             _ => Err(LLMError::InvalidRequest(format!(
                 "Unknown LLM backend: {s}"
             ))),
@@ -87,6 +97,41 @@ impl std::str::FromStr for LLMBackend {
     }
 }
 
+/// Formats the backend as the name accepted by `from_str`.
+impl std::fmt::Display for LLMBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            LLMBackend::OpenAI => "openai",
+            LLMBackend::Anthropic => "anthropic",
+            LLMBackend::Ollama => "ollama",
+            LLMBackend::DeepSeek => "deepseek",
+            LLMBackend::XAI => "xai",
+            LLMBackend::Phind => "phind",
+            LLMBackend::Google => "google",
+            LLMBackend::Groq => "groq",
+            LLMBackend::AzureOpenAI => "azure-openai",
+            LLMBackend::OpenRouter => "openrouter",
+            LLMBackend::Mistral => "mistral",
+            LLMBackend::Cohere => "cohere",
+            LLMBackend::OpenAICompatible => "openai-compatible",
+        };
+        f.write_str(name)
+    }
+}
+
+impl serde::Serialize for LLMBackend {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for LLMBackend {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// Builder for configuring and instantiating LLM providers.
 ///
 /// Provides a fluent interface for setting various configuration options
@@ -146,6 +191,22 @@ pub struct LLMBuilder<L: LLMProvider> {
     /// Whether to normalize response format
     #[allow(dead_code)]
     pub(crate) normalize_response: Option<bool>,
+    /// Extra HTTP headers sent with every request
+    #[allow(dead_code)]
+    pub(crate) headers: Vec<(String, String)>,
+    /// Enable prompt caching
+    #[allow(dead_code)]
+    pub(crate) prompt_caching: Option<bool>,
+    /// Optional request fields accepted by an OpenAI-compatible server
+    #[cfg(feature = "openai_compatible")]
+    pub(crate) openai_compatible_quirks:
+        Option<crate::backends::openai_compatible::OpenAICompatibleQuirks>,
+    /// Model used for reranking by Cohere
+    #[cfg(feature = "cohere")]
+    pub(crate) cohere_rerank_model: Option<String>,
+    /// `input_type` of Cohere embedding requests
+    #[cfg(feature = "cohere")]
+    pub(crate) cohere_embedding_input_type: Option<String>,
 }
 
 impl<L: LLMProvider> Default for LLMBuilder<L> {
@@ -178,6 +239,14 @@ impl<L: LLMProvider> Default for LLMBuilder<L> {
             deployment_id: None,
             voice: None,
             normalize_response: None,
+            headers: Vec::new(),
+            prompt_caching: None,
+            #[cfg(feature = "openai_compatible")]
+            openai_compatible_quirks: None,
+            #[cfg(feature = "cohere")]
+            cohere_rerank_model: None,
+            #[cfg(feature = "cohere")]
+            cohere_embedding_input_type: None,
         }
     }
 }
@@ -295,6 +364,8 @@ impl<L: LLMProvider> LLMBuilder<L> {
     /// # Arguments
     ///
     /// * `f` - Function that takes a response string and returns Ok(()) if valid, or Err with error message if invalid
+    ///
+    /// Only applied by [`Self::build_validated`]; `build` rejects a builder with a validator.
     pub fn validator<F>(mut self, f: F) -> Self
     where
         F: Fn(&str) -> Result<(), String> + Send + Sync + 'static,
@@ -308,6 +379,8 @@ impl<L: LLMProvider> LLMBuilder<L> {
     /// # Arguments
     ///
     /// * `attempts` - Maximum number of times to retry generating a valid response
+    ///
+    /// Only applied by [`Self::build_validated`]; `build` rejects a builder with attempts set.
     pub fn validator_attempts(mut self, attempts: usize) -> Self {
         self.validator_attempts = attempts;
         self
@@ -343,6 +416,63 @@ impl<L: LLMProvider> LLMBuilder<L> {
         self.deployment_id = Some(deployment_id.into());
         self
     }
+
+    /// Add an HTTP header sent with every request. Used by OpenAI-compatible servers.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Enable prompt caching. Anthropic then caches the system prompt, the tool
+    /// definitions and the conversation up to the latest message between requests.
+    pub fn prompt_caching(mut self, enable: bool) -> Self {
+        self.prompt_caching = Some(enable);
+        self
+    }
+
+    /// Backends' `build` return the bare provider, so a validator set on the builder
+    /// would be silently dropped there; it has to go through [`Self::build_validated`].
+    #[allow(dead_code)]
+    pub(crate) fn ensure_unvalidated(&self) -> Result<(), LLMError> {
+        if self.validator.is_some() || self.validator_attempts > 0 {
+            return Err(LLMError::InvalidRequest(
+                "A validator is configured, use build_validated to apply it".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Builds the provider and wraps it in a [`ValidatedLLM`] when a validator or
+    /// retry attempts are configured.
+    ///
+    /// Every response is checked against the requested JSON schema and the custom
+    /// validator, and the model is re-prompted with the validation errors up to
+    /// `validator_attempts` times.
+    ///
+    /// # Arguments
+    ///
+    /// * `build` - The backend build function, e.g. `LLMBuilder::<OpenAI>::build`
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let llm = LLMBuilder::<OpenAI>::new()
+    ///     .api_key(key)
+    ///     .validator_attempts(2)
+    ///     .build_validated(LLMBuilder::<OpenAI>::build)?;
+    /// ```
+    pub fn build_validated<F>(mut self, build: F) -> Result<Arc<dyn LLMProvider>, LLMError>
+    where
+        F: FnOnce(Self) -> Result<Arc<L>, LLMError>,
+    {
+        let validator = self.validator.take();
+        let attempts = std::mem::take(&mut self.validator_attempts);
+        let provider: Arc<dyn LLMProvider> = build(self)?;
+        if validator.is_none() && attempts == 0 {
+            return Ok(provider);
+        }
+        Ok(Arc::new(ValidatedLLM::new(provider, validator, attempts)))
+    }
 }
 
 /// Builder for function parameters
@@ -539,6 +669,18 @@ mod tests {
             LLMBackend::from_str("azure-openai").unwrap(),
             LLMBackend::AzureOpenAI
         ));
+        assert!(matches!(
+            LLMBackend::from_str("mistral").unwrap(),
+            LLMBackend::Mistral
+        ));
+        assert!(matches!(
+            LLMBackend::from_str("cohere").unwrap(),
+            LLMBackend::Cohere
+        ));
+        assert!(matches!(
+            LLMBackend::from_str("openai-compatible").unwrap(),
+            LLMBackend::OpenAICompatible
+        ));
 
         let result = LLMBackend::from_str("invalid");
         assert!(result.is_err());
@@ -548,6 +690,23 @@ mod tests {
             .contains("Unknown LLM backend"));
     }
 
+    #[test]
+    fn test_llm_backend_display_round_trip() {
+        for backend in [
+            LLMBackend::OpenAI,
+            LLMBackend::AzureOpenAI,
+            LLMBackend::OpenAICompatible,
+        ] {
+            assert_eq!(LLMBackend::from_str(&backend.to_string()).unwrap(), backend);
+        }
+        assert_eq!(
+            serde_json::to_value(LLMBackend::AzureOpenAI).unwrap(),
+            json!("azure-openai")
+        );
+        let backend: LLMBackend = serde_json::from_value(json!("Anthropic")).unwrap();
+        assert_eq!(backend, LLMBackend::Anthropic);
+    }
+
     #[test]
     fn test_param_builder_new() {
         let builder = ParamBuilder::new("test_param");
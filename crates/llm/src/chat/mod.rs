@@ -8,6 +8,8 @@ use serde_json::Value;
 
 use crate::{error::LLMError, ToolCall};
 
+pub(crate) mod structured_output;
+
 /// Role of a participant in a chat conversation.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum ChatRole {
@@ -44,6 +46,17 @@ impl ImageMime {
             ImageMime::WEBP => "image/webp",
         }
     }
+
+    /// Parse a MIME type such as `image/png`
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        match mime_type.to_ascii_lowercase().as_str() {
+            "image/jpeg" | "image/jpg" => Some(ImageMime::JPEG),
+            "image/png" => Some(ImageMime::PNG),
+            "image/gif" => Some(ImageMime::GIF),
+            "image/webp" => Some(ImageMime::WEBP),
+            _ => None,
+        }
+    }
 }
 
 /// The type of a message in a chat conversation.
@@ -55,13 +68,20 @@ pub enum MessageType {
     /// An image message
     Image((ImageMime, Vec<u8>)),
     /// PDF message
-    Pdf(Vec<u8>),
+    Pdf {
+        /// File name, sent to the providers that accept one
+        name: String,
+        /// Raw PDF bytes
+        data: Vec<u8>,
+    },
     /// An image URL message
     ImageURL(String),
     /// A tool use
     ToolUse(Vec<ToolCall>),
     /// Tool result
     ToolResult(Vec<ToolCall>),
+    /// Prompt caching breakpoint, see [`ChatMessage::cache_breakpoint`]
+    CacheBreakpoint,
 }
 
 /// The type of reasoning effort for a message in a chat conversation.
@@ -256,9 +276,12 @@ pub struct CompletionTokensDetails {
 /// Breakdown of prompt tokens.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PromptTokensDetails {
-    /// Tokens used for cached content
+    /// Tokens read from the prompt cache
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cached_tokens: Option<u32>,
+    /// Tokens written to the prompt cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_tokens: Option<u32>,
     /// Tokens used for audio input
     #[serde(skip_serializing_if = "Option::is_none")]
     pub audio_tokens: Option<u32>,
@@ -291,6 +314,78 @@ pub struct StreamResponse {
     pub usage: Option<Usage>,
 }
 
+impl StreamResponse {
+    /// A single-choice response carrying `delta`
+    pub(crate) fn from_delta(delta: StreamDelta) -> Self {
+        Self {
+            choices: vec![StreamChoice { delta }],
+            usage: None,
+        }
+    }
+
+    /// A response carrying only text content
+    pub(crate) fn from_content(content: impl Into<String>) -> Self {
+        Self::from_delta(StreamDelta {
+            content: Some(content.into()),
+            tool_calls: None,
+            reasoning_content: None,
+        })
+    }
+
+    /// A response carrying only reasoning text
+    pub(crate) fn from_reasoning(reasoning: impl Into<String>) -> Self {
+        Self::from_delta(StreamDelta {
+            content: None,
+            tool_calls: None,
+            reasoning_content: Some(reasoning.into()),
+        })
+    }
+
+    /// A response carrying part of the tool call at `index`. Later parts of the same
+    /// call may leave `name` empty and only append to the arguments.
+    #[cfg(any(
+        feature = "anthropic",
+        feature = "cohere",
+        feature = "google",
+        feature = "ollama"
+    ))]
+    pub(crate) fn from_tool_call(
+        index: usize,
+        name: impl Into<String>,
+        arguments: impl Into<String>,
+    ) -> Self {
+        Self::from_delta(StreamDelta {
+            content: None,
+            tool_calls: Some(vec![StreamToolCallDelta {
+                index,
+                function: Some(StreamToolCallFunction {
+                    name: name.into(),
+                    arguments: arguments.into(),
+                }),
+            }]),
+            reasoning_content: None,
+        })
+    }
+
+    /// A response carrying only usage, as sent at the end of a stream
+    #[cfg(any(
+        feature = "anthropic",
+        feature = "cohere",
+        feature = "google",
+        feature = "ollama"
+    ))]
+    pub(crate) fn from_usage(usage: Usage) -> Self {
+        Self {
+            usage: Some(usage),
+            ..Self::from_delta(StreamDelta {
+                content: None,
+                tool_calls: None,
+                reasoning_content: None,
+            })
+        }
+    }
+}
+
 /// Individual choice in a streaming response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamChoice {
@@ -318,6 +413,9 @@ pub struct StreamDelta {
     pub content: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<StreamToolCallDelta>>,
+    /// The incremental reasoning or thinking text, separate from the answer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
 }
 
 pub trait ChatResponse: std::fmt::Debug + std::fmt::Display + Send + Sync {
@@ -422,8 +520,27 @@ impl ChatMessage {
     pub fn assistant() -> ChatMessageBuilder {
         ChatMessageBuilder::new(ChatRole::Assistant)
     }
+
+    /// A marker placed after a message to cache the prompt up to and including that
+    /// message, for providers with prompt caching such as Anthropic. It carries no content
+    /// and other providers drop it.
+    pub fn cache_breakpoint() -> Self {
+        Self {
+            role: ChatRole::System,
+            message_type: MessageType::CacheBreakpoint,
+            content: String::new(),
+        }
+    }
+
+    /// Whether this message is a [`cache_breakpoint`](Self::cache_breakpoint) marker
+    pub fn is_cache_breakpoint(&self) -> bool {
+        self.message_type == MessageType::CacheBreakpoint
+    }
 }
 
+/// File name of a PDF attached without one
+pub const DEFAULT_PDF_NAME: &str = "document.pdf";
+
 /// Builder for ChatMessage
 #[derive(Debug)]
 pub struct ChatMessageBuilder {
@@ -454,9 +571,17 @@ impl ChatMessageBuilder {
         self
     }
 
-    /// Set the message type as Image
-    pub fn pdf(mut self, raw_bytes: Vec<u8>) -> Self {
-        self.message_type = MessageType::Pdf(raw_bytes);
+    /// Set the message type as Pdf, named `document.pdf`
+    pub fn pdf(self, raw_bytes: Vec<u8>) -> Self {
+        self.pdf_file(DEFAULT_PDF_NAME, raw_bytes)
+    }
+
+    /// Set the message type as Pdf with the given file name
+    pub fn pdf_file(mut self, name: impl Into<String>, raw_bytes: Vec<u8>) -> Self {
+        self.message_type = MessageType::Pdf {
+            name: name.into(),
+            data: raw_bytes,
+        };
         self
     }
 
@@ -527,6 +652,88 @@ where
     Box::pin(stream)
 }
 
+/// Decodes a streaming response body into [`StreamResponse`]s, one line at a time.
+///
+/// Decoders keep per-stream state, such as the index of the current tool call or usage
+/// reported before the end of the stream, which a stateless chunk parser cannot.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) trait StreamLineDecoder: Send + 'static {
+    /// Decode one complete line of the body, without its line terminator
+    fn decode_line(&mut self, line: &str) -> Vec<Result<StreamResponse, LLMError>>;
+
+    /// Responses to emit once the body has ended
+    fn finish(&mut self) -> Vec<Result<StreamResponse, LLMError>> {
+        Vec::new()
+    }
+}
+
+/// Creates a stream of [`StreamResponse`]s from a line-oriented (SSE or NDJSON) HTTP response.
+///
+/// Network chunks are buffered until a full line is available, so events split across
+/// chunks are decoded whole.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn create_line_stream<D: StreamLineDecoder>(
+    response: reqwest::Response,
+    decoder: D,
+) -> std::pin::Pin<Box<dyn Stream<Item = Result<StreamResponse, LLMError>> + Send>> {
+    let chunks = response
+        .bytes_stream()
+        .map(Some)
+        .chain(futures::stream::once(async { None }));
+    let stream = chunks
+        .scan((decoder, Vec::<u8>::new()), |(decoder, buffer), chunk| {
+            let mut results = Vec::new();
+            match chunk {
+                Some(Ok(bytes)) => {
+                    buffer.extend_from_slice(&bytes);
+                    while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                        let line: Vec<u8> = buffer.drain(..=end).collect();
+                        let line = String::from_utf8_lossy(&line);
+                        results.extend(decoder.decode_line(line.trim_end()));
+                    }
+                }
+                Some(Err(e)) => results.push(Err(LLMError::HttpError(e.to_string()))),
+                None => {
+                    if !buffer.is_empty() {
+                        let line = String::from_utf8_lossy(buffer).into_owned();
+                        buffer.clear();
+                        results.extend(decoder.decode_line(line.trim_end()));
+                    }
+                    results.extend(decoder.finish());
+                }
+            }
+            futures::future::ready(Some(results))
+        })
+        .flat_map(futures::stream::iter);
+    Box::pin(stream)
+}
+
+/// The payload of a Server-Sent Events `data:` line
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(any(feature = "anthropic", feature = "cohere", feature = "google"))]
+pub(crate) fn sse_data(line: &str) -> Option<&str> {
+    line.strip_prefix("data:").map(str::trim_start)
+}
+
+/// Reduce a structured stream to its non-empty text content, for `chat_stream`
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn content_stream(
+    stream: std::pin::Pin<Box<dyn Stream<Item = Result<StreamResponse, LLMError>> + Send>>,
+) -> std::pin::Pin<Box<dyn Stream<Item = Result<String, LLMError>> + Send>> {
+    Box::pin(stream.filter_map(|result| async move {
+        match result {
+            Ok(response) => response
+                .choices
+                .into_iter()
+                .next()
+                .and_then(|choice| choice.delta.content)
+                .filter(|content| !content.is_empty())
+                .map(Ok),
+            Err(e) => Some(Err(e)),
+        }
+    }))
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub mod utils {
     use crate::error::LLMError;
@@ -589,6 +796,8 @@ mod tests {
         assert_eq!(ImageMime::PNG.mime_type(), "image/png");
         assert_eq!(ImageMime::GIF.mime_type(), "image/gif");
         assert_eq!(ImageMime::WEBP.mime_type(), "image/webp");
+        assert_eq!(ImageMime::from_mime_type("image/PNG"), Some(ImageMime::PNG));
+        assert_eq!(ImageMime::from_mime_type("application/pdf"), None);
     }
 
     #[test]
@@ -659,7 +868,29 @@ mod tests {
 
         assert_eq!(message.role, ChatRole::User);
         assert_eq!(message.content, "Review this PDF");
-        assert_eq!(message.message_type, MessageType::Pdf(pdf_data));
+        assert_eq!(
+            message.message_type,
+            MessageType::Pdf {
+                name: "document.pdf".to_string(),
+                data: pdf_data
+            }
+        );
+    }
+
+    #[test]
+    fn test_chat_message_builder_pdf_file() {
+        let message = ChatMessage::user()
+            .pdf_file("report.pdf", vec![0x25, 0x50])
+            .build();
+
+        assert!(message.content.is_empty());
+        assert_eq!(
+            message.message_type,
+            MessageType::Pdf {
+                name: "report.pdf".to_string(),
+                data: vec![0x25, 0x50]
+            }
+        );
     }
 
     #[test]
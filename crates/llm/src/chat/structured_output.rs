@@ -0,0 +1,339 @@
+//! Validation and prompting helpers for [`StructuredOutputFormat`].
+//!
+//! Providers with native JSON-schema support enforce the schema server side, but the
+//! model can still return fenced or partially valid JSON. The helpers in this module
+//! let callers check a response against the schema and build the prompts used to ask
+//! the model for a corrected answer. Providers without native support use the same
+//! instructions as a prompt-based fallback.
+
+use super::StructuredOutputFormat;
+#[cfg(any(feature = "phind", feature = "ollama", feature = "liquid_edge"))]
+use super::{ChatMessage, ChatRole, MessageType};
+use serde_json::Value;
+
+impl StructuredOutputFormat {
+    /// Parse a model response and validate it against this format's JSON schema.
+    ///
+    /// Markdown code fences and leading/trailing prose around the JSON document are
+    /// tolerated. If no `schema` is set, any valid JSON document is accepted.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Value)` - The parsed JSON document
+    /// * `Err(Vec<String>)` - Every validation error that was found
+    pub fn parse_response(&self, text: &str) -> Result<Value, Vec<String>> {
+        let candidate = extract_json(text);
+        let value: Value = serde_json::from_str(candidate)
+            .map_err(|e| vec![format!("response is not valid JSON: {e}")])?;
+
+        if let Some(schema) = &self.schema {
+            let mut errors = Vec::new();
+            validate_value(&value, schema, "$", &mut errors);
+            if !errors.is_empty() {
+                return Err(errors);
+            }
+        }
+        Ok(value)
+    }
+
+    /// Instructions that ask the model to answer with JSON matching this format.
+    pub fn prompt_instructions(&self) -> String {
+        let schema = self
+            .schema
+            .as_ref()
+            .and_then(|s| serde_json::to_string_pretty(s).ok())
+            .unwrap_or_else(|| "{}".to_string());
+        let description = self
+            .description
+            .as_ref()
+            .map(|d| format!(" ({d})"))
+            .unwrap_or_default();
+        format!(
+            "You must respond with a single JSON object named `{}`{description} that matches this JSON schema:\n{schema}\nOnly return the JSON, without markdown code fences or any additional text.",
+            self.name
+        )
+    }
+
+    /// Prompt asking the model to fix a response that failed validation.
+    pub fn repair_prompt(&self, errors: &[String]) -> String {
+        format!(
+            "Your previous response did not match the required JSON schema:\n- {}\n\n{}",
+            errors.join("\n- "),
+            self.prompt_instructions()
+        )
+    }
+}
+
+/// Prepend schema instructions as a system message for providers that cannot enforce
+/// a JSON schema natively.
+#[cfg(any(feature = "phind", feature = "ollama", feature = "liquid_edge"))]
+pub(crate) fn with_schema_instructions(
+    messages: &[ChatMessage],
+    schema: &StructuredOutputFormat,
+) -> Vec<ChatMessage> {
+    let mut modified = Vec::with_capacity(messages.len() + 1);
+    modified.push(ChatMessage {
+        role: ChatRole::System,
+        message_type: MessageType::Text,
+        content: schema.prompt_instructions(),
+    });
+    modified.extend_from_slice(messages);
+    modified
+}
+
+/// Extract the JSON document from a response that may be wrapped in code fences or prose.
+fn extract_json(text: &str) -> &str {
+    let trimmed = text.trim();
+
+    if let Some(start) = trimmed.find("```") {
+        let after_fence = &trimmed[start + 3..];
+        // Skip an optional language tag such as ```json
+        let body_start = after_fence.find('\n').map(|i| i + 1).unwrap_or(0);
+        let body = &after_fence[body_start..];
+        if let Some(end) = body.find("```") {
+            return body[..end].trim();
+        }
+    }
+
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return trimmed;
+    }
+
+    match (trimmed.find(['{', '[']), trimmed.rfind(['}', ']'])) {
+        (Some(start), Some(end)) if end > start => &trimmed[start..=end],
+        _ => trimmed,
+    }
+}
+
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn matches_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        other => json_type_name(value) == other,
+    }
+}
+
+fn validate_value(value: &Value, schema: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| matches_type(value, t)) {
+            errors.push(format!(
+                "{path}: expected {}, found {}",
+                allowed.join(" or "),
+                json_type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(Value::Array(choices)) = schema.get("enum") {
+        if !choices.contains(value) {
+            let choices = choices
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            errors.push(format!("{path}: {value} is not one of [{choices}]"));
+        }
+    }
+
+    for keyword in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(variants)) = schema.get(keyword) {
+            let matched = variants.iter().any(|variant| {
+                let mut variant_errors = Vec::new();
+                validate_value(value, variant, path, &mut variant_errors);
+                variant_errors.is_empty()
+            });
+            if !matched {
+                errors.push(format!("{path}: does not match any allowed schema"));
+            }
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for field in required.iter().filter_map(Value::as_str) {
+                    if !map.contains_key(field) {
+                        errors.push(format!("{path}: missing required property `{field}`"));
+                    }
+                }
+            }
+
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, field_value) in map {
+                match properties.and_then(|p| p.get(key)) {
+                    Some(field_schema) => {
+                        validate_value(field_value, field_schema, &format!("{path}.{key}"), errors)
+                    }
+                    None => {
+                        if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                            errors.push(format!("{path}: unexpected property `{key}`"));
+                        }
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_value(item, item_schema, &format!("{path}[{index}]"), errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn person_format() -> StructuredOutputFormat {
+        StructuredOutputFormat {
+            name: "Person".to_string(),
+            description: Some("A person".to_string()),
+            schema: Some(json!({
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "age": {"type": "number"},
+                    "role": {"type": "string", "enum": ["admin", "user"]},
+                    "tags": {"type": "array", "items": {"type": "string"}}
+                },
+                "required": ["name", "age"],
+                "additionalProperties": false
+            })),
+            strict: Some(true),
+        }
+    }
+
+    #[test]
+    fn test_parse_response_valid() {
+        let value = person_format()
+            .parse_response(r#"{"name": "Ada", "age": 36, "tags": ["math"]}"#)
+            .unwrap();
+        assert_eq!(value["name"], "Ada");
+    }
+
+    #[test]
+    fn test_parse_response_strips_code_fences() {
+        let text = "Here you go:\n```json\n{\"name\": \"Ada\", \"age\": 36}\n```";
+        let value = person_format().parse_response(text).unwrap();
+        assert_eq!(value["age"], 36);
+    }
+
+    #[test]
+    fn test_parse_response_extracts_embedded_object() {
+        let text = "Sure! {\"name\": \"Ada\", \"age\": 36} Hope that helps.";
+        assert!(person_format().parse_response(text).is_ok());
+    }
+
+    #[test]
+    fn test_parse_response_invalid_json() {
+        let errors = person_format().parse_response("not json").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("not valid JSON"));
+    }
+
+    #[test]
+    fn test_parse_response_reports_all_errors() {
+        let errors = person_format()
+            .parse_response(r#"{"age": "old", "role": "guest", "tags": [1], "extra": true}"#)
+            .unwrap_err();
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("missing required property `name`")));
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("$.age: expected number, found string")));
+        assert!(errors.iter().any(|e| e.contains("$.role")));
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("$.tags[0]: expected string")));
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("unexpected property `extra`")));
+    }
+
+    #[test]
+    fn test_parse_response_without_schema_accepts_any_json() {
+        let format = StructuredOutputFormat {
+            name: "Any".to_string(),
+            description: None,
+            schema: None,
+            strict: None,
+        };
+        assert!(format.parse_response("[1, 2, 3]").is_ok());
+        assert!(format.parse_response("nope").is_err());
+    }
+
+    #[test]
+    fn test_integer_type_and_any_of() {
+        let format = StructuredOutputFormat {
+            name: "Count".to_string(),
+            description: None,
+            schema: Some(json!({
+                "type": "object",
+                "properties": {
+                    "count": {"type": "integer"},
+                    "value": {"anyOf": [{"type": "string"}, {"type": "null"}]}
+                }
+            })),
+            strict: None,
+        };
+        assert!(format
+            .parse_response(r#"{"count": 3, "value": null}"#)
+            .is_ok());
+        let errors = format
+            .parse_response(r#"{"count": 3.5, "value": 1}"#)
+            .unwrap_err();
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_prompt_instructions_and_repair_prompt() {
+        let format = person_format();
+        let instructions = format.prompt_instructions();
+        assert!(instructions.contains("`Person`"));
+        assert!(instructions.contains("\"required\""));
+
+        let repair = format.repair_prompt(&["$.age: expected number, found string".to_string()]);
+        assert!(repair.contains("$.age: expected number, found string"));
+        assert!(repair.contains(&instructions));
+    }
+
+    #[cfg(any(feature = "phind", feature = "ollama", feature = "liquid_edge"))]
+    #[test]
+    fn test_with_schema_instructions_prepends_system_message() {
+        let messages = vec![ChatMessage::user().content("Who are you?").build()];
+        let modified = with_schema_instructions(&messages, &person_format());
+        assert_eq!(modified.len(), 2);
+        assert_eq!(modified[0].role, ChatRole::System);
+        assert!(modified[0].content.contains("JSON schema"));
+        assert_eq!(modified[1].content, "Who are you?");
+    }
+}
@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::chat::ImageMime;
+use crate::error::LLMError;
+
+/// How generated images are returned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageResponseFormat {
+    /// Encoded image bytes
+    #[default]
+    Bytes,
+    /// Temporary URL hosted by the provider, where supported
+    Url,
+}
+
+/// A text-to-image request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageGenerationRequest {
+    pub prompt: String,
+    /// Model override; each provider falls back to its default image model
+    pub model: Option<String>,
+    /// Size as `WIDTHxHEIGHT` (e.g. `1024x1024`) or an aspect ratio such as `16:9`
+    pub size: Option<String>,
+    /// Number of images to generate
+    pub n: u32,
+    pub format: ImageResponseFormat,
+}
+
+impl ImageGenerationRequest {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            model: None,
+            size: None,
+            n: 1,
+            format: ImageResponseFormat::default(),
+        }
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn size(mut self, size: impl Into<String>) -> Self {
+        self.size = Some(size.into());
+        self
+    }
+
+    pub fn n(mut self, n: u32) -> Self {
+        self.n = n.max(1);
+        self
+    }
+
+    pub fn format(mut self, format: ImageResponseFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+/// An image produced by an [`ImageGenerationProvider`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeneratedImage {
+    Bytes { mime: ImageMime, data: Vec<u8> },
+    Url { url: String },
+}
+
+/// Providers that can generate images from a text prompt.
+#[async_trait]
+pub trait ImageGenerationProvider: Send + Sync {
+    async fn generate_images(
+        &self,
+        request: &ImageGenerationRequest,
+    ) -> Result<Vec<GeneratedImage>, LLMError>;
+}
+
+/// Guess the MIME type of encoded image bytes from their signature, defaulting to PNG
+pub fn sniff_image_mime(data: &[u8]) -> ImageMime {
+    match data {
+        [0xFF, 0xD8, 0xFF, ..] => ImageMime::JPEG,
+        [b'G', b'I', b'F', b'8', ..] => ImageMime::GIF,
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => ImageMime::WEBP,
+        _ => ImageMime::PNG,
+    }
+}
+
+/// Decode base64 image data returned by a provider
+#[cfg(any(feature = "openai", feature = "azure_openai", feature = "google"))]
+pub(crate) fn decode_image(encoded: &str) -> Result<Vec<u8>, LLMError> {
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+    STANDARD
+        .decode(encoded)
+        .map_err(|e| LLMError::ResponseFormatError {
+            message: format!("Invalid base64 image data: {e}"),
+            raw_response: String::new(),
+        })
+}
+
+/// JSON body for the OpenAI-style `images/generations` endpoint, shared by OpenAI and
+/// Azure OpenAI
+#[cfg(any(feature = "openai", feature = "azure_openai"))]
+#[derive(Serialize, Debug)]
+pub(crate) struct OpenAIImageRequest<'a> {
+    /// Omitted for Azure, where the deployment decides the model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<&'a str>,
+    pub prompt: &'a str,
+    pub n: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<&'a str>,
+    pub response_format: &'static str,
+}
+
+#[cfg(any(feature = "openai", feature = "azure_openai"))]
+impl<'a> OpenAIImageRequest<'a> {
+    pub(crate) fn new(request: &'a ImageGenerationRequest, model: Option<&'a str>) -> Self {
+        Self {
+            model,
+            prompt: &request.prompt,
+            n: request.n,
+            size: request.size.as_deref(),
+            response_format: match request.format {
+                ImageResponseFormat::Bytes => "b64_json",
+                ImageResponseFormat::Url => "url",
+            },
+        }
+    }
+}
+
+#[cfg(any(feature = "openai", feature = "azure_openai"))]
+#[derive(Deserialize, Debug)]
+pub(crate) struct OpenAIImageResponse {
+    data: Vec<OpenAIImageData>,
+}
+
+#[cfg(any(feature = "openai", feature = "azure_openai"))]
+#[derive(Deserialize, Debug)]
+struct OpenAIImageData {
+    b64_json: Option<String>,
+    url: Option<String>,
+}
+
+#[cfg(any(feature = "openai", feature = "azure_openai"))]
+impl OpenAIImageResponse {
+    pub(crate) fn into_images(self) -> Result<Vec<GeneratedImage>, LLMError> {
+        self.data
+            .into_iter()
+            .map(|image| match (image.b64_json, image.url) {
+                (Some(encoded), _) => {
+                    let data = decode_image(&encoded)?;
+                    Ok(GeneratedImage::Bytes {
+                        mime: sniff_image_mime(&data),
+                        data,
+                    })
+                }
+                (None, Some(url)) => Ok(GeneratedImage::Url { url }),
+                (None, None) => Err(LLMError::ResponseFormatError {
+                    message: "Image without data or URL".to_string(),
+                    raw_response: String::new(),
+                }),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_image_mime() {
+        assert_eq!(sniff_image_mime(&[0x89, b'P', b'N', b'G']), ImageMime::PNG);
+        assert_eq!(sniff_image_mime(&[0xFF, 0xD8, 0xFF, 0xE0]), ImageMime::JPEG);
+        assert_eq!(sniff_image_mime(b"GIF89a"), ImageMime::GIF);
+        assert_eq!(sniff_image_mime(b"RIFF\0\0\0\0WEBPVP8 "), ImageMime::WEBP);
+        assert_eq!(sniff_image_mime(&[]), ImageMime::PNG);
+    }
+
+    #[test]
+    fn test_request_clamps_count() {
+        let request = ImageGenerationRequest::new("a cat").n(0).size("512x512");
+        assert_eq!(request.n, 1);
+        assert_eq!(request.size.as_deref(), Some("512x512"));
+        assert_eq!(request.format, ImageResponseFormat::Bytes);
+    }
+}
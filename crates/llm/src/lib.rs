@@ -7,6 +7,10 @@
 //! - Chat-based interactions
 //! - Text completion
 //! - Embeddings generation
+//! - Speech-to-text and text-to-speech
+//! - Image generation
+//! - Content moderation
+//! - Batch processing for bulk offline jobs
 //! - Multiple providers (OpenAI, Anthropic, etc.)
 //! - Request validation and retry logic
 //!
@@ -15,9 +19,15 @@
 
 use serde::{Deserialize, Serialize};
 
+/// Speech-to-text and text-to-speech providers
+pub mod audio;
+
 /// Backend implementations for supported LLM providers like OpenAI, Anthropic, etc.
 pub mod backends;
 
+/// Batch processing of chat requests
+pub mod batch;
+
 /// Builder pattern for configuring and instantiating LLM providers
 pub mod builder;
 
@@ -33,6 +43,9 @@ pub mod embedding;
 /// Error types and handling
 pub mod error;
 
+/// Image generation from text prompts
+pub mod images;
+
 /// Evaluator for LLM providers
 pub mod evaluator;
 
@@ -43,8 +56,14 @@ pub mod secret_store;
 /// Listing models support
 pub mod models;
 
+/// Content moderation
+pub mod moderation;
+
 pub mod providers;
 
+/// Response validation with automatic re-prompting
+pub mod validated_llm;
+
 /// Core trait that all LLM providers must implement, combining chat, completion
 /// and embedding capabilities into a unified interface
 pub trait LLMProvider:
@@ -56,6 +75,10 @@ pub trait LLMProvider:
     + Sync
     + 'static
 {
+    /// What the configured model supports, if known. See [`models::CapabilityRegistry`].
+    fn capabilities(&self) -> Option<models::ModelCapabilities> {
+        None
+    }
 }
 
 /// Tool call represents a function call that an LLM wants to make.
@@ -0,0 +1,303 @@
+//! What models support, so callers can adapt before sending a request.
+//!
+//! The [`CapabilityRegistry`] holds built-in data for well-known models, matched by
+//! backend and model ID prefix. Entries can be added or overridden at runtime, including
+//! from configuration files through [`CapabilityEntry`].
+
+use crate::{builder::LLMBackend, chat::MessageType, error::LLMError};
+use serde::{Deserialize, Serialize};
+use std::sync::{OnceLock, RwLock};
+
+/// Features supported by a model through its backend.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelCapabilities {
+    /// Native tool calling
+    pub tool_calling: bool,
+    /// Image input
+    pub vision: bool,
+    /// PDF document input
+    pub pdf_input: bool,
+    /// Responses constrained to a JSON schema
+    pub structured_output: bool,
+    /// Streaming responses
+    pub streaming: bool,
+    /// Reasoning or extended thinking
+    pub reasoning: bool,
+    /// Context window in tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u32>,
+    /// Maximum output tokens per response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+}
+
+impl ModelCapabilities {
+    /// Whether the model accepts messages of this type
+    pub fn supports_message(&self, message_type: &MessageType) -> bool {
+        match message_type {
+            MessageType::Image(_) | MessageType::ImageURL(_) => self.vision,
+            MessageType::Pdf { .. } => self.pdf_input,
+            _ => true,
+        }
+    }
+}
+
+/// Capabilities of the models whose ID starts with `model`, e.g. as read from a config file:
+///
+/// ```json
+/// {"backend": "ollama", "model": "my-finetune", "capabilities": {"tool_calling": true}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapabilityEntry {
+    pub backend: LLMBackend,
+    /// Model ID prefix
+    pub model: String,
+    pub capabilities: ModelCapabilities,
+}
+
+const TOOLS: u8 = 1;
+const VISION: u8 = 1 << 1;
+const PDF: u8 = 1 << 2;
+const JSON: u8 = 1 << 3;
+const REASONING: u8 = 1 << 4;
+
+/// Built-in capabilities: backend, model ID prefix, features, context window, max output
+#[rustfmt::skip]
+const BUILTIN: &[(LLMBackend, &str, u8, u32, u32)] = &[
+    (LLMBackend::OpenAI, "gpt-3.5-turbo", TOOLS, 16_385, 4_096),
+    (LLMBackend::OpenAI, "gpt-4-turbo", TOOLS | VISION, 128_000, 4_096),
+    (LLMBackend::OpenAI, "gpt-4o", TOOLS | VISION | PDF | JSON, 128_000, 16_384),
+    (LLMBackend::OpenAI, "gpt-4.1", TOOLS | VISION | PDF | JSON, 1_047_576, 32_768),
+    (LLMBackend::OpenAI, "o1", TOOLS | VISION | PDF | JSON | REASONING, 200_000, 100_000),
+    (LLMBackend::OpenAI, "o1-mini", REASONING, 128_000, 65_536),
+    (LLMBackend::OpenAI, "o3", TOOLS | VISION | PDF | JSON | REASONING, 200_000, 100_000),
+    (LLMBackend::OpenAI, "o3-mini", TOOLS | JSON | REASONING, 200_000, 100_000),
+    (LLMBackend::OpenAI, "o4-mini", TOOLS | VISION | PDF | JSON | REASONING, 200_000, 100_000),
+    (LLMBackend::Anthropic, "claude-3-haiku", TOOLS | VISION, 200_000, 4_096),
+    (LLMBackend::Anthropic, "claude-3-sonnet", TOOLS | VISION, 200_000, 4_096),
+    (LLMBackend::Anthropic, "claude-3-opus", TOOLS | VISION, 200_000, 4_096),
+    (LLMBackend::Anthropic, "claude-3-5-haiku", TOOLS | VISION | PDF, 200_000, 8_192),
+    (LLMBackend::Anthropic, "claude-3-5-sonnet", TOOLS | VISION | PDF, 200_000, 8_192),
+    (LLMBackend::Anthropic, "claude-3-7-sonnet", TOOLS | VISION | PDF | REASONING, 200_000, 64_000),
+    (LLMBackend::Anthropic, "claude-sonnet-4", TOOLS | VISION | PDF | REASONING, 200_000, 64_000),
+    (LLMBackend::Anthropic, "claude-opus-4", TOOLS | VISION | PDF | REASONING, 200_000, 32_000),
+    (LLMBackend::Google, "gemini-1.5-flash", TOOLS | VISION | PDF | JSON, 1_048_576, 8_192),
+    (LLMBackend::Google, "gemini-1.5-pro", TOOLS | VISION | PDF | JSON, 2_097_152, 8_192),
+    (LLMBackend::Google, "gemini-2.0-flash", TOOLS | VISION | PDF | JSON, 1_048_576, 8_192),
+    (LLMBackend::Google, "gemini-2.5-flash", TOOLS | VISION | PDF | JSON | REASONING, 1_048_576, 65_536),
+    (LLMBackend::Google, "gemini-2.5-pro", TOOLS | VISION | PDF | JSON | REASONING, 1_048_576, 65_536),
+    // The DeepSeek backend does not send tools
+    (LLMBackend::DeepSeek, "deepseek-chat", 0, 65_536, 8_192),
+    (LLMBackend::DeepSeek, "deepseek-reasoner", REASONING, 65_536, 8_192),
+    (LLMBackend::XAI, "grok-2", TOOLS | JSON, 131_072, 32_768),
+    (LLMBackend::XAI, "grok-2-vision", TOOLS | VISION | JSON, 32_768, 32_768),
+    (LLMBackend::XAI, "grok-3", TOOLS | JSON, 131_072, 32_768),
+    (LLMBackend::XAI, "grok-3-mini", TOOLS | JSON | REASONING, 131_072, 32_768),
+    // Groq, Mistral, OpenRouter and generic OpenAI-compatible servers share the
+    // OpenAI-compatible provider, which sends images as data-URL `image_url` parts
+    (LLMBackend::Groq, "llama-3.1-8b-instant", TOOLS, 131_072, 131_072),
+    (LLMBackend::Groq, "llama-3.3-70b-versatile", TOOLS, 131_072, 32_768),
+    (LLMBackend::Mistral, "mistral-large", TOOLS | JSON, 131_072, 131_072),
+    (LLMBackend::Mistral, "mistral-small", TOOLS | VISION | JSON, 131_072, 131_072),
+    (LLMBackend::Mistral, "pixtral-large", TOOLS | VISION | JSON, 131_072, 131_072),
+    (LLMBackend::Mistral, "codestral", TOOLS | JSON, 262_144, 262_144),
+    (LLMBackend::Cohere, "command-r", TOOLS | JSON, 128_000, 4_096),
+    (LLMBackend::Cohere, "command-a", TOOLS | JSON, 256_000, 8_192),
+    (LLMBackend::Ollama, "llama3.1", TOOLS | JSON, 131_072, 131_072),
+    (LLMBackend::Ollama, "llama3.2", TOOLS | JSON, 131_072, 131_072),
+    (LLMBackend::Ollama, "llama3.2-vision", VISION | JSON, 131_072, 131_072),
+    (LLMBackend::Ollama, "llava", VISION | JSON, 4_096, 4_096),
+    (LLMBackend::Ollama, "qwen2.5", TOOLS | JSON, 32_768, 32_768),
+    (LLMBackend::Ollama, "qwen3", TOOLS | JSON | REASONING, 40_960, 40_960),
+];
+
+/// Model capabilities keyed by backend and model ID prefix.
+///
+/// The entry with the longest matching prefix wins, and among equal prefixes the one
+/// registered last, so registered entries override the built-in ones.
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityRegistry {
+    entries: Vec<CapabilityEntry>,
+}
+
+impl CapabilityRegistry {
+    /// A registry without any entries
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with the built-in data for well-known models
+    pub fn builtin() -> Self {
+        let entries = BUILTIN
+            .iter()
+            .map(|(backend, model, flags, context, output)| CapabilityEntry {
+                backend: backend.clone(),
+                model: model.to_string(),
+                capabilities: ModelCapabilities {
+                    tool_calling: flags & TOOLS != 0,
+                    vision: flags & VISION != 0,
+                    pdf_input: flags & PDF != 0,
+                    structured_output: flags & JSON != 0,
+                    streaming: true,
+                    reasoning: flags & REASONING != 0,
+                    context_window: Some(*context),
+                    max_output_tokens: Some(*output),
+                },
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// The process-wide registry used by [`LLMProvider::capabilities`](crate::LLMProvider::capabilities),
+    /// initialized with the built-in data
+    pub fn global() -> &'static RwLock<CapabilityRegistry> {
+        static GLOBAL: OnceLock<RwLock<CapabilityRegistry>> = OnceLock::new();
+        GLOBAL.get_or_init(|| RwLock::new(Self::builtin()))
+    }
+
+    /// Add or override the capabilities of the models whose ID starts with `model`
+    pub fn register(
+        &mut self,
+        backend: LLMBackend,
+        model: impl Into<String>,
+        capabilities: ModelCapabilities,
+    ) {
+        self.entries.push(CapabilityEntry {
+            backend,
+            model: model.into(),
+            capabilities,
+        });
+    }
+
+    /// Add or override entries, e.g. from a configuration file
+    pub fn extend(&mut self, entries: impl IntoIterator<Item = CapabilityEntry>) {
+        self.entries.extend(entries);
+    }
+
+    /// Add or override entries from a JSON array of [`CapabilityEntry`]
+    pub fn load_json(&mut self, json: &str) -> Result<(), LLMError> {
+        let entries: Vec<CapabilityEntry> = serde_json::from_str(json)?;
+        self.extend(entries);
+        Ok(())
+    }
+
+    /// Capabilities of `model` on `backend`, if known.
+    ///
+    /// Vendor or path prefixes such as `models/` or `openai/` are ignored when the full ID
+    /// has no match. Aggregating backends (OpenRouter, Azure OpenAI) fall back to the data
+    /// of the backend serving the model.
+    pub fn lookup(&self, backend: &LLMBackend, model: &str) -> Option<ModelCapabilities> {
+        let name = model.rsplit('/').next().unwrap_or(model);
+        self.find(Some(backend), model)
+            .or_else(|| self.find(Some(backend), name))
+            .or_else(|| {
+                matches!(backend, LLMBackend::OpenRouter | LLMBackend::AzureOpenAI)
+                    .then(|| self.find(None, name))
+                    .flatten()
+            })
+    }
+
+    fn find(&self, backend: Option<&LLMBackend>, model: &str) -> Option<ModelCapabilities> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| backend.is_none_or(|b| *b == e.backend) && model.starts_with(&e.model))
+            .max_by_key(|(i, e)| (e.model.len(), *i))
+            .map(|(_, e)| e.capabilities.clone())
+    }
+}
+
+/// Capabilities of `model` on `backend` in the [global](CapabilityRegistry::global) registry
+pub fn lookup_capabilities(backend: &LLMBackend, model: &str) -> Option<ModelCapabilities> {
+    CapabilityRegistry::global()
+        .read()
+        .ok()
+        .and_then(|registry| registry.lookup(backend, model))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let registry = CapabilityRegistry::builtin();
+        let mini = registry
+            .lookup(&LLMBackend::OpenAI, "o3-mini-2025-01-31")
+            .unwrap();
+        assert!(!mini.vision);
+        assert!(mini.reasoning);
+        let o3 = registry.lookup(&LLMBackend::OpenAI, "o3").unwrap();
+        assert!(o3.vision);
+        assert_eq!(
+            registry
+                .lookup(&LLMBackend::OpenAI, "gpt-4o-mini")
+                .unwrap()
+                .context_window,
+            Some(128_000)
+        );
+    }
+
+    #[test]
+    fn test_unknown_model() {
+        let registry = CapabilityRegistry::builtin();
+        assert_eq!(registry.lookup(&LLMBackend::OpenAI, "davinci-002"), None);
+        // Entries are per backend
+        assert_eq!(registry.lookup(&LLMBackend::Groq, "gpt-4o"), None);
+    }
+
+    #[test]
+    fn test_vendor_prefixes() {
+        let registry = CapabilityRegistry::builtin();
+        let gemini = registry
+            .lookup(&LLMBackend::Google, "models/gemini-2.0-flash")
+            .unwrap();
+        assert!(gemini.pdf_input);
+        let claude = registry
+            .lookup(&LLMBackend::OpenRouter, "anthropic/claude-3-5-sonnet")
+            .unwrap();
+        assert!(claude.tool_calling);
+        assert!(registry
+            .lookup(&LLMBackend::Ollama, "openai/gpt-4o")
+            .is_none());
+    }
+
+    #[test]
+    fn test_register_overrides_builtin() {
+        let mut registry = CapabilityRegistry::builtin();
+        registry.register(
+            LLMBackend::DeepSeek,
+            "deepseek-chat",
+            ModelCapabilities {
+                tool_calling: true,
+                ..Default::default()
+            },
+        );
+        let caps = registry
+            .lookup(&LLMBackend::DeepSeek, "deepseek-chat")
+            .unwrap();
+        assert!(caps.tool_calling);
+        assert_eq!(caps.context_window, None);
+    }
+
+    #[test]
+    fn test_load_json() {
+        let mut registry = CapabilityRegistry::new();
+        registry
+            .load_json(
+                r#"[{"backend": "ollama", "model": "my-finetune", "capabilities": {"tool_calling": true, "context_window": 8192}}]"#,
+            )
+            .unwrap();
+        let caps = registry
+            .lookup(&LLMBackend::Ollama, "my-finetune:latest")
+            .unwrap();
+        assert!(caps.tool_calling);
+        assert!(!caps.vision);
+        assert_eq!(caps.context_window, Some(8192));
+
+        assert!(registry
+            .load_json(r#"[{"backend": "nope", "model": "x", "capabilities": {}}]"#)
+            .is_err());
+    }
+}
@@ -5,10 +5,29 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fmt::Debug;
 
+/// Model capability registry
+pub mod capabilities;
+
+pub use capabilities::{
+    lookup_capabilities, CapabilityEntry, CapabilityRegistry, ModelCapabilities,
+};
+
 pub trait ModelListResponse: std::fmt::Debug {
     fn get_models(&self) -> Vec<String>;
     fn get_models_raw(&self) -> Vec<Box<dyn ModelListRawEntry>>;
     fn get_backend(&self) -> LLMBackend;
+
+    /// Model ID's with their capabilities from the global [`CapabilityRegistry`], if known
+    fn get_models_with_capabilities(&self) -> Vec<(String, Option<ModelCapabilities>)> {
+        let backend = self.get_backend();
+        self.get_models()
+            .into_iter()
+            .map(|id| {
+                let capabilities = lookup_capabilities(&backend, &id);
+                (id, capabilities)
+            })
+            .collect()
+    }
 }
 
 pub trait ModelListRawEntry: Debug {
@@ -348,6 +367,23 @@ mod tests {
         assert!(matches!(response.get_backend(), LLMBackend::Google));
     }
 
+    #[test]
+    fn test_get_models_with_capabilities() {
+        let response = MockModelListResponse {
+            models: vec![
+                "models/gemini-1.5-pro".to_string(),
+                "models/unknown".to_string(),
+            ],
+            raw_entries: vec![],
+            backend: LLMBackend::Google,
+        };
+
+        let models = response.get_models_with_capabilities();
+        assert_eq!(models[0].0, "models/gemini-1.5-pro");
+        assert!(models[0].1.as_ref().unwrap().vision);
+        assert_eq!(models[1].1, None);
+    }
+
     #[tokio::test]
     async fn test_mock_models_provider_success() {
         let models = vec!["gpt-3.5-turbo".to_string(), "gpt-4".to_string()];
@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::chat::{ChatMessage, ChatMessageBuilder, ChatProvider, ChatRole};
+use crate::error::LLMError;
+
+/// Categories checked by [`LLMModerator`] unless others are configured
+const DEFAULT_CATEGORIES: [&str; 6] = [
+    "harassment",
+    "hate",
+    "self-harm",
+    "sexual",
+    "violence",
+    "illicit",
+];
+
+/// The verdict of a [`ModerationProvider`] on a piece of text.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModerationResult {
+    pub flagged: bool,
+    /// Categories the text was flagged for
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Per-category scores between 0 and 1, where the provider reports them
+    #[serde(default)]
+    pub scores: BTreeMap<String, f32>,
+}
+
+/// Providers that can check text against a content policy.
+#[async_trait]
+pub trait ModerationProvider: Send + Sync {
+    async fn moderate(&self, input: &str) -> Result<ModerationResult, LLMError>;
+}
+
+/// Moderation by prompting a chat model, for providers without a moderation endpoint.
+///
+/// The model is asked for a JSON verdict, so a model that follows instructions reliably
+/// gives the best results.
+pub struct LLMModerator {
+    llm: Arc<dyn ChatProvider>,
+    categories: Vec<String>,
+}
+
+impl LLMModerator {
+    pub fn new(llm: Arc<dyn ChatProvider>) -> Self {
+        Self {
+            llm,
+            categories: DEFAULT_CATEGORIES.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    /// Replace the categories the model checks for
+    pub fn with_categories<S: Into<String>>(
+        mut self,
+        categories: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.categories = categories.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn instructions(&self) -> String {
+        format!(
+            "You are a content moderation system. Decide whether the user's text contains \
+content in any of these categories: {}.\n\
+Reply with only a JSON object of the form {{\"flagged\": true, \"categories\": [\"violence\"]}}, \
+listing every category that applies, or {{\"flagged\": false, \"categories\": []}}.",
+            self.categories.join(", ")
+        )
+    }
+
+    /// Parse the verdict, ignoring any text around the JSON object
+    fn parse(&self, text: &str) -> Result<ModerationResult, LLMError> {
+        let json = text
+            .find('{')
+            .zip(text.rfind('}'))
+            .filter(|(start, end)| start < end)
+            .map(|(start, end)| &text[start..=end])
+            .unwrap_or(text);
+        let mut result: ModerationResult =
+            serde_json::from_str(json).map_err(|e| LLMError::ResponseFormatError {
+                message: format!("Invalid moderation verdict: {e}"),
+                raw_response: text.to_string(),
+            })?;
+        result.flagged |= !result.categories.is_empty();
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl ModerationProvider for LLMModerator {
+    async fn moderate(&self, input: &str) -> Result<ModerationResult, LLMError> {
+        let messages = [
+            ChatMessageBuilder::new(ChatRole::System)
+                .content(self.instructions())
+                .build(),
+            ChatMessage::user().content(input).build(),
+        ];
+        let response = self.llm.chat(&messages, None, None).await?;
+        self.parse(&response.text().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::{ChatResponse, StructuredOutputFormat, Tool};
+    use crate::ToolCall;
+    use std::sync::Mutex;
+
+    #[derive(Debug)]
+    struct Reply(String);
+
+    impl ChatResponse for Reply {
+        fn text(&self) -> Option<String> {
+            Some(self.0.clone())
+        }
+
+        fn tool_calls(&self) -> Option<Vec<ToolCall>> {
+            None
+        }
+    }
+
+    impl std::fmt::Display for Reply {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    struct Judge {
+        verdict: &'static str,
+        prompts: Mutex<Vec<Vec<ChatMessage>>>,
+    }
+
+    #[async_trait]
+    impl ChatProvider for Judge {
+        async fn chat(
+            &self,
+            messages: &[ChatMessage],
+            _tools: Option<&[Tool]>,
+            _json_schema: Option<StructuredOutputFormat>,
+        ) -> Result<Box<dyn ChatResponse>, LLMError> {
+            self.prompts.lock().unwrap().push(messages.to_vec());
+            Ok(Box::new(Reply(self.verdict.to_string())))
+        }
+    }
+
+    fn judge(verdict: &'static str) -> Arc<Judge> {
+        Arc::new(Judge {
+            verdict,
+            prompts: Mutex::new(Vec::new()),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_llm_moderator_parses_verdict() {
+        let llm = judge("Sure.\n```json\n{\"flagged\": true, \"categories\": [\"violence\"]}\n```");
+        let moderator = LLMModerator::new(llm.clone()).with_categories(["violence", "spam"]);
+
+        let result = moderator.moderate("I will hurt you").await.unwrap();
+        assert!(result.flagged);
+        assert_eq!(result.categories, vec!["violence"]);
+
+        let prompts = llm.prompts.lock().unwrap();
+        assert!(prompts[0][0].content.contains("violence, spam"));
+        assert_eq!(prompts[0][1].content, "I will hurt you");
+    }
+
+    #[tokio::test]
+    async fn test_llm_moderator_flags_listed_categories() {
+        let moderator = LLMModerator::new(judge(r#"{"flagged": false, "categories": ["hate"]}"#));
+        assert!(moderator.moderate("text").await.unwrap().flagged);
+
+        let moderator = LLMModerator::new(judge(r#"{"flagged": false}"#));
+        assert!(!moderator.moderate("text").await.unwrap().flagged);
+    }
+
+    #[tokio::test]
+    async fn test_llm_moderator_rejects_non_json() {
+        let moderator = LLMModerator::new(judge("I can't help with that"));
+        assert!(matches!(
+            moderator.moderate("text").await,
+            Err(LLMError::ResponseFormatError { .. })
+        ));
+    }
+}
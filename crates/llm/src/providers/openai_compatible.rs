@@ -15,10 +15,12 @@ use crate::{
     default_call_type, ToolCall,
 };
 use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
 use either::*;
-use futures::{stream::Stream, StreamExt};
+use futures::stream::Stream;
 use reqwest::{Client, Url};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::marker::PhantomData;
 
 /// Generic OpenAI-compatible provider
@@ -45,6 +47,10 @@ pub struct OpenAICompatibleProvider<T: OpenAIProviderConfig> {
     #[allow(dead_code)]
     pub embedding_dimensions: Option<u32>,
     pub normalize_response: bool,
+    /// Request-shaping switches, defaulting to the values of `T`
+    pub quirks: OpenAICompatibleQuirks,
+    /// Headers added to every request, defaulting to `T::custom_headers()`
+    pub headers: Vec<(String, String)>,
     pub client: Client,
     _phantom: PhantomData<T>,
 }
@@ -70,12 +76,45 @@ pub trait OpenAIProviderConfig: Send + Sync {
     const SUPPORTS_PARALLEL_TOOL_CALLS: bool = false;
     /// Whether this provider supports stream options (like include_usage)
     const SUPPORTS_STREAM_OPTIONS: bool = false;
+    /// Whether requests are rejected without an API key
+    const REQUIRES_API_KEY: bool = true;
     /// Custom headers to add to requests
     fn custom_headers() -> Option<Vec<(String, String)>> {
         None
     }
 }
 
+/// Which optional request fields an OpenAI-compatible server accepts
+///
+/// Providers get these from their [`OpenAIProviderConfig`] constants; self-hosted
+/// servers differ in what they reject, so they can also be set at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenAICompatibleQuirks {
+    /// Send `reasoning_effort`
+    pub supports_reasoning_effort: bool,
+    /// Send `response_format` for structured output
+    pub supports_structured_output: bool,
+    /// Send `parallel_tool_calls`
+    pub supports_parallel_tool_calls: bool,
+    /// Send `stream_options` to request usage on streams
+    pub supports_stream_options: bool,
+    /// Fail requests early when no API key is set, instead of sending them without auth
+    pub requires_api_key: bool,
+}
+
+impl OpenAICompatibleQuirks {
+    /// The quirks declared by a provider configuration
+    pub fn from_config<T: OpenAIProviderConfig>() -> Self {
+        Self {
+            supports_reasoning_effort: T::SUPPORTS_REASONING_EFFORT,
+            supports_structured_output: T::SUPPORTS_STRUCTURED_OUTPUT,
+            supports_parallel_tool_calls: T::SUPPORTS_PARALLEL_TOOL_CALLS,
+            supports_stream_options: T::SUPPORTS_STREAM_OPTIONS,
+            requires_api_key: T::REQUIRES_API_KEY,
+        }
+    }
+}
+
 /// Generic OpenAI-compatible chat message
 #[derive(Serialize, Debug)]
 pub struct OpenAIChatMessage<'a> {
@@ -96,9 +135,11 @@ pub struct OpenAIMessageContent<'a> {
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub message_type: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub text: Option<&'a str>,
+    pub text: Option<Cow<'a, str>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub image_url: Option<ImageUrlContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<FileContent>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "tool_call_id")]
     pub tool_call_id: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "content")]
@@ -110,6 +151,13 @@ pub struct ImageUrlContent {
     pub url: String,
 }
 
+/// Inline file (e.g. PDF) in an OpenAI-compatible chat conversation
+#[derive(Serialize, Debug)]
+pub struct FileContent {
+    pub filename: String,
+    pub file_data: String,
+}
+
 /// Generic OpenAI-compatible chat request
 #[derive(Serialize, Debug)]
 pub struct OpenAIChatRequest<'a> {
@@ -198,6 +246,10 @@ pub struct OpenAIStreamDelta {
     pub content: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<StreamToolCall>>,
+    /// Reasoning text, as sent by DeepSeek and xAI
+    pub reasoning_content: Option<String>,
+    /// Reasoning text, as sent by OpenRouter
+    pub reasoning: Option<String>,
 }
 
 /// Tool call represents a function call that an LLM wants to make.
@@ -344,6 +396,8 @@ impl<T: OpenAIProviderConfig> OpenAICompatibleProvider<T> {
             normalize_response: normalize_response.unwrap_or(true),
             embedding_encoding_format,
             embedding_dimensions,
+            quirks: OpenAICompatibleQuirks::from_config::<T>(),
+            headers: T::custom_headers().unwrap_or_default(),
             client: builder.build().expect("Failed to build reqwest Client"),
             _phantom: PhantomData,
         }
@@ -352,6 +406,7 @@ impl<T: OpenAIProviderConfig> OpenAICompatibleProvider<T> {
     pub fn prepare_messages(&self, messages: &[ChatMessage]) -> Vec<OpenAIChatMessage<'_>> {
         let mut openai_msgs: Vec<OpenAIChatMessage> = messages
             .iter()
+            .filter(|m| !m.is_cache_breakpoint())
             .flat_map(|msg| {
                 if let MessageType::ToolResult(ref results) = msg.message_type {
                     // Expand ToolResult into multiple messages
@@ -377,8 +432,9 @@ impl<T: OpenAIProviderConfig> OpenAICompatibleProvider<T> {
                     role: "system",
                     content: Some(Left(vec![OpenAIMessageContent {
                         message_type: Some("text"),
-                        text: Some(system.as_str()),
+                        text: Some(Cow::Borrowed(system.as_str())),
                         image_url: None,
+                        file: None,
                         tool_call_id: None,
                         tool_output: None,
                     }])),
@@ -389,6 +445,40 @@ impl<T: OpenAIProviderConfig> OpenAICompatibleProvider<T> {
         }
         openai_msgs
     }
+
+    /// Fail with an auth error when an API key is required but missing
+    pub(crate) fn check_api_key(&self) -> Result<(), LLMError> {
+        if self.quirks.requires_api_key && self.api_key.is_empty() {
+            return Err(LLMError::AuthError(format!(
+                "Missing {} API key",
+                T::PROVIDER_NAME
+            )));
+        }
+        Ok(())
+    }
+
+    /// A request to `path` under the base URL, with auth, custom headers and timeout applied
+    pub(crate) fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+    ) -> Result<reqwest::RequestBuilder, LLMError> {
+        let url = self
+            .base_url
+            .join(path)
+            .map_err(|e| LLMError::HttpError(e.to_string()))?;
+        let mut request = self.client.request(method, url);
+        if !self.api_key.is_empty() {
+            request = request.bearer_auth(&self.api_key);
+        }
+        for (key, value) in &self.headers {
+            request = request.header(key, value);
+        }
+        if let Some(timeout) = self.timeout_seconds {
+            request = request.timeout(std::time::Duration::from_secs(timeout));
+        }
+        Ok(request)
+    }
 }
 
 #[async_trait]
@@ -400,33 +490,29 @@ impl<T: OpenAIProviderConfig> ChatProvider for OpenAICompatibleProvider<T> {
         tools: Option<&[Tool]>,
         json_schema: Option<StructuredOutputFormat>,
     ) -> Result<Box<dyn ChatResponse>, LLMError> {
-        if self.api_key.is_empty() {
-            return Err(LLMError::AuthError(format!(
-                "Missing {} API key",
-                T::PROVIDER_NAME
-            )));
-        }
+        self.check_api_key()?;
         let openai_msgs = self.prepare_messages(messages);
-        let response_format: Option<OpenAIResponseFormat> = if T::SUPPORTS_STRUCTURED_OUTPUT {
-            json_schema
-                .clone()
-                .or_else(|| json_schema.clone())
-                .map(|s| s.into())
-        } else {
-            None
-        };
+        let response_format: Option<OpenAIResponseFormat> =
+            if self.quirks.supports_structured_output {
+                json_schema
+                    .clone()
+                    .or_else(|| json_schema.clone())
+                    .map(|s| s.into())
+            } else {
+                None
+            };
         let request_tools = tools.map(|t| t.to_vec());
         let request_tool_choice = if request_tools.is_some() {
             self.tool_choice.clone()
         } else {
             None
         };
-        let reasoning_effort = if T::SUPPORTS_REASONING_EFFORT {
+        let reasoning_effort = if self.quirks.supports_reasoning_effort {
             self.reasoning_effort.clone()
         } else {
             None
         };
-        let parallel_tool_calls = if T::SUPPORTS_PARALLEL_TOOL_CALLS {
+        let parallel_tool_calls = if self.quirks.supports_parallel_tool_calls {
             Some(self.parallel_tool_calls)
         } else {
             None
@@ -446,25 +532,14 @@ impl<T: OpenAIProviderConfig> ChatProvider for OpenAICompatibleProvider<T> {
             stream_options: None,
             parallel_tool_calls,
         };
-        let url = self
-            .base_url
-            .join(T::CHAT_ENDPOINT)
-            .map_err(|e| LLMError::HttpError(e.to_string()))?;
-        let mut request = self.client.post(url).bearer_auth(&self.api_key).json(&body);
-        // Add custom headers if provider specifies them
-        if let Some(headers) = T::custom_headers() {
-            for (key, value) in headers {
-                request = request.header(key, value);
-            }
-        }
+        let request = self
+            .request(reqwest::Method::POST, T::CHAT_ENDPOINT)?
+            .json(&body);
         if log::log_enabled!(log::Level::Trace) {
             if let Ok(json) = serde_json::to_string(&body) {
                 log::trace!("{} request payload: {}", T::PROVIDER_NAME, json);
             }
         }
-        if let Some(timeout) = self.timeout_seconds {
-            request = request.timeout(std::time::Duration::from_secs(timeout));
-        }
         let response = request.send().await?;
         log::debug!("{} HTTP status: {}", T::PROVIDER_NAME, response.status());
         if !response.status().is_success() {
@@ -498,22 +573,7 @@ impl<T: OpenAIProviderConfig> ChatProvider for OpenAICompatibleProvider<T> {
         let struct_stream = self
             .chat_stream_struct(messages, tools, json_schema)
             .await?;
-        let content_stream = struct_stream.filter_map(|result| async move {
-            match result {
-                Ok(stream_response) => {
-                    if let Some(choice) = stream_response.choices.first() {
-                        if let Some(content) = &choice.delta.content {
-                            if !content.is_empty() {
-                                return Some(Ok(content.clone()));
-                            }
-                        }
-                    }
-                    None
-                }
-                Err(e) => Some(Err(e)),
-            }
-        });
-        Ok(Box::pin(content_stream))
+        Ok(crate::chat::content_stream(struct_stream))
     }
 
     /// Stream chat responses as `ChatMessage` structured objects, including usage information
@@ -526,12 +586,7 @@ impl<T: OpenAIProviderConfig> ChatProvider for OpenAICompatibleProvider<T> {
         std::pin::Pin<Box<dyn Stream<Item = Result<StreamResponse, LLMError>> + Send>>,
         LLMError,
     > {
-        if self.api_key.is_empty() {
-            return Err(LLMError::AuthError(format!(
-                "Missing {} API key",
-                T::PROVIDER_NAME
-            )));
-        }
+        self.check_api_key()?;
         let openai_msgs = self.prepare_messages(messages);
         let request_tools = tools.map(|t| t.to_vec());
         let request_tool_choice = if request_tools.is_some() {
@@ -539,14 +594,15 @@ impl<T: OpenAIProviderConfig> ChatProvider for OpenAICompatibleProvider<T> {
         } else {
             None
         };
-        let response_format: Option<OpenAIResponseFormat> = if T::SUPPORTS_STRUCTURED_OUTPUT {
-            json_schema
-                .clone()
-                .or_else(|| json_schema.clone())
-                .map(|s| s.into())
-        } else {
-            None
-        };
+        let response_format: Option<OpenAIResponseFormat> =
+            if self.quirks.supports_structured_output {
+                json_schema
+                    .clone()
+                    .or_else(|| json_schema.clone())
+                    .map(|s| s.into())
+            } else {
+                None
+            };
         let body = OpenAIChatRequest {
             model: &self.model,
             messages: openai_msgs,
@@ -557,39 +613,30 @@ impl<T: OpenAIProviderConfig> ChatProvider for OpenAICompatibleProvider<T> {
             top_k: self.top_k,
             tools: request_tools,
             tool_choice: request_tool_choice,
-            reasoning_effort: if T::SUPPORTS_REASONING_EFFORT {
+            reasoning_effort: if self.quirks.supports_reasoning_effort {
                 self.reasoning_effort.clone()
             } else {
                 None
             },
             response_format,
-            stream_options: if T::SUPPORTS_STREAM_OPTIONS {
+            stream_options: if self.quirks.supports_stream_options {
                 Some(OpenAIStreamOptions {
                     include_usage: true,
                 })
             } else {
                 None
             },
-            parallel_tool_calls: if T::SUPPORTS_PARALLEL_TOOL_CALLS {
+            parallel_tool_calls: if self.quirks.supports_parallel_tool_calls {
                 Some(self.parallel_tool_calls)
             } else {
                 None
             },
         };
-        let url = self
-            .base_url
-            .join(T::CHAT_ENDPOINT)
-            .map_err(|e| LLMError::HttpError(e.to_string()))?;
-        let mut request = self.client.post(url).bearer_auth(&self.api_key).json(&body);
-        if let Some(headers) = T::custom_headers() {
-            for (key, value) in headers {
-                request = request.header(key, value);
-            }
-        }
-        if let Some(timeout) = self.timeout_seconds {
-            request = request.timeout(std::time::Duration::from_secs(timeout));
-        }
-        let response = request.send().await?;
+        let response = self
+            .request(reqwest::Method::POST, T::CHAT_ENDPOINT)?
+            .json(&body)
+            .send()
+            .await?;
         if !response.status().is_success() {
             let status = response.status();
             let error_text = response.text().await?;
@@ -602,6 +649,37 @@ impl<T: OpenAIProviderConfig> ChatProvider for OpenAICompatibleProvider<T> {
     }
 }
 
+fn image_url_part(url: String) -> OpenAIMessageContent<'static> {
+    OpenAIMessageContent {
+        message_type: Some("image_url"),
+        text: None,
+        image_url: Some(ImageUrlContent { url }),
+        file: None,
+        tool_output: None,
+        tool_call_id: None,
+    }
+}
+
+/// Content parts for a multimodal message, with the message text (if any) before the media part
+fn with_text_part(
+    text: &str,
+    media: OpenAIMessageContent<'static>,
+) -> Vec<OpenAIMessageContent<'static>> {
+    let mut parts = Vec::with_capacity(2);
+    if !text.is_empty() {
+        parts.push(OpenAIMessageContent {
+            message_type: Some("text"),
+            text: Some(Cow::Owned(text.to_string())),
+            image_url: None,
+            file: None,
+            tool_output: None,
+            tool_call_id: None,
+        });
+    }
+    parts.push(media);
+    parts
+}
+
 /// Create OpenAICompatibleChatMessage` that doesn't borrow from any temporary variables
 pub fn chat_message_to_openai_message(chat_msg: ChatMessage) -> OpenAIChatMessage<'static> {
     OpenAIChatMessage {
@@ -614,17 +692,37 @@ pub fn chat_message_to_openai_message(chat_msg: ChatMessage) -> OpenAIChatMessag
         tool_call_id: None,
         content: match &chat_msg.message_type {
             MessageType::Text => Some(Right(chat_msg.content.clone())),
-            MessageType::Image(_) => unreachable!(),
-            MessageType::Pdf(_) => unimplemented!(),
-            MessageType::ImageURL(url) => Some(Left(vec![OpenAIMessageContent {
-                message_type: Some("image_url"),
-                text: None,
-                image_url: Some(ImageUrlContent { url: url.clone() }),
-                tool_output: None,
-                tool_call_id: None,
-            }])),
+            MessageType::Image((image_mime, raw_bytes)) => Some(Left(with_text_part(
+                &chat_msg.content,
+                image_url_part(format!(
+                    "data:{};base64,{}",
+                    image_mime.mime_type(),
+                    BASE64_STANDARD.encode(raw_bytes)
+                )),
+            ))),
+            MessageType::Pdf { name, data } => Some(Left(with_text_part(
+                &chat_msg.content,
+                OpenAIMessageContent {
+                    message_type: Some("file"),
+                    text: None,
+                    image_url: None,
+                    file: Some(FileContent {
+                        filename: name.clone(),
+                        file_data: format!(
+                            "data:application/pdf;base64,{}",
+                            BASE64_STANDARD.encode(data)
+                        ),
+                    }),
+                    tool_output: None,
+                    tool_call_id: None,
+                },
+            ))),
+            MessageType::ImageURL(url) => Some(Left(with_text_part(
+                &chat_msg.content,
+                image_url_part(url.clone()),
+            ))),
             MessageType::ToolUse(_) => None,
-            MessageType::ToolResult(_) => None,
+            MessageType::ToolResult(_) | MessageType::CacheBreakpoint => None,
         },
         tool_calls: match &chat_msg.message_type {
             MessageType::ToolUse(calls) => {
@@ -656,6 +754,8 @@ pub fn create_sse_stream(
     struct SSEStreamParser {
         event_buffer: String,
         tool_buffer: ToolCall,
+        /// Index of the next normalized tool call
+        tool_index: usize,
         usage: Option<Usage>,
         results: Vec<Result<StreamResponse, LLMError>>,
         normalize_response: bool,
@@ -666,6 +766,7 @@ pub fn create_sse_stream(
                 event_buffer: String::new(),
                 usage: None,
                 results: Vec::new(),
+                tool_index: 0,
                 tool_buffer: ToolCall {
                     id: String::new(),
                     call_type: "function".to_string(),
@@ -683,7 +784,7 @@ pub fn create_sse_stream(
             if self.normalize_response && !self.tool_buffer.function.name.is_empty() {
                 // Convert ToolCall to StreamToolCallDelta for normalized output
                 let tool_call_delta = StreamToolCallDelta {
-                    index: 0,
+                    index: self.tool_index,
                     function: Some(StreamToolCallFunction {
                         name: self.tool_buffer.function.name.clone(),
                         arguments: self.tool_buffer.function.arguments.clone(),
@@ -694,10 +795,12 @@ pub fn create_sse_stream(
                         delta: StreamDelta {
                             content: None,
                             tool_calls: Some(vec![tool_call_delta]),
+                            reasoning_content: None,
                         },
                     }],
                     usage: None,
                 }));
+                self.tool_index += 1;
             }
             self.tool_buffer = ToolCall {
                 id: String::new(),
@@ -722,6 +825,7 @@ pub fn create_sse_stream(
                                     delta: StreamDelta {
                                         content: None,
                                         tool_calls: None,
+                                        reasoning_content: None,
                                     },
                                 }],
                                 usage: Some(usage),
@@ -743,6 +847,12 @@ pub fn create_sse_stream(
                 }
                 for choice in &response.choices {
                     let content = choice.delta.content.clone();
+                    let reasoning = choice
+                        .delta
+                        .reasoning_content
+                        .clone()
+                        .or_else(|| choice.delta.reasoning.clone())
+                        .filter(|r| !r.is_empty());
 
                     if self.normalize_response {
                         // Map StreamToolCall (some fields are optional) to ToolCall for normalization
@@ -761,7 +871,14 @@ pub fn create_sse_stream(
                                     .collect::<Vec<ToolCall>>()
                             });
 
-                        if content.is_some() || tool_calls.is_some() {
+                        if let Some(reasoning) = reasoning {
+                            self.results
+                                .push(Ok(StreamResponse::from_reasoning(reasoning)));
+                        }
+                        if let Some(content) = content.filter(|c| !c.is_empty()) {
+                            self.results.push(Ok(StreamResponse::from_content(content)));
+                        }
+                        if tool_calls.is_some() {
                             // If normalize_response is enabled, accumulate tool call outputs
                             if let Some(calls) = &tool_calls {
                                 for call in calls {
@@ -804,13 +921,14 @@ pub fn create_sse_stream(
                                     .collect()
                             });
 
-                        if content.is_some() || tool_call_deltas.is_some() {
+                        if content.is_some() || tool_call_deltas.is_some() || reasoning.is_some() {
                             self.push_tool_call();
                             self.results.push(Ok(StreamResponse {
                                 choices: vec![StreamChoice {
                                     delta: StreamDelta {
                                         content,
                                         tool_calls: tool_call_deltas,
+                                        reasoning_content: reasoning,
                                     },
                                 }],
                                 usage: None,
@@ -822,29 +940,28 @@ pub fn create_sse_stream(
         }
     }
 
-    let bytes_stream = response.bytes_stream();
-    let stream = bytes_stream
-        .scan(SSEStreamParser::new(normalize_response), |parser, chunk| {
-            let results = match chunk {
-                Ok(bytes) => {
-                    let text = String::from_utf8_lossy(&bytes);
-                    for line in text.lines() {
-                        let line = line.trim_end();
-                        if line.is_empty() {
-                            // Blank line: end of event, parse accumulated event_buffer
-                            parser.parse_event();
-                            parser.event_buffer.clear();
-                        } else {
-                            parser.event_buffer.push_str(line);
-                            parser.event_buffer.push('\n');
-                        }
-                    }
-                    parser.results.drain(..).collect::<Vec<_>>()
-                }
-                Err(e) => vec![Err(LLMError::HttpError(e.to_string()))],
-            };
-            futures::future::ready(Some(results))
-        })
-        .flat_map(futures::stream::iter);
-    Box::pin(stream)
+    impl crate::chat::StreamLineDecoder for SSEStreamParser {
+        fn decode_line(&mut self, line: &str) -> Vec<Result<StreamResponse, LLMError>> {
+            if line.is_empty() {
+                // Blank line: end of event, parse accumulated event_buffer
+                self.parse_event();
+                self.event_buffer.clear();
+            } else {
+                self.event_buffer.push_str(line);
+                self.event_buffer.push('\n');
+            }
+            self.results.drain(..).collect()
+        }
+
+        fn finish(&mut self) -> Vec<Result<StreamResponse, LLMError>> {
+            // Servers may close the body without a trailing blank line
+            if !self.event_buffer.is_empty() {
+                self.parse_event();
+                self.event_buffer.clear();
+            }
+            self.results.drain(..).collect()
+        }
+    }
+
+    crate::chat::create_line_stream(response, SSEStreamParser::new(normalize_response))
 }
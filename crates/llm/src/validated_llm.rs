@@ -0,0 +1,417 @@
+//! Provider wrapper that validates chat responses and re-prompts the model on failure.
+//!
+//! [`ValidatedLLM`] is created by [`LLMBuilder::build_validated`](crate::builder::LLMBuilder::build_validated)
+//! from the builder's `validator` and `validator_attempts` settings, or directly through
+//! [`ValidatedLLM::new`]. Every text response is checked against the requested JSON
+//! schema (if any) and the custom validator. When a check fails, the invalid response
+//! and the validation errors are appended to the conversation and the request is retried.
+//! The same loop is available as [`repair_until_valid`] for callers that already hold a
+//! response, such as agent executors validating their final answer.
+
+use crate::{
+    builder::ValidatorFn,
+    chat::{ChatMessage, ChatProvider, ChatResponse, StreamResponse, StructuredOutputFormat, Tool},
+    completion::{CompletionProvider, CompletionRequest, CompletionResponse},
+    embedding::EmbeddingProvider,
+    error::LLMError,
+    models::{ModelListRequest, ModelListResponse, ModelsProvider},
+    LLMProvider,
+};
+use async_trait::async_trait;
+use futures::Stream;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// An [`LLMProvider`] that enforces response validation with a parse-and-repair loop.
+pub struct ValidatedLLM {
+    inner: Arc<dyn LLMProvider>,
+    validator: Option<Box<ValidatorFn>>,
+    attempts: usize,
+}
+
+impl ValidatedLLM {
+    /// Wrap a provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The provider to delegate requests to
+    /// * `validator` - Optional custom check applied to every text response
+    /// * `attempts` - Number of retries after the first invalid response
+    pub fn new(
+        inner: Arc<dyn LLMProvider>,
+        validator: Option<Box<ValidatorFn>>,
+        attempts: usize,
+    ) -> Self {
+        Self {
+            inner,
+            validator,
+            attempts,
+        }
+    }
+
+    /// Returns every validation error for a response text.
+    fn validate(&self, text: &str, json_schema: Option<&StructuredOutputFormat>) -> Vec<String> {
+        let mut errors = Vec::new();
+        if let Some(schema) = json_schema {
+            if let Err(schema_errors) = schema.parse_response(text) {
+                errors.extend(schema_errors);
+            }
+        }
+        if let Some(validator) = &self.validator {
+            if let Err(e) = validator(text) {
+                errors.push(e);
+            }
+        }
+        errors
+    }
+}
+
+/// Why [`repair_until_valid`] gave up.
+#[derive(Debug)]
+pub enum RepairError {
+    /// The provider failed while re-prompting
+    LLM(LLMError),
+    /// The last response still failed validation
+    Invalid {
+        /// Number of responses that were checked, including the first one
+        attempts: usize,
+        /// Validation errors of the last response
+        errors: Vec<String>,
+        /// Text of the last response
+        text: String,
+    },
+}
+
+/// The parse-and-repair loop shared by [`ValidatedLLM`] and agent output validation.
+///
+/// Checks `text`, the answer the model gave to `messages`, with `validate`. While it
+/// fails, the invalid answer and the errors are appended to the conversation and the
+/// model is asked again, up to `retries` times.
+///
+/// Returns `Ok(None)` when `text` is valid as is, or the first valid repaired response.
+/// A response with tool calls is not a final answer, so it is returned unchecked.
+pub async fn repair_until_valid<F>(
+    llm: &dyn ChatProvider,
+    messages: &[ChatMessage],
+    text: String,
+    tools: Option<&[Tool]>,
+    json_schema: Option<&StructuredOutputFormat>,
+    retries: usize,
+    validate: F,
+) -> Result<Option<Box<dyn ChatResponse>>, RepairError>
+where
+    F: Fn(&str) -> Vec<String> + Send + Sync,
+{
+    let mut conversation = messages.to_vec();
+    let mut text = text;
+    let mut repaired = None;
+    let mut attempt = 0;
+    loop {
+        let errors = validate(&text);
+        if errors.is_empty() {
+            return Ok(repaired);
+        }
+        if attempt == retries {
+            return Err(RepairError::Invalid {
+                attempts: retries + 1,
+                errors,
+                text,
+            });
+        }
+        attempt += 1;
+        log::debug!(
+            "Validation failed on attempt {attempt}/{}: {}",
+            retries + 1,
+            errors.join("; ")
+        );
+
+        let feedback = match json_schema {
+            Some(schema) => schema.repair_prompt(&errors),
+            None => format!(
+                "Your previous response was invalid:\n- {}\nPlease try again.",
+                errors.join("\n- ")
+            ),
+        };
+        conversation.push(ChatMessage::assistant().content(text).build());
+        conversation.push(ChatMessage::user().content(feedback).build());
+
+        let response = llm
+            .chat(&conversation, tools, json_schema.cloned())
+            .await
+            .map_err(RepairError::LLM)?;
+        if response.tool_calls().is_some_and(|calls| !calls.is_empty()) {
+            return Ok(Some(response));
+        }
+        text = response.text().unwrap_or_default();
+        repaired = Some(response);
+    }
+}
+
+#[async_trait]
+impl ChatProvider for ValidatedLLM {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<Box<dyn ChatResponse>, LLMError> {
+        let response = self
+            .inner
+            .chat(messages, tools, json_schema.clone())
+            .await?;
+
+        // Tool calls are not final answers, so they are passed through untouched
+        if response.tool_calls().is_some_and(|calls| !calls.is_empty()) {
+            return Ok(response);
+        }
+
+        let text = response.text().unwrap_or_default();
+        let repaired = repair_until_valid(
+            self.inner.as_ref(),
+            messages,
+            text,
+            tools,
+            json_schema.as_ref(),
+            self.attempts,
+            |text| self.validate(text, json_schema.as_ref()),
+        )
+        .await;
+        match repaired {
+            Ok(None) => Ok(response),
+            Ok(Some(repaired)) => Ok(repaired),
+            Err(RepairError::LLM(e)) => Err(e),
+            Err(RepairError::Invalid {
+                attempts,
+                errors,
+                text,
+            }) => Err(LLMError::ResponseFormatError {
+                message: format!(
+                    "response failed validation after {attempts} attempt(s): {}",
+                    errors.join("; ")
+                ),
+                raw_response: text,
+            }),
+        }
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<String, LLMError>> + Send>>, LLMError> {
+        self.inner.chat_stream(messages, tools, json_schema).await
+    }
+
+    async fn chat_stream_struct(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[Tool]>,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamResponse, LLMError>> + Send>>, LLMError>
+    {
+        self.inner
+            .chat_stream_struct(messages, tools, json_schema)
+            .await
+    }
+}
+
+#[async_trait]
+impl CompletionProvider for ValidatedLLM {
+    async fn complete(
+        &self,
+        req: &CompletionRequest,
+        json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<CompletionResponse, LLMError> {
+        let mut last_errors = Vec::new();
+        let mut last_text = String::new();
+        for _ in 0..=self.attempts {
+            let response = self.inner.complete(req, json_schema.clone()).await?;
+            let errors = self.validate(&response.text, json_schema.as_ref());
+            if errors.is_empty() {
+                return Ok(response);
+            }
+            last_errors = errors;
+            last_text = response.text;
+        }
+        Err(LLMError::ResponseFormatError {
+            message: format!(
+                "completion failed validation after {} attempt(s): {}",
+                self.attempts + 1,
+                last_errors.join("; ")
+            ),
+            raw_response: last_text,
+        })
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for ValidatedLLM {
+    async fn embed(&self, input: Vec<String>) -> Result<Vec<Vec<f32>>, LLMError> {
+        self.inner.embed(input).await
+    }
+}
+
+#[async_trait]
+impl ModelsProvider for ValidatedLLM {
+    async fn list_models(
+        &self,
+        request: Option<&ModelListRequest>,
+    ) -> Result<Box<dyn ModelListResponse>, LLMError> {
+        self.inner.list_models(request).await
+    }
+}
+
+impl LLMProvider for ValidatedLLM {
+    fn capabilities(&self) -> Option<crate::models::ModelCapabilities> {
+        self.inner.capabilities()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToolCall;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    /// Returns the scripted responses in order and records every request.
+    struct ScriptedProvider {
+        responses: Mutex<Vec<String>>,
+        requests: Mutex<Vec<Vec<ChatMessage>>>,
+    }
+
+    impl ScriptedProvider {
+        fn new(responses: &[&str]) -> Self {
+            Self {
+                responses: Mutex::new(responses.iter().rev().map(|r| r.to_string()).collect()),
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct TextResponse(String);
+
+    impl std::fmt::Display for TextResponse {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl ChatResponse for TextResponse {
+        fn text(&self) -> Option<String> {
+            Some(self.0.clone())
+        }
+
+        fn tool_calls(&self) -> Option<Vec<ToolCall>> {
+            None
+        }
+    }
+
+    #[async_trait]
+    impl ChatProvider for ScriptedProvider {
+        async fn chat(
+            &self,
+            messages: &[ChatMessage],
+            _tools: Option<&[Tool]>,
+            _json_schema: Option<StructuredOutputFormat>,
+        ) -> Result<Box<dyn ChatResponse>, LLMError> {
+            self.requests.lock().unwrap().push(messages.to_vec());
+            let text = self.responses.lock().unwrap().pop().unwrap_or_default();
+            Ok(Box::new(TextResponse(text)))
+        }
+    }
+
+    #[async_trait]
+    impl CompletionProvider for ScriptedProvider {
+        async fn complete(
+            &self,
+            _req: &CompletionRequest,
+            _json_schema: Option<StructuredOutputFormat>,
+        ) -> Result<CompletionResponse, LLMError> {
+            let text = self.responses.lock().unwrap().pop().unwrap_or_default();
+            Ok(CompletionResponse { text })
+        }
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for ScriptedProvider {
+        async fn embed(&self, _input: Vec<String>) -> Result<Vec<Vec<f32>>, LLMError> {
+            Ok(vec![])
+        }
+    }
+
+    impl ModelsProvider for ScriptedProvider {}
+
+    impl LLMProvider for ScriptedProvider {}
+
+    fn answer_schema() -> StructuredOutputFormat {
+        StructuredOutputFormat {
+            name: "Answer".to_string(),
+            description: None,
+            schema: Some(json!({
+                "type": "object",
+                "properties": {"answer": {"type": "number"}},
+                "required": ["answer"]
+            })),
+            strict: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repairs_invalid_schema_response() {
+        let inner = Arc::new(ScriptedProvider::new(&[
+            "the answer is 42",
+            r#"{"answer": 42}"#,
+        ]));
+        let llm = ValidatedLLM::new(inner.clone(), None, 2);
+        let messages = vec![ChatMessage::user().content("What is 6 * 7?").build()];
+
+        let response = llm
+            .chat(&messages, None, Some(answer_schema()))
+            .await
+            .unwrap();
+        assert_eq!(response.text().unwrap(), r#"{"answer": 42}"#);
+
+        let requests = inner.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let retry = &requests[1];
+        assert_eq!(retry.len(), 3);
+        assert_eq!(retry[1].content, "the answer is 42");
+        assert!(retry[2].content.contains("not valid JSON"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_validator_and_exhausted_attempts() {
+        let inner = Arc::new(ScriptedProvider::new(&["short", "still short"]));
+        let llm = ValidatedLLM::new(
+            inner.clone(),
+            Some(Box::new(|text: &str| {
+                if text.len() > 20 {
+                    Ok(())
+                } else {
+                    Err("response too short".to_string())
+                }
+            })),
+            1,
+        );
+        let messages = vec![ChatMessage::user().content("Write an essay").build()];
+
+        let err = llm.chat(&messages, None, None).await.err().unwrap();
+        assert!(matches!(err, LLMError::ResponseFormatError { .. }));
+        assert!(err.to_string().contains("response too short"));
+        assert_eq!(inner.requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_complete_retries_until_valid() {
+        let inner = Arc::new(ScriptedProvider::new(&["{}", r#"{"answer": 1}"#]));
+        let llm = ValidatedLLM::new(inner, None, 1);
+        let response = llm
+            .complete(&CompletionRequest::new("1"), Some(answer_schema()))
+            .await
+            .unwrap();
+        assert_eq!(response.text, r#"{"answer": 1}"#);
+    }
+}
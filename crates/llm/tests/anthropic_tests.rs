@@ -11,12 +11,14 @@ use autoagents_llm::{
     models::ModelsProvider,
     FunctionCall, ToolCall,
 };
+use futures::StreamExt;
 use serde_json::json;
 use std::sync::Arc;
 
 #[cfg(feature = "anthropic")]
 mod anthropic_test_cases {
     use super::*;
+    use crate::common::{collect_stream, MockServer};
     use autoagents_llm::backends::anthropic::Anthropic;
 
     fn create_test_anthropic() -> Arc<Anthropic> {
@@ -592,4 +594,586 @@ mod anthropic_test_cases {
             _ => panic!("Expected ProviderError"),
         }
     }
+
+    const STREAM_FIXTURE: &str = r#"event: message_start
+data: {"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","content":[],"model":"claude-3-sonnet-20240229","usage":{"input_tokens":25,"output_tokens":1}}}
+
+event: content_block_start
+data: {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}
+
+event: ping
+data: {"type":"ping"}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Let me check "}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"the weather."}}
+
+event: content_block_stop
+data: {"type":"content_block_stop","index":0}
+
+event: content_block_start
+data: {"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"get_weather","input":{}}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":""}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"city\": \"Par"}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"is\"}"}}
+
+event: content_block_stop
+data: {"type":"content_block_stop","index":1}
+
+event: message_delta
+data: {"type":"message_delta","delta":{"stop_reason":"tool_use","stop_sequence":null},"usage":{"output_tokens":42}}
+
+event: message_stop
+data: {"type":"message_stop"}
+
+"#;
+
+    fn weather_tool() -> Tool {
+        Tool {
+            tool_type: "function".to_string(),
+            function: FunctionTool {
+                name: "get_weather".to_string(),
+                description: "Get the weather for a city".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}},
+                    "required": ["city"]
+                }),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_struct_tool_use() {
+        let server = MockServer::sse(STREAM_FIXTURE).await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user().content("Weather in Paris?").build()];
+        let stream = client
+            .chat_stream_struct(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+        let collected = collect_stream(stream).await.unwrap();
+
+        assert_eq!(collected.content, "Let me check the weather.");
+        assert_eq!(
+            collected.tool_calls,
+            vec![(
+                "get_weather".to_string(),
+                r#"{"city": "Paris"}"#.to_string()
+            )]
+        );
+        let usage = collected.usage.unwrap();
+        assert_eq!(
+            (
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.total_tokens
+            ),
+            (25, 42, 67)
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/messages");
+        assert_eq!(requests[0].header("x-api-key"), Some("test-key"));
+        assert_eq!(requests[0].body["stream"], json!(true));
+        assert_eq!(requests[0].body["tools"][0]["name"], json!("get_weather"));
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_text_and_errors() {
+        let server = MockServer::sse(STREAM_FIXTURE).await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        let chunks: Vec<String> = client
+            .chat_stream(&messages, None, None)
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks, vec!["Let me check ", "the weather."]);
+
+        let server = MockServer::sse(
+            "event: error\ndata: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n",
+        )
+        .await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+        let stream = client
+            .chat_stream_struct(&messages, None, None)
+            .await
+            .unwrap();
+        match collect_stream(stream).await {
+            Err(LLMError::ProviderError(msg)) => assert!(msg.contains("Overloaded")),
+            other => panic!("Expected ProviderError, got {other:?}"),
+        }
+    }
+
+    fn cached_chat_response() -> serde_json::Value {
+        json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": [{"type": "text", "text": "Hello"}],
+            "usage": {
+                "input_tokens": 10,
+                "output_tokens": 5,
+                "cache_creation_input_tokens": 200,
+                "cache_read_input_tokens": 1000
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_prompt_caching_breakpoints() {
+        let server = MockServer::json(200, cached_chat_response()).await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .system("Long system prompt")
+            .prompt_caching(true)
+            .build()
+            .unwrap();
+        assert!(client.prompt_caching);
+
+        let messages = vec![
+            ChatMessage::user().content("First question").build(),
+            ChatMessage::assistant().content("First answer").build(),
+            ChatMessage::user().content("Second question").build(),
+        ];
+        let tools = [weather_tool(), weather_tool()];
+        client.chat(&messages, Some(&tools), None).await.unwrap();
+
+        let body = &server.requests()[0].body;
+        let ephemeral = json!({"type": "ephemeral"});
+        assert_eq!(
+            body["system"],
+            json!([{"type": "text", "text": "Long system prompt", "cache_control": ephemeral}])
+        );
+        assert!(body["tools"][0].get("cache_control").is_none());
+        assert_eq!(body["tools"][1]["cache_control"], ephemeral);
+        assert!(body["messages"][0]["content"][0]
+            .get("cache_control")
+            .is_none());
+        assert_eq!(
+            body["messages"][2]["content"][0]["cache_control"],
+            ephemeral
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prompt_caching_hoists_agent_instructions() {
+        let server = MockServer::json(200, cached_chat_response()).await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .prompt_caching(true)
+            .build()
+            .unwrap();
+
+        // The message list a ReAct executor sends on its second turn
+        let call = ToolCall {
+            id: "toolu_1".to_string(),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: "get_weather".to_string(),
+                arguments: r#"{"city": "Paris"}"#.to_string(),
+            },
+        };
+        let result = ToolCall {
+            function: FunctionCall {
+                arguments: r#"{"temp": 21}"#.to_string(),
+                ..call.function.clone()
+            },
+            ..call.clone()
+        };
+        let messages = vec![
+            ChatMessage {
+                role: ChatRole::System,
+                message_type: MessageType::Text,
+                content: "You are a weather agent".to_string(),
+            },
+            ChatMessage::user().content("Weather in Paris?").build(),
+            ChatMessage {
+                role: ChatRole::Assistant,
+                message_type: MessageType::ToolUse(vec![call]),
+                content: String::new(),
+            },
+            ChatMessage {
+                role: ChatRole::Tool,
+                message_type: MessageType::ToolResult(vec![result]),
+                content: String::new(),
+            },
+        ];
+        client
+            .chat(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+
+        let body = &server.requests()[0].body;
+        let ephemeral = json!({"type": "ephemeral"});
+        assert_eq!(
+            body["system"],
+            json!([
+                {"type": "text", "text": "You are a helpful assistant."},
+                {"type": "text", "text": "You are a weather agent", "cache_control": ephemeral}
+            ])
+        );
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["role"], json!("user"));
+        assert!(messages[1]["content"][0].get("cache_control").is_none());
+        assert_eq!(messages[2]["content"][0]["cache_control"], ephemeral);
+        assert_eq!(body["tools"][0]["cache_control"], ephemeral);
+    }
+
+    #[tokio::test]
+    async fn test_chat_sends_pdf_with_its_name() {
+        let server = MockServer::json(200, cached_chat_response()).await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user()
+            .pdf_file("report.pdf", vec![0x25, 0x50])
+            .build()];
+        client.chat(&messages, None, None).await.unwrap();
+
+        assert_eq!(
+            server.requests()[0].body["messages"][0]["content"],
+            json!([{
+                "type": "document",
+                "source": {"type": "base64", "media_type": "application/pdf", "data": "JVA="},
+                "title": "report.pdf"
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_breakpoint_marks_previous_message() {
+        let server = MockServer::json(200, cached_chat_response()).await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .system("Plain system prompt")
+            .build()
+            .unwrap();
+
+        let messages = vec![
+            ChatMessage {
+                role: ChatRole::System,
+                message_type: MessageType::Text,
+                content: "Long instructions".to_string(),
+            },
+            ChatMessage::cache_breakpoint(),
+            ChatMessage::user().content("Long document").build(),
+            ChatMessage::cache_breakpoint(),
+            ChatMessage::assistant().content("Summary").build(),
+            ChatMessage::user().content("Question").build(),
+        ];
+        client.chat(&messages, None, None).await.unwrap();
+
+        let body = &server.requests()[0].body;
+        let ephemeral = json!({"type": "ephemeral"});
+        assert_eq!(
+            body["system"],
+            json!([
+                {"type": "text", "text": "Plain system prompt"},
+                {"type": "text", "text": "Long instructions", "cache_control": ephemeral}
+            ])
+        );
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["content"][0]["cache_control"], ephemeral);
+        assert!(messages[1]["content"][0].get("cache_control").is_none());
+        assert!(messages[2]["content"][0].get("cache_control").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cache_breakpoints_keep_latest_within_limit() {
+        let server = MockServer::json(200, cached_chat_response()).await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .prompt_caching(true)
+            .build()
+            .unwrap();
+
+        // System prompt, tools and the last message already take three of the four slots
+        let messages = vec![
+            ChatMessage::user().content("First").build(),
+            ChatMessage::cache_breakpoint(),
+            ChatMessage::assistant().content("Second").build(),
+            ChatMessage::cache_breakpoint(),
+            ChatMessage::user().content("Third").build(),
+            ChatMessage::cache_breakpoint(),
+        ];
+        client
+            .chat(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+
+        let body = &server.requests()[0].body;
+        let ephemeral = json!({"type": "ephemeral"});
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert!(messages[0]["content"][0].get("cache_control").is_none());
+        assert_eq!(messages[1]["content"][0]["cache_control"], ephemeral);
+        assert_eq!(messages[2]["content"][0]["cache_control"], ephemeral);
+        assert_eq!(body["system"][0]["cache_control"], ephemeral);
+        assert_eq!(body["tools"][0]["cache_control"], ephemeral);
+    }
+
+    #[tokio::test]
+    async fn test_leading_system_messages_join_system_prompt() {
+        let server = MockServer::json(200, cached_chat_response()).await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .system("Plain system prompt")
+            .build()
+            .unwrap();
+
+        client
+            .chat(&[ChatMessage::user().content("Hi").build()], None, None)
+            .await
+            .unwrap();
+        let messages = vec![
+            ChatMessage {
+                role: ChatRole::System,
+                message_type: MessageType::Text,
+                content: "Be brief".to_string(),
+            },
+            ChatMessage::user().content("Hi").build(),
+        ];
+        client.chat(&messages, None, None).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].body["system"], json!("Plain system prompt"));
+        assert_eq!(
+            requests[1].body["system"],
+            json!([
+                {"type": "text", "text": "Plain system prompt"},
+                {"type": "text", "text": "Be brief"}
+            ])
+        );
+        assert_eq!(requests[1].body["messages"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_chat_cache_usage() {
+        let server = MockServer::json(200, cached_chat_response()).await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        let response = client.chat(&messages, None, None).await.unwrap();
+
+        let usage = response.usage().unwrap();
+        assert_eq!(
+            (
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.total_tokens
+            ),
+            (1210, 5, 1215)
+        );
+        let details = usage.prompt_tokens_details.unwrap();
+        assert_eq!(details.cached_tokens, Some(1000));
+        assert_eq!(details.cache_creation_tokens, Some(200));
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_cache_usage() {
+        let server = MockServer::sse(&STREAM_FIXTURE.replace(
+            r#""usage":{"input_tokens":25,"output_tokens":1}"#,
+            r#""usage":{"input_tokens":25,"output_tokens":1,"cache_creation_input_tokens":0,"cache_read_input_tokens":300}"#,
+        ))
+        .await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        let stream = client
+            .chat_stream_struct(&messages, None, None)
+            .await
+            .unwrap();
+
+        let usage = collect_stream(stream).await.unwrap().usage.unwrap();
+        assert_eq!((usage.prompt_tokens, usage.total_tokens), (325, 367));
+        let details = usage.prompt_tokens_details.unwrap();
+        assert_eq!(details.cached_tokens, Some(300));
+        assert_eq!(details.cache_creation_tokens, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_thinking() {
+        let server = MockServer::sse(
+            r#"event: message_start
+data: {"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","content":[],"usage":{"input_tokens":10,"output_tokens":1}}}
+
+event: content_block_start
+data: {"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"The user wants "}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"a greeting."}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"abc"}}
+
+event: content_block_start
+data: {"type":"content_block_start","index":1,"content_block":{"type":"text","text":""}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"Hello!"}}
+
+event: message_stop
+data: {"type":"message_stop"}
+
+"#,
+        )
+        .await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .reasoning(true)
+            .build()
+            .unwrap();
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        let stream = client
+            .chat_stream_struct(&messages, None, None)
+            .await
+            .unwrap();
+
+        let collected = collect_stream(stream).await.unwrap();
+        assert_eq!(collected.reasoning, "The user wants a greeting.");
+        assert_eq!(collected.content, "Hello!");
+    }
+
+    #[tokio::test]
+    async fn test_batch_round_trip() {
+        use autoagents_llm::batch::{BatchProvider, BatchRequest, BatchStatus};
+
+        let batch = |status: &str, [processing, succeeded, errored, expired]: [usize; 4]| {
+            json!({
+                "id": "msgbatch_1",
+                "type": "message_batch",
+                "processing_status": status,
+                "request_counts": {
+                    "processing": processing,
+                    "succeeded": succeeded,
+                    "errored": errored,
+                    "canceled": 0,
+                    "expired": expired
+                },
+                "results_url": null
+            })
+            .to_string()
+        };
+        let results = [
+            json!({
+                "custom_id": "ticket-1",
+                "result": {
+                    "type": "succeeded",
+                    "message": {
+                        "id": "msg_1",
+                        "type": "message",
+                        "role": "assistant",
+                        "content": [{"type": "text", "text": "billing"}],
+                        "usage": {"input_tokens": 10, "output_tokens": 1}
+                    }
+                }
+            }),
+            json!({
+                "custom_id": "ticket-2",
+                "result": {
+                    "type": "errored",
+                    "error": {
+                        "type": "error",
+                        "error": {"type": "invalid_request_error", "message": "Bad input"}
+                    }
+                }
+            }),
+            json!({"custom_id": "ticket-3", "result": {"type": "expired"}}),
+        ]
+        .map(|line| line.to_string())
+        .join("\n");
+        let server = MockServer::sequence(vec![
+            (200, "application/json", batch("in_progress", [3, 0, 0, 0])),
+            (200, "application/json", batch("ended", [0, 1, 1, 1])),
+            (200, "application/binary", results),
+        ])
+        .await;
+        let client = LLMBuilder::<Anthropic>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .model("claude-3-5-haiku-latest")
+            .build()
+            .unwrap();
+
+        let requests = ["ticket-1", "ticket-2", "ticket-3"]
+            .map(|id| BatchRequest::new(id, vec![ChatMessage::user().content("Classify").build()]));
+        let job = client.submit_batch(&requests).await.unwrap();
+        assert_eq!(job.status, BatchStatus::InProgress);
+        assert_eq!(job.total, 3);
+
+        let job = client.batch_status("msgbatch_1").await.unwrap();
+        assert_eq!(job.status, BatchStatus::Completed);
+        assert_eq!((job.total, job.completed, job.failed), (3, 1, 2));
+
+        let results = client.batch_results("msgbatch_1").await.unwrap();
+        assert_eq!(
+            results[0].response.as_ref().unwrap().text().as_deref(),
+            Some("billing")
+        );
+        assert!(matches!(
+            &results[1].response,
+            Err(LLMError::ProviderError(message)) if message == "invalid_request_error: Bad input"
+        ));
+        assert_eq!(results[2].custom_id, "ticket-3");
+        assert!(results[2].response.is_err());
+
+        let recorded = server.requests();
+        assert_eq!(recorded[0].path, "/messages/batches");
+        assert_eq!(recorded[0].header("x-api-key"), Some("test-key"));
+        let params = &recorded[0].body["requests"][1]["params"];
+        assert_eq!(
+            recorded[0].body["requests"][1]["custom_id"],
+            json!("ticket-2")
+        );
+        assert_eq!(params["model"], json!("claude-3-5-haiku-latest"));
+        assert!(params.get("stream").is_none());
+        assert_eq!(recorded[1].method, "GET");
+        assert_eq!(recorded[1].path, "/messages/batches/msgbatch_1");
+        assert_eq!(recorded[2].path, "/messages/batches/msgbatch_1/results");
+    }
 }
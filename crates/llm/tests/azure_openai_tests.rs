@@ -2,8 +2,8 @@
 use autoagents_llm::{
     builder::LLMBuilder,
     chat::{
-        ChatMessage, ChatProvider, ChatResponse, ChatRole, MessageType, StructuredOutputFormat,
-        Tool, ToolChoice,
+        ChatMessage, ChatProvider, ChatResponse, ChatRole, FunctionTool, MessageType,
+        StructuredOutputFormat, Tool, ToolChoice,
     },
     completion::{CompletionProvider, CompletionRequest},
     error::LLMError,
@@ -16,6 +16,7 @@ use std::sync::Arc;
 #[cfg(feature = "azure_openai")]
 mod azure_openai_test_cases {
     use super::*;
+    use crate::common::{collect_stream, MockServer};
     use autoagents_llm::{backends::azure_openai::AzureOpenAI, chat::ReasoningEffort};
 
     fn create_test_azure_openai() -> Arc<AzureOpenAI> {
@@ -242,4 +243,132 @@ mod azure_openai_test_cases {
             "https://myresource.openai.azure.com/openai/deployments/my-deployment/"
         );
     }
+    const OPENAI_STREAM_FIXTURE: &str = r#"data: {"id":"1","choices":[{"index":0,"delta":{"role":"assistant","content":"Checking"}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{"content":" now."}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"get_weather","arguments":""}}]}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"city\": "}}]}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"Paris\"}"}}]}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{},"finish_reason":"tool_calls"}]}
+
+data: {"id":"1","choices":[],"usage":{"prompt_tokens":12,"completion_tokens":8,"total_tokens":20}}
+
+data: [DONE]
+
+"#;
+
+    fn weather_tool() -> Tool {
+        Tool {
+            tool_type: "function".to_string(),
+            function: FunctionTool {
+                name: "get_weather".to_string(),
+                description: "Get the weather for a city".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}},
+                    "required": ["city"]
+                }),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_struct_fixture() {
+        let server = MockServer::sse(OPENAI_STREAM_FIXTURE).await;
+        let client = LLMBuilder::<AzureOpenAI>::new()
+            .api_key("test-key")
+            .api_version("2024-06-01")
+            .deployment_id("gpt-4-deployment")
+            .base_url(&server.url)
+            .model("gpt-4")
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user().content("Weather in Paris?").build()];
+        let stream = client
+            .chat_stream_struct(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+        let collected = collect_stream(stream).await.unwrap();
+
+        assert_eq!(collected.content, "Checking now.");
+        assert_eq!(
+            collected.tool_calls,
+            vec![(
+                "get_weather".to_string(),
+                r#"{"city": "Paris"}"#.to_string()
+            )]
+        );
+        assert_eq!(collected.usage.unwrap().total_tokens, 20);
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].path,
+            "/openai/deployments/gpt-4-deployment/chat/completions?api-version=2024-06-01"
+        );
+        assert_eq!(requests[0].header("api-key"), Some("test-key"));
+        assert_eq!(requests[0].body["stream"], json!(true));
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_against_deployment() {
+        use autoagents_llm::audio::{SpeechToTextProvider, TranscriptionRequest};
+
+        let server = MockServer::json(200, json!({"text": "Hello"})).await;
+        let client = LLMBuilder::<AzureOpenAI>::new()
+            .api_key("test-key")
+            .api_version("2024-06-01")
+            .deployment_id("whisper")
+            .base_url(&server.url)
+            .model("whisper")
+            .build()
+            .unwrap();
+
+        let request = TranscriptionRequest::new(b"audio".to_vec(), "clip.mp3");
+        let transcription = client.transcribe(&request).await.unwrap();
+        assert_eq!(transcription.text, "Hello");
+        assert!(transcription.segments.is_empty());
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].path,
+            "/openai/deployments/whisper/audio/transcriptions?api-version=2024-06-01"
+        );
+        assert_eq!(requests[0].header("api-key"), Some("test-key"));
+        assert!(requests[0].body_text().contains("name=\"response_format\""));
+    }
+
+    #[tokio::test]
+    async fn test_generate_images_against_deployment() {
+        use autoagents_llm::images::{
+            GeneratedImage, ImageGenerationProvider, ImageGenerationRequest,
+        };
+
+        let server = MockServer::json(200, json!({"data": [{"b64_json": "/9j/4A=="}]})).await;
+        let client = LLMBuilder::<AzureOpenAI>::new()
+            .api_key("test-key")
+            .api_version("2024-06-01")
+            .deployment_id("dalle")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+
+        let images = client
+            .generate_images(&ImageGenerationRequest::new("A cat"))
+            .await
+            .unwrap();
+        assert!(matches!(images[0], GeneratedImage::Bytes { .. }));
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].path,
+            "/openai/deployments/dalle/images/generations?api-version=2024-06-01"
+        );
+        assert_eq!(requests[0].header("api-key"), Some("test-key"));
+        assert!(requests[0].body.get("model").is_none());
+    }
 }
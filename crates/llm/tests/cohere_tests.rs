@@ -0,0 +1,394 @@
+#[cfg(feature = "cohere")]
+mod cohere_test_cases {
+    use crate::common::{collect_stream, MockServer};
+    use autoagents_llm::backends::cohere::{Cohere, CohereRerankResult};
+    use autoagents_llm::{
+        builder::LLMBuilder,
+        chat::{ChatMessage, ChatProvider, FunctionTool, StructuredOutputFormat, Tool, ToolChoice},
+        embedding::EmbeddingProvider,
+        error::LLMError,
+        models::ModelsProvider,
+        FunctionCall, ToolCall,
+    };
+    use serde_json::json;
+    use std::sync::Arc;
+
+    const STREAM_FIXTURE: &str = r#"event: message-start
+data: {"id":"1","type":"message-start","delta":{"message":{"role":"assistant","content":[],"tool_plan":"","tool_calls":[],"citations":[]}}}
+
+event: content-start
+data: {"type":"content-start","index":0,"delta":{"message":{"content":{"type":"text","text":""}}}}
+
+event: content-delta
+data: {"type":"content-delta","index":0,"delta":{"message":{"content":{"text":"Checking"}}}}
+
+event: content-delta
+data: {"type":"content-delta","index":0,"delta":{"message":{"content":{"text":" now."}}}}
+
+event: content-end
+data: {"type":"content-end","index":0}
+
+event: tool-call-start
+data: {"type":"tool-call-start","index":0,"delta":{"message":{"tool_calls":{"id":"get_weather_1","type":"function","function":{"name":"get_weather","arguments":""}}}}}
+
+event: tool-call-delta
+data: {"type":"tool-call-delta","index":0,"delta":{"message":{"tool_calls":{"function":{"arguments":"{\"city\": "}}}}}
+
+event: tool-call-delta
+data: {"type":"tool-call-delta","index":0,"delta":{"message":{"tool_calls":{"function":{"arguments":"\"Paris\"}"}}}}}
+
+event: tool-call-end
+data: {"type":"tool-call-end","index":0}
+
+event: message-end
+data: {"type":"message-end","delta":{"finish_reason":"TOOL_CALL","usage":{"billed_units":{"input_tokens":30,"output_tokens":12},"tokens":{"input_tokens":850,"output_tokens":40}}}}
+
+data: [DONE]
+
+"#;
+
+    fn create_client(server: &MockServer) -> Arc<Cohere> {
+        LLMBuilder::<Cohere>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .model("command-a-03-2025")
+            .system("Be brief")
+            .build()
+            .expect("Failed to build Cohere client")
+    }
+
+    fn weather_tool() -> Tool {
+        Tool {
+            tool_type: "function".to_string(),
+            function: FunctionTool {
+                name: "get_weather".to_string(),
+                description: "Get the weather for a city".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}},
+                    "required": ["city"]
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn test_cohere_builder() {
+        let client = LLMBuilder::<Cohere>::new()
+            .api_key("test-key")
+            .rerank_model("rerank-multilingual-v3.0")
+            .embedding_input_type("search_query")
+            .build()
+            .unwrap();
+        assert_eq!(client.model, "command-a-03-2025");
+        assert_eq!(client.base_url, "https://api.cohere.com");
+        assert_eq!(client.rerank_model, "rerank-multilingual-v3.0");
+        assert_eq!(client.embedding_input_type, "search_query");
+
+        let result = LLMBuilder::<Cohere>::new().build();
+        match result.err().unwrap() {
+            LLMError::InvalidRequest(msg) => assert!(msg.contains("No API key provided")),
+            _ => panic!("Expected InvalidRequest error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_tools() {
+        let server = MockServer::json(
+            200,
+            json!({
+                "id": "1",
+                "finish_reason": "TOOL_CALL",
+                "message": {
+                    "role": "assistant",
+                    "tool_plan": "I will look up the weather.",
+                    "tool_calls": [{
+                        "id": "get_weather_1",
+                        "type": "function",
+                        "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+                    }]
+                },
+                "usage": {
+                    "billed_units": {"input_tokens": 30, "output_tokens": 12},
+                    "tokens": {"input_tokens": 850, "output_tokens": 40}
+                }
+            }),
+        )
+        .await;
+        let client = create_client(&server);
+
+        let messages = vec![ChatMessage::user().content("Weather in Paris?").build()];
+        let response = client
+            .chat(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+        assert_eq!(response.text(), None);
+        let tool_calls = response.tool_calls().unwrap();
+        assert_eq!(tool_calls[0].id, "get_weather_1");
+        assert_eq!(tool_calls[0].function.arguments, "{\"city\":\"Paris\"}");
+        let usage = response.usage().unwrap();
+        assert_eq!(
+            (
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.total_tokens
+            ),
+            (850, 40, 890)
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v2/chat");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer test-key"));
+        let body = &requests[0].body;
+        assert_eq!(
+            body["messages"],
+            json!([
+                {"role": "system", "content": "Be brief"},
+                {"role": "user", "content": "Weather in Paris?"}
+            ])
+        );
+        assert_eq!(body["tools"][0]["type"], json!("function"));
+        assert_eq!(body["tools"][0]["function"]["name"], json!("get_weather"));
+        assert!(body.get("tool_choice").is_none());
+        assert_eq!(body["stream"], json!(false));
+    }
+
+    #[tokio::test]
+    async fn test_chat_tool_round_trip_messages() {
+        let server = MockServer::json(
+            200,
+            json!({
+                "message": {"role": "assistant", "content": [{"type": "text", "text": "It is sunny."}]}
+            }),
+        )
+        .await;
+        let client = LLMBuilder::<Cohere>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .tool_choice(ToolChoice::Any)
+            .build()
+            .unwrap();
+
+        let call = ToolCall {
+            id: "get_weather_1".to_string(),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: "get_weather".to_string(),
+                arguments: "{\"city\":\"Paris\"}".to_string(),
+            },
+        };
+        let result = ToolCall {
+            function: FunctionCall {
+                name: "get_weather".to_string(),
+                arguments: "sunny".to_string(),
+            },
+            ..call.clone()
+        };
+        let messages = vec![
+            ChatMessage::user().content("Weather in Paris?").build(),
+            ChatMessage::assistant().tool_use(vec![call]).build(),
+            ChatMessage::user().tool_result(vec![result]).build(),
+        ];
+        let response = client
+            .chat(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+        assert_eq!(response.text(), Some("It is sunny.".to_string()));
+
+        let body = &server.requests()[0].body;
+        assert_eq!(body["tool_choice"], json!("REQUIRED"));
+        assert_eq!(
+            body["messages"][1],
+            json!({
+                "role": "assistant",
+                "tool_calls": [{
+                    "id": "get_weather_1",
+                    "type": "function",
+                    "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+                }]
+            })
+        );
+        assert_eq!(
+            body["messages"][2],
+            json!({"role": "tool", "content": "sunny", "tool_call_id": "get_weather_1"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_json_response_format() {
+        let server = MockServer::json(
+            200,
+            json!({
+                "message": {"role": "assistant", "content": [{"type": "text", "text": "{\"answer\": 42}"}]}
+            }),
+        )
+        .await;
+        let client = create_client(&server);
+        let schema = StructuredOutputFormat {
+            name: "Answer".to_string(),
+            description: None,
+            schema: Some(json!({"type": "object", "properties": {"answer": {"type": "integer"}}})),
+            strict: None,
+        };
+
+        let messages = vec![ChatMessage::user().content("Answer?").build()];
+        client.chat(&messages, None, Some(schema)).await.unwrap();
+
+        let body = &server.requests()[0].body;
+        assert_eq!(
+            body["response_format"],
+            json!({
+                "type": "json_object",
+                "json_schema": {"type": "object", "properties": {"answer": {"type": "integer"}}}
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_struct_fixture() {
+        let server = MockServer::sse(STREAM_FIXTURE).await;
+        let client = create_client(&server);
+
+        let messages = vec![ChatMessage::user().content("Weather in Paris?").build()];
+        let stream = client
+            .chat_stream_struct(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+        let collected = collect_stream(stream).await.unwrap();
+
+        assert_eq!(collected.content, "Checking now.");
+        assert_eq!(
+            collected.tool_calls,
+            vec![(
+                "get_weather".to_string(),
+                r#"{"city": "Paris"}"#.to_string()
+            )]
+        );
+        let usage = collected.usage.unwrap();
+        assert_eq!((usage.prompt_tokens, usage.completion_tokens), (850, 40));
+        assert_eq!(server.requests()[0].body["stream"], json!(true));
+    }
+
+    #[tokio::test]
+    async fn test_embed() {
+        let server = MockServer::json(
+            200,
+            json!({
+                "id": "1",
+                "embeddings": {"float": [[0.1, 0.2], [0.3, 0.4]]},
+                "texts": ["first", "second"],
+                "meta": {"api_version": {"version": "2"}}
+            }),
+        )
+        .await;
+        let client = LLMBuilder::<Cohere>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .model("embed-v4.0")
+            .embedding_dimensions(256)
+            .build()
+            .unwrap();
+
+        let embeddings = client
+            .embed(vec!["first".to_string(), "second".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(embeddings, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v2/embed");
+        assert_eq!(
+            requests[0].body,
+            json!({
+                "model": "embed-v4.0",
+                "texts": ["first", "second"],
+                "input_type": "search_document",
+                "embedding_types": ["float"],
+                "output_dimension": 256
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rerank() {
+        let server = MockServer::json(
+            200,
+            json!({
+                "id": "1",
+                "results": [
+                    {"index": 2, "relevance_score": 0.91},
+                    {"index": 0, "relevance_score": 0.12}
+                ],
+                "meta": {"billed_units": {"search_units": 1}}
+            }),
+        )
+        .await;
+        let client = create_client(&server);
+
+        let results = client
+            .rerank("capital of France", &["Berlin", "Madrid", "Paris"], Some(2))
+            .await
+            .unwrap();
+        assert_eq!(
+            results,
+            vec![
+                CohereRerankResult {
+                    index: 2,
+                    relevance_score: 0.91
+                },
+                CohereRerankResult {
+                    index: 0,
+                    relevance_score: 0.12
+                }
+            ]
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v2/rerank");
+        assert_eq!(
+            requests[0].body,
+            json!({
+                "model": "rerank-v3.5",
+                "query": "capital of France",
+                "documents": ["Berlin", "Madrid", "Paris"],
+                "top_n": 2
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_models() {
+        let server = MockServer::json(
+            200,
+            json!({
+                "models": [
+                    {"name": "command-a-03-2025", "endpoints": ["chat"], "context_length": 256000},
+                    {"name": "embed-v4.0", "endpoints": ["embed"]}
+                ],
+                "next_page_token": null
+            }),
+        )
+        .await;
+        let client = create_client(&server);
+
+        let models = client.list_models(None).await.unwrap();
+        assert_eq!(
+            models.get_models(),
+            vec!["command-a-03-2025".to_string(), "embed-v4.0".to_string()]
+        );
+        assert_eq!(server.requests()[0].path, "/v1/models");
+    }
+
+    #[tokio::test]
+    async fn test_error_status() {
+        let server = MockServer::json(401, json!({"message": "invalid api token"})).await;
+        let client = create_client(&server);
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        match client.chat(&messages, None, None).await {
+            Err(LLMError::ResponseFormatError { raw_response, .. }) => {
+                assert!(raw_response.contains("invalid api token"))
+            }
+            _ => panic!("Expected ResponseFormatError"),
+        }
+    }
+}
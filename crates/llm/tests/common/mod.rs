@@ -0,0 +1,223 @@
+//! Minimal HTTP server serving canned responses, for request/response fixture tests
+
+use autoagents_llm::chat::{StreamResponse, Usage};
+use autoagents_llm::error::LLMError;
+use futures::{Stream, StreamExt};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Bytes written per write when streaming a body, so events are split across network
+/// chunks the way they are by real servers
+const CHUNK_SIZE: usize = 24;
+
+/// A request received by a [`MockServer`]
+///
+/// Each backend's tests check different parts of it, so a build with a single backend
+/// leaves some of these helpers unused.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path and query string
+    pub path: String,
+    /// Header names are lowercase
+    pub headers: Vec<(String, String)>,
+    /// The body parsed as JSON, or `Null` when it is not JSON
+    pub body: Value,
+    pub raw_body: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The raw body as text, e.g. to inspect multipart form fields
+    pub fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.raw_body).to_string()
+    }
+}
+
+/// A canned response: status, content type and body
+pub type MockResponse = (u16, &'static str, String);
+
+/// HTTP server on a local port answering requests with canned responses
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    /// Serve a Server-Sent Events body
+    #[allow(dead_code)]
+    pub async fn sse(body: impl Into<String>) -> Self {
+        Self::start(200, "text/event-stream", body.into()).await
+    }
+
+    /// Serve a newline-delimited JSON body
+    #[allow(dead_code)]
+    pub async fn ndjson(body: impl Into<String>) -> Self {
+        Self::start(200, "application/x-ndjson", body.into()).await
+    }
+
+    /// Serve a JSON body with the given status
+    #[allow(dead_code)]
+    pub async fn json(status: u16, body: Value) -> Self {
+        Self::start(status, "application/json", body.to_string()).await
+    }
+
+    /// Answer every request with the same response
+    pub async fn start(status: u16, content_type: &'static str, body: String) -> Self {
+        Self::sequence(vec![(status, content_type, body)]).await
+    }
+
+    /// Answer the n-th request with the n-th response, repeating the last one, for
+    /// flows spanning several calls
+    pub async fn sequence(responses: Vec<MockResponse>) -> Self {
+        assert!(!responses.is_empty(), "MockServer needs a response");
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let responses = Arc::new(responses);
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(handle(socket, recorded.clone(), responses.clone()));
+            }
+        });
+        Self { url, requests }
+    }
+
+    /// Requests received so far, in order
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Record one request from `socket`, then answer it
+async fn handle(
+    mut socket: TcpStream,
+    recorded: Arc<Mutex<Vec<RecordedRequest>>>,
+    responses: Arc<Vec<MockResponse>>,
+) -> Option<()> {
+    let request = read_request(&mut socket).await?;
+    let index = {
+        let mut recorded = recorded.lock().unwrap();
+        recorded.push(request);
+        (recorded.len() - 1).min(responses.len() - 1)
+    };
+    let (status, content_type, body) = &responses[index];
+
+    let head = format!(
+        "HTTP/1.1 {status} Mock\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    socket.write_all(head.as_bytes()).await.ok()?;
+    for chunk in body.as_bytes().chunks(CHUNK_SIZE) {
+        socket.write_all(chunk).await.ok()?;
+        socket.flush().await.ok()?;
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    socket.shutdown().await.ok()
+}
+
+async fn read_request(socket: &mut TcpStream) -> Option<RecordedRequest> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let header_end = loop {
+        let n = socket.read(&mut buf).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        data.extend_from_slice(&buf[..n]);
+        if let Some(end) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&data[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+    let content_length = headers
+        .iter()
+        .find(|(key, _)| key == "content-length")
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    while data.len() < header_end + content_length {
+        let n = socket.read(&mut buf).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+
+    let raw_body = data[header_end..].to_vec();
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+        body: serde_json::from_slice(&raw_body).unwrap_or(Value::Null),
+        raw_body,
+    })
+}
+
+/// What a consumer such as `ReActAgent` assembles from a structured stream
+#[derive(Debug, Default)]
+#[allow(dead_code)]
+pub struct CollectedStream {
+    pub content: String,
+    /// Reasoning text, kept apart from the content
+    pub reasoning: String,
+    /// Name and concatenated arguments of each tool call, in index order
+    pub tool_calls: Vec<(String, String)>,
+    pub usage: Option<Usage>,
+}
+
+/// Drain a `chat_stream_struct` stream, assembling tool calls by index
+#[allow(dead_code)]
+pub async fn collect_stream(
+    mut stream: Pin<Box<dyn Stream<Item = Result<StreamResponse, LLMError>> + Send>>,
+) -> Result<CollectedStream, LLMError> {
+    let mut collected = CollectedStream::default();
+    let mut tool_calls: BTreeMap<usize, (String, String)> = BTreeMap::new();
+    while let Some(response) = stream.next().await {
+        let response = response?;
+        for choice in response.choices {
+            if let Some(content) = choice.delta.content {
+                collected.content.push_str(&content);
+            }
+            if let Some(reasoning) = choice.delta.reasoning_content {
+                collected.reasoning.push_str(&reasoning);
+            }
+            for delta in choice.delta.tool_calls.into_iter().flatten() {
+                let entry = tool_calls.entry(delta.index).or_default();
+                if let Some(function) = delta.function {
+                    if !function.name.is_empty() {
+                        entry.0 = function.name;
+                    }
+                    entry.1.push_str(&function.arguments);
+                }
+            }
+        }
+        if response.usage.is_some() {
+            collected.usage = response.usage;
+        }
+    }
+    collected.tool_calls = tool_calls.into_values().collect();
+    Ok(collected)
+}
@@ -11,12 +11,14 @@ use autoagents_llm::{
     models::ModelsProvider,
     FunctionCall, ToolCall,
 };
+use futures::StreamExt;
 use serde_json::json;
 use std::sync::Arc;
 
 #[cfg(feature = "google")]
 mod google_test_cases {
     use super::*;
+    use crate::common::{collect_stream, MockServer};
     use autoagents_llm::backends::google::Google;
 
     fn create_test_google() -> Arc<Google> {
@@ -493,4 +495,142 @@ mod google_test_cases {
         assert_eq!(client1.temperature, client2.temperature);
         assert_eq!(client1.system, client2.system);
     }
+    const STREAM_FIXTURE: &str = r#"data: {"candidates":[{"content":{"parts":[{"text":"Checking"}],"role":"model"}}],"usageMetadata":{"promptTokenCount":10,"totalTokenCount":10}}
+
+data: {"candidates":[{"content":{"parts":[{"text":" now."}],"role":"model"}}]}
+
+data: {"candidates":[{"content":{"parts":[{"functionCall":{"name":"get_weather","args":{"city":"Paris"}}}],"role":"model"},"finishReason":"STOP"}],"usageMetadata":{"promptTokenCount":10,"candidatesTokenCount":7,"totalTokenCount":17}}
+
+"#;
+
+    fn weather_tool() -> Tool {
+        Tool {
+            tool_type: "function".to_string(),
+            function: FunctionTool {
+                name: "get_weather".to_string(),
+                description: "Get the weather for a city".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}},
+                    "required": ["city"]
+                }),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_struct_fixture() {
+        let server = MockServer::sse(STREAM_FIXTURE).await;
+        let client = LLMBuilder::<Google>::new()
+            .api_key("test-key")
+            .model("gemini-1.5-flash")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user().content("Weather in Paris?").build()];
+        let stream = client
+            .chat_stream_struct(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+        let collected = collect_stream(stream).await.unwrap();
+
+        assert_eq!(collected.content, "Checking now.");
+        assert_eq!(collected.tool_calls.len(), 1);
+        assert_eq!(collected.tool_calls[0].0, "get_weather");
+        let args: serde_json::Value = serde_json::from_str(&collected.tool_calls[0].1).unwrap();
+        assert_eq!(args, json!({"city": "Paris"}));
+        let usage = collected.usage.unwrap();
+        assert_eq!(
+            (
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.total_tokens
+            ),
+            (10, 7, 17)
+        );
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].path,
+            "/models/gemini-1.5-flash:streamGenerateContent?alt=sse&key=test-key"
+        );
+        assert_eq!(
+            requests[0].body["tools"][0]["functionDeclarations"][0]["name"],
+            json!("get_weather")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_fixture_text_only() {
+        let server = MockServer::sse(STREAM_FIXTURE).await;
+        let client = LLMBuilder::<Google>::new()
+            .api_key("test-key")
+            .model("gemini-1.5-flash")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        let chunks: Vec<String> = client
+            .chat_stream(&messages, None, None)
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks, vec!["Checking", " now."]);
+    }
+
+    #[tokio::test]
+    async fn test_generate_images_with_imagen() {
+        use autoagents_llm::chat::ImageMime;
+        use autoagents_llm::images::{
+            GeneratedImage, ImageGenerationProvider, ImageGenerationRequest,
+        };
+
+        let server = MockServer::json(
+            200,
+            json!({"predictions": [
+                {"bytesBase64Encoded": "/9j/4A==", "mimeType": "image/jpeg"},
+                {"bytesBase64Encoded": "iVBORw=="}
+            ]}),
+        )
+        .await;
+        let client = LLMBuilder::<Google>::new()
+            .api_key("test-key")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+
+        let request = ImageGenerationRequest::new("A lighthouse at dusk")
+            .n(2)
+            .size("1920x1080");
+        let images = client.generate_images(&request).await.unwrap();
+        assert_eq!(
+            images,
+            vec![
+                GeneratedImage::Bytes {
+                    mime: ImageMime::JPEG,
+                    data: vec![0xFF, 0xD8, 0xFF, 0xE0]
+                },
+                GeneratedImage::Bytes {
+                    mime: ImageMime::PNG,
+                    data: vec![0x89, b'P', b'N', b'G']
+                },
+            ]
+        );
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].path,
+            "/models/imagen-3.0-generate-002:predict?key=test-key"
+        );
+        assert_eq!(
+            requests[0].body,
+            json!({
+                "instances": [{"prompt": "A lighthouse at dusk"}],
+                "parameters": {"sampleCount": 2, "aspectRatio": "16:9"}
+            })
+        );
+    }
 }
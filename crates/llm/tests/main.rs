@@ -1,5 +1,20 @@
 //! Main test file that includes all backend-specific test modules
 
+#[cfg(any(
+    feature = "anthropic",
+    feature = "openai",
+    feature = "google",
+    feature = "ollama",
+    feature = "azure_openai",
+    feature = "openai_compatible",
+    feature = "mistral",
+    feature = "cohere",
+    feature = "deepseek",
+    feature = "xai",
+    feature = "groq"
+))]
+mod common;
+
 #[cfg(feature = "anthropic")]
 mod anthropic_tests;
 
@@ -15,6 +30,15 @@ mod ollama_tests;
 #[cfg(feature = "azure_openai")]
 mod azure_openai_tests;
 
+#[cfg(feature = "openai_compatible")]
+mod openai_compatible_tests;
+
+#[cfg(feature = "mistral")]
+mod mistral_tests;
+
+#[cfg(feature = "cohere")]
+mod cohere_tests;
+
 #[cfg(any(
     feature = "deepseek",
     feature = "xai",
@@ -103,7 +127,7 @@ mod common_tests {
             .build();
 
         match &msg.message_type {
-            MessageType::Pdf(data) => {
+            MessageType::Pdf { data, .. } => {
                 assert_eq!(data, &pdf_data);
             }
             _ => panic!("Expected Pdf message type"),
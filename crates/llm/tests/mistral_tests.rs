@@ -0,0 +1,284 @@
+#[cfg(feature = "mistral")]
+mod mistral_test_cases {
+    use crate::common::{collect_stream, MockServer};
+    use autoagents_llm::backends::mistral::Mistral;
+    use autoagents_llm::{
+        builder::LLMBuilder,
+        chat::{ChatMessage, ChatProvider, FunctionTool, StructuredOutputFormat, Tool},
+        embedding::EmbeddingProvider,
+        error::LLMError,
+        models::ModelsProvider,
+    };
+    use serde_json::json;
+    use std::sync::Arc;
+
+    const STREAM_FIXTURE: &str = r#"data: {"id":"1","object":"chat.completion.chunk","model":"mistral-small-latest","choices":[{"index":0,"delta":{"role":"assistant","content":""},"finish_reason":null}]}
+
+data: {"id":"1","object":"chat.completion.chunk","model":"mistral-small-latest","choices":[{"index":0,"delta":{"content":"Checking"},"finish_reason":null}]}
+
+data: {"id":"1","object":"chat.completion.chunk","model":"mistral-small-latest","choices":[{"index":0,"delta":{"tool_calls":[{"id":"abc123xyz","function":{"name":"get_weather","arguments":"{\"city\": \"Paris\"}"},"index":0}]},"finish_reason":"tool_calls"}],"usage":{"prompt_tokens":60,"total_tokens":80,"completion_tokens":20}}
+
+data: [DONE]
+
+"#;
+
+    fn create_client(server: &MockServer) -> Arc<Mistral> {
+        LLMBuilder::<Mistral>::new()
+            .api_key("test-key")
+            .base_url(format!("{}/v1", server.url))
+            .model("mistral-small-latest")
+            .build()
+            .expect("Failed to build Mistral client")
+    }
+
+    fn weather_tool() -> Tool {
+        Tool {
+            tool_type: "function".to_string(),
+            function: FunctionTool {
+                name: "get_weather".to_string(),
+                description: "Get the weather for a city".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}},
+                    "required": ["city"]
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn test_mistral_builder() {
+        let client = LLMBuilder::<Mistral>::new()
+            .api_key("test-key")
+            .temperature(0.3)
+            .build()
+            .unwrap();
+        assert_eq!(client.model, "mistral-small-latest");
+        assert_eq!(client.base_url.as_str(), "https://api.mistral.ai/v1/");
+        assert_eq!(client.temperature, Some(0.3));
+
+        let result = LLMBuilder::<Mistral>::new().build();
+        match result.err().unwrap() {
+            LLMError::InvalidRequest(msg) => assert!(msg.contains("No API key provided")),
+            _ => panic!("Expected InvalidRequest error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_tools() {
+        let server = MockServer::json(
+            200,
+            json!({
+                "id": "cmpl-1",
+                "object": "chat.completion",
+                "model": "mistral-small-latest",
+                "choices": [{
+                    "index": 0,
+                    "message": {
+                        "role": "assistant",
+                        "content": "",
+                        "tool_calls": [{
+                            "id": "abc123xyz",
+                            "type": "function",
+                            "function": {"name": "get_weather", "arguments": "{\"city\": \"Paris\"}"}
+                        }]
+                    },
+                    "finish_reason": "tool_calls"
+                }],
+                "usage": {"prompt_tokens": 60, "completion_tokens": 20, "total_tokens": 80}
+            }),
+        )
+        .await;
+        let client = create_client(&server);
+
+        let messages = vec![ChatMessage::user().content("Weather in Paris?").build()];
+        let response = client
+            .chat(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+        let tool_calls = response.tool_calls().unwrap();
+        assert_eq!(tool_calls[0].id, "abc123xyz");
+        assert_eq!(tool_calls[0].function.name, "get_weather");
+        assert_eq!(response.usage().unwrap().total_tokens, 80);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v1/chat/completions");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer test-key"));
+        let body = &requests[0].body;
+        assert_eq!(body["model"], json!("mistral-small-latest"));
+        assert_eq!(body["tools"][0]["function"]["name"], json!("get_weather"));
+        assert_eq!(body["parallel_tool_calls"], json!(false));
+        assert!(body.get("top_k").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_chat_sends_image_to_vision_model() {
+        use autoagents_llm::chat::ImageMime;
+        use autoagents_llm::LLMProvider;
+
+        let server = MockServer::json(
+            200,
+            json!({
+                "choices": [{"message": {"role": "assistant", "content": "A cat"}}]
+            }),
+        )
+        .await;
+        let client = create_client(&server);
+        let capabilities = client.capabilities().unwrap();
+        assert!(capabilities.vision);
+        assert!(!capabilities.pdf_input);
+
+        let messages = vec![ChatMessage::user()
+            .content("What is this?")
+            .image(ImageMime::JPEG, vec![0xFF, 0xD8])
+            .build()];
+        let response = client.chat(&messages, None, None).await.unwrap();
+        assert_eq!(response.text(), Some("A cat".to_string()));
+
+        let content = &server.requests()[0].body["messages"][0]["content"];
+        assert_eq!(content[0], json!({"type": "text", "text": "What is this?"}));
+        assert_eq!(
+            content[1],
+            json!({"type": "image_url", "image_url": {"url": "data:image/jpeg;base64,/9g="}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_json_mode() {
+        let server = MockServer::json(
+            200,
+            json!({
+                "choices": [{"message": {"role": "assistant", "content": "{\"answer\": 42}"}}]
+            }),
+        )
+        .await;
+        let client = create_client(&server);
+        let schema = StructuredOutputFormat {
+            name: "Answer".to_string(),
+            description: None,
+            schema: Some(json!({
+                "type": "object",
+                "properties": {"answer": {"type": "integer"}}
+            })),
+            strict: Some(true),
+        };
+
+        let messages = vec![ChatMessage::user().content("Answer?").build()];
+        let response = client.chat(&messages, None, Some(schema)).await.unwrap();
+        assert_eq!(response.text(), Some("{\"answer\": 42}".to_string()));
+
+        let body = &server.requests()[0].body;
+        assert_eq!(body["response_format"]["type"], json!("json_schema"));
+        assert_eq!(
+            body["response_format"]["json_schema"]["name"],
+            json!("Answer")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_struct_fixture() {
+        let server = MockServer::sse(STREAM_FIXTURE).await;
+        let client = create_client(&server);
+
+        let messages = vec![ChatMessage::user().content("Weather in Paris?").build()];
+        let stream = client
+            .chat_stream_struct(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+        let collected = collect_stream(stream).await.unwrap();
+
+        assert_eq!(collected.content, "Checking");
+        assert_eq!(
+            collected.tool_calls,
+            vec![(
+                "get_weather".to_string(),
+                r#"{"city": "Paris"}"#.to_string()
+            )]
+        );
+        assert_eq!(collected.usage.unwrap().total_tokens, 80);
+
+        let body = &server.requests()[0].body;
+        assert_eq!(body["stream"], json!(true));
+        assert!(body.get("stream_options").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_embed() {
+        let server = MockServer::json(
+            200,
+            json!({
+                "id": "emb-1",
+                "object": "list",
+                "model": "mistral-embed",
+                "data": [
+                    {"object": "embedding", "embedding": [0.1, 0.2], "index": 0},
+                    {"object": "embedding", "embedding": [0.3, 0.4], "index": 1}
+                ],
+                "usage": {"prompt_tokens": 6, "total_tokens": 6}
+            }),
+        )
+        .await;
+        let client = LLMBuilder::<Mistral>::new()
+            .api_key("test-key")
+            .base_url(format!("{}/v1/", server.url))
+            .model("mistral-embed")
+            .build()
+            .unwrap();
+
+        let embeddings = client
+            .embed(vec!["first".to_string(), "second".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(embeddings, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v1/embeddings");
+        assert_eq!(
+            requests[0].body,
+            json!({"model": "mistral-embed", "input": ["first", "second"]})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_models() {
+        let server = MockServer::json(
+            200,
+            json!({
+                "object": "list",
+                "data": [
+                    {"id": "mistral-small-latest", "object": "model", "created": 1700000000, "owned_by": "mistralai"},
+                    {"id": "mistral-embed", "object": "model", "created": 1700000000, "owned_by": "mistralai"}
+                ]
+            }),
+        )
+        .await;
+        let client = create_client(&server);
+
+        let models = client.list_models(None).await.unwrap();
+        assert_eq!(
+            models.get_models(),
+            vec![
+                "mistral-small-latest".to_string(),
+                "mistral-embed".to_string()
+            ]
+        );
+        assert_eq!(server.requests()[0].path, "/v1/models");
+    }
+
+    #[tokio::test]
+    async fn test_error_status() {
+        let server = MockServer::json(
+            422,
+            json!({"object": "error", "message": "Extra inputs are not permitted"}),
+        )
+        .await;
+        let client = create_client(&server);
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        match client.chat(&messages, None, None).await {
+            Err(LLMError::ResponseFormatError { raw_response, .. }) => {
+                assert!(raw_response.contains("Extra inputs"))
+            }
+            _ => panic!("Expected ResponseFormatError"),
+        }
+    }
+}
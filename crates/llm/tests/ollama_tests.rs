@@ -2,8 +2,8 @@
 use autoagents_llm::{
     builder::LLMBuilder,
     chat::{
-        ChatMessage, ChatProvider, ChatResponse, ChatRole, MessageType, StructuredOutputFormat,
-        Tool, ToolChoice,
+        ChatMessage, ChatProvider, ChatResponse, ChatRole, FunctionTool, MessageType,
+        StructuredOutputFormat, Tool, ToolChoice,
     },
     completion::{CompletionProvider, CompletionRequest},
     embedding::EmbeddingProvider,
@@ -17,6 +17,7 @@ use std::sync::Arc;
 #[cfg(feature = "ollama")]
 mod ollama_test_cases {
     use super::*;
+    use crate::common::{collect_stream, MockServer};
     use autoagents_llm::backends::ollama::Ollama;
 
     fn create_test_ollama() -> Arc<Ollama> {
@@ -94,6 +95,33 @@ mod ollama_test_cases {
         }
     }
 
+    #[tokio::test]
+    async fn test_chat_rejects_unsupported_attachments() {
+        let client = Ollama::new(
+            "http://localhost:11434",
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        for message in [
+            ChatMessage::user()
+                .image_url("https://example.com/cat.png")
+                .build(),
+            ChatMessage::user().pdf_file("report.pdf", vec![1]).build(),
+        ] {
+            match client.chat(&[message], None, None).await.err().unwrap() {
+                LLMError::InvalidRequest(msg) => assert!(msg.contains("Ollama")),
+                other => panic!("Expected InvalidRequest error, got {other:?}"),
+            }
+        }
+    }
+
     #[tokio::test]
     async fn test_completion_missing_base_url() {
         let client = Ollama::new(
@@ -135,4 +163,79 @@ mod ollama_test_cases {
             _ => panic!("Expected InvalidRequest error"),
         }
     }
+    const STREAM_FIXTURE: &str = r#"{"model":"llama3.1","message":{"role":"assistant","content":"Checking"},"done":false}
+{"model":"llama3.1","message":{"role":"assistant","content":" now."},"done":false}
+{"model":"llama3.1","message":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"get_weather","arguments":{"city":"Paris"}}}]},"done":false}
+{"model":"llama3.1","message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","prompt_eval_count":15,"eval_count":9}
+"#;
+
+    fn weather_tool() -> Tool {
+        Tool {
+            tool_type: "function".to_string(),
+            function: FunctionTool {
+                name: "get_weather".to_string(),
+                description: "Get the weather for a city".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}},
+                    "required": ["city"]
+                }),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_struct_fixture() {
+        let server = MockServer::ndjson(STREAM_FIXTURE).await;
+        let client = LLMBuilder::<Ollama>::new()
+            .base_url(&server.url)
+            .model("llama3.1")
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user().content("Weather in Paris?").build()];
+        let stream = client
+            .chat_stream_struct(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+        let collected = collect_stream(stream).await.unwrap();
+
+        assert_eq!(collected.content, "Checking now.");
+        assert_eq!(collected.tool_calls.len(), 1);
+        assert_eq!(collected.tool_calls[0].0, "get_weather");
+        let args: serde_json::Value = serde_json::from_str(&collected.tool_calls[0].1).unwrap();
+        assert_eq!(args, json!({"city": "Paris"}));
+        let usage = collected.usage.unwrap();
+        assert_eq!(
+            (
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.total_tokens
+            ),
+            (15, 9, 24)
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/api/chat");
+        assert_eq!(requests[0].body["stream"], json!(true));
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_struct_error_line() {
+        let server = MockServer::ndjson("{\"error\":\"model 'llama3.1' not found\"}\n").await;
+        let client = LLMBuilder::<Ollama>::new()
+            .base_url(&server.url)
+            .model("llama3.1")
+            .build()
+            .unwrap();
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        let stream = client
+            .chat_stream_struct(&messages, None, None)
+            .await
+            .unwrap();
+        match collect_stream(stream).await {
+            Err(LLMError::ProviderError(msg)) => assert!(msg.contains("not found")),
+            other => panic!("Expected ProviderError, got {other:?}"),
+        }
+    }
 }
@@ -0,0 +1,310 @@
+#[cfg(feature = "openai_compatible")]
+mod openai_compatible_test_cases {
+    use crate::common::{collect_stream, MockServer};
+    use autoagents_llm::backends::openai_compatible::{OpenAICompatible, OpenAICompatibleQuirks};
+    use autoagents_llm::{
+        builder::LLMBuilder,
+        chat::{ChatMessage, ChatProvider, FunctionTool, StructuredOutputFormat, Tool},
+        error::LLMError,
+        models::ModelsProvider,
+    };
+    use serde_json::json;
+
+    const STREAM_FIXTURE: &str = r#"data: {"id":"1","choices":[{"index":0,"delta":{"role":"assistant","content":"Hello"}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{"content":" there"}}]}
+
+data: {"id":"1","choices":[],"usage":{"prompt_tokens":5,"completion_tokens":2,"total_tokens":7}}
+
+data: [DONE]
+
+"#;
+
+    fn chat_completion() -> serde_json::Value {
+        json!({
+            "choices": [{"message": {"role": "assistant", "content": "Hello there"}}],
+            "usage": {"prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7}
+        })
+    }
+
+    fn weather_tool() -> Tool {
+        Tool {
+            tool_type: "function".to_string(),
+            function: FunctionTool {
+                name: "get_weather".to_string(),
+                description: "Get the weather for a city".to_string(),
+                parameters: json!({"type": "object", "properties": {}}),
+            },
+        }
+    }
+
+    #[test]
+    fn test_builder_requires_base_url_and_model() {
+        let result = LLMBuilder::<OpenAICompatible>::new()
+            .model("llama3")
+            .build();
+        match result.err().unwrap() {
+            LLMError::InvalidRequest(msg) => assert!(msg.contains("No base URL")),
+            _ => panic!("Expected InvalidRequest error"),
+        }
+
+        let result = LLMBuilder::<OpenAICompatible>::new()
+            .base_url("http://localhost:8000/v1")
+            .build();
+        match result.err().unwrap() {
+            LLMError::InvalidRequest(msg) => assert!(msg.contains("No model")),
+            _ => panic!("Expected InvalidRequest error"),
+        }
+
+        let result = LLMBuilder::<OpenAICompatible>::new()
+            .base_url("not a url")
+            .model("llama3")
+            .build();
+        match result.err().unwrap() {
+            LLMError::InvalidRequest(msg) => assert!(msg.contains("Invalid base URL")),
+            _ => panic!("Expected InvalidRequest error"),
+        }
+    }
+
+    #[test]
+    fn test_builder_defaults() {
+        let client = LLMBuilder::<OpenAICompatible>::new()
+            .base_url("http://localhost:8000/v1")
+            .model("llama3")
+            .header("X-Team", "search")
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url.as_str(), "http://localhost:8000/v1/");
+        assert_eq!(client.api_key, "");
+        assert_eq!(client.quirks, OpenAICompatibleQuirks::default());
+        assert!(!client.quirks.requires_api_key);
+        assert_eq!(
+            client.headers,
+            vec![("X-Team".to_string(), "search".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_without_api_key_sends_custom_headers() {
+        let server = MockServer::json(200, chat_completion()).await;
+        let client = LLMBuilder::<OpenAICompatible>::new()
+            .base_url(format!("{}/v1", server.url))
+            .model("llama3")
+            .header("X-Team", "search")
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        let response = client.chat(&messages, None, None).await.unwrap();
+        assert_eq!(response.text(), Some("Hello there".to_string()));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v1/chat/completions");
+        assert_eq!(requests[0].header("authorization"), None);
+        assert_eq!(requests[0].header("x-team"), Some("search"));
+        assert_eq!(requests[0].body["model"], json!("llama3"));
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_api_key() {
+        let server = MockServer::json(200, chat_completion()).await;
+        let client = LLMBuilder::<OpenAICompatible>::new()
+            .base_url(&server.url)
+            .api_key("secret")
+            .model("llama3")
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        client.chat(&messages, None, None).await.unwrap();
+        assert_eq!(
+            server.requests()[0].header("authorization"),
+            Some("Bearer secret")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_sends_image_and_pdf_attachments() {
+        use autoagents_llm::chat::ImageMime;
+
+        let server = MockServer::json(200, chat_completion()).await;
+        let client = LLMBuilder::<OpenAICompatible>::new()
+            .base_url(&server.url)
+            .model("llava")
+            .build()
+            .unwrap();
+
+        let messages = vec![
+            ChatMessage::user()
+                .content("What is in this picture?")
+                .image(ImageMime::PNG, vec![1, 2, 3])
+                .build(),
+            ChatMessage::user()
+                .pdf_file("report.pdf", vec![0x25, 0x50])
+                .build(),
+        ];
+        client.chat(&messages, None, None).await.unwrap();
+
+        let body = &server.requests()[0].body;
+        assert_eq!(
+            body["messages"][0]["content"],
+            json!([
+                {"type": "text", "text": "What is in this picture?"},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,AQID"}}
+            ])
+        );
+        assert_eq!(
+            body["messages"][1]["content"],
+            json!([{
+                "type": "file",
+                "file": {"filename": "report.pdf", "file_data": "data:application/pdf;base64,JVA="}
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requires_api_key_quirk() {
+        let client = LLMBuilder::<OpenAICompatible>::new()
+            .base_url("http://localhost:8000/v1")
+            .model("llama3")
+            .quirks(OpenAICompatibleQuirks {
+                requires_api_key: true,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        match client.chat(&messages, None, None).await {
+            Err(LLMError::AuthError(msg)) => assert!(msg.contains("API key")),
+            _ => panic!("Expected AuthError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_options_follow_quirks() {
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+
+        let server = MockServer::sse(STREAM_FIXTURE).await;
+        let client = LLMBuilder::<OpenAICompatible>::new()
+            .base_url(&server.url)
+            .model("llama3")
+            .enable_parallel_tool_use(true)
+            .quirks(OpenAICompatibleQuirks {
+                supports_parallel_tool_calls: true,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        let stream = client
+            .chat_stream_struct(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+        let collected = collect_stream(stream).await.unwrap();
+        assert_eq!(collected.content, "Hello there");
+        assert_eq!(collected.usage.unwrap().total_tokens, 7);
+        let body = &server.requests()[0].body;
+        assert_eq!(body["stream_options"]["include_usage"], json!(true));
+        assert_eq!(body["parallel_tool_calls"], json!(true));
+
+        // llama.cpp-style server rejecting both fields
+        let server = MockServer::sse(STREAM_FIXTURE).await;
+        let client = LLMBuilder::<OpenAICompatible>::new()
+            .base_url(&server.url)
+            .model("llama3")
+            .enable_parallel_tool_use(true)
+            .quirks(OpenAICompatibleQuirks {
+                supports_stream_options: false,
+                supports_parallel_tool_calls: false,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        let stream = client
+            .chat_stream_struct(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+        collect_stream(stream).await.unwrap();
+        let body = &server.requests()[0].body;
+        assert!(body.get("stream_options").is_none());
+        assert!(body.get("parallel_tool_calls").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_structured_output_quirk() {
+        let schema = StructuredOutputFormat {
+            name: "Answer".to_string(),
+            description: None,
+            schema: Some(json!({"type": "object", "properties": {}})),
+            strict: None,
+        };
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+
+        let server = MockServer::json(200, chat_completion()).await;
+        let client = LLMBuilder::<OpenAICompatible>::new()
+            .base_url(&server.url)
+            .model("llama3")
+            .quirks(OpenAICompatibleQuirks {
+                supports_structured_output: false,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        client
+            .chat(&messages, None, Some(schema.clone()))
+            .await
+            .unwrap();
+        assert!(server.requests()[0].body.get("response_format").is_none());
+
+        let server = MockServer::json(200, chat_completion()).await;
+        let client = LLMBuilder::<OpenAICompatible>::new()
+            .base_url(&server.url)
+            .model("llama3")
+            .build()
+            .unwrap();
+        client.chat(&messages, None, Some(schema)).await.unwrap();
+        assert_eq!(
+            server.requests()[0].body["response_format"]["type"],
+            json!("json_schema")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_models() {
+        let server = MockServer::json(
+            200,
+            json!({"object": "list", "data": [{"id": "llama3", "object": "model", "created": 0, "owned_by": "local"}]}),
+        )
+        .await;
+        let client = LLMBuilder::<OpenAICompatible>::new()
+            .base_url(format!("{}/v1/", server.url))
+            .model("llama3")
+            .header("X-Team", "search")
+            .build()
+            .unwrap();
+
+        let models = client.list_models(None).await.unwrap();
+        assert_eq!(models.get_models(), vec!["llama3".to_string()]);
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].path, "/v1/models");
+        assert_eq!(requests[0].header("x-team"), Some("search"));
+    }
+
+    #[tokio::test]
+    async fn test_error_status() {
+        let server = MockServer::json(400, json!({"error": "unsupported field"})).await;
+        let client = LLMBuilder::<OpenAICompatible>::new()
+            .base_url(&server.url)
+            .model("llama3")
+            .build()
+            .unwrap();
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        match client.chat(&messages, None, None).await {
+            Err(LLMError::ResponseFormatError { raw_response, .. }) => {
+                assert!(raw_response.contains("unsupported field"))
+            }
+            _ => panic!("Expected ResponseFormatError"),
+        }
+    }
+}
@@ -17,6 +17,7 @@ use std::sync::Arc;
 #[cfg(feature = "openai")]
 mod openai_test_cases {
     use super::*;
+    use crate::common::MockServer;
     use autoagents_llm::{backends::openai::OpenAI, chat::ReasoningEffort};
 
     fn create_test_openai() -> Arc<OpenAI> {
@@ -65,6 +66,25 @@ mod openai_test_cases {
         }
     }
 
+    #[test]
+    fn test_build_rejects_validator() {
+        let result = LLMBuilder::<OpenAI>::new()
+            .api_key("test-key")
+            .validator(|_| Ok(()))
+            .build();
+        match result.err().unwrap() {
+            LLMError::InvalidRequest(msg) => assert!(msg.contains("build_validated")),
+            _ => panic!("Expected InvalidRequest error"),
+        }
+
+        let validated = LLMBuilder::<OpenAI>::new()
+            .api_key("test-key")
+            .validator(|_| Ok(()))
+            .validator_attempts(2)
+            .build_validated(LLMBuilder::<OpenAI>::build);
+        assert!(validated.is_ok());
+    }
+
     #[test]
     fn test_openai_default_values() {
         let client = OpenAI::new(
@@ -775,4 +795,382 @@ mod openai_test_cases {
             assert_eq!(client.system, Some(prompt.to_string()));
         }
     }
+
+    #[tokio::test]
+    async fn test_chat_cached_tokens_usage() {
+        let server = MockServer::json(
+            200,
+            json!({
+                "choices": [{"message": {"role": "assistant", "content": "Hello"}}],
+                "usage": {
+                    "prompt_tokens": 1200,
+                    "completion_tokens": 5,
+                    "total_tokens": 1205,
+                    "prompt_tokens_details": {"cached_tokens": 1024}
+                }
+            }),
+        )
+        .await;
+        let client = LLMBuilder::<OpenAI>::new()
+            .api_key("test-key")
+            .base_url(format!("{}/v1/", server.url))
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user().content("Hi").build()];
+        let response = client.chat(&messages, None, None).await.unwrap();
+        let usage = response.usage().unwrap();
+        assert_eq!(usage.prompt_tokens, 1200);
+        let details = usage.prompt_tokens_details.unwrap();
+        assert_eq!(details.cached_tokens, Some(1024));
+        assert_eq!(details.cache_creation_tokens, None);
+    }
+
+    #[tokio::test]
+    async fn test_chat_drops_cache_breakpoints() {
+        let server = MockServer::json(
+            200,
+            json!({"choices": [{"message": {"role": "assistant", "content": "Hello"}}]}),
+        )
+        .await;
+        let client = LLMBuilder::<OpenAI>::new()
+            .api_key("test-key")
+            .base_url(format!("{}/v1/", server.url))
+            .build()
+            .unwrap();
+
+        let messages = vec![
+            ChatMessage::user().content("Long document").build(),
+            ChatMessage::cache_breakpoint(),
+            ChatMessage::user().content("Hi").build(),
+        ];
+        client.chat(&messages, None, None).await.unwrap();
+
+        let sent = server.requests()[0].body["messages"].clone();
+        assert_eq!(
+            sent,
+            json!([
+                {"role": "user", "content": "Long document"},
+                {"role": "user", "content": "Hi"}
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_with_timestamps() {
+        use autoagents_llm::audio::{SpeechToTextProvider, TranscriptionRequest};
+
+        let server = MockServer::json(
+            200,
+            json!({
+                "task": "transcribe",
+                "language": "english",
+                "duration": 2.0,
+                "text": "Book a table for two",
+                "segments": [{"id": 0, "start": 0.0, "end": 2.0, "text": "Book a table for two"}]
+            }),
+        )
+        .await;
+        let client = LLMBuilder::<OpenAI>::new()
+            .api_key("test-key")
+            .base_url(format!("{}/v1/", server.url))
+            .build()
+            .unwrap();
+
+        let request = TranscriptionRequest::new(b"RIFF....WAVE".to_vec(), "clip.wav")
+            .language("en")
+            .timestamps(true);
+        let transcription = client.transcribe(&request).await.unwrap();
+        assert_eq!(transcription.text, "Book a table for two");
+        assert_eq!(transcription.duration, Some(2.0));
+        assert_eq!(transcription.segments.len(), 1);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v1/audio/transcriptions");
+        assert!(requests[0]
+            .header("content-type")
+            .unwrap()
+            .starts_with("multipart/form-data"));
+        let form = requests[0].body_text();
+        assert!(form.contains("filename=\"clip.wav\""));
+        assert!(form.contains("RIFF....WAVE"));
+        assert!(form.contains("whisper-1"));
+        assert!(form.contains("verbose_json"));
+        assert!(form.contains("name=\"timestamp_granularities[]\""));
+    }
+
+    #[tokio::test]
+    async fn test_synthesize_uses_configured_voice() {
+        use autoagents_llm::audio::{AudioFormat, SpeechRequest, TextToSpeechProvider};
+        use futures::StreamExt;
+
+        let server = MockServer::start(200, "audio/ogg", "OggS-audio-bytes".to_string()).await;
+        let client = LLMBuilder::<OpenAI>::new()
+            .api_key("test-key")
+            .base_url(format!("{}/v1/", server.url))
+            .voice("nova")
+            .build()
+            .unwrap();
+
+        let request = SpeechRequest::new("Your table is booked").format(AudioFormat::Opus);
+        let audio = client.synthesize(&request).await.unwrap();
+        assert_eq!(audio, b"OggS-audio-bytes");
+
+        let chunks: Vec<_> = client
+            .synthesize_stream(&request.clone().voice("echo"))
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        let streamed: Vec<u8> = chunks.into_iter().flat_map(|c| c.unwrap()).collect();
+        assert_eq!(streamed, b"OggS-audio-bytes");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v1/audio/speech");
+        assert_eq!(
+            requests[0].body,
+            json!({
+                "model": "tts-1",
+                "input": "Your table is booked",
+                "voice": "nova",
+                "response_format": "opus"
+            })
+        );
+        assert_eq!(requests[1].body["voice"], json!("echo"));
+    }
+
+    #[tokio::test]
+    async fn test_generate_images() {
+        use autoagents_llm::chat::ImageMime;
+        use autoagents_llm::images::{
+            GeneratedImage, ImageGenerationProvider, ImageGenerationRequest, ImageResponseFormat,
+        };
+
+        let server = MockServer::json(
+            200,
+            json!({"created": 1, "data": [{"b64_json": "iVBORw==", "revised_prompt": "A cat"}]}),
+        )
+        .await;
+        let client = LLMBuilder::<OpenAI>::new()
+            .api_key("test-key")
+            .base_url(format!("{}/v1/", server.url))
+            .build()
+            .unwrap();
+
+        let request = ImageGenerationRequest::new("A cat").size("1024x1024");
+        let images = client.generate_images(&request).await.unwrap();
+        assert_eq!(
+            images,
+            vec![GeneratedImage::Bytes {
+                mime: ImageMime::PNG,
+                data: vec![0x89, b'P', b'N', b'G']
+            }]
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v1/images/generations");
+        assert_eq!(
+            requests[0].body,
+            json!({
+                "model": "dall-e-3",
+                "prompt": "A cat",
+                "n": 1,
+                "size": "1024x1024",
+                "response_format": "b64_json"
+            })
+        );
+
+        let server =
+            MockServer::json(200, json!({"data": [{"url": "https://img.example/1.png"}]})).await;
+        let client = LLMBuilder::<OpenAI>::new()
+            .api_key("test-key")
+            .base_url(format!("{}/v1/", server.url))
+            .build()
+            .unwrap();
+        let request = ImageGenerationRequest::new("A cat").format(ImageResponseFormat::Url);
+        let images = client.generate_images(&request).await.unwrap();
+        assert_eq!(
+            images,
+            vec![GeneratedImage::Url {
+                url: "https://img.example/1.png".to_string()
+            }]
+        );
+        assert_eq!(server.requests()[0].body["response_format"], json!("url"));
+    }
+
+    #[tokio::test]
+    async fn test_moderate() {
+        use autoagents_llm::moderation::ModerationProvider;
+
+        let server = MockServer::json(
+            200,
+            json!({
+                "id": "modr-1",
+                "model": "omni-moderation-latest",
+                "results": [{
+                    "flagged": true,
+                    "categories": {"harassment": true, "violence": true, "sexual": false},
+                    "category_scores": {"harassment": 0.91, "violence": 0.64, "sexual": 0.01}
+                }]
+            }),
+        )
+        .await;
+        let client = LLMBuilder::<OpenAI>::new()
+            .api_key("test-key")
+            .base_url(format!("{}/v1/", server.url))
+            .build()
+            .unwrap();
+
+        let result = client.moderate("I will find you").await.unwrap();
+        assert!(result.flagged);
+        assert_eq!(result.categories, vec!["harassment", "violence"]);
+        assert_eq!(result.scores["sexual"], 0.01);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v1/moderations");
+        assert_eq!(
+            requests[0].body,
+            json!({"model": "omni-moderation-latest", "input": "I will find you"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_round_trip() {
+        use autoagents_llm::batch::{BatchProvider, BatchRequest, BatchStatus};
+
+        let batch = |status: &str| {
+            json!({
+                "id": "batch_1",
+                "object": "batch",
+                "status": status,
+                "request_counts": {"total": 2, "completed": 1, "failed": 1},
+                "output_file_id": "file-out",
+                "error_file_id": "file-err"
+            })
+            .to_string()
+        };
+        let output = json!({
+            "id": "batch_req_1",
+            "custom_id": "ticket-1",
+            "response": {
+                "status_code": 200,
+                "body": {
+                    "choices": [{"message": {"role": "assistant", "content": "billing"}}],
+                    "usage": {"prompt_tokens": 12, "completion_tokens": 1, "total_tokens": 13}
+                }
+            },
+            "error": null
+        });
+        let errors = json!({
+            "id": "batch_req_2",
+            "custom_id": "ticket-2",
+            "response": {
+                "status_code": 400,
+                "body": {"error": {"message": "Invalid model"}}
+            },
+            "error": null
+        });
+        let server = MockServer::sequence(vec![
+            (
+                200,
+                "application/json",
+                json!({"id": "file-in"}).to_string(),
+            ),
+            (200, "application/json", batch("validating")),
+            (200, "application/json", batch("completed")),
+            (200, "application/json", batch("completed")),
+            (200, "application/jsonl", format!("{output}\n")),
+            (200, "application/jsonl", format!("{errors}\n")),
+        ])
+        .await;
+        let client = LLMBuilder::<OpenAI>::new()
+            .api_key("test-key")
+            .base_url(format!("{}/v1/", server.url))
+            .model("gpt-4o-mini")
+            .build()
+            .unwrap();
+
+        let requests = [
+            BatchRequest::new(
+                "ticket-1",
+                vec![ChatMessage::user().content("Refund my order").build()],
+            ),
+            BatchRequest::new(
+                "ticket-2",
+                vec![ChatMessage::user().content("App crashes").build()],
+            ),
+        ];
+        let job = client.submit_batch(&requests).await.unwrap();
+        assert_eq!(job.id, "batch_1");
+        assert_eq!(job.status, BatchStatus::Validating);
+
+        let job = client
+            .wait_for_batch("batch_1", std::time::Duration::from_millis(1))
+            .await
+            .unwrap();
+        assert_eq!((job.total, job.completed, job.failed), (2, 1, 1));
+
+        let results = client.batch_results("batch_1").await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].custom_id, "ticket-1");
+        let response = results[0].response.as_ref().unwrap();
+        assert_eq!(response.text().as_deref(), Some("billing"));
+        assert_eq!(response.usage().unwrap().total_tokens, 13);
+        assert_eq!(results[1].custom_id, "ticket-2");
+        assert!(matches!(
+            &results[1].response,
+            Err(LLMError::ProviderError(message)) if message == "Invalid model"
+        ));
+
+        let recorded = server.requests();
+        let paths: Vec<_> = recorded.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/v1/files",
+                "/v1/batches",
+                "/v1/batches/batch_1",
+                "/v1/batches/batch_1",
+                "/v1/files/file-out/content",
+                "/v1/files/file-err/content",
+            ]
+        );
+        let upload = recorded[0].body_text();
+        assert!(upload.contains("name=\"purpose\"\r\n\r\nbatch"));
+        let line: serde_json::Value = upload
+            .lines()
+            .find(|line| line.starts_with('{'))
+            .map(|line| serde_json::from_str(line).unwrap())
+            .unwrap();
+        assert_eq!(line["custom_id"], json!("ticket-1"));
+        assert_eq!(line["url"], json!("/v1/chat/completions"));
+        assert_eq!(line["body"]["model"], json!("gpt-4o-mini"));
+        assert_eq!(recorded[1].body["input_file_id"], json!("file-in"));
+        assert_eq!(recorded[1].body["completion_window"], json!("24h"));
+    }
+
+    #[test]
+    fn test_batch_file_has_one_line_per_request() {
+        use autoagents_llm::batch::BatchRequest;
+
+        let client = LLMBuilder::<OpenAI>::new()
+            .api_key("test-key")
+            .model("gpt-4o-mini")
+            .build()
+            .unwrap();
+        let file = client
+            .batch_file(&[
+                BatchRequest::new("a", vec![ChatMessage::user().content("one").build()]),
+                BatchRequest::new("b", vec![ChatMessage::user().content("two").build()]),
+            ])
+            .unwrap();
+        let lines: Vec<serde_json::Value> = file
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["custom_id"], json!("b"));
+        assert_eq!(lines[1]["method"], json!("POST"));
+        assert_eq!(lines[1]["body"]["stream"], json!(false));
+    }
 }
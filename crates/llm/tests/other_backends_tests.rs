@@ -1,7 +1,7 @@
 #![allow(unused_imports)]
 use autoagents_llm::{
     builder::LLMBuilder,
-    chat::{ChatMessage, ChatProvider, ChatRole, StructuredOutputFormat},
+    chat::{ChatMessage, ChatProvider, ChatRole, FunctionTool, StructuredOutputFormat, Tool},
     completion::{CompletionProvider, CompletionRequest},
     embedding::EmbeddingProvider,
     error::LLMError,
@@ -10,9 +10,29 @@ use autoagents_llm::{
 use serde_json::json;
 use std::sync::Arc;
 
+#[cfg(any(feature = "deepseek", feature = "xai"))]
+const OPENAI_STREAM_FIXTURE: &str = r#"data: {"id":"1","choices":[{"index":0,"delta":{"role":"assistant","content":"Checking"}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{"content":" now."}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"get_weather","arguments":""}}]}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"city\": "}}]}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"Paris\"}"}}]}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{},"finish_reason":"tool_calls"}]}
+
+data: {"id":"1","choices":[],"usage":{"prompt_tokens":12,"completion_tokens":8,"total_tokens":20}}
+
+data: [DONE]
+
+"#;
+
 #[cfg(feature = "deepseek")]
 mod deepseek_tests {
     use super::*;
+    use crate::common::{collect_stream, MockServer};
     use autoagents_llm::backends::deepseek::DeepSeek;
 
     fn create_test_deepseek() -> Arc<DeepSeek> {
@@ -157,11 +177,79 @@ mod deepseek_tests {
         assert_eq!(client.max_tokens, Some(u32::MAX));
         assert_eq!(client.temperature, Some(2.0));
     }
+    #[tokio::test]
+    async fn test_chat_stream_struct_fixture() {
+        let server = MockServer::sse(OPENAI_STREAM_FIXTURE).await;
+        let client = LLMBuilder::<DeepSeek>::new()
+            .api_key("test-key")
+            .model("deepseek-chat")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user().content("Weather in Paris?").build()];
+        let stream = client
+            .chat_stream_struct(&messages, None, None)
+            .await
+            .unwrap();
+        let collected = collect_stream(stream).await.unwrap();
+
+        assert_eq!(collected.content, "Checking now.");
+        assert_eq!(
+            collected.tool_calls,
+            vec![(
+                "get_weather".to_string(),
+                r#"{"city": "Paris"}"#.to_string()
+            )]
+        );
+        assert_eq!(collected.usage.unwrap().total_tokens, 20);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/chat/completions");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer test-key"));
+        assert_eq!(requests[0].body["stream"], json!(true));
+        assert_eq!(
+            requests[0].body["stream_options"]["include_usage"],
+            json!(true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_reasoning_content() {
+        let server = MockServer::sse(
+            r#"data: {"id":"1","choices":[{"index":0,"delta":{"role":"assistant","content":null,"reasoning_content":"Paris is "}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{"content":null,"reasoning_content":"in France."}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{"content":"France","reasoning_content":null}}]}
+
+data: [DONE]
+
+"#,
+        )
+        .await;
+        let client = LLMBuilder::<DeepSeek>::new()
+            .api_key("test-key")
+            .model("deepseek-reasoner")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user().content("Where is Paris?").build()];
+        let stream = client
+            .chat_stream_struct(&messages, None, None)
+            .await
+            .unwrap();
+        let collected = collect_stream(stream).await.unwrap();
+        assert_eq!(collected.reasoning, "Paris is in France.");
+        assert_eq!(collected.content, "France");
+    }
 }
 
 #[cfg(feature = "xai")]
 mod xai_tests {
     use super::*;
+    use crate::common::{collect_stream, MockServer};
     use autoagents_llm::backends::xai::XAI;
 
     fn create_test_xai() -> Arc<XAI> {
@@ -438,6 +526,92 @@ mod xai_tests {
         assert_eq!(client.xai_search_from_date, Some("2023-06-01".to_string()));
         assert_eq!(client.xai_search_to_date, Some("2023-12-01".to_string()));
     }
+    fn weather_tool() -> Tool {
+        Tool {
+            tool_type: "function".to_string(),
+            function: FunctionTool {
+                name: "get_weather".to_string(),
+                description: "Get the weather for a city".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}},
+                    "required": ["city"]
+                }),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_struct_fixture() {
+        let server = MockServer::sse(OPENAI_STREAM_FIXTURE).await;
+        let client = LLMBuilder::<XAI>::new()
+            .api_key("test-key")
+            .model("grok-2-latest")
+            .base_url(&server.url)
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user().content("Weather in Paris?").build()];
+        let stream = client
+            .chat_stream_struct(&messages, Some(&[weather_tool()]), None)
+            .await
+            .unwrap();
+        let collected = collect_stream(stream).await.unwrap();
+
+        assert_eq!(collected.content, "Checking now.");
+        assert_eq!(
+            collected.tool_calls,
+            vec![(
+                "get_weather".to_string(),
+                r#"{"city": "Paris"}"#.to_string()
+            )]
+        );
+        assert_eq!(collected.usage.unwrap().total_tokens, 20);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/chat/completions");
+        assert_eq!(
+            requests[0].body["tools"][0]["function"]["name"],
+            json!("get_weather")
+        );
+    }
+}
+
+#[cfg(feature = "openrouter")]
+mod openrouter_tests {
+    use super::*;
+    use crate::common::{collect_stream, MockServer};
+    use autoagents_llm::backends::openrouter::OpenRouter;
+
+    #[tokio::test]
+    async fn test_chat_stream_reasoning() {
+        let server = MockServer::sse(
+            r#"data: {"id":"1","choices":[{"index":0,"delta":{"role":"assistant","content":"","reasoning":"Two plus two"}}]}
+
+data: {"id":"1","choices":[{"index":0,"delta":{"content":"4"}}]}
+
+data: [DONE]
+
+"#,
+        )
+        .await;
+        let client = LLMBuilder::<OpenRouter>::new()
+            .api_key("test-key")
+            .model("deepseek/deepseek-r1")
+            .base_url(format!("{}/", server.url))
+            .build()
+            .unwrap();
+
+        let messages = vec![ChatMessage::user().content("2 + 2?").build()];
+        let stream = client
+            .chat_stream_struct(&messages, None, None)
+            .await
+            .unwrap();
+        let collected = collect_stream(stream).await.unwrap();
+        assert_eq!(collected.reasoning, "Two plus two");
+        assert_eq!(collected.content, "4");
+        assert_eq!(server.requests()[0].path, "/chat/completions");
+    }
 }
 
 #[cfg(feature = "phind")]
@@ -601,6 +775,7 @@ mod phind_tests {
 #[cfg(feature = "groq")]
 mod groq_tests {
     use super::*;
+    use crate::common::MockServer;
     use autoagents_llm::backends::groq::Groq;
 
     fn create_test_groq() -> Arc<Groq> {
@@ -964,4 +1139,30 @@ mod groq_tests {
             assert_eq!(client.model, input);
         }
     }
+
+    #[tokio::test]
+    async fn test_transcribe_with_whisper() {
+        use autoagents_llm::audio::{SpeechToTextProvider, TranscriptionRequest};
+
+        let server = MockServer::json(200, json!({"text": "Hola", "language": "spanish"})).await;
+        let client = LLMBuilder::<Groq>::new()
+            .api_key("test-key")
+            .base_url(format!("{}/openai/v1/", server.url))
+            .build()
+            .unwrap();
+
+        let request = TranscriptionRequest::new(b"audio".to_vec(), "clip.m4a")
+            .language("es")
+            .prompt("Restaurant booking");
+        let transcription = client.transcribe(&request).await.unwrap();
+        assert_eq!(transcription.text, "Hola");
+        assert_eq!(transcription.language.as_deref(), Some("spanish"));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/openai/v1/audio/transcriptions");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer test-key"));
+        let form = requests[0].body_text();
+        assert!(form.contains("whisper-large-v3-turbo"));
+        assert!(form.contains("Restaurant booking"));
+    }
 }
@@ -1,10 +1,13 @@
 use autoagents::async_trait;
 use autoagents_llm::{
-    chat::{ChatMessage, ChatProvider, ChatResponse, StructuredOutputFormat},
+    chat::{
+        ChatMessage, ChatProvider, ChatResponse, StreamChoice, StreamDelta, StreamResponse,
+        StructuredOutputFormat,
+    },
     completion::{CompletionProvider, CompletionRequest, CompletionResponse},
     embedding::EmbeddingProvider,
     error::LLMError,
-    models::ModelsProvider,
+    models::{ModelCapabilities, ModelsProvider},
     LLMProvider, ToolCall,
 };
 
@@ -75,3 +78,179 @@ impl std::fmt::Display for MockChatResponse {
         write!(f, "{}", self.text.as_deref().unwrap_or(""))
     }
 }
+
+/// LLM provider that replies with scripted responses in order and records every request.
+///
+/// Once the script is exhausted, the last response is repeated. When streaming, each
+/// response is sent as one content chunk, after a reasoning chunk if one is set.
+pub struct ScriptedLLMProvider {
+    responses: std::sync::Mutex<Vec<String>>,
+    last: std::sync::Mutex<String>,
+    requests: std::sync::Mutex<Vec<Vec<ChatMessage>>>,
+    reasoning: Option<String>,
+    capabilities: Option<ModelCapabilities>,
+}
+
+impl ScriptedLLMProvider {
+    pub fn new<S: Into<String>>(responses: impl IntoIterator<Item = S>) -> Self {
+        let mut responses: Vec<String> = responses.into_iter().map(Into::into).collect();
+        responses.reverse();
+        Self {
+            responses: std::sync::Mutex::new(responses),
+            last: std::sync::Mutex::new(String::new()),
+            requests: std::sync::Mutex::new(Vec::new()),
+            reasoning: None,
+            capabilities: None,
+        }
+    }
+
+    /// Report `capabilities` for the model
+    pub fn with_capabilities(mut self, capabilities: ModelCapabilities) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    /// Stream `reasoning` before every response
+    pub fn with_reasoning(mut self, reasoning: impl Into<String>) -> Self {
+        self.reasoning = Some(reasoning.into());
+        self
+    }
+
+    /// Messages of every chat request received so far
+    pub fn requests(&self) -> Vec<Vec<ChatMessage>> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn next_response(&self) -> String {
+        let mut last = self.last.lock().unwrap();
+        if let Some(next) = self.responses.lock().unwrap().pop() {
+            *last = next;
+        }
+        last.clone()
+    }
+}
+
+#[async_trait]
+impl ChatProvider for ScriptedLLMProvider {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        _tools: Option<&[autoagents_llm::chat::Tool]>,
+        _json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<Box<dyn ChatResponse>, LLMError> {
+        self.requests.lock().unwrap().push(messages.to_vec());
+        Ok(Box::new(MockChatResponse {
+            text: Some(self.next_response()),
+        }))
+    }
+
+    async fn chat_stream_struct(
+        &self,
+        messages: &[ChatMessage],
+        _tools: Option<&[autoagents_llm::chat::Tool]>,
+        _json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<
+        std::pin::Pin<Box<dyn futures::Stream<Item = Result<StreamResponse, LLMError>> + Send>>,
+        LLMError,
+    > {
+        self.requests.lock().unwrap().push(messages.to_vec());
+        let chunk = |content: Option<String>, reasoning_content: Option<String>| {
+            Ok(StreamResponse {
+                choices: vec![StreamChoice {
+                    delta: StreamDelta {
+                        content,
+                        tool_calls: None,
+                        reasoning_content,
+                    },
+                }],
+                usage: None,
+            })
+        };
+        let mut chunks = Vec::new();
+        if let Some(reasoning) = &self.reasoning {
+            chunks.push(chunk(None, Some(reasoning.clone())));
+        }
+        chunks.push(chunk(Some(self.next_response()), None));
+        Ok(Box::pin(futures::stream::iter(chunks)))
+    }
+}
+
+#[async_trait]
+impl CompletionProvider for ScriptedLLMProvider {
+    async fn complete(
+        &self,
+        _req: &CompletionRequest,
+        _json_schema: Option<StructuredOutputFormat>,
+    ) -> Result<CompletionResponse, LLMError> {
+        Ok(CompletionResponse {
+            text: self.next_response(),
+        })
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for ScriptedLLMProvider {
+    async fn embed(&self, _text: Vec<String>) -> Result<Vec<Vec<f32>>, LLMError> {
+        Ok(vec![vec![0.1, 0.2, 0.3]])
+    }
+}
+
+#[async_trait]
+impl ModelsProvider for ScriptedLLMProvider {}
+
+impl LLMProvider for ScriptedLLMProvider {
+    fn capabilities(&self) -> Option<ModelCapabilities> {
+        self.capabilities.clone()
+    }
+}
+
+/// Embedding provider with deterministic bag-of-words embeddings.
+///
+/// Every lowercase word is hashed into one of `dimension` buckets and the vector is
+/// normalized, so texts sharing words have a high cosine similarity. The size of every
+/// batch received is recorded.
+pub struct MockEmbeddingProvider {
+    dimension: usize,
+    batches: std::sync::Mutex<Vec<usize>>,
+}
+
+impl MockEmbeddingProvider {
+    pub fn new(dimension: usize) -> Self {
+        Self {
+            dimension,
+            batches: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Number of texts in each `embed` call so far
+    pub fn batches(&self) -> Vec<usize> {
+        self.batches.lock().unwrap().clone()
+    }
+
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0; self.dimension];
+        for word in text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+        {
+            let hash = word
+                .to_lowercase()
+                .bytes()
+                .fold(2166136261u32, |h, b| (h ^ b as u32).wrapping_mul(16777619));
+            vector[hash as usize % self.dimension] += 1.0;
+        }
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|v| *v /= norm);
+        }
+        vector
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for MockEmbeddingProvider {
+    async fn embed(&self, input: Vec<String>) -> Result<Vec<Vec<f32>>, LLMError> {
+        self.batches.lock().unwrap().push(input.len());
+        Ok(input.iter().map(|text| self.embed_text(text)).collect())
+    }
+}
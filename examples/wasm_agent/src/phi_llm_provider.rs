@@ -179,6 +179,7 @@ impl ChatProvider for PhiLLMProvider {
                             delta: StreamDelta {
                                 content: Some(first_token.token),
                                 tool_calls: None,
+                                reasoning_content: None,
                             },
                         }],
                         usage: None,
@@ -224,6 +225,7 @@ impl ChatProvider for PhiLLMProvider {
                             delta: StreamDelta {
                                 content: Some(token.token),
                                  tool_calls: None,
+                                reasoning_content: None,
                             },
                         }],
                         usage: None,